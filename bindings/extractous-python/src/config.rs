@@ -97,6 +97,29 @@ impl PdfParserConfig {
         Ok(Self(inner))
     }
 
+    /// If true, put the text in reading order by its position on the page instead of the order
+    /// it is drawn in, e.g. for multi-column PDFs.
+    /// Default: false.
+    pub fn set_sort_by_position(&self, val: bool) -> PyResult<Self> {
+        let inner = self.0.clone().set_sort_by_position(val);
+        Ok(Self(inner))
+    }
+
+    /// If true, leave out the text of the layers of the PDF that are hidden by default, e.g. a
+    /// watermark layer.
+    /// Default: false.
+    pub fn set_respect_ocg_visibility(&self, val: bool) -> PyResult<Self> {
+        let inner = self.0.clone().set_respect_ocg_visibility(val);
+        Ok(Self(inner))
+    }
+
+    /// Sets the password of encrypted (password protected) PDFs.
+    /// Default: None
+    pub fn set_password(&self, val: String) -> PyResult<Self> {
+        let inner = self.0.clone().set_password(val);
+        Ok(Self(inner))
+    }
+
     fn __repr__(&self) -> String {
         format!("{:?}", self.0)
     }
@@ -156,8 +179,9 @@ impl OfficeParserConfig {
     }
 
     /// Whether to include headers and footers. This only operates on headers and footers in
-    /// Word and Excel, not master slide content in PowerPoint.
-    /// Default: true
+    /// Word and Excel, not master slide content in PowerPoint. The pure Rust docx parser
+    /// honors it too.
+    /// Default: false
     pub fn set_include_headers_and_footers(&self, val: bool) -> PyResult<Self> {
         let inner = self.0.clone().set_include_headers_and_footers(val);
         Ok(Self(inner))
@@ -207,6 +231,13 @@ impl OfficeParserConfig {
         Ok(Self(inner))
     }
 
+    /// Sets the password of encrypted (password protected) Office documents.
+    /// Default: None
+    pub fn set_password(&self, val: String) -> PyResult<Self> {
+        let inner = self.0.clone().set_password(val);
+        Ok(Self(inner))
+    }
+
     fn __repr__(&self) -> String {
         format!("{:?}", self.0)
     }
@@ -278,6 +309,32 @@ impl TesseractOcrConfig {
         Ok(Self(inner))
     }
 
+    /// Sets the directory containing the tesseract `.traineddata` language files.
+    /// Default: None, tesseract uses its built-in location or `TESSDATA_PREFIX`.
+    pub fn set_tessdata_path(&self, val: std::path::PathBuf) -> PyResult<Self> {
+        let inner = self.0.clone().set_tessdata_path(val);
+        Ok(Self(inner))
+    }
+
+    /// Sets tesseract config variables, passed as `-c key=value` to every tesseract run, e.g.
+    /// `{"preserve_interword_spaces": "1"}`.
+    /// Default: empty.
+    pub fn set_tesseract_config(
+        &self,
+        val: std::collections::HashMap<String, String>,
+    ) -> PyResult<Self> {
+        let inner = self.0.clone().set_tesseract_config(val);
+        Ok(Self(inner))
+    }
+
+    /// Sets the maximum number of pixels of an image that is OCRed, larger PNG images are
+    /// downscaled before OCR.
+    /// Default: None.
+    pub fn set_max_ocr_image_pixels(&self, val: u64) -> PyResult<Self> {
+        let inner = self.0.clone().set_max_ocr_image_pixels(val);
+        Ok(Self(inner))
+    }
+
     fn __repr__(&self) -> String {
         format!("{:?}", self.0)
     }
@@ -130,6 +130,15 @@ impl Extractor {
         Ok(Self(inner))
     }
 
+    /// Set the password of encrypted (password protected) PDF and Office documents, for the
+    /// configs without a password of their own. It is kept by a later set_pdf_config or
+    /// set_office_config.
+    /// Default: None
+    pub fn set_document_password(&self, password: String) -> PyResult<Self> {
+        let inner = self.0.clone().set_document_password(password);
+        Ok(Self(inner))
+    }
+
     /// Set the configuration for the Tesseract OCR
     pub fn set_ocr_config(&self, config: TesseractOcrConfig) -> PyResult<Self> {
         let inner = self.0.clone().set_ocr_config(config.into());
@@ -142,6 +151,12 @@ impl Extractor {
         Ok(Self(inner))
     }
 
+    /// Set the configuration for the output as json, only supported by the `*_to_string` methods
+    pub fn set_json_output(&self, json_output: bool) -> PyResult<Self> {
+        let inner = self.0.clone().set_json_output(json_output);
+        Ok(Self(inner))
+    }
+
     /// Extracts text from a file path. Returns a tuple with stream of the extracted text
     /// the stream is decoded using the extractor's `encoding` and tika metadata.
     pub fn extract_file<'py>(
@@ -168,6 +168,10 @@ fn text_processing_benchmarks(c: &mut Criterion) {
         })
     });
 
+    group.bench_function("normalize_whitespace_simd", |b| {
+        b.iter(|| extractous::normalize_whitespace_simd(&sample_text))
+    });
+
     group.bench_function("truncate_text_smart", |b| {
         b.iter(|| {
             extractous::truncate_text_smart(&sample_text, 500)
@@ -259,6 +263,147 @@ fn mmap_threshold_optimization(c: &mut Criterion) {
     group.finish();
 }
 
+/// Benchmark the small file fast path against Tika on a 2KB HTML file
+fn small_file_fast_path(c: &mut Criterion) {
+    let mut group = c.benchmark_group("small_file_fast_path");
+
+    let file_path = "../test_files/documents/small-page.html";
+
+    // Tika parses the file, the thread attaches to the vm on every call
+    let tika_extractor = Extractor::new().set_use_pure_rust(false);
+
+    group.bench_function("tika", |b| {
+        b.iter(|| tika_extractor.extract_file_to_string(file_path).unwrap())
+    });
+
+    // The pure Rust html parser takes the file, no vm involved
+    #[cfg(feature = "pure-rust")]
+    {
+        let fast_path_extractor = Extractor::new()
+            .set_use_pure_rust(false)
+            .set_small_file_fast_path(true);
+
+        group.bench_function("fast_path", |b| {
+            b.iter(|| {
+                fast_path_extractor
+                    .extract_file_to_string(file_path)
+                    .unwrap()
+            })
+        });
+    }
+
+    group.finish();
+}
+
+/// Benchmark extracting only the metadata against the full extraction
+fn metadata_only(c: &mut Criterion) {
+    let mut group = c.benchmark_group("metadata_only");
+
+    let file_path = "../test_files/documents/2022_Q3_AAPL.pdf";
+    let extractor = Extractor::new().set_use_pure_rust(false);
+
+    group.bench_function("full_extraction", |b| {
+        b.iter(|| extractor.extract_file_to_string(file_path).unwrap())
+    });
+
+    group.bench_function("metadata_only", |b| {
+        b.iter(|| extractor.extract_file_metadata_only(file_path).unwrap())
+    });
+
+    group.finish();
+}
+
+/// Benchmark draining a Tika stream with `read_to_end` against a `read` per 8KB buffer
+fn stream_read_to_end(c: &mut Criterion) {
+    let mut group = c.benchmark_group("stream_read_to_end");
+
+    let file_path = "../test_files/documents/2022_Q3_AAPL.pdf";
+    let extractor = Extractor::new().set_use_pure_rust(false);
+
+    let per_read = |extractor: &Extractor| {
+        let (mut stream, _metadata) = extractor.extract_file(file_path).unwrap();
+        let mut buffer = [0u8; 8192];
+        while stream.read(&mut buffer).unwrap() > 0 {}
+        stream.jni_round_trips()
+    };
+    let to_end = |extractor: &Extractor| {
+        let (mut stream, _metadata) = extractor.extract_file(file_path).unwrap();
+        let mut buffer = Vec::new();
+        stream.read_to_end(&mut buffer).unwrap();
+        stream.jni_round_trips()
+    };
+    // Draining in LARGE_BUF_SIZE chunks takes fewer JNI round trips than a read per 8KB
+    let (per_read_trips, to_end_trips) = (per_read(&extractor), to_end(&extractor));
+    assert!(
+        to_end_trips < per_read_trips,
+        "read_to_end took {} JNI round trips, a read per 8KB {}",
+        to_end_trips,
+        per_read_trips
+    );
+
+    group.bench_function("read_per_8kb", |b| b.iter(|| per_read(&extractor)));
+
+    group.bench_function("read_to_end", |b| b.iter(|| to_end(&extractor)));
+
+    group.finish();
+}
+
+/// Benchmark string extraction of a large file mapped into memory against Tika reading it
+fn mmap_string_extraction(c: &mut Criterion) {
+    let mut group = c.benchmark_group("mmap_string_extraction");
+    group.measurement_time(Duration::from_secs(15));
+
+    // Larger than the default mmap threshold of 512KB
+    let file_path = "../test_files/documents/science-exploration-1p.pptx";
+    let mmap_extractor = Extractor::new().set_use_pure_rust(false).set_use_mmap(true);
+    let file_extractor = Extractor::new()
+        .set_use_pure_rust(false)
+        .set_use_mmap(false);
+
+    group.bench_function("file", |b| {
+        b.iter(|| file_extractor.extract_file_to_string(file_path).unwrap())
+    });
+
+    group.bench_function("mmap", |b| {
+        b.iter(|| mmap_extractor.extract_file_to_string(file_path).unwrap())
+    });
+
+    group.finish();
+}
+
+/// Benchmark OCR of a multi-page PDF page after page against its pages in parallel
+fn parallel_ocr(c: &mut Criterion) {
+    use extractous::{PdfOcrStrategy, PdfParserConfig};
+
+    let mut group = c.benchmark_group("parallel_ocr");
+    group.sample_size(10);
+    group.measurement_time(Duration::from_secs(60));
+
+    // Every page is rendered and OCRed
+    let file_path = "../test_files/documents/ten-pages.pdf";
+    let pdf_config = PdfParserConfig::new().set_ocr_strategy(PdfOcrStrategy::OCR_ONLY);
+    let serial_extractor = Extractor::new().set_pdf_config(pdf_config);
+
+    group.bench_function("serial", |b| {
+        b.iter(|| serial_extractor.extract_file_to_string(file_path).unwrap())
+    });
+
+    #[cfg(all(feature = "parallel", feature = "pure-rust"))]
+    {
+        let parallel_extractor = serial_extractor.clone().set_parallel_ocr(true);
+
+        group.bench_function("parallel", |b| {
+            b.iter(|| {
+                parallel_extractor
+                    .extract_file_to_string(file_path)
+                    .unwrap()
+            })
+        });
+    }
+
+    group.finish();
+}
+
 criterion_group!(
     benches,
     extract_to_stream,
@@ -268,6 +413,11 @@ criterion_group!(
     text_processing_benchmarks,
     buffer_size_impact,
     mmap_threshold_optimization,
+    small_file_fast_path,
+    metadata_only,
+    stream_read_to_end,
+    mmap_string_extraction,
+    parallel_ocr,
 );
 
 criterion_main!(benches);
@@ -0,0 +1,65 @@
+//! Reordering of text mixing right-to-left and left-to-right scripts from logical into visual
+//! order, see `BidiReordering`
+
+use crate::BidiReordering;
+use unicode_bidi::{BidiInfo, Direction, Level};
+
+/// Reorders every line of `text`, each line being a paragraph, into visual order
+pub(crate) fn reorder(text: &str, mode: BidiReordering) -> String {
+    match mode {
+        BidiReordering::Off => text.to_string(),
+        BidiReordering::Global => {
+            let rtl = unicode_bidi::get_base_direction_full(text) == Direction::Rtl;
+            reorder_paragraph(text, Some(rtl))
+        }
+        BidiReordering::PerParagraph => reorder_paragraph(text, None),
+    }
+}
+
+/// Reorders the lines of a paragraph into visual order. `rtl` is its base direction, `None` to
+/// detect it for each line from its first strong character
+pub(crate) fn reorder_paragraph(paragraph: &str, rtl: Option<bool>) -> String {
+    let level = rtl.map(|rtl| if rtl { Level::rtl() } else { Level::ltr() });
+    paragraph
+        .split('\n')
+        .map(|line| reorder_line(line, level))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn reorder_line(line: &str, level: Option<Level>) -> String {
+    let info = BidiInfo::new(line, level);
+    if !info.has_rtl() {
+        return line.to_string();
+    }
+    info.paragraphs
+        .iter()
+        .map(|paragraph| info.reorder_line(paragraph, paragraph.range.clone()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // "שלום עולם", hello world in Hebrew
+    const HEBREW: &str = "\u{5e9}\u{5dc}\u{5d5}\u{5dd} \u{5e2}\u{5d5}\u{5dc}\u{5dd}";
+    const HEBREW_VISUAL: &str = "\u{5dd}\u{5dc}\u{5d5}\u{5e2} \u{5dd}\u{5d5}\u{5dc}\u{5e9}";
+
+    #[test]
+    fn test_reorder_per_paragraph() {
+        let text = format!("{} 2024\nHello {}", HEBREW, HEBREW);
+
+        assert_eq!(reorder(&text, BidiReordering::Off), text);
+        // The first line is right-to-left, the second keeps its left-to-right base direction
+        assert_eq!(
+            reorder(&text, BidiReordering::PerParagraph),
+            format!("2024 {}\nHello {}", HEBREW_VISUAL, HEBREW_VISUAL)
+        );
+        // Both lines use the right-to-left direction of the document
+        assert_eq!(
+            reorder(&text, BidiReordering::Global),
+            format!("2024 {}\n{} Hello", HEBREW_VISUAL, HEBREW_VISUAL)
+        );
+    }
+}
@@ -0,0 +1,287 @@
+//! Chunks of a document along its natural boundaries, as returned by
+//! `Extractor::extract_file_chunked`, and the cleanup of page segmented text
+
+use crate::Metadata;
+use std::collections::{HashMap, HashSet};
+
+/// The document boundary a [`Chunk`] was split at
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChunkKind {
+    /// A page of a PDF, its number is in the `Page-Number` metadata
+    Page,
+    /// A sheet of a spreadsheet, its name is in the `Sheet-Name` metadata
+    Sheet,
+    /// The whole document, for formats without natural boundaries
+    Document,
+}
+
+/// A part of a document. The metadata holds the metadata of the document together with the
+/// page number or sheet name of the chunk
+#[derive(Debug, Clone, PartialEq)]
+pub struct Chunk {
+    /// Position of the chunk in the document, starting at 0
+    pub index: usize,
+    pub kind: ChunkKind,
+    pub text: String,
+    pub metadata: Metadata,
+}
+
+/// Splits Tika's XHTML into the text of its `<div class="page">` elements, one entry per page.
+/// Block elements end a line, the text outside of pages is ignored
+pub(crate) fn xhtml_pages(xhtml: &str) -> Vec<String> {
+    let mut pages = Vec::new();
+    // Depth of nested divs inside the current page, None outside of a page
+    let mut page_depth: Option<usize> = None;
+    let mut text = String::new();
+    let mut rest = xhtml;
+
+    while let Some(start) = rest.find('<') {
+        if page_depth.is_some() {
+            text.push_str(&unescape(&rest[..start]));
+        }
+        let Some(end) = rest[start..].find('>') else {
+            break;
+        };
+        let tag = &rest[start + 1..start + end];
+        rest = &rest[start + end + 1..];
+
+        let closing = tag.starts_with('/');
+        let name = tag
+            .trim_start_matches('/')
+            .split(|c: char| c.is_whitespace() || c == '/')
+            .next()
+            .unwrap_or("");
+        let self_closing = tag.ends_with('/');
+
+        match (name, closing, page_depth) {
+            ("div", false, None) if tag.contains("class=\"page\"") && !self_closing => {
+                page_depth = Some(0);
+            }
+            ("div", false, Some(depth)) if !self_closing => page_depth = Some(depth + 1),
+            ("div", true, Some(0)) => {
+                pages.push(text.trim().to_string());
+                text.clear();
+                page_depth = None;
+            }
+            ("div", true, Some(depth)) => {
+                page_depth = Some(depth - 1);
+                text.push('\n');
+            }
+            ("p" | "br" | "li" | "tr" | "h1" | "h2" | "h3" | "h4" | "h5" | "h6", _, Some(_))
+                if closing || self_closing =>
+            {
+                text.push('\n');
+            }
+            ("td" | "th", true, Some(_)) => text.push('\t'),
+            _ => {}
+        }
+    }
+    pages
+}
+
+/// Number of lines at the top and at the bottom of a page that can be a header or a footer
+const EDGE_LINES: usize = 3;
+
+/// Removes running headers and footers, such as page numbers or a document title repeated on
+/// every page, from the text of consecutive pages.
+///
+/// A line counts as a header (footer) when more than half of the pages have it at the same
+/// position among their first (last) non-empty lines. Digits are ignored when comparing lines,
+/// so `Page 3 of 10` matches `Page 4 of 10`. Only lines at the edges of a page are removed, a
+/// short heading in the body is kept, as are the blank lines of the body, only those between the
+/// removed lines and the body go with them. Needs at least three pages, fewer are returned
+/// unchanged.
+pub fn remove_repeated_headers_footers(pages: &[String]) -> Vec<String> {
+    if pages.len() < 3 {
+        return pages.to_vec();
+    }
+
+    let all_lines: Vec<Vec<&str>> = pages.iter().map(|page| page.lines().collect()).collect();
+    // The indices of the non-empty lines of every page
+    let content: Vec<Vec<usize>> = all_lines
+        .iter()
+        .map(|page| {
+            (0..page.len())
+                .filter(|&i| !page[i].trim().is_empty())
+                .collect()
+        })
+        .collect();
+    let lines: Vec<Vec<&str>> = all_lines
+        .iter()
+        .zip(&content)
+        .map(|(page, content)| content.iter().map(|&i| page[i]).collect())
+        .collect();
+    // (position from the edge, line key) of the lines repeated on most pages
+    let repeated = |from_bottom: bool| -> HashSet<(usize, String)> {
+        let mut counts: HashMap<(usize, String), usize> = HashMap::new();
+        for page in &lines {
+            for position in 0..EDGE_LINES.min(page.len()) {
+                let line = edge_line(page, position, from_bottom);
+                *counts.entry((position, line_key(line))).or_default() += 1;
+            }
+        }
+        counts
+            .into_iter()
+            .filter(|(_, count)| count * 2 > pages.len())
+            .map(|(line, _)| line)
+            .collect()
+    };
+    let headers = repeated(false);
+    let footers = repeated(true);
+
+    lines
+        .iter()
+        .zip(content.iter().zip(&all_lines))
+        .map(|(page, (content, all_lines))| {
+            let edge = |set: &HashSet<(usize, String)>, from_bottom: bool| {
+                (0..EDGE_LINES.min(page.len()))
+                    .take_while(|&position| {
+                        let line = edge_line(page, position, from_bottom);
+                        set.contains(&(position, line_key(line)))
+                    })
+                    .count()
+            };
+            let header_lines = edge(&headers, false);
+            let footer_lines = edge(&footers, true).min(page.len() - header_lines);
+            let kept = &content[header_lines..page.len() - footer_lines];
+            let (Some(&first), Some(&last)) = (kept.first(), kept.last()) else {
+                return String::new();
+            };
+            // From the first line after the headers to the last line before the footers
+            let start = if header_lines > 0 { first } else { 0 };
+            let end = if footer_lines > 0 {
+                last + 1
+            } else {
+                all_lines.len()
+            };
+            all_lines[start..end].join("\n")
+        })
+        .collect()
+}
+
+/// The line at `position` counted from the top or from the bottom of the page
+fn edge_line<'a>(page: &[&'a str], position: usize, from_bottom: bool) -> &'a str {
+    if from_bottom {
+        page[page.len() - 1 - position]
+    } else {
+        page[position]
+    }
+}
+
+/// A line with its whitespace collapsed and every run of digits replaced by `#`
+fn line_key(line: &str) -> String {
+    let mut key = String::new();
+    for word in line.split_whitespace() {
+        if !key.is_empty() {
+            key.push(' ');
+        }
+        for c in word.chars() {
+            if !c.is_ascii_digit() {
+                key.push(c);
+            } else if !key.ends_with('#') {
+                key.push('#');
+            }
+        }
+    }
+    key
+}
+
+/// Puts the page marker of `Extractor::set_annotate_page_boundaries` on its own line at the start
+/// of every page, with `{page}` replaced by the page number
+pub(crate) fn annotate_pages(pages: &[String], marker: &str) -> Vec<String> {
+    pages
+        .iter()
+        .enumerate()
+        .map(|(index, page)| {
+            let marker = marker.replace("{page}", &(index + 1).to_string());
+            format!("{}\n{}", marker, page.trim())
+        })
+        .collect()
+}
+
+pub(crate) fn unescape(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_xhtml_pages() {
+        let xhtml = "<html><head><title>Report</title></head><body>\
+            <div class=\"page\"><p>First page</p><p>Fish &amp; chips</p></div>\
+            <div class=\"page\"><div class=\"annotation\"><p>Note</p></div><p>Second</p></div>\
+            <div class=\"page\"></div>\
+            </body></html>";
+
+        assert_eq!(
+            xhtml_pages(xhtml),
+            ["First page\nFish & chips", "Note\n\nSecond", ""]
+        );
+    }
+
+    #[test]
+    fn test_remove_repeated_headers_footers() {
+        let bodies = [
+            ("OVERVIEW", "The year was shaped by the new plant."),
+            ("REVENUE", "Sales grew in every region."),
+            ("COSTS", "Energy prices weighed on margins."),
+            ("OUTLOOK", "We expect a stable next year."),
+        ];
+        let pages: Vec<String> = bodies
+            .iter()
+            .enumerate()
+            .map(|(i, (heading, body))| {
+                format!(
+                    "ACME Annual Report\n{}\n{}\n\nPage {} of 4",
+                    heading,
+                    body,
+                    i + 1
+                )
+            })
+            .collect();
+
+        let cleaned = remove_repeated_headers_footers(&pages);
+        // The running title and the page numbers are removed, the short all caps headings stay
+        assert_eq!(
+            cleaned[0],
+            "OVERVIEW\nThe year was shaped by the new plant."
+        );
+        assert_eq!(cleaned[3], "OUTLOOK\nWe expect a stable next year.");
+    }
+
+    #[test]
+    fn test_remove_repeated_headers_footers_keeps_unique_lines() {
+        let pages = vec![
+            "Introduction\nSome text.\nConfidential".to_string(),
+            "Some text.\nMethods\nConfidential".to_string(),
+            "Results\nMore text.\nConfidential".to_string(),
+        ];
+
+        let cleaned = remove_repeated_headers_footers(&pages);
+        assert_eq!(
+            cleaned,
+            [
+                "Introduction\nSome text.",
+                "Some text.\nMethods",
+                "Results\nMore text."
+            ]
+        );
+        // Too few pages to tell a footer from content
+        assert_eq!(remove_repeated_headers_footers(&pages[..2]), &pages[..2]);
+
+        // Blank lines between the paragraphs of the body are kept
+        let pages: Vec<String> = ["Apples", "Pears", "Plums"]
+            .iter()
+            .enumerate()
+            .map(|(i, fruit)| format!("Report\n\n{0} grew.\n\n{0} sold.\n\n{1}", fruit, i + 1))
+            .collect();
+        let cleaned = remove_repeated_headers_footers(&pages);
+        assert_eq!(cleaned[1], "Pears grew.\n\nPears sold.");
+    }
+}
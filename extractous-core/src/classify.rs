@@ -0,0 +1,279 @@
+//! Heuristic classification of documents into coarse categories, based on keywords and the
+//! structure of the extracted text. No model is involved, the rules are plain data and can be
+//! extended with `Extractor::register_category_rule`.
+
+use strum_macros::Display;
+
+/// A coarse document category
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Display)]
+pub enum DocumentCategory {
+    Invoice,
+    Resume,
+    Contract,
+    Article,
+    /// A category of a user registered rule
+    #[strum(to_string = "{0}")]
+    Custom(String),
+    /// No rule matched strongly enough
+    Unknown,
+}
+
+/// A structural feature of the text that counts towards a category
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TextSignal {
+    /// Money amounts such as `$1,200.00` or `99.90 EUR`
+    CurrencyAmounts,
+    /// Year ranges such as `2019 - 2022` or `2021 – Present`
+    DateRanges,
+    /// Numbered clauses such as `1.1` or `Section 4`
+    NumberedClauses,
+    /// Paragraphs of running prose, at least 40 words
+    LongParagraphs,
+}
+
+/// Scores text for one category. Each keyword counts at most three times, each signal
+/// occurrence at most five times, so a single repeated word cannot decide the category
+#[derive(Debug, Clone, PartialEq)]
+pub struct CategoryRule {
+    category: DocumentCategory,
+    keywords: Vec<(String, f32)>,
+    signals: Vec<(TextSignal, f32)>,
+}
+
+impl CategoryRule {
+    pub fn new(category: DocumentCategory) -> Self {
+        Self {
+            category,
+            keywords: Vec::new(),
+            signals: Vec::new(),
+        }
+    }
+
+    /// Adds a keyword or phrase, matched case insensitively on word boundaries
+    pub fn keyword(mut self, keyword: &str, weight: f32) -> Self {
+        self.keywords.push((keyword.to_lowercase(), weight));
+        self
+    }
+
+    /// Adds a structural signal
+    pub fn signal(mut self, signal: TextSignal, weight: f32) -> Self {
+        self.signals.push((signal, weight));
+        self
+    }
+
+    pub fn category(&self) -> &DocumentCategory {
+        &self.category
+    }
+
+    fn score(&self, words: &[String], text: &str) -> f32 {
+        let keyword_score: f32 = self
+            .keywords
+            .iter()
+            .map(|(keyword, weight)| weight * count_phrase(words, keyword).min(3) as f32)
+            .sum();
+        let signal_score: f32 = self
+            .signals
+            .iter()
+            .map(|(signal, weight)| weight * count_signal(*signal, text).min(5) as f32)
+            .sum();
+        keyword_score + signal_score
+    }
+}
+
+/// Scores below this are classified as `DocumentCategory::Unknown`
+const MIN_SCORE: f32 = 4.0;
+
+/// The built-in rules
+pub(crate) fn default_rules() -> Vec<CategoryRule> {
+    vec![
+        CategoryRule::new(DocumentCategory::Invoice)
+            .keyword("invoice", 3.0)
+            .keyword("invoice number", 2.0)
+            .keyword("bill to", 2.0)
+            .keyword("amount due", 2.0)
+            .keyword("due date", 1.5)
+            .keyword("subtotal", 1.5)
+            .keyword("vat", 1.0)
+            .keyword("tax", 0.5)
+            .keyword("total", 1.0)
+            .keyword("qty", 1.0)
+            .keyword("unit price", 1.5)
+            .signal(TextSignal::CurrencyAmounts, 0.5),
+        CategoryRule::new(DocumentCategory::Resume)
+            .keyword("resume", 2.0)
+            .keyword("curriculum vitae", 3.0)
+            .keyword("work experience", 2.0)
+            .keyword("experience", 1.0)
+            .keyword("education", 1.5)
+            .keyword("skills", 1.5)
+            .keyword("references", 0.5)
+            .keyword("certifications", 1.0)
+            .signal(TextSignal::DateRanges, 0.75),
+        CategoryRule::new(DocumentCategory::Contract)
+            .keyword("agreement", 2.0)
+            .keyword("hereby", 1.5)
+            .keyword("whereas", 2.0)
+            .keyword("party", 1.0)
+            .keyword("parties", 1.5)
+            .keyword("terms and conditions", 1.5)
+            .keyword("governing law", 2.0)
+            .keyword("termination", 1.0)
+            .keyword("indemnify", 1.5)
+            .keyword("in witness whereof", 3.0)
+            .signal(TextSignal::NumberedClauses, 0.5),
+        CategoryRule::new(DocumentCategory::Article)
+            .keyword("abstract", 1.5)
+            .keyword("introduction", 1.0)
+            .keyword("conclusion", 1.0)
+            .keyword("according to", 0.5)
+            .keyword("published", 1.0)
+            .keyword("author", 0.5)
+            .signal(TextSignal::LongParagraphs, 1.0),
+    ]
+}
+
+/// Classifies `text` with `rules`. Returns the best category and a confidence in `0.0..=1.0`,
+/// the share of the best score in the total score of all rules
+pub(crate) fn classify_text(rules: &[CategoryRule], text: &str) -> (DocumentCategory, f32) {
+    let words = words(text);
+    let scores: Vec<(&CategoryRule, f32)> = rules
+        .iter()
+        .map(|rule| (rule, rule.score(&words, text)))
+        .collect();
+    let total: f32 = scores.iter().map(|(_, score)| score).sum();
+
+    match scores.iter().max_by(|a, b| a.1.total_cmp(&b.1)) {
+        Some((rule, score)) if *score >= MIN_SCORE => (rule.category.clone(), score / total),
+        _ => (DocumentCategory::Unknown, 0.0),
+    }
+}
+
+/// Lowercase words of the text, punctuation removed
+fn words(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect()
+}
+
+/// Counts the occurrences of a lowercase phrase in the words of a text
+fn count_phrase(words: &[String], phrase: &str) -> usize {
+    let phrase: Vec<&str> = phrase.split_whitespace().collect();
+    if phrase.is_empty() {
+        return 0;
+    }
+    words
+        .windows(phrase.len())
+        .filter(|window| window.iter().zip(&phrase).all(|(w, p)| w == p))
+        .count()
+}
+
+fn count_signal(signal: TextSignal, text: &str) -> usize {
+    let tokens: Vec<&str> = text.split_whitespace().collect();
+    match signal {
+        TextSignal::CurrencyAmounts => tokens
+            .iter()
+            .enumerate()
+            .filter(|(i, token)| {
+                let currency_symbol = token.starts_with(['$', '€', '£', '¥']);
+                let currency_code = tokens
+                    .get(i + 1)
+                    .is_some_and(|next| matches!(*next, "USD" | "EUR" | "GBP" | "CHF"));
+                is_decimal_amount(token.trim_start_matches(['$', '€', '£', '¥']))
+                    && (currency_symbol || currency_code || token.contains('.'))
+            })
+            .count(),
+        TextSignal::DateRanges => tokens
+            .windows(3)
+            .filter(|w| {
+                is_year(w[0])
+                    && matches!(w[1], "-" | "–" | "—" | "to")
+                    && (is_year(w[2]) || w[2].eq_ignore_ascii_case("present"))
+            })
+            .count(),
+        TextSignal::NumberedClauses => {
+            text.lines()
+                .filter(|line| {
+                    let first = line.split_whitespace().next().unwrap_or("");
+                    let numbered = first.trim_end_matches('.').split('.').count() > 1
+                        && first.trim_end_matches('.').split('.').all(|part| {
+                            !part.is_empty() && part.chars().all(|c| c.is_ascii_digit())
+                        });
+                    numbered || first.eq_ignore_ascii_case("section")
+                })
+                .count()
+        }
+        TextSignal::LongParagraphs => text
+            .split("\n\n")
+            .filter(|paragraph| paragraph.split_whitespace().count() >= 40)
+            .count(),
+    }
+}
+
+/// `1,200.00` or `99.90`, with exactly two decimals
+fn is_decimal_amount(token: &str) -> bool {
+    let token = token.trim_end_matches([',', ';']);
+    match token.rsplit_once('.') {
+        Some((whole, cents)) => {
+            cents.len() == 2
+                && cents.chars().all(|c| c.is_ascii_digit())
+                && !whole.is_empty()
+                && whole.chars().all(|c| c.is_ascii_digit() || c == ',')
+        }
+        None => false,
+    }
+}
+
+fn is_year(token: &str) -> bool {
+    let token = token.trim_matches(|c: char| !c.is_ascii_digit());
+    token.len() == 4
+        && token
+            .parse::<u32>()
+            .is_ok_and(|year| (1900..=2100).contains(&year))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INVOICE: &str = "ACME Supplies Ltd.\n\
+        INVOICE\n\
+        Invoice Number: INV-2024-0042\n\
+        Bill To: Globex Corporation\n\
+        Due Date: 30 April 2024\n\n\
+        Description      Qty   Unit Price   Amount\n\
+        Paper, A4 box     10   $24.50       $245.00\n\
+        Toner cartridge    2   $89.99       $179.98\n\n\
+        Subtotal $424.98\n\
+        VAT 20% $85.00\n\
+        Total Amount Due $509.98\n";
+
+    #[test]
+    fn test_classify_invoice() {
+        let (category, confidence) = classify_text(&default_rules(), INVOICE);
+        assert_eq!(category, DocumentCategory::Invoice);
+        assert!(confidence > 0.5, "confidence was {}", confidence);
+    }
+
+    #[test]
+    fn test_classify_unknown() {
+        let (category, confidence) = classify_text(&default_rules(), "Shopping list: eggs, milk");
+        assert_eq!(category, DocumentCategory::Unknown);
+        assert_eq!(confidence, 0.0);
+    }
+
+    #[test]
+    fn test_custom_rule() {
+        let mut rules = default_rules();
+        rules.push(
+            CategoryRule::new(DocumentCategory::Custom("Recipe".to_string()))
+                .keyword("ingredients", 3.0)
+                .keyword("preheat", 2.0)
+                .keyword("tablespoon", 1.0),
+        );
+        let text = "Ingredients: 2 eggs, 1 tablespoon sugar. Preheat the oven to 180C.";
+
+        let (category, _) = classify_text(&rules, text);
+        assert_eq!(category.to_string(), "Recipe");
+    }
+}
@@ -0,0 +1,142 @@
+//! Decompression of gzip, bzip2 and xz files, see `Extractor::set_max_decompressed_size`
+
+use crate::errors::{Error, ExtractResult};
+use crate::Compression;
+use std::io::{self, Read, Write};
+
+/// Decompresses `data`, concatenated members included. Fails with
+/// `Error::DecompressedTooLarge` as soon as the output grows past `limit` bytes, so a small
+/// decompression bomb is stopped before it takes up the memory it expands to
+pub(crate) fn decompress(
+    data: &[u8],
+    compression: Compression,
+    limit: u64,
+) -> ExtractResult<Vec<u8>> {
+    let mut output = LimitedWriter {
+        buffer: Vec::new(),
+        limit,
+    };
+    let result = match compression {
+        Compression::Gzip => {
+            io::copy(&mut flate2::read::MultiGzDecoder::new(data), &mut output).map(|_| ())
+        }
+        Compression::Bzip2 => {
+            io::copy(&mut bzip2::read::MultiBzDecoder::new(data), &mut output).map(|_| ())
+        }
+        Compression::Xz => lzma_rs::xz_decompress(&mut io::BufReader::new(data), &mut output)
+            .map_err(|e| match e {
+                lzma_rs::error::Error::IoError(e) => e,
+                e => io::Error::new(io::ErrorKind::InvalidData, e.to_string()),
+            }),
+    };
+
+    match result {
+        Ok(()) => Ok(output.buffer),
+        Err(e) if e.kind() == io::ErrorKind::WriteZero => {
+            Err(Error::DecompressedTooLarge { limit })
+        }
+        Err(e) => Err(Error::ParseError(format!(
+            "Failed to decompress {} data: {}",
+            compression.mime_type(),
+            e
+        ))),
+    }
+}
+
+/// Collects the decompressed bytes and refuses to write past the limit
+struct LimitedWriter {
+    buffer: Vec<u8>,
+    limit: u64,
+}
+
+impl Write for LimitedWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.buffer.len() as u64 + buf.len() as u64 > self.limit {
+            return Err(io::Error::new(
+                io::ErrorKind::WriteZero,
+                "decompressed size limit reached",
+            ));
+        }
+        self.buffer.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Reads the first bytes of the file at `path` and decompresses the whole file when they are
+/// the magic bytes of a compression format. `None` for files that are not compressed, and for
+/// files that cannot be opened, whose error is left to the extraction
+pub(crate) fn decompress_file(path: &str, limit: u64) -> ExtractResult<Option<Vec<u8>>> {
+    let Ok(mut file) = std::fs::File::open(path) else {
+        return Ok(None);
+    };
+    let mut header = Vec::with_capacity(6);
+    if (&mut file).take(6).read_to_end(&mut header).is_err() {
+        return Ok(None);
+    }
+    let Some(compression) = crate::detect_compression(&header) else {
+        return Ok(None);
+    };
+
+    let mut data = header;
+    file.read_to_end(&mut data)
+        .map_err(|e| Error::IoError(e.to_string()))?;
+    decompress(&data, compression, limit).map(Some)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn compress(data: &[u8], compression: Compression) -> Vec<u8> {
+        match compression {
+            Compression::Gzip => {
+                let mut encoder =
+                    flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+                encoder.write_all(data).unwrap();
+                encoder.finish().unwrap()
+            }
+            Compression::Bzip2 => {
+                let mut encoder =
+                    bzip2::write::BzEncoder::new(Vec::new(), bzip2::Compression::default());
+                encoder.write_all(data).unwrap();
+                encoder.finish().unwrap()
+            }
+            Compression::Xz => {
+                let mut compressed = Vec::new();
+                lzma_rs::xz_compress(&mut io::BufReader::new(data), &mut compressed).unwrap();
+                compressed
+            }
+        }
+    }
+
+    #[test]
+    fn test_decompress() {
+        let text = "name,city\nZoë,Zürich\n".repeat(100);
+        for compression in [Compression::Gzip, Compression::Bzip2, Compression::Xz] {
+            let compressed = compress(text.as_bytes(), compression);
+            assert_eq!(crate::detect_compression(&compressed), Some(compression));
+            let decompressed = decompress(&compressed, compression, 1 << 20).unwrap();
+            assert_eq!(decompressed, text.as_bytes(), "{:?}", compression);
+        }
+    }
+
+    #[test]
+    fn test_decompress_stops_at_the_limit() {
+        let bomb = vec![0u8; 1 << 20];
+        for compression in [Compression::Gzip, Compression::Bzip2, Compression::Xz] {
+            let compressed = compress(&bomb, compression);
+            assert!(matches!(
+                decompress(&compressed, compression, 64 * 1024),
+                Err(Error::DecompressedTooLarge { limit: 65536 })
+            ));
+        }
+        assert!(matches!(
+            decompress(b"\x1F\x8B\x08not gzip", Compression::Gzip, 1024),
+            Err(Error::ParseError(_))
+        ));
+    }
+}
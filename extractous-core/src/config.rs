@@ -1,3 +1,6 @@
+use crate::errors::{Error, ExtractResult};
+use std::collections::HashMap;
+use std::path::PathBuf;
 use strum_macros::{Display, EnumString};
 
 /// OCR Strategy for PDF parsing
@@ -11,6 +14,105 @@ pub enum PdfOcrStrategy {
     AUTO,
 }
 
+/// How tables are rendered by the pure Rust parsers
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Display, EnumString)]
+pub enum TableRendering {
+    /// Table cells are flattened into the surrounding text
+    #[default]
+    Flat,
+    /// Table rows are separated by newlines and cells by tabs
+    Tabs,
+    /// Table rows are separated by newlines and cells are padded with spaces so that the columns
+    /// line up in a monospace font
+    AlignedColumns,
+}
+
+/// How footnotes are handled by the pure Rust DOCX and PDF parsers
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Display, EnumString)]
+pub enum FootnoteHandling {
+    /// Footnotes are kept where they appear in the document
+    #[default]
+    Inline,
+    /// Footnotes are collected and appended after the body text, under a separator
+    Endnotes,
+    /// Footnotes are removed from the extracted text
+    Drop,
+}
+
+/// What happens to the targets of hyperlinks, e.g. the `href` of an HTML `<a>`, in the
+/// extracted text. Links within the document, such as `#section-2`, are always dropped
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Display, EnumString)]
+pub enum LinkMode {
+    /// Only the text of a link is kept
+    #[default]
+    Drop,
+    /// The target follows the text of the link in parentheses, `text (https://...)`
+    Inline,
+    /// The text of the link is followed by a number, `text [1]`, and the numbered targets are
+    /// appended after the body text, under a separator
+    Footnotes,
+}
+
+/// How text that mixes right-to-left scripts, such as Arabic or Hebrew, with left-to-right
+/// text is ordered. Documents store text in logical (reading) order, the reordering turns it
+/// into visual (display) order for consumers without bidi support.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Display, EnumString)]
+pub enum BidiReordering {
+    /// The text is kept in logical order
+    #[default]
+    Off,
+    /// Every paragraph is reordered with the base direction of the document, given by its first
+    /// strong character
+    Global,
+    /// Every paragraph is reordered with its own base direction, given by its first strong
+    /// character or, for DOCX, its `w:bidi` property
+    PerParagraph,
+}
+
+/// Unicode normalization form applied to the extracted text
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Display, EnumString)]
+pub enum NormalizationForm {
+    /// Canonical composition, e.g. `e` followed by a combining acute accent becomes `é`
+    Nfc,
+    /// Canonical decomposition, `é` becomes `e` followed by a combining acute accent
+    Nfd,
+    /// Compatibility composition, also replaces compatibility characters such as the `ﬁ`
+    /// ligature by `fi` or the superscript `²` by `2`
+    Nfkc,
+    /// Compatibility decomposition
+    Nfkd,
+}
+
+/// The line endings of the extracted text
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Display, EnumString)]
+pub enum LineEnding {
+    /// The line endings are kept as the parser wrote them, possibly mixed
+    #[default]
+    Preserve,
+    /// Every `\r\n` and lone `\r` becomes `\n`
+    Lf,
+    /// Every `\n` and lone `\r` becomes `\r\n`
+    Crlf,
+}
+
+/// The Tika content handler that turns the parsed document into text.
+///
+/// Tika parsers emit XHTML. `Body` and `Text` both write its character data, including the
+/// newlines Tika inserts after block elements such as `<p>`, `<div>` or `<li>`. `Body` only
+/// writes the content of `<body>`, while `Text` also writes the `<head>`, i.e. the document
+/// title and the newlines that follow the `<title>` and `<meta>` elements. `Xhtml` writes the
+/// XHTML markup itself, like `Extractor::set_xml_output`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Display, EnumString)]
+pub enum TikaHandler {
+    /// All text of the XHTML, head included
+    Text,
+    /// The text of the XHTML body, Tika's `BodyContentHandler`
+    #[default]
+    Body,
+    /// The XHTML markup, Tika's `ToXMLContentHandler`
+    Xhtml,
+}
+
 /// PDF parsing configuration settings
 ///
 /// These settings are used to configure the behavior of the PDF parsing.
@@ -21,6 +123,10 @@ pub struct PdfParserConfig {
     pub(crate) extract_unique_inline_images_only: bool,
     pub(crate) extract_marked_content: bool,
     pub(crate) extract_annotation_text: bool,
+    pub(crate) extract_acroform_content: bool,
+    pub(crate) sort_by_position: bool,
+    pub(crate) respect_ocg_visibility: bool,
+    pub(crate) password: Option<Password>,
 }
 
 impl Default for PdfParserConfig {
@@ -31,6 +137,10 @@ impl Default for PdfParserConfig {
             extract_unique_inline_images_only: false,
             extract_marked_content: false,
             extract_annotation_text: true,
+            extract_acroform_content: true,
+            sort_by_position: false,
+            respect_ocg_visibility: false,
+            password: None,
         }
     }
 }
@@ -90,6 +200,56 @@ impl PdfParserConfig {
         self.extract_annotation_text = val;
         self
     }
+
+    /// If true, extract the names and values of the fields of fillable forms (AcroForm), after
+    /// the text of the pages. See `Extractor::extract_pdf_form_fields` to get them separately.
+    /// Default: true.
+    pub fn set_extract_acroform_content(mut self, val: bool) -> Self {
+        self.extract_acroform_content = val;
+        self
+    }
+
+    /// If true, put the text in reading order by its position on the page instead of the order
+    /// it is drawn in, for PDFs that draw their text out of order. Tika sorts the text top to
+    /// bottom and left to right, with PDFBox's `setSortByPosition`. The pure-rust parser also
+    /// keeps the columns of multi-column pages apart, reading each column before the one to its
+    /// right, and leaves footnotes inline.
+    /// Default: false.
+    pub fn set_sort_by_position(mut self, val: bool) -> Self {
+        self.sort_by_position = val;
+        self
+    }
+
+    /// If true, leave out the text of the optional content groups (layers) that are hidden by
+    /// default, e.g. a watermark layer only shown when printing. Tika and the pure-rust parser
+    /// are given a copy of the PDF without the hidden content of its pages, so this needs the
+    /// `pure-rust` feature, without it extraction fails with `Error::InvalidConfiguration`. Text
+    /// of hidden layers inside form XObjects is kept unless the whole XObject belongs to a hidden
+    /// layer.
+    /// Default: false.
+    pub fn set_respect_ocg_visibility(mut self, val: bool) -> Self {
+        self.respect_ocg_visibility = val;
+        self
+    }
+
+    /// Sets the password of encrypted (password protected) PDFs. Without it, or with a wrong
+    /// password, encrypted PDFs fail with `Error::EncryptedDocument`, unless they open with an
+    /// empty user password.
+    /// Default: None
+    pub fn set_password(mut self, val: String) -> Self {
+        self.password = Some(Password(val));
+        self
+    }
+
+    /// Checks that the settings that need an optional feature of this crate are only used with it
+    pub(crate) fn check_features(&self) -> ExtractResult<()> {
+        if self.respect_ocg_visibility && !cfg!(feature = "pure-rust") {
+            return Err(Error::InvalidConfiguration(
+                "respect_ocg_visibility needs the pure-rust feature".to_string(),
+            ));
+        }
+        Ok(())
+    }
 }
 
 /// Microsoft Office parser configuration settings
@@ -107,6 +267,18 @@ pub struct OfficeParserConfig {
     pub(crate) include_slide_master_content: bool,
     pub(crate) concatenate_phonetic_runs: bool,
     pub(crate) extract_all_alternatives_from_msg: bool,
+    pub(crate) include_comments: bool,
+    pub(crate) password: Option<Password>,
+}
+
+/// A document password, kept out of `Debug` output
+#[derive(Clone, PartialEq)]
+pub(crate) struct Password(pub(crate) String);
+
+impl std::fmt::Debug for Password {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("Password(***)")
+    }
 }
 
 impl Default for OfficeParserConfig {
@@ -122,6 +294,8 @@ impl Default for OfficeParserConfig {
             include_slide_master_content: true,
             concatenate_phonetic_runs: true,
             extract_all_alternatives_from_msg: false,
+            include_comments: true,
+            password: None,
         }
     }
 }
@@ -146,6 +320,14 @@ impl OfficeParserConfig {
         self
     }
 
+    /// Sets whether tracked changes are kept, i.e. both `set_include_deleted_content` and
+    /// `set_include_move_from_content`, for reviewing what was removed or moved in a document.
+    /// Default: false
+    pub fn set_include_moved_deleted(self, val: bool) -> Self {
+        self.set_include_deleted_content(val)
+            .set_include_move_from_content(val)
+    }
+
     /// With track changes on for the docx parser, when a section is moved, the content is stored in
     /// both the "moveFrom" section and in the "moveTo" section. If you'd like to include the
     /// section both in its original location (moveFrom) and in its new location (moveTo),
@@ -165,8 +347,9 @@ impl OfficeParserConfig {
     }
 
     /// Whether to include headers and footers. This only operates on headers and footers in
-    /// Word and Excel, not master slide content in PowerPoint.
-    /// Default: true
+    /// Word and Excel, not master slide content in PowerPoint. The pure Rust docx parser
+    /// honors it too.
+    /// Default: false
     pub fn set_include_headers_and_footers(mut self, val: bool) -> Self {
         self.include_headers_and_footers = val;
         self
@@ -215,6 +398,164 @@ impl OfficeParserConfig {
         self.extract_all_alternatives_from_msg = val;
         self
     }
+
+    /// Sets whether the comments of Word documents are included, after the paragraph they are
+    /// anchored in. Tika has no option for this, without comments it is given a copy of the docx
+    /// whose `word/comments.xml` is empty.
+    /// Default: true
+    pub fn set_include_comments(mut self, val: bool) -> Self {
+        self.include_comments = val;
+        self
+    }
+
+    /// Sets the password of encrypted (password protected) Office documents. Tika decrypts them,
+    /// and the pure-rust parsers decrypt docx and xlsx with the `office-encryption` feature.
+    /// Without a password, or with a wrong one, encrypted documents fail with
+    /// `Error::EncryptedDocument`.
+    /// Default: None
+    pub fn set_password(mut self, val: String) -> Self {
+        self.password = Some(Password(val));
+        self
+    }
+}
+
+/// Text cleaning steps for the formats they are configured for, see
+/// `Extractor::set_cleaning_profile_for`. Steps that help one format can hurt another, e.g.
+/// collapsing whitespace cleans up PDF text but destroys the rows of a CSV.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct CleaningOptions {
+    pub(crate) normalize_whitespace: bool,
+    pub(crate) dehyphenate: bool,
+    pub(crate) remove_headers_footers: bool,
+}
+
+impl CleaningOptions {
+    /// Creates a new instance of CleaningOptions with every cleaning step disabled.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a new instance of CleaningOptions with every cleaning step enabled.
+    pub fn aggressive() -> Self {
+        Self {
+            normalize_whitespace: true,
+            dehyphenate: true,
+            remove_headers_footers: true,
+        }
+    }
+
+    /// Collapse every run of whitespace, line breaks included, into a single space.
+    /// Source code files keep their whitespace, see `Extractor::set_preserve_code_formatting`.
+    /// Default: false
+    pub fn set_normalize_whitespace(mut self, val: bool) -> Self {
+        self.normalize_whitespace = val;
+        self
+    }
+
+    /// Join the words hyphenated at the end of a line, see [`crate::dehyphenate`].
+    /// Default: false
+    pub fn set_dehyphenate(mut self, val: bool) -> Self {
+        self.dehyphenate = val;
+        self
+    }
+
+    /// Remove running headers and footers, such as page numbers. Only applies to PDFs
+    /// extracted by `Extractor::extract_file_to_string`, whose pages can be told apart,
+    /// see [`crate::remove_repeated_headers_footers`].
+    /// Default: false
+    pub fn set_remove_headers_footers(mut self, val: bool) -> Self {
+        self.remove_headers_footers = val;
+        self
+    }
+}
+
+/// Granularity of the text cleaning of `Extractor::set_enable_text_cleaning`, see
+/// `Extractor::set_text_cleaning_config`. Unlike the default cleaning, which collapses all
+/// whitespace into single spaces, line breaks are kept and the characters that carry layout can
+/// be preserved.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextCleaningConfig {
+    pub(crate) preserve_page_breaks: bool,
+    pub(crate) preserve_tabs: bool,
+    pub(crate) collapse_blank_lines: bool,
+}
+
+impl Default for TextCleaningConfig {
+    fn default() -> Self {
+        Self {
+            preserve_page_breaks: true,
+            preserve_tabs: false,
+            collapse_blank_lines: true,
+        }
+    }
+}
+
+impl TextCleaningConfig {
+    /// Creates a new instance of TextCleaningConfig with the default settings.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Replace form feeds, which mark page breaks, with a blank line instead of a space.
+    /// Default: true
+    pub fn set_preserve_page_breaks(mut self, val: bool) -> Self {
+        self.preserve_page_breaks = val;
+        self
+    }
+
+    /// Keep tabs, e.g. between the cells of a table row, instead of replacing them with spaces.
+    /// Default: false
+    pub fn set_preserve_tabs(mut self, val: bool) -> Self {
+        self.preserve_tabs = val;
+        self
+    }
+
+    /// Collapse runs of blank lines into a single blank line.
+    /// Default: true
+    pub fn set_collapse_blank_lines(mut self, val: bool) -> Self {
+        self.collapse_blank_lines = val;
+        self
+    }
+}
+
+/// Settings of [`crate::clean_document_text_with_config`], which trims the lines of a document
+/// and cleans each of them with [`crate::clean_text_fast`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct DocumentCleaningConfig {
+    pub(crate) collapse_blank_lines: bool,
+    pub(crate) drop_short_lines: bool,
+}
+
+impl Default for DocumentCleaningConfig {
+    fn default() -> Self {
+        Self {
+            collapse_blank_lines: true,
+            drop_short_lines: false,
+        }
+    }
+}
+
+impl DocumentCleaningConfig {
+    /// Creates a new instance of DocumentCleaningConfig with the default settings.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Collapse runs of blank lines into a single blank line. When false, blank lines are
+    /// removed and the lines of text follow each other directly.
+    /// Default: true
+    pub fn set_collapse_blank_lines(mut self, val: bool) -> Self {
+        self.collapse_blank_lines = val;
+        self
+    }
+
+    /// Drop lines shorter than 3 characters and upper case lines shorter than 20, which are
+    /// often page numbers or running headers, but can also be headings.
+    /// Default: false
+    pub fn set_drop_short_lines(mut self, val: bool) -> Self {
+        self.drop_short_lines = val;
+        self
+    }
 }
 
 /// Tesseract OCR configuration settings
@@ -228,6 +569,11 @@ pub struct TesseractOcrConfig {
     pub(crate) enable_image_preprocessing: bool,
     pub(crate) apply_rotation: bool,
     pub(crate) language: String,
+    pub(crate) tessdata_path: Option<PathBuf>,
+    pub(crate) tesseract_config: HashMap<String, String>,
+    pub(crate) max_ocr_image_pixels: Option<u64>,
+    /// Set by `Extractor::set_ocr_enabled`, turns Tika's image OCR parser off
+    pub(crate) skip_ocr: bool,
 }
 
 impl Default for TesseractOcrConfig {
@@ -239,6 +585,10 @@ impl Default for TesseractOcrConfig {
             enable_image_preprocessing: false,
             apply_rotation: false,
             language: "eng".to_string(),
+            tessdata_path: None,
+            tesseract_config: HashMap::new(),
+            max_ocr_image_pixels: None,
+            skip_ocr: false,
         }
     }
 }
@@ -293,4 +643,91 @@ impl TesseractOcrConfig {
         self.timeout_seconds = val;
         self
     }
+
+    /// Sets the directory containing the tesseract `.traineddata` language files, like the
+    /// `TESSDATA_PREFIX` environment variable but for this config only. Extraction fails with
+    /// `Error::OcrLanguageMissing` if a language set with `set_language` has no data file there.
+    /// Default: None, tesseract uses its built-in location or `TESSDATA_PREFIX`.
+    pub fn set_tessdata_path(mut self, val: PathBuf) -> Self {
+        self.tessdata_path = Some(val);
+        self
+    }
+
+    /// Sets tesseract config variables, passed as `-c key=value` to every tesseract run, e.g.
+    /// `preserve_interword_spaces` set to `1` or a custom `tessedit_char_whitelist`. Keys and
+    /// values may only contain ASCII letters, digits and `-_./`, extraction fails with
+    /// `Error::InvalidConfiguration` for others.
+    /// Default: empty
+    pub fn set_tesseract_config(mut self, val: HashMap<String, String>) -> Self {
+        self.tesseract_config = val;
+        self
+    }
+
+    /// Sets the maximum number of pixels of an image that is OCRed. PNG images with more pixels
+    /// are downscaled by averaging blocks of pixels before tesseract reads them, which bounds the
+    /// memory of OCR on very high resolution scans. The metadata of a downscaled image gets an
+    /// `OCR-Downscaled` note with the original and the new size, e.g. `4800x800 to 2400x400`.
+    /// Needs the `ocr-downscale` feature, without it extraction fails with
+    /// `Error::InvalidConfiguration`. Other image formats are OCRed at their full resolution.
+    /// Default: None, images are never downscaled.
+    pub fn set_max_ocr_image_pixels(mut self, val: u64) -> Self {
+        self.max_ocr_image_pixels = Some(val);
+        self
+    }
+
+    /// Checks that the tessdata directory, if set, exists and has a `.traineddata` file for every
+    /// language
+    pub(crate) fn check_tessdata(&self) -> ExtractResult<()> {
+        let Some(tessdata_path) = self.tessdata_path.as_ref().filter(|_| !self.skip_ocr) else {
+            return Ok(());
+        };
+        if !tessdata_path.is_dir() {
+            return Err(Error::InvalidConfiguration(format!(
+                "The tessdata directory {:?} does not exist",
+                tessdata_path
+            )));
+        }
+        for language in self.language.split('+').filter(|l| !l.is_empty()) {
+            // Script languages such as "script/Arabic" live in a subdirectory
+            let traineddata = tessdata_path.join(format!("{}.traineddata", language));
+            if !traineddata.is_file() {
+                return Err(Error::OcrLanguageMissing {
+                    language: language.to_string(),
+                    tessdata_path: tessdata_path.clone(),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks that the settings that need an optional feature of this crate are only used with it
+    pub(crate) fn check_features(&self) -> ExtractResult<()> {
+        if self.max_ocr_image_pixels.is_some() && !cfg!(feature = "ocr-downscale") {
+            return Err(Error::InvalidConfiguration(
+                "max_ocr_image_pixels needs the ocr-downscale feature".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Checks that the tesseract config variables only use the characters Tika accepts
+    pub(crate) fn check_tesseract_config(&self) -> ExtractResult<()> {
+        let allowed = |text: &str| {
+            !text.is_empty()
+                && text
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.' | '/'))
+        };
+        let invalid = self
+            .tesseract_config
+            .iter()
+            .find(|(key, value)| !allowed(key) || !allowed(value));
+        match invalid {
+            Some((key, value)) => Err(Error::InvalidConfiguration(format!(
+                "Invalid tesseract config variable {}={}",
+                key, value
+            ))),
+            None => Ok(()),
+        }
+    }
 }
@@ -0,0 +1,187 @@
+//! Detection of cross-references in extracted text, such as the citation `[12]`, the section
+//! reference `see Section 3.2` or the figure reference `Fig. 4`
+
+/// The kind of a [`Reference`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RefKind {
+    /// A bracketed citation such as `[12]`, `[3, 5]` or `[1-4]`
+    Citation,
+    /// A reference to a section such as `Section 3.2`, `Sec. 3` or `§ 4`
+    SectionRef,
+    /// A reference to a figure such as `Figure 2`, `Fig. 4` or `Fig. 4b`
+    FigureRef,
+    /// A reference to a table such as `Table 1` or `Tab. 1`
+    TableRef,
+}
+
+/// A reference found by [`extract_references`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Reference {
+    pub kind: RefKind,
+    /// The reference as it appears in the text
+    pub raw: String,
+    /// Byte offsets of the start and the end (exclusive) of the reference in the text
+    pub span: (usize, usize),
+}
+
+/// Words that introduce a numbered reference, matched case insensitively at the start of a word.
+/// Longer words come first, so `sections` is not taken for `section`
+const REFERENCE_WORDS: &[(&str, RefKind)] = &[
+    ("sections", RefKind::SectionRef),
+    ("section", RefKind::SectionRef),
+    ("sec.", RefKind::SectionRef),
+    ("§", RefKind::SectionRef),
+    ("figures", RefKind::FigureRef),
+    ("figure", RefKind::FigureRef),
+    ("figs.", RefKind::FigureRef),
+    ("fig.", RefKind::FigureRef),
+    ("tables", RefKind::TableRef),
+    ("table", RefKind::TableRef),
+    ("tab.", RefKind::TableRef),
+];
+
+/// Finds the citations and the section, figure and table references in `text`, in the order
+/// they appear. Only numbered references are found, `the table below` is not a reference
+pub fn extract_references(text: &str) -> Vec<Reference> {
+    let mut references = Vec::new();
+    let mut start = 0;
+    while let Some(c) = text[start..].chars().next() {
+        let rest = &text[start..];
+        let at_word_start = !text[..start]
+            .chars()
+            .next_back()
+            .is_some_and(char::is_alphanumeric);
+        let found = if c == '[' {
+            citation_len(rest).map(|len| (RefKind::Citation, len))
+        } else if at_word_start {
+            numbered_reference(rest)
+        } else {
+            None
+        };
+
+        match found {
+            Some((kind, len)) => {
+                references.push(Reference {
+                    kind,
+                    raw: rest[..len].to_string(),
+                    span: (start, start + len),
+                });
+                start += len;
+            }
+            None => start += c.len_utf8(),
+        }
+    }
+    references
+}
+
+/// The length of the bracketed citation at the start of `text`, brackets included. The
+/// brackets hold numbers separated by commas or ranges
+fn citation_len(text: &str) -> Option<usize> {
+    let mut previous = '[';
+    for (offset, c) in text.char_indices().skip(1) {
+        match c {
+            ']' if previous.is_ascii_digit() => return Some(offset + 1),
+            '0'..='9' => {}
+            ',' | ' ' | '-' | '\u{2013}' if previous != '[' => {}
+            _ => return None,
+        }
+        previous = c;
+    }
+    None
+}
+
+/// The kind and the length of the section, figure or table reference at the start of `text`
+fn numbered_reference(text: &str) -> Option<(RefKind, usize)> {
+    let &(word, kind) = REFERENCE_WORDS.iter().find(|(word, _)| {
+        text.get(..word.len())
+            .is_some_and(|prefix| prefix.eq_ignore_ascii_case(word))
+    })?;
+
+    let after_word = &text[word.len()..];
+    let spaces = after_word.len() - after_word.trim_start_matches([' ', '\u{a0}']).len();
+    // `Table2` is a word of its own, but `§4` and `Fig.4` are references
+    if spaces == 0 && word.ends_with(|c: char| c.is_ascii_alphabetic()) {
+        return None;
+    }
+
+    let number_start = word.len() + spaces;
+    match number_len(&text[number_start..]) {
+        0 => None,
+        len => Some((kind, number_start + len)),
+    }
+}
+
+/// The length of the number at the start of `text`, e.g. `3`, `3.2.1` or `4b`. A trailing
+/// full stop is not part of the number. Returns 0 when there is no number or it runs into a word
+fn number_len(text: &str) -> usize {
+    let bytes = text.as_bytes();
+    let digits = |from: usize| {
+        bytes[from..]
+            .iter()
+            .take_while(|b| b.is_ascii_digit())
+            .count()
+    };
+
+    let mut len = digits(0);
+    if len == 0 {
+        return 0;
+    }
+    while bytes.get(len) == Some(&b'.') && digits(len + 1) > 0 {
+        len += 1 + digits(len + 1);
+    }
+    // A sub-figure letter as in `Fig. 4b`
+    if bytes.get(len).is_some_and(u8::is_ascii_lowercase)
+        && !bytes.get(len + 1).is_some_and(u8::is_ascii_alphanumeric)
+    {
+        len += 1;
+    }
+
+    if bytes.get(len).is_some_and(u8::is_ascii_alphanumeric) {
+        0
+    } else {
+        len
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_references() {
+        let text = "Prior work [12] differs, see Section 3.2. Results are in Fig. 4b and Table 1.";
+        let references = extract_references(text);
+
+        let found: Vec<(RefKind, &str, (usize, usize))> = references
+            .iter()
+            .map(|reference| (reference.kind, reference.raw.as_str(), reference.span))
+            .collect();
+        assert_eq!(
+            found,
+            [
+                (RefKind::Citation, "[12]", (11, 15)),
+                (RefKind::SectionRef, "Section 3.2", (29, 40)),
+                (RefKind::FigureRef, "Fig. 4b", (57, 64)),
+                (RefKind::TableRef, "Table 1", (69, 76)),
+            ]
+        );
+        for reference in &references {
+            assert_eq!(&text[reference.span.0..reference.span.1], reference.raw);
+        }
+    }
+
+    #[test]
+    fn test_extract_references_ignores_lookalikes() {
+        let text =
+            "Café [3, 5–7] and §4, but not [a], [12, ], the table below, Tables or Subsection 2";
+        let references = extract_references(text);
+
+        let raw: Vec<&str> = references
+            .iter()
+            .map(|reference| reference.raw.as_str())
+            .collect();
+        assert_eq!(raw, ["[3, 5–7]", "§4"]);
+        // Spans are byte offsets, the é takes two bytes
+        assert_eq!(references[0].span, (6, 16));
+    }
+}
@@ -1,8 +1,33 @@
 use std::io;
+use std::path::PathBuf;
 use std::str::Utf8Error;
+use std::time::Duration;
+
+/// The category of an [`Error`], for matching without inspecting error messages
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ErrorKind {
+    Unknown,
+    Io,
+    Parse,
+    Utf8,
+    Jni,
+    UnsupportedFormat,
+    UnsupportedScheme,
+    FileTooLarge,
+    DecompressedTooLarge,
+    Timeout,
+    OcrLanguageMissing,
+    InvalidConfiguration,
+    MemberNotFound,
+    ObjectNotFound,
+    EncryptedDocument,
+    NativeInitFailed,
+}
 
 /// Represent errors returned by extractous
 #[derive(thiserror::Error, Debug)]
+#[non_exhaustive]
 pub enum Error {
     #[error("{0}")]
     Unknown(String),
@@ -13,6 +38,24 @@ pub enum Error {
     #[error("{0}")]
     ParseError(String),
 
+    #[error("{0}")]
+    UnsupportedFormat(String),
+
+    #[error("{0}")]
+    UnsupportedScheme(String),
+
+    #[error("{0}")]
+    InvalidConfiguration(String),
+
+    #[error("Member {0} not found in the container")]
+    MemberNotFound(String),
+
+    #[error("PDF object {obj} {gen} R not found")]
+    ObjectNotFound { obj: u32, gen: u16 },
+
+    #[error("{0}")]
+    EncryptedDocument(String),
+
     #[error("{0}")]
     Utf8Error(#[from] Utf8Error),
 
@@ -21,30 +64,94 @@ pub enum Error {
 
     #[error("{0}")]
     JniEnvCall(&'static str),
+
+    #[error("Failed to initialize the native Tika library: {0}")]
+    NativeInitFailed(String),
+
+    #[error("File size of {size} bytes exceeds the limit of {limit} bytes")]
+    FileTooLarge { size: u64, limit: u64 },
+
+    #[error("Decompressed content exceeds the limit of {limit} bytes")]
+    DecompressedTooLarge { limit: u64 },
+
+    #[error("Extraction timed out after {0:?}")]
+    Timeout(Duration),
+
+    #[error("Tesseract language data {language}.traineddata not found in {tessdata_path:?}")]
+    OcrLanguageMissing {
+        language: String,
+        tessdata_path: PathBuf,
+    },
+}
+
+impl Error {
+    /// Returns the category of this error
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            Error::Unknown(_) => ErrorKind::Unknown,
+            Error::IoError(_) => ErrorKind::Io,
+            Error::ParseError(_) => ErrorKind::Parse,
+            Error::UnsupportedFormat(_) => ErrorKind::UnsupportedFormat,
+            Error::UnsupportedScheme(_) => ErrorKind::UnsupportedScheme,
+            Error::InvalidConfiguration(_) => ErrorKind::InvalidConfiguration,
+            Error::MemberNotFound(_) => ErrorKind::MemberNotFound,
+            Error::ObjectNotFound { .. } => ErrorKind::ObjectNotFound,
+            Error::EncryptedDocument(_) => ErrorKind::EncryptedDocument,
+            Error::Utf8Error(_) => ErrorKind::Utf8,
+            Error::JniError(_) | Error::JniEnvCall(_) => ErrorKind::Jni,
+            Error::NativeInitFailed(_) => ErrorKind::NativeInitFailed,
+            Error::FileTooLarge { .. } => ErrorKind::FileTooLarge,
+            Error::DecompressedTooLarge { .. } => ErrorKind::DecompressedTooLarge,
+            Error::Timeout(_) => ErrorKind::Timeout,
+            Error::OcrLanguageMissing { .. } => ErrorKind::OcrLanguageMissing,
+        }
+    }
 }
 
 // Implement the conversion from our Error type to io::Error
 // This allows us to use the ? when implementing std::io traits such as: Read, Write Seek etc ...
 impl From<Error> for io::Error {
     fn from(err: Error) -> Self {
-        match err {
-            Error::IoError(msg) => {
-                io::Error::new(io::ErrorKind::Other, format!("Io error: {}", msg))
-            }
-            Error::ParseError(msg) => {
-                io::Error::new(io::ErrorKind::Other, format!("Parse error: {}", msg))
-            }
-            Error::Utf8Error(e) => {
-                io::Error::new(io::ErrorKind::Other, format!("UTF8 error: {}", e))
-            }
-            Error::JniError(e) => io::Error::new(io::ErrorKind::Other, format!("JNI error: {}", e)),
-            Error::JniEnvCall(msg) => {
-                io::Error::new(io::ErrorKind::Other, format!("JNI env call error: {}", msg))
+        let kind = match err {
+            Error::UnsupportedFormat(_) | Error::UnsupportedScheme(_) => io::ErrorKind::Unsupported,
+            Error::InvalidConfiguration(_) | Error::FileTooLarge { .. } => {
+                io::ErrorKind::InvalidInput
             }
-            _ => io::Error::new(io::ErrorKind::Other, "Unknown error"),
-        }
+            Error::MemberNotFound(_)
+            | Error::ObjectNotFound { .. }
+            | Error::OcrLanguageMissing { .. } => io::ErrorKind::NotFound,
+            Error::EncryptedDocument(_) => io::ErrorKind::PermissionDenied,
+            Error::DecompressedTooLarge { .. } => io::ErrorKind::InvalidData,
+            Error::Timeout(_) => io::ErrorKind::TimedOut,
+            _ => io::ErrorKind::Other,
+        };
+        // The message of the error is kept as it is, its Display already says what failed
+        io::Error::new(kind, err.to_string())
     }
 }
 
 /// Result that is a wrapper of Result<T, extractous::Error>
 pub type ExtractResult<T> = Result<T, Error>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn io_error_from_error_test() {
+        let err = io::Error::from(Error::Unknown("the reader was closed".to_string()));
+        assert_eq!(err.kind(), io::ErrorKind::Other);
+        assert_eq!(err.to_string(), "the reader was closed");
+
+        let err = io::Error::from(Error::NativeInitFailed("no isolate".to_string()));
+        assert_eq!(
+            err.to_string(),
+            "Failed to initialize the native Tika library: no isolate"
+        );
+
+        let err = io::Error::from(Error::ParseError("XML parse error".to_string()));
+        assert_eq!(err.to_string(), "XML parse error");
+        let err = io::Error::from(Error::Timeout(Duration::from_secs(2)));
+        assert_eq!(err.kind(), io::ErrorKind::TimedOut);
+    }
+}
@@ -0,0 +1,316 @@
+//! Structural events of Tika's XHTML output, as returned by `Extractor::extract_file_events`,
+//! for callers that process the document structure themselves
+
+use crate::chunk::unescape;
+use crate::StreamReader;
+use std::collections::HashMap;
+use std::io::{self, BufRead, BufReader};
+
+/// An event of the XHTML of a document, similar to the SAX events of Tika's content handlers
+#[derive(Debug, Clone, PartialEq)]
+pub enum XhtmlEvent {
+    /// The start of an element, e.g. `p`, with its attributes in document order
+    StartElement {
+        name: String,
+        attributes: Vec<(String, String)>,
+    },
+    /// The end of an element, a self-closing element also has one
+    EndElement { name: String },
+    /// The text between two tags, with the entities replaced
+    Characters(String),
+}
+
+/// Iterator over the [`XhtmlEvent`]s of a document. The XHTML is parsed while it is streamed
+/// from Tika, so the events of a large document are never held in memory at once. When reading
+/// from Tika fails, or the XHTML ends inside a tag, the error is the last item of the iteration.
+pub struct XhtmlEvents {
+    reader: BufReader<StreamReader>,
+    /// Whether the `<` of a tag has been read
+    in_tag: bool,
+    /// The end event of a self-closing element
+    pending: Option<XhtmlEvent>,
+    /// Whether an error ended the iteration
+    failed: bool,
+}
+
+impl XhtmlEvents {
+    /// Creates an iterator over the XHTML read from `reader`, which must be UTF-8 encoded
+    pub(crate) fn new(reader: StreamReader) -> Self {
+        Self {
+            reader: BufReader::new(reader),
+            in_tag: false,
+            pending: None,
+            failed: false,
+        }
+    }
+
+    /// The event of a tag without its angle brackets, None for declarations and comments
+    fn tag_event(&mut self, tag: &str) -> Option<XhtmlEvent> {
+        if tag.starts_with(['?', '!']) {
+            return None;
+        }
+        if let Some(name) = tag.strip_prefix('/') {
+            return Some(XhtmlEvent::EndElement {
+                name: name.trim().to_string(),
+            });
+        }
+
+        let (tag, self_closing) = match tag.strip_suffix('/') {
+            Some(tag) => (tag, true),
+            None => (tag, false),
+        };
+        let name_end = tag.find(char::is_whitespace).unwrap_or(tag.len());
+        let name = tag[..name_end].to_string();
+        if self_closing {
+            self.pending = Some(XhtmlEvent::EndElement { name: name.clone() });
+        }
+        Some(XhtmlEvent::StartElement {
+            name,
+            attributes: parse_attributes(&tag[name_end..]),
+        })
+    }
+
+    /// The next event, `None` at the end of the document
+    fn read_event(&mut self) -> io::Result<Option<XhtmlEvent>> {
+        if let Some(event) = self.pending.take() {
+            return Ok(Some(event));
+        }
+
+        loop {
+            if !self.in_tag {
+                let mut text = Vec::new();
+                if self.reader.read_until(b'<', &mut text)? == 0 {
+                    return Ok(None);
+                }
+                if text.last() == Some(&b'<') {
+                    text.pop();
+                    self.in_tag = true;
+                }
+                // `<` and `>` never occur inside a multi-byte UTF-8 sequence
+                if !text.is_empty() {
+                    let text = String::from_utf8_lossy(&text);
+                    return Ok(Some(XhtmlEvent::Characters(unescape(&text))));
+                }
+                continue;
+            }
+
+            let mut tag = Vec::new();
+            self.reader.read_until(b'>', &mut tag)?;
+            // A comment ends at the first `-->`, not at the first `>`
+            while tag.starts_with(b"!--") && !tag.ends_with(b"-->") {
+                if self.reader.read_until(b'>', &mut tag)? == 0 {
+                    break;
+                }
+            }
+            self.in_tag = false;
+            if tag.pop() != Some(b'>') {
+                return Err(io::Error::new(
+                    io::ErrorKind::UnexpectedEof,
+                    "The XHTML ended inside a tag",
+                ));
+            }
+            if let Some(event) = self.tag_event(&String::from_utf8_lossy(&tag)) {
+                return Ok(Some(event));
+            }
+        }
+    }
+}
+
+impl Iterator for XhtmlEvents {
+    type Item = io::Result<XhtmlEvent>;
+
+    fn next(&mut self) -> Option<io::Result<XhtmlEvent>> {
+        if self.failed {
+            return None;
+        }
+        let event = self.read_event();
+        self.failed = event.is_err();
+        event.transpose()
+    }
+}
+
+/// Collects the form fields of the `<div class="acroform">` that Tika's PDF parser writes after
+/// the pages, one `<li>name: value</li>` per field. The items of a nested `<ol>` are the children
+/// of the field before it and get qualified names, e.g. `address.city`
+pub(crate) fn acroform_fields(
+    events: impl Iterator<Item = io::Result<XhtmlEvent>>,
+) -> io::Result<HashMap<String, String>> {
+    let mut fields = HashMap::new();
+    // Depth of nested divs inside the acroform div, None outside of it
+    let mut div_depth: Option<usize> = None;
+    let mut list_depth = 0usize;
+    // Qualified names of the fields whose children are listed
+    let mut parents: Vec<String> = Vec::new();
+    let mut last_field: Option<String> = None;
+    let mut item: Option<String> = None;
+
+    for event in events {
+        match (event?, div_depth) {
+            (XhtmlEvent::StartElement { name, attributes }, None) if name == "div" => {
+                let class = attributes.iter().find(|(name, _)| name == "class");
+                if class.is_some_and(|(_, class)| class == "acroform") {
+                    div_depth = Some(0);
+                }
+            }
+            (XhtmlEvent::StartElement { name, .. }, Some(depth)) if name == "div" => {
+                div_depth = Some(depth + 1);
+            }
+            (XhtmlEvent::EndElement { name }, Some(depth)) if name == "div" => {
+                div_depth = depth.checked_sub(1);
+            }
+            (XhtmlEvent::StartElement { name, .. }, Some(_)) if name == "ol" => {
+                list_depth += 1;
+                if list_depth > 1 {
+                    let parent = match last_field.take() {
+                        Some(parent) => {
+                            fields.remove(&parent);
+                            parent
+                        }
+                        None => parents.last().cloned().unwrap_or_default(),
+                    };
+                    parents.push(parent);
+                }
+            }
+            (XhtmlEvent::EndElement { name }, Some(_)) if name == "ol" => {
+                if list_depth > 1 {
+                    parents.pop();
+                }
+                list_depth = list_depth.saturating_sub(1);
+                last_field = None;
+            }
+            (XhtmlEvent::StartElement { name, .. }, Some(_)) if name == "li" => {
+                item = Some(String::new());
+            }
+            (XhtmlEvent::Characters(text), Some(_)) => {
+                if let Some(item) = &mut item {
+                    item.push_str(&text);
+                }
+            }
+            (XhtmlEvent::EndElement { name }, Some(_)) if name == "li" => {
+                // Fields without a name only have their value, they cannot be told apart
+                let Some(text) = item.take() else {
+                    continue;
+                };
+                let Some((name, value)) = text.split_once(':') else {
+                    continue;
+                };
+                let name = name.trim().to_string();
+                let value = value.strip_prefix(' ').unwrap_or(value).to_string();
+                let qualified = match parents.last() {
+                    Some(parent) if !parent.is_empty() => format!("{}.{}", parent, name),
+                    _ => name,
+                };
+                fields.insert(qualified.clone(), value);
+                last_field = Some(qualified);
+            }
+            _ => {}
+        }
+    }
+    Ok(fields)
+}
+
+/// Parses the `name="value"` attributes of a tag. Tika's XHTML escapes `<`, `>` and quotes in
+/// attribute values
+fn parse_attributes(mut rest: &str) -> Vec<(String, String)> {
+    let mut attributes = Vec::new();
+    while let Some(equals) = rest.find('=') {
+        let name = rest[..equals].trim();
+        let value = rest[equals + 1..].trim_start();
+        let Some(quote) = value.chars().next().filter(|c| matches!(c, '"' | '\'')) else {
+            break;
+        };
+        let Some(end) = value[1..].find(quote) else {
+            break;
+        };
+        attributes.push((name.to_string(), unescape(&value[1..end + 1])));
+        rest = &value[end + 2..];
+    }
+    attributes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_xhtml_events() {
+        let xhtml = "<?xml version=\"1.0\" encoding=\"UTF-8\"?><html \
+            xmlns=\"http://www.w3.org/1999/xhtml\"><body><!-- a <b> comment -->\
+            <p class=\"lead\">Fish &amp; chips</p><img src=\"a.png\" alt='A &lt;map&gt;'/>\
+            <p/></body></html>";
+        let events: Vec<XhtmlEvent> = XhtmlEvents::new(StreamReader::from_text(xhtml.to_string()))
+            .collect::<io::Result<_>>()
+            .unwrap();
+
+        let start = |name: &str, attributes: &[(&str, &str)]| XhtmlEvent::StartElement {
+            name: name.to_string(),
+            attributes: attributes
+                .iter()
+                .map(|(name, value)| (name.to_string(), value.to_string()))
+                .collect(),
+        };
+        let end = |name: &str| XhtmlEvent::EndElement {
+            name: name.to_string(),
+        };
+        assert_eq!(
+            events,
+            [
+                start("html", &[("xmlns", "http://www.w3.org/1999/xhtml")]),
+                start("body", &[]),
+                start("p", &[("class", "lead")]),
+                XhtmlEvent::Characters("Fish & chips".to_string()),
+                end("p"),
+                start("img", &[("src", "a.png"), ("alt", "A <map>")]),
+                end("img"),
+                start("p", &[]),
+                end("p"),
+                end("body"),
+                end("html"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_xhtml_events_end_inside_tag() {
+        let xhtml = "<html><body><p>Cut off</p><img src=\"a.p";
+        let mut events = XhtmlEvents::new(StreamReader::from_text(xhtml.to_string()));
+
+        assert_eq!(
+            events
+                .by_ref()
+                .take(5)
+                .filter(|event| event.is_ok())
+                .count(),
+            5
+        );
+        let error = events.next().unwrap().unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::UnexpectedEof);
+        assert!(events.next().is_none());
+    }
+
+    #[test]
+    fn test_acroform_fields() {
+        let xhtml = "<html><body><div class=\"page\"><p>Name</p></div>\
+            <div class=\"acroform\"><ol><li>full_name: Jane Doe</li><li>subscribed: Yes</li>\
+            <li>address: </li><ol><li>city: Lyon</li><li>zip: 69001</li></ol>\
+            <li>notes: </li><li>Unnamed value</li></ol></div></body></html>";
+        let fields =
+            acroform_fields(XhtmlEvents::new(StreamReader::from_text(xhtml.to_string()))).unwrap();
+
+        let mut fields: Vec<(&str, &str)> = fields
+            .iter()
+            .map(|(name, value)| (name.as_str(), value.as_str()))
+            .collect();
+        fields.sort();
+        assert_eq!(
+            fields,
+            [
+                ("address.city", "Lyon"),
+                ("address.zip", "69001"),
+                ("full_name", "Jane Doe"),
+                ("notes", ""),
+                ("subscribed", "Yes"),
+            ]
+        );
+    }
+}
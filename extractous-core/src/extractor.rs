@@ -1,9 +1,18 @@
 use crate::errors::ExtractResult;
+use crate::object_storage;
 use crate::tika;
 use crate::tika::JReaderInputStream;
-use crate::{OfficeParserConfig, PdfParserConfig, TesseractOcrConfig, MMAP_THRESHOLD};
+use crate::{
+    BidiReordering, Chunk, ChunkKind, CleaningOptions, DocumentFormat, FootnoteHandling,
+    LineEnding, LinkMode, MultipartPart, NormalizationForm, OfficeParserConfig, PdfOcrStrategy,
+    PdfParserConfig, TableRendering, TesseractOcrConfig, TextCleaningConfig, TikaHandler,
+    XhtmlEvents, DEFAULT_BUF_SIZE, DEFAULT_PAGE_MARKER, MMAP_THRESHOLD, SMALL_FILE_THRESHOLD,
+};
+use std::borrow::Cow;
 use std::collections::HashMap;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::sync::{mpsc, Arc};
+use std::time::Duration;
 use strum_macros::{Display, EnumString};
 
 #[cfg(feature = "mmap")]
@@ -12,9 +21,46 @@ use memmap2::MmapOptions;
 #[cfg(feature = "parallel")]
 use rayon::prelude::*;
 
+#[cfg(feature = "ocr-downscale")]
+use crate::ocr_image::{DownscaledImage, OCR_DOWNSCALED_KEY};
+#[cfg(feature = "ocr-downscale")]
+use crate::ImageKind;
+
 /// Metadata type alias
 pub type Metadata = HashMap<String, Vec<String>>;
 
+/// Keys that differ between two metadata maps, see [`Extractor::compare_metadata`].
+/// Each list is sorted
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MetadataDiff {
+    /// Keys only present in the new metadata
+    pub added: Vec<String>,
+    /// Keys only present in the old metadata
+    pub removed: Vec<String>,
+    /// Keys present in both with different values
+    pub changed: Vec<String>,
+}
+
+impl MetadataDiff {
+    /// Returns true if both metadata maps are identical
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+/// A document embedded in a container, e.g. a zip entry or an email attachment, see
+/// [`Extractor::extract_file_with_embedded`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct EmbeddedDoc {
+    /// The file name of the document, or its path in the container when it has no name
+    pub name: String,
+    /// The detected mime type, e.g. `application/pdf`
+    pub mime_type: String,
+    pub text: String,
+    /// The metadata of the embedded document, without the text
+    pub metadata: Metadata,
+}
+
 /// CharSet enum of all supported encodings
 #[derive(Debug, Clone, Default, Copy, PartialEq, Eq, Hash, Display, EnumString)]
 #[allow(non_camel_case_types)]
@@ -41,14 +87,253 @@ pub enum CharSet {
 /// buf_reader.read_to_string(&mut content).unwrap();
 /// println!("{}", content);
 /// ```
+/// or drained into a `String` with `into_string`, which decodes the extractor's `encoding`.
 ///
+/// The reader can be consumed and dropped on any thread. The thread is attached to the Tika vm
+/// for the duration of each read only.
 pub struct StreamReader {
-    pub(crate) inner: JReaderInputStream,
+    pub(crate) inner: StreamSource,
+    /// Input bytes owned by the extractor, for example a downloaded object or a downscaled
+    /// image. Tika reads them while the stream is consumed, so they must live as long as the
+    /// reader
+    #[allow(dead_code)]
+    pub(crate) owned_input: Option<Vec<u8>>,
+    /// Whether the C0 control characters other than tabs and line breaks are dropped
+    pub(crate) strip_control_chars: bool,
+    /// The encoding of the bytes read from the stream
+    pub(crate) charset: CharSet,
+}
+
+/// Where a [`StreamReader`] reads the extracted text from
+pub(crate) enum StreamSource {
+    /// Text streamed from Tika as it parses
+    Tika(JReaderInputStream),
+    /// Text that is already extracted, e.g. by the pure-rust parsers
+    Memory(std::io::Cursor<Vec<u8>>),
+}
+
+impl StreamReader {
+    /// Creates a reader over text that is already extracted
+    pub(crate) fn from_text(text: String) -> Self {
+        Self::from_text_in(text, CharSet::UTF_8)
+    }
+
+    /// Creates a reader over text that is already extracted, encoded in `charset` like the
+    /// streams of Tika. Characters outside of US-ASCII are replaced by `?`, as Java does
+    pub(crate) fn from_text_in(text: String, charset: CharSet) -> Self {
+        let bytes = match charset {
+            CharSet::UTF_8 => text.into_bytes(),
+            CharSet::US_ASCII => text
+                .chars()
+                .map(|c| if c.is_ascii() { c as u8 } else { b'?' })
+                .collect(),
+            CharSet::UTF_16BE => text.encode_utf16().flat_map(u16::to_be_bytes).collect(),
+        };
+        Self {
+            inner: StreamSource::Memory(std::io::Cursor::new(bytes)),
+            owned_input: None,
+            strip_control_chars: false,
+            charset,
+        }
+    }
+
+    /// Reads the rest of the stream into a string, decoding the bytes from the encoding they
+    /// were extracted in, see `Extractor::set_encoding`. Returns `Error::Utf8Error` or
+    /// `Error::ParseError` for bytes that are not valid in that encoding.
+    pub fn into_string(mut self) -> ExtractResult<String> {
+        use std::io::Read;
+
+        let mut bytes = Vec::new();
+        self.read_to_end(&mut bytes)
+            .map_err(|e| crate::errors::Error::IoError(e.to_string()))?;
+        match self.charset {
+            // ASCII is a subset of UTF-8
+            CharSet::UTF_8 | CharSet::US_ASCII => {
+                String::from_utf8(bytes).map_err(|e| e.utf8_error().into())
+            }
+            CharSet::UTF_16BE => encoding_rs::UTF_16BE
+                .decode_without_bom_handling_and_without_replacement(&bytes)
+                .map(|text| text.into_owned())
+                .ok_or_else(|| {
+                    crate::errors::Error::ParseError("The stream is not valid UTF-16BE".to_string())
+                }),
+        }
+    }
+
+    /// The number of JNI round trips the reader took to read from Tika so far, one per call of
+    /// the Java Reader's `read`. Always 0 for text that was extracted without streaming
+    pub fn jni_round_trips(&self) -> usize {
+        match &self.inner {
+            StreamSource::Tika(reader) => reader.round_trips,
+            StreamSource::Memory(_) => 0,
+        }
+    }
 }
 
 impl std::io::Read for StreamReader {
     fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
-        self.inner.read(buf)
+        loop {
+            let len = match &mut self.inner {
+                StreamSource::Tika(reader) => reader.read(buf)?,
+                StreamSource::Memory(cursor) => cursor.read(buf)?,
+            };
+            if !self.strip_control_chars || len == 0 {
+                return Ok(len);
+            }
+
+            // Bytes below 0x20 never occur inside a multi-byte UTF-8 sequence
+            let mut kept = 0;
+            for i in 0..len {
+                let byte = buf[i];
+                if byte >= 0x20 || matches!(byte, b'\t' | b'\n' | b'\r') {
+                    buf[kept] = byte;
+                    kept += 1;
+                }
+            }
+            // A read of only control characters must not be taken for the end of the stream
+            if kept > 0 {
+                return Ok(kept);
+            }
+        }
+    }
+
+    /// Drains the stream in large chunks, Tika streams take one JNI round trip per
+    /// `LARGE_BUF_SIZE` bytes instead of one per small buffer
+    fn read_to_end(&mut self, buf: &mut Vec<u8>) -> std::io::Result<usize> {
+        let start = buf.len();
+        match &mut self.inner {
+            StreamSource::Tika(reader) => reader.read_to_end(buf)?,
+            StreamSource::Memory(cursor) => cursor.read_to_end(buf)?,
+        };
+        if self.strip_control_chars {
+            let mut kept = start;
+            for i in start..buf.len() {
+                let byte = buf[i];
+                if byte >= 0x20 || matches!(byte, b'\t' | b'\n' | b'\r') {
+                    buf[kept] = byte;
+                    kept += 1;
+                }
+            }
+            buf.truncate(kept);
+        }
+        Ok(buf.len() - start)
+    }
+}
+
+/// The lines of a [`StreamReader`], decoded from its encoding as the stream is read, see
+/// `Extractor::extract_file_lines`
+struct StreamLines {
+    reader: std::io::BufReader<StreamReader>,
+    charset: CharSet,
+    decoder: encoding_rs::Decoder,
+    /// Decoded text, the lines before `start` are returned already
+    pending: String,
+    start: usize,
+    /// The text of `pending` from `start` to here has no line break
+    searched: usize,
+    done: bool,
+}
+
+impl StreamLines {
+    fn new(reader: StreamReader, buf_size: usize) -> Self {
+        let encoding = match reader.charset {
+            // ASCII is a subset of UTF-8
+            CharSet::UTF_8 | CharSet::US_ASCII => encoding_rs::UTF_8,
+            CharSet::UTF_16BE => encoding_rs::UTF_16BE,
+        };
+        Self {
+            charset: reader.charset,
+            reader: std::io::BufReader::with_capacity(buf_size, reader),
+            decoder: encoding.new_decoder_without_bom_handling(),
+            pending: String::new(),
+            start: 0,
+            searched: 0,
+            done: false,
+        }
+    }
+
+    /// Decodes the next chunk of the stream into `pending`
+    fn fill(&mut self) -> std::io::Result<()> {
+        use std::io::BufRead;
+
+        let chunk = loop {
+            match self.reader.fill_buf() {
+                Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+                result => break result?,
+            }
+        };
+        let len = chunk.len();
+        let last = len == 0;
+        let mut input = chunk;
+        loop {
+            let needed = self
+                .decoder
+                .max_utf8_buffer_length_without_replacement(input.len());
+            self.pending.reserve(needed.unwrap_or(input.len()));
+            let (result, read) =
+                self.decoder
+                    .decode_to_string_without_replacement(input, &mut self.pending, last);
+            input = &input[read..];
+            match result {
+                encoding_rs::DecoderResult::InputEmpty => break,
+                encoding_rs::DecoderResult::OutputFull => continue,
+                encoding_rs::DecoderResult::Malformed(_, _) => {
+                    return Err(std::io::Error::new(
+                        std::io::ErrorKind::InvalidData,
+                        format!("The stream is not valid {}", self.charset),
+                    ));
+                }
+            }
+        }
+        self.reader.consume(len);
+        self.done = last;
+        Ok(())
+    }
+}
+
+impl Iterator for StreamLines {
+    type Item = std::io::Result<String>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            // Only the text decoded since the last search is searched, so a long line is not
+            // scanned again for every chunk
+            if let Some(offset) = self.pending[self.searched..].find('\n') {
+                let end = self.searched + offset;
+                let line = &self.pending[self.start..end];
+                let line = line.strip_suffix('\r').unwrap_or(line).to_string();
+                self.start = end + 1;
+                self.searched = self.start;
+                return Some(Ok(line));
+            }
+            self.searched = self.pending.len();
+            if self.done {
+                // The last line without a line break, if any
+                let line = self.pending.split_off(self.start);
+                self.pending.clear();
+                (self.start, self.searched) = (0, 0);
+                return Some(line).filter(|line| !line.is_empty()).map(Ok);
+            }
+            // The returned lines are dropped before more text is decoded
+            self.pending.drain(..self.start);
+            self.searched -= self.start;
+            self.start = 0;
+            if let Err(e) = self.fill() {
+                self.done = true;
+                self.pending.clear();
+                return Some(Err(e));
+            }
+        }
+    }
+}
+
+/// The predicate of `Extractor::set_char_filter`, shared by the clones of an extractor
+#[derive(Clone)]
+struct CharFilter(Arc<dyn Fn(char) -> bool + Send + Sync>);
+
+impl std::fmt::Debug for CharFilter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("CharFilter")
     }
 }
 
@@ -68,34 +353,127 @@ impl std::io::Read for StreamReader {
 #[derive(Debug, Clone)]
 pub struct Extractor {
     extract_string_max_length: i32,
+    truncation_suffix: String,
     encoding: CharSet,
     pdf_config: PdfParserConfig,
     office_config: OfficeParserConfig,
+    /// The password of `set_document_password`, applied to the configs passed to Tika
+    document_password: Option<crate::config::Password>,
     ocr_config: TesseractOcrConfig,
     xml_output: bool,
+    json_output: bool,
+    markdown_output: bool,
+    tika_handler: TikaHandler,
     // Performance optimization settings
     use_mmap: bool,
     mmap_threshold: usize,
     enable_parallel: bool,
+    #[cfg(all(feature = "parallel", feature = "pure-rust"))]
+    parallel_ocr: bool,
     use_pure_rust: bool,
+    small_file_fast_path: bool,
     enable_text_cleaning: bool,
+    preserve_code_formatting: bool,
+    cleaning_profile: Option<CleaningOptions>,
+    cleaning_profiles: HashMap<DocumentFormat, CleaningOptions>,
+    text_cleaning_config: Option<TextCleaningConfig>,
+    unicode_normalization: Option<NormalizationForm>,
+    line_ending: LineEnding,
+    char_filter: Option<CharFilter>,
+    strip_control_chars: bool,
+    metadata_keys: Option<Vec<String>>,
+    table_rendering: TableRendering,
+    footnote_handling: FootnoteHandling,
+    link_mode: LinkMode,
+    bidi_reordering: BidiReordering,
+    skip_blank_pages: bool,
+    remove_headers_footers: bool,
+    annotate_page_boundaries: bool,
+    page_marker: String,
+    preview_pages: Option<u32>,
+    #[cfg(feature = "pure-rust")]
+    locale: Option<String>,
+    #[cfg(feature = "pure-rust")]
+    strip_markdown_syntax: bool,
+    ocr_enabled: bool,
+    ocr_fallback_on_empty: bool,
+    ocr_fallback_min_chars: usize,
+    max_file_size: Option<u64>,
+    #[cfg(feature = "compression")]
+    max_decompressed_size: u64,
+    extraction_timeout: Option<Duration>,
+    url_max_retries: u32,
+    url_retry_backoff: Duration,
+    tika_config_xml: Option<String>,
+    #[cfg(feature = "classify")]
+    category_rules: Vec<crate::CategoryRule>,
+    #[cfg(feature = "language-detection")]
+    detect_language: bool,
+    #[cfg(feature = "content-hash")]
+    content_hash: Option<crate::HashAlgo>,
 }
 
 impl Default for Extractor {
     fn default() -> Self {
         Self {
             extract_string_max_length: 500_000, // 500KB
+            truncation_suffix: "...".to_string(),
             encoding: CharSet::UTF_8,
             pdf_config: PdfParserConfig::default(),
             office_config: OfficeParserConfig::default(),
+            document_password: None,
             ocr_config: TesseractOcrConfig::default(),
             xml_output: false,
+            json_output: false,
+            markdown_output: false,
+            tika_handler: TikaHandler::Body,
             // Enable optimizations by default when features are available
             use_mmap: cfg!(feature = "mmap"),
             mmap_threshold: MMAP_THRESHOLD,
             enable_parallel: cfg!(feature = "parallel"),
+            #[cfg(all(feature = "parallel", feature = "pure-rust"))]
+            parallel_ocr: false,
             use_pure_rust: cfg!(feature = "pure-rust"),
+            small_file_fast_path: false,
             enable_text_cleaning: false, // Disabled by default to avoid overhead
+            preserve_code_formatting: true,
+            cleaning_profile: None,
+            cleaning_profiles: HashMap::new(),
+            text_cleaning_config: None,
+            unicode_normalization: None,
+            line_ending: LineEnding::Preserve,
+            char_filter: None,
+            strip_control_chars: false,
+            metadata_keys: None,
+            table_rendering: TableRendering::Flat,
+            footnote_handling: FootnoteHandling::Inline,
+            link_mode: LinkMode::Drop,
+            bidi_reordering: BidiReordering::Off,
+            skip_blank_pages: false,
+            remove_headers_footers: false,
+            annotate_page_boundaries: false,
+            page_marker: DEFAULT_PAGE_MARKER.to_string(),
+            preview_pages: None,
+            #[cfg(feature = "pure-rust")]
+            locale: None,
+            #[cfg(feature = "pure-rust")]
+            strip_markdown_syntax: false,
+            ocr_enabled: true,
+            ocr_fallback_on_empty: false,
+            ocr_fallback_min_chars: 10,
+            max_file_size: None, // Unlimited
+            #[cfg(feature = "compression")]
+            max_decompressed_size: 256 * 1024 * 1024,
+            extraction_timeout: None,
+            url_max_retries: 0,
+            url_retry_backoff: Duration::ZERO,
+            tika_config_xml: None,
+            #[cfg(feature = "classify")]
+            category_rules: crate::classify::default_rules(),
+            #[cfg(feature = "language-detection")]
+            detect_language: false,
+            #[cfg(feature = "content-hash")]
+            content_hash: None,
         }
     }
 }
@@ -105,13 +483,22 @@ impl Extractor {
         Self::default()
     }
 
-    /// Set the maximum length of the extracted text. Used only for extract_to_string functions
+    /// Set the maximum length of the extracted text in characters. Used only for
+    /// extract_to_string functions
     /// Default: 500_000
     pub fn set_extract_string_max_length(mut self, max_length: i32) -> Self {
         self.extract_string_max_length = max_length;
         self
     }
 
+    /// Set the suffix appended to a text cut at `extract_string_max_length`, e.g. `"\u{2026}"`,
+    /// or an empty string for none. The suffix counts towards the maximum length.
+    /// Default: "..."
+    pub fn set_truncation_suffix(mut self, suffix: String) -> Self {
+        self.truncation_suffix = suffix;
+        self
+    }
+
     /// Set the encoding to use for when extracting text to a stream.
     /// Not used for extract_to_string functions.
     /// Default: CharSet::UTF_8
@@ -132,18 +519,71 @@ impl Extractor {
         self
     }
 
+    /// Set the password of encrypted (password protected) PDF and Office documents, for the
+    /// configs whose own password, see `PdfParserConfig::set_password` and
+    /// `OfficeParserConfig::set_password`, is not set. It is kept when `set_pdf_config` or
+    /// `set_office_config` is called later. Without it, or with a wrong password, the
+    /// `extract_*_to_string` methods return `Error::EncryptedDocument`, the stream methods fail
+    /// on reading.
+    /// Default: None
+    pub fn set_document_password(mut self, password: String) -> Self {
+        self.document_password = Some(crate::config::Password(password));
+        self
+    }
+
     /// Set the configuration for the Tesseract OCR
     pub fn set_ocr_config(mut self, config: TesseractOcrConfig) -> Self {
         self.ocr_config = config;
         self
     }
 
+    /// Enable or disable OCR altogether. Disabled, no OCR runs whatever `PdfParserConfig` and
+    /// `TesseractOcrConfig` say: PDFs are parsed with `PdfOcrStrategy::NO_OCR`, Tika's image
+    /// parser is turned off and `set_ocr_fallback_on_empty` has no effect. Scans and images then
+    /// give little or no text, but the extraction time no longer depends on OCR.
+    /// Default: true
+    pub fn set_ocr_enabled(mut self, ocr_enabled: bool) -> Self {
+        self.ocr_enabled = ocr_enabled;
+        self
+    }
+
     /// Set the configuration for the parse as xml
     pub fn set_xml_output(mut self, xml_output: bool) -> Self {
         self.xml_output = xml_output;
         self
     }
 
+    /// Set the Tika content handler that turns the parsed document into text, see
+    /// [`TikaHandler`] for how they differ. Only affects the Tika parsers, and
+    /// `set_xml_output(true)` takes precedence.
+    /// Default: TikaHandler::Body
+    pub fn set_tika_handler(mut self, tika_handler: TikaHandler) -> Self {
+        self.tika_handler = tika_handler;
+        self
+    }
+
+    /// Set the configuration for the output as json. The `extract_*_to_string` methods then
+    /// return a json document instead of plain text:
+    /// `{"content": {"text": "...", "blocks": [{"text": "..."}]}, "metadata": {"key": ["value"]}}`
+    /// where blocks are the paragraphs of the text, separated by blank lines.
+    /// Cannot be combined with `set_xml_output` or `TikaHandler::Xhtml`, and is not supported by
+    /// the stream methods,
+    /// both return `Error::InvalidConfiguration`.
+    pub fn set_json_output(mut self, json_output: bool) -> Self {
+        self.json_output = json_output;
+        self
+    }
+
+    /// Set the configuration for the output as Markdown. The `extract_*_to_string` methods then
+    /// convert Tika's XHTML to Markdown, keeping headings, lists, tables and links. The pure
+    /// Rust parsers, which produce plain text, are not used.
+    /// Cannot be combined with `set_xml_output`, `set_json_output` or `TikaHandler::Xhtml`, and
+    /// is not supported by the stream methods, all return `Error::InvalidConfiguration`.
+    pub fn set_markdown_output(mut self, markdown_output: bool) -> Self {
+        self.markdown_output = markdown_output;
+        self
+    }
+
     /// Enable or disable memory-mapped file I/O for large files
     /// This can significantly improve performance for large files
     pub fn set_use_mmap(mut self, use_mmap: bool) -> Self {
@@ -164,6 +604,20 @@ impl Extractor {
         self
     }
 
+    /// OCR the pages of a PDF in parallel on the rayon thread pool when the PDF's
+    /// `PdfOcrStrategy` is `OCR_ONLY` or `OCR_AND_TEXT_EXTRACTION`, e.g. for long scans, where
+    /// OCR page after page is the bottleneck. Every page is split off into a PDF of its own and
+    /// rendered and OCRed by Tika, the text of the pages is joined in page order. Applies to
+    /// `extract_file_to_string`, including the OCR of `set_ocr_fallback_on_empty`. Running
+    /// headers and footers of the cleaning profile and the markers of
+    /// `set_annotate_page_boundaries` are applied to the OCRed pages.
+    /// Default: false
+    #[cfg(all(feature = "parallel", feature = "pure-rust"))]
+    pub fn set_parallel_ocr(mut self, parallel_ocr: bool) -> Self {
+        self.parallel_ocr = parallel_ocr;
+        self
+    }
+
     /// Enable or disable pure Rust parsers for better performance
     /// When enabled, uses pure Rust implementations for supported formats
     /// Falls back to Tika for unsupported formats
@@ -172,6 +626,16 @@ impl Extractor {
         self
     }
 
+    /// Enable or disable parsing small HTML, XML and CSV files with the pure Rust parsers even
+    /// when `set_use_pure_rust` disabled them. Files under `SMALL_FILE_THRESHOLD` then skip
+    /// attaching to the Tika vm, which takes longer than parsing them. Only has an effect with
+    /// the `pure-rust` feature, and not with Markdown output.
+    /// Default: false
+    pub fn set_small_file_fast_path(mut self, small_file_fast_path: bool) -> Self {
+        self.small_file_fast_path = small_file_fast_path;
+        self
+    }
+
     /// Enable or disable SIMD-optimized text cleaning
     /// When enabled, applies text normalization and cleaning for better quality
     pub fn set_enable_text_cleaning(mut self, enable_text_cleaning: bool) -> Self {
@@ -179,343 +643,4077 @@ impl Extractor {
         self
     }
 
-    /// Extracts text from a file path. Returns a tuple with stream of the extracted text and metadata.
-    /// the stream is decoded using the extractor's `encoding`
-    ///
-    /// Performance optimizations:
-    /// - Uses pure Rust parsers for supported formats when enabled (2-3x faster)
-    /// - Uses memory-mapped I/O for large files when enabled
-    /// - Adaptive buffer sizing based on file size
-    /// - Falls back to Tika for unsupported formats
-    pub fn extract_file(&self, file_path: &str) -> ExtractResult<(StreamReader, Metadata)> {
-        // Try pure Rust parsers first for maximum performance
-        #[cfg(feature = "pure-rust")]
-        if self.use_pure_rust {
-            if let Ok((text, metadata)) = self.try_pure_rust_extraction(file_path) {
-                // Convert string result to StreamReader for API compatibility
-                return Ok((self.string_to_stream_reader(text), metadata));
-            }
-        }
-
-        #[cfg(feature = "mmap")]
-        if self.use_mmap {
-            if let Ok(file_size) = std::fs::metadata(file_path).map(|m| m.len() as usize) {
-                if file_size > self.mmap_threshold {
-                    return self.extract_file_with_mmap(file_path);
-                }
-            }
-        }
-
-        // Fallback to standard Tika extraction
-        tika::parse_file(
-            file_path,
-            &self.encoding,
-            &self.pdf_config,
-            &self.office_config,
-            &self.ocr_config,
-            self.xml_output,
-        )
+    /// Keep the whitespace of source code files, e.g. `.py` or `.rs` files, when text cleaning
+    /// is enabled. Collapsing whitespace would destroy the indentation of the code.
+    /// Code is recognised by the file extension or the content type Tika detects.
+    /// Default: true
+    pub fn set_preserve_code_formatting(mut self, preserve_code_formatting: bool) -> Self {
+        self.preserve_code_formatting = preserve_code_formatting;
+        self
     }
 
-    /// Extracts text from a byte buffer. Returns a tuple with stream of the extracted text and metadata.
-    /// the stream is decoded using the extractor's `encoding`
-    pub fn extract_bytes(&self, buffer: &[u8]) -> ExtractResult<(StreamReader, Metadata)> {
-        tika::parse_bytes(
-            buffer,
-            &self.encoding,
-            &self.pdf_config,
-            &self.office_config,
-            &self.ocr_config,
-            self.xml_output,
-        )
+    /// Set the cleaning applied to the text of formats without a profile of their own, see
+    /// `set_cleaning_profile_for`. Replaces the cleaning of `set_enable_text_cleaning`.
+    /// Applies to the extract_to_string functions.
+    /// Default: None
+    pub fn set_cleaning_profile(mut self, options: CleaningOptions) -> Self {
+        self.cleaning_profile = Some(options);
+        self
     }
 
-    /// Extracts text from an url. Returns a tuple with stream of the extracted text and metadata.
-    /// the stream is decoded using the extractor's `encoding`
-    pub fn extract_url(&self, url: &str) -> ExtractResult<(StreamReader, Metadata)> {
-        tika::parse_url(
-            url,
-            &self.encoding,
-            &self.pdf_config,
-            &self.office_config,
-            &self.ocr_config,
-            self.xml_output,
-        )
+    /// Set the cleaning applied to the text of one format, e.g. aggressive cleaning for PDFs
+    /// and none for CSVs. The format is the content type reported by the parser, or detected
+    /// from the file path.
+    pub fn set_cleaning_profile_for(
+        mut self,
+        format: DocumentFormat,
+        options: CleaningOptions,
+    ) -> Self {
+        self.cleaning_profiles.insert(format, options);
+        self
     }
 
-    /// Extracts text from a file path. Returns a tuple with string that is of maximum length
-    /// of the extractor's `extract_string_max_length` and metadata.
-    ///
-    /// Performance optimizations:
-    /// - Uses pure Rust parsers when available for 2-3x speedup
-    /// - Applies optimized text processing when enabled
-    /// - Smart text truncation that respects word boundaries
-    pub fn extract_file_to_string(&self, file_path: &str) -> ExtractResult<(String, Metadata)> {
-        // Try pure Rust parsers first for maximum performance
-        #[cfg(feature = "pure-rust")]
-        if self.use_pure_rust {
-            if let Ok((text, metadata)) = self.try_pure_rust_extraction(file_path) {
-                return Ok(self.post_process_text(text, metadata));
-            }
-        }
+    /// Set the granularity of the cleaning of `set_enable_text_cleaning`. Without a config
+    /// the cleaning collapses all whitespace of large texts into single spaces, with one it
+    /// keeps the line breaks and applies to texts of any size, e.g. to keep page breaks.
+    /// Default: None
+    pub fn set_text_cleaning_config(mut self, config: TextCleaningConfig) -> Self {
+        self.text_cleaning_config = Some(config);
+        self
+    }
 
-        // Standard Tika extraction (optimized through buffer improvements)
-        let (text, metadata) = tika::parse_file_to_string(
-            file_path,
-            self.extract_string_max_length,
-            &self.pdf_config,
-            &self.office_config,
-            &self.ocr_config,
-            self.xml_output,
-        )?;
+    /// Set the Unicode normalization form of the extracted text. PDFs often contain ligatures
+    /// such as `ﬁ` or decomposed accents that break search, `NormalizationForm::Nfkc` replaces
+    /// both by their plain equivalents. Applies to the extract_to_string functions.
+    /// Default: None
+    pub fn set_unicode_normalization(
+        mut self,
+        unicode_normalization: Option<NormalizationForm>,
+    ) -> Self {
+        self.unicode_normalization = unicode_normalization;
+        self
+    }
 
-        Ok(self.post_process_text(text, metadata))
+    /// Set the line endings of the extracted text, e.g. `LineEnding::Lf` for the same text on
+    /// every platform whatever the document was written with. Applies to the extract_to_string
+    /// functions, before the text is cut at `extract_string_max_length`.
+    /// Default: LineEnding::Preserve
+    pub fn set_line_endings(mut self, line_ending: LineEnding) -> Self {
+        self.line_ending = line_ending;
+        self
     }
 
+    /// Set a predicate that keeps the characters of the extracted text for which it returns true
+    /// and removes the others, e.g. `Box::new(|c| c != '\u{ad}')` drops soft hyphens. Applies to
+    /// the extract_to_string functions, right after the text is extracted.
+    /// Default: None
+    pub fn set_char_filter(mut self, char_filter: Box<dyn Fn(char) -> bool + Send + Sync>) -> Self {
+        self.char_filter = Some(CharFilter(Arc::from(char_filter)));
+        self
+    }
 
+    /// Strip the C0 control characters except tabs and line breaks, e.g. the NUL bytes that
+    /// malformed PDFs leave in the text and that break JSON serializers downstream. Applies to
+    /// the streams and the strings of all extract functions, independently of text cleaning.
+    /// Streams decoded as UTF-16BE are left alone.
+    /// Default: false
+    pub fn set_strip_control_chars(mut self, strip_control_chars: bool) -> Self {
+        self.strip_control_chars = strip_control_chars;
+        self
+    }
 
-    /// Extracts text from a byte buffer. Returns a tuple with string that is of maximum length
-    /// of the extractor's `extract_string_max_length` and metadata.
-    pub fn extract_bytes_to_string(&self, buffer: &[u8]) -> ExtractResult<(String, Metadata)> {
-        let (text, metadata) = tika::parse_bytes_to_string(
-            buffer,
-            self.extract_string_max_length,
-            &self.pdf_config,
-            &self.office_config,
-            &self.ocr_config,
-            self.xml_output,
-        )?;
+    /// Set the metadata keys to return, matched case insensitively, e.g. `dc:title` and
+    /// `Content-Type`. Tika returns dozens of keys, such as the parser chain, that are noise in
+    /// an index. `None` returns all keys. Applies to the extract_to_string functions and
+    /// `extract_file_metadata_only`.
+    /// Default: None
+    pub fn set_metadata_keys(mut self, metadata_keys: Option<Vec<String>>) -> Self {
+        self.metadata_keys =
+            metadata_keys.map(|keys| keys.iter().map(|key| key.to_lowercase()).collect());
+        self
+    }
 
-        Ok(self.post_process_text(text, metadata))
+    /// Set how tables are rendered by the pure Rust parsers.
+    /// Use `TableRendering::Tabs` to keep rows on separate lines and cells separated by tabs, or
+    /// `TableRendering::AlignedColumns` to pad the cells so that columns line up for display.
+    /// Default: TableRendering::Flat
+    pub fn set_table_rendering(mut self, table_rendering: TableRendering) -> Self {
+        self.table_rendering = table_rendering;
+        self
     }
 
-    /// Extracts text from a URL. Returns a tuple with string that is of maximum length
-    /// of the extractor's `extract_string_max_length` and metadata.
-    pub fn extract_url_to_string(&self, url: &str) -> ExtractResult<(String, Metadata)> {
-        let (text, metadata) = tika::parse_url_to_string(
-            url,
-            self.extract_string_max_length,
-            &self.pdf_config,
-            &self.office_config,
-            &self.ocr_config,
-            self.xml_output,
-        )?;
+    /// Set how footnotes are handled by the pure Rust DOCX and PDF parsers.
+    /// `FootnoteHandling::Endnotes` moves the footnotes after the body text, under a separator,
+    /// and `FootnoteHandling::Drop` removes them. PDF footnotes are detected heuristically from
+    /// their font size and position on the page.
+    /// Default: FootnoteHandling::Inline
+    pub fn set_footnote_handling(mut self, footnote_handling: FootnoteHandling) -> Self {
+        self.footnote_handling = footnote_handling;
+        self
+    }
 
-        Ok(self.post_process_text(text, metadata))
+    /// Set what happens to the targets of links, e.g. in HTML or PDF documents.
+    /// `LinkMode::Inline` writes the target after the text of the link, `text (https://...)`,
+    /// and `LinkMode::Footnotes` numbers the links and lists their targets after the body text,
+    /// under a separator. For Tika, the `extract_*_to_string` methods take the links from its
+    /// XHTML output, the stream methods return `Error::InvalidConfiguration` for them. Markdown
+    /// and xml output keep the links in their own markup.
+    /// Default: LinkMode::Drop
+    pub fn set_include_link_urls(mut self, link_mode: LinkMode) -> Self {
+        self.link_mode = link_mode;
+        self
     }
 
-    /// Memory-mapped file extraction for improved performance on large files
-    #[cfg(feature = "mmap")]
-    fn extract_file_with_mmap(&self, file_path: &str) -> ExtractResult<(StreamReader, Metadata)> {
-        use std::fs::File;
+    /// Set whether the markup of Markdown files is removed from their text, so that
+    /// `## [Install](install.md)` becomes `Install`. Code blocks are kept as they are. The YAML
+    /// frontmatter of Markdown files is returned as metadata, e.g. `title` as `dc:title`, and
+    /// left out of the text either way. Needs the pure-rust parsers.
+    /// Default: false
+    #[cfg(feature = "pure-rust")]
+    pub fn set_strip_markdown_syntax(mut self, strip_markdown_syntax: bool) -> Self {
+        self.strip_markdown_syntax = strip_markdown_syntax;
+        self
+    }
 
-        let file = File::open(file_path)
-            .map_err(|e| crate::errors::Error::IoError(e.to_string()))?;
+    /// Set how text mixing right-to-left and left-to-right scripts is reordered into visual
+    /// order. `BidiReordering::PerParagraph` gives each paragraph its own base direction, so
+    /// documents alternating Arabic or Hebrew paragraphs with English ones stay readable. DOCX
+    /// documents are read by the pure Rust parser in this mode to honour `w:bidi`.
+    /// Applies to the extract_to_string functions and the pure Rust streams.
+    /// Default: BidiReordering::Off
+    pub fn set_bidi_reordering(mut self, bidi_reordering: BidiReordering) -> Self {
+        self.bidi_reordering = bidi_reordering;
+        self
+    }
 
-        let mmap = unsafe { MmapOptions::new().map(&file) }
-            .map_err(|e| crate::errors::Error::IoError(e.to_string()))?;
+    /// Drop pages whose text is empty after trimming from the result of `extract_file_pages`
+    /// and `extract_file_chunked`. The number of pages dropped by `extract_file_pages` is
+    /// reported in the `Blank-Pages-Skipped` metadata.
+    /// Default: false
+    pub fn set_skip_blank_pages(mut self, skip_blank_pages: bool) -> Self {
+        self.skip_blank_pages = skip_blank_pages;
+        self
+    }
 
-        // Use the memory-mapped data as a byte slice for extraction
-        self.extract_bytes(&mmap)
+    /// Remove running headers and footers, such as page numbers, from the pages returned by
+    /// `extract_file_pages` and `extract_file_chunked`.
+    /// See [`crate::remove_repeated_headers_footers`] for how they are detected.
+    /// Pages left blank by the removal are dropped as well when `set_skip_blank_pages` is enabled.
+    /// Default: false
+    pub fn set_remove_headers_footers(mut self, remove_headers_footers: bool) -> Self {
+        self.remove_headers_footers = remove_headers_footers;
+        self
     }
 
-    /// Extract multiple files in parallel (when parallel feature is enabled)
-    #[cfg(feature = "parallel")]
-    pub fn extract_files_parallel<P: AsRef<Path> + Sync>(
-        &self,
-        file_paths: &[P],
-    ) -> Vec<ExtractResult<(String, Metadata)>> {
-        if self.enable_parallel {
-            file_paths
-                .par_iter()
-                .map(|path| self.extract_file_to_string(path.as_ref().to_str().unwrap_or("")))
-                .collect()
-        } else {
-            file_paths
-                .iter()
-                .map(|path| self.extract_file_to_string(path.as_ref().to_str().unwrap_or("")))
-                .collect()
-        }
+    /// Put the `set_page_marker` marker on its own line at the start of every page of a PDF in
+    /// the text of `extract_file_to_string`, so chunks of the text can be cited by page. The
+    /// pages are split the same way as by `extract_file_pages`, blank pages keep their marker.
+    /// Not applied with Markdown output.
+    /// Default: false
+    pub fn set_annotate_page_boundaries(mut self, annotate_page_boundaries: bool) -> Self {
+        self.annotate_page_boundaries = annotate_page_boundaries;
+        self
     }
 
-    /// Extract multiple files sequentially (fallback when parallel feature is disabled)
-    #[cfg(not(feature = "parallel"))]
-    pub fn extract_files_parallel<P: AsRef<Path>>(
-        &self,
-        file_paths: &[P],
-    ) -> Vec<ExtractResult<(String, Metadata)>> {
-        file_paths
-            .iter()
-            .map(|path| self.extract_file_to_string(path.as_ref().to_str().unwrap_or("")))
-            .collect()
+    /// The marker of `set_annotate_page_boundaries`, in which `{page}` is replaced by the page
+    /// number, counted from 1.
+    /// Default: `[page {page}]`
+    pub fn set_page_marker(mut self, page_marker: String) -> Self {
+        self.page_marker = page_marker;
+        self
     }
 
-    /// Try pure Rust extraction for supported formats
+    /// Write the numbers and dates of the cells of `extract_spreadsheet` the way a locale does,
+    /// given as a language tag such as `de-DE`, `en_GB` or `fr`: `1234.5` becomes `1.234,5`
+    /// and the date `2024-03-15` becomes `15.03.2024` for `de-DE`. Times stay in 24 hour
+    /// `HH:MM:SS`. `extract_spreadsheet` returns `Error::InvalidConfiguration` for locales it
+    /// does not know.
+    /// Default: None, numbers are plain and dates ISO 8601
     #[cfg(feature = "pure-rust")]
-    fn try_pure_rust_extraction(&self, file_path: &str) -> ExtractResult<(String, Metadata)> {
-        let pure_extractor = crate::pure_rust_parsers::PureRustExtractor::with_max_length(
-            self.extract_string_max_length as usize
-        );
-        pure_extractor.extract_file(file_path)
+    pub fn set_locale(mut self, locale: String) -> Self {
+        self.locale = Some(locale);
+        self
     }
 
-    /// Convert string to StreamReader for API compatibility
-    /// This is a temporary workaround - in practice, pure Rust extraction
-    /// should use the extract_file_to_string method for best performance
-    #[allow(dead_code)]
-    fn string_to_stream_reader(&self, text: String) -> StreamReader {
-        // Convert back to bytes and use extract_bytes
-        // This maintains API compatibility but isn't optimal
-        let bytes = text.into_bytes();
-        match self.extract_bytes(&bytes) {
-            Ok((stream, _)) => stream,
-            Err(_) => {
-                // This shouldn't happen in normal operation
-                // If it does, we have a serious issue with the extraction pipeline
-                panic!("Failed to create StreamReader from extracted text - this indicates a bug in the extraction logic")
-            }
-        }
+    /// Extract only the text of the first `preview_pages` pages of a PDF with
+    /// `extract_file_to_string`, e.g. for previews. With the `pure-rust` feature parsing stops
+    /// after those pages, and the later pages are not OCRed, which makes the preview of a long
+    /// document fast. Without it Tika still parses and OCRs the whole document, and only the
+    /// text of the first pages is returned. Page boundaries, header and footer removal and the
+    /// other page options do not apply to previews. Other formats are extracted whole.
+    /// Default: None
+    pub fn set_preview_pages(mut self, preview_pages: Option<u32>) -> Self {
+        self.preview_pages = preview_pages;
+        self
     }
 
-    /// Post-process extracted text with minimal overhead optimizations
-    fn post_process_text(&self, mut text: String, mut metadata: Metadata) -> (String, Metadata) {
-        if self.enable_text_cleaning {
-            // Only apply expensive operations if text is large enough to benefit
-            if text.len() > 5000 { // Increased threshold to reduce overhead
-                // Apply lightweight text cleaning only
-                text = crate::simd_text::normalize_whitespace(&text);
-                metadata.insert("Text-Processing".to_string(), vec!["lightweight".to_string()]);
-            }
+    /// Run OCR on PDFs whose text layer is empty or nearly so, e.g. scans. The PDF is extracted
+    /// as usual first, and extracted again with `PdfOcrStrategy::OCR_ONLY` when the text has
+    /// fewer non-whitespace characters than `set_ocr_fallback_min_chars`. The `OCR-Fallback`
+    /// metadata is then set to `true`. Applies to `extract_file_to_string`.
+    /// Default: false
+    pub fn set_ocr_fallback_on_empty(mut self, ocr_fallback_on_empty: bool) -> Self {
+        self.ocr_fallback_on_empty = ocr_fallback_on_empty;
+        self
+    }
 
-            // Smart truncation only if needed
-            if text.len() > self.extract_string_max_length as usize {
-                text = crate::simd_text::truncate_text_smart(&text, self.extract_string_max_length as usize);
-            }
-        }
+    /// Set the number of non-whitespace characters below which the text layer of a PDF counts
+    /// as empty, see `set_ocr_fallback_on_empty`.
+    /// Default: 10
+    pub fn set_ocr_fallback_min_chars(mut self, ocr_fallback_min_chars: usize) -> Self {
+        self.ocr_fallback_min_chars = ocr_fallback_min_chars;
+        self
+    }
 
-        (text, metadata)
+    /// Set the maximum size in bytes of the files and buffers to extract. Larger inputs are
+    /// rejected with `Error::FileTooLarge` before any extraction work is done.
+    /// Default: unlimited
+    pub fn set_max_file_size(mut self, max_file_size: u64) -> Self {
+        self.max_file_size = Some(max_file_size);
+        self
     }
 
-}
+    /// Set the maximum size in bytes that a gzip, bzip2 or xz file may decompress to.
+    /// `extract_file` and `extract_file_to_string` decompress such files and extract the
+    /// document inside, e.g. the html of `page.html.gz`. Content that grows past the limit fails
+    /// with `Error::DecompressedTooLarge`, which guards against decompression bombs.
+    /// Default: 256 MiB
+    #[cfg(feature = "compression")]
+    pub fn set_max_decompressed_size(mut self, max_decompressed_size: u64) -> Self {
+        self.max_decompressed_size = max_decompressed_size;
+        self
+    }
 
-#[cfg(test)]
-mod tests {
-    use super::StreamReader;
-    use crate::Extractor;
-    use std::fs::File;
-    use std::io::BufReader;
-    use std::io::{self, Read};
-    use std::str;
+    /// Set the maximum time an extraction may take before `Error::Timeout` is returned.
+    /// The extraction runs on a separate thread, attached to the JVM on its own. When the timeout
+    /// expires, the Rust call returns but the orphaned thread may keep running inside Tika until
+    /// the parse finishes, as JNI calls cannot be interrupted.
+    /// `extract_bytes` is not covered because Tika keeps reading the caller's buffer while the
+    /// returned stream is consumed. For the other stream methods only the initial call is
+    /// covered, not the reading of the stream.
+    /// Default: no timeout
+    pub fn set_extraction_timeout(mut self, timeout: Duration) -> Self {
+        self.extraction_timeout = Some(timeout);
+        self
+    }
 
-    const TEST_FILE: &str = "README.md";
+    /// Retry `extract_url` and `extract_url_to_string` up to `max_retries` times when the
+    /// server cannot be reached, the connection breaks or it answers with a 5xx status. The
+    /// first retry waits `backoff`, every further one twice as long as the one before. Other
+    /// errors, e.g. a 4xx status, are returned at once, as is the error of the last attempt.
+    /// With `set_extraction_timeout` the timeout covers all the attempts.
+    /// Default: no retries
+    pub fn set_url_retry(mut self, max_retries: u32, backoff: Duration) -> Self {
+        self.url_max_retries = max_retries;
+        self.url_retry_backoff = backoff;
+        self
+    }
 
-    const TEST_URL: &str = "https://www.google.com/";
+    /// Set a Tika config XML document, e.g. to disable parsers or tune the detectors, that Tika
+    /// builds its parsers from for this extractor instead of its default config. The pure-Rust
+    /// parsers are not affected. An invalid document fails the Tika extractions with
+    /// `Error::ParseError`. See https://tika.apache.org/2.9.2/configuring.html for the format.
+    /// Default: None
+    pub fn set_tika_config_xml(mut self, xml: String) -> Self {
+        self.tika_config_xml = Some(xml);
+        self
+    }
+
+    /// Returns the mime types Tika can extract, sorted, e.g. `application/pdf` or `text/html`.
+    /// The list comes from the parsers registered in Tika, so it matches what `extract_*` accepts.
+    /// See `DocumentFormat::all` for the formats of the pure-Rust parsers.
+    pub fn supported_mime_types() -> ExtractResult<Vec<String>> {
+        tika::supported_mime_types()
+    }
+
+    /// Returns the version of the bundled Apache Tika, e.g. `2.9.2`, or an empty string if the
+    /// native library does not know it. Useful in bug reports together with `Extractor::version`.
+    pub fn tika_version() -> ExtractResult<String> {
+        tika::tika_version()
+    }
+
+    /// Returns the version of this crate, e.g. `0.3.0`
+    pub fn version() -> &'static str {
+        env!("CARGO_PKG_VERSION")
+    }
+
+    /// Detect the language of the extracted text, from its first 16 KB, and set the ISO 639-1
+    /// code, e.g. `en`, as `dc:language` in the metadata, replacing a language declared by the
+    /// document. The confidence, between 0 and 1, is set as `Language-Confidence`. Nothing is
+    /// set when the language cannot be told. Applies to the extract_to_string functions.
+    /// Default: false
+    #[cfg(feature = "language-detection")]
+    pub fn set_detect_language(mut self, detect_language: bool) -> Self {
+        self.detect_language = detect_language;
+        self
+    }
+
+    /// Hash the raw input, the bytes of the file or the buffer before any decompression or
+    /// parsing, and set the hash as `Content-Hash` in the metadata, e.g. `sha256:9f86...`, to
+    /// deduplicate documents. Files that are mapped into memory for parsing, see
+    /// `set_mmap_threshold`, are hashed from the mapping. All other files are read a second time
+    /// for the hash after they are extracted, as Tika and the pure Rust parsers read them on
+    /// their own. Applies to the extract_file and extract_bytes functions.
+    /// Default: no hash
+    #[cfg(feature = "content-hash")]
+    pub fn set_compute_content_hash(mut self, algo: crate::HashAlgo) -> Self {
+        self.content_hash = Some(algo);
+        self
+    }
+
+    /// Adds a rule for `classify_document`, in addition to the built-in rules for invoices,
+    /// resumes, contracts and articles
+    #[cfg(feature = "classify")]
+    pub fn register_category_rule(mut self, rule: crate::CategoryRule) -> Self {
+        self.category_rules.push(rule);
+        self
+    }
+
+    /// Guesses the category of a document from keyword and structure heuristics over its
+    /// extracted text. Returns the category and a confidence between 0 and 1,
+    /// `DocumentCategory::Unknown` with 0 if no rule matches well enough.
+    #[cfg(feature = "classify")]
+    pub fn classify_document(
+        &self,
+        file_path: &str,
+    ) -> ExtractResult<(crate::DocumentCategory, f32)> {
+        let (text, _metadata) = self.extract_file_to_string(file_path)?;
+        Ok(self.classify_text(&text))
+    }
+
+    /// Same as `classify_document` for text that is already extracted
+    #[cfg(feature = "classify")]
+    pub fn classify_text(&self, text: &str) -> (crate::DocumentCategory, f32) {
+        crate::classify::classify_text(&self.category_rules, text)
+    }
+
+    /// Compares the metadata of two extractions of the same document, for example to detect
+    /// drift between periodic re-extractions. Values are compared in order.
+    pub fn compare_metadata(old: &Metadata, new: &Metadata) -> MetadataDiff {
+        let mut diff = MetadataDiff::default();
+        for (key, old_values) in old {
+            match new.get(key) {
+                None => diff.removed.push(key.clone()),
+                Some(new_values) if new_values != old_values => diff.changed.push(key.clone()),
+                Some(_) => {}
+            }
+        }
+        diff.added = new
+            .keys()
+            .filter(|key| !old.contains_key(*key))
+            .cloned()
+            .collect();
+
+        diff.added.sort();
+        diff.removed.sort();
+        diff.changed.sort();
+        diff
+    }
+
+    /// Whether the file at `file_path` is extracted by the pure Rust parsers rather than Tika,
+    /// judged by its detected format without extracting it. Always false without the
+    /// `pure-rust` feature, when `set_use_pure_rust` disabled the parsers or with Markdown
+    /// output.
+    pub fn pure_rust_supports(&self, file_path: &str) -> bool {
+        #[cfg(feature = "pure-rust")]
+        if self.use_pure_rust && !self.markdown_output {
+            let format = crate::detect_format(file_path);
+            return self.pure_rust_extractor().supports(&format);
+        }
+        self.uses_small_file_fast_path(file_path)
+    }
+
+    /// Extracts text from a file path. Returns a tuple with stream of the extracted text and metadata.
+    /// the stream is decoded using the extractor's `encoding`
+    ///
+    /// Performance optimizations:
+    /// - Uses pure Rust parsers for supported formats when enabled (2-3x faster)
+    /// - Uses memory-mapped I/O for large files when enabled
+    /// - Adaptive buffer sizing based on file size
+    /// - Falls back to Tika for unsupported formats
+    ///
+    /// PNG, JPEG and TIFF images go straight to Tika, whose Tesseract parser reads their text
+    /// with the extractor's `TesseractOcrConfig`. With the `compression` feature, gzip, bzip2
+    /// and xz files are decompressed and the document inside is extracted, see
+    /// `set_max_decompressed_size`.
+    ///
+    /// An empty file is not parsed, it is extracted to empty text with `Content-Length: 0` as
+    /// its only metadata. The same goes for `extract_file_to_string`, `extract_bytes` and
+    /// `extract_bytes_to_string`.
+    pub fn extract_file(&self, file_path: &str) -> ExtractResult<(StreamReader, Metadata)> {
+        self.check_output_mode(false)?;
+        self.check_file_size(file_path)?;
+        if is_empty_file(file_path) {
+            let mut metadata = empty_document_metadata();
+            self.insert_file_hash(file_path, &mut metadata)?;
+            return Ok((self.text_stream(String::new()), metadata));
+        }
+        if let Some(timeout) = self.extraction_timeout {
+            let file_path = file_path.to_string();
+            return self
+                .run_with_timeout(timeout, move |extractor| extractor.extract_file(&file_path));
+        }
+
+        let (reader, mut metadata) = self.extract_file_contents(file_path)?;
+        self.insert_file_hash(file_path, &mut metadata)?;
+        Ok((reader, metadata))
+    }
+
+    /// `extract_file` after the checks, without the content hash
+    fn extract_file_contents(&self, file_path: &str) -> ExtractResult<(StreamReader, Metadata)> {
+        #[cfg(feature = "compression")]
+        if let Some((data, format)) = self.decompress_file(file_path)? {
+            return self.extract_decompressed(data, format);
+        }
+
+        if crate::detect_format(file_path).is_image() {
+            #[cfg(feature = "ocr-downscale")]
+            if let Some(image) = self.downscale_ocr_image(file_path) {
+                let (reader, mut metadata) =
+                    self.parse_owned_bytes(image.data, Some("image/png"))?;
+                metadata.insert(OCR_DOWNSCALED_KEY.to_string(), vec![image.note]);
+                return Ok((reader, metadata));
+            }
+            return self.extract_file_with_tika(file_path);
+        }
+
+        #[cfg(feature = "pure-rust")]
+        if let Some(data) = self.prepared_pdf(file_path) {
+            if self.use_pure_rust {
+                let extraction = crate::pure_rust_parsers::pdf::extract_pdf_from_bytes(&data);
+                if let Ok((text, metadata)) = extraction {
+                    return Ok((self.text_stream(text), metadata));
+                }
+            }
+            return self.parse_owned_bytes(data, Some("application/pdf"));
+        }
+
+        // Try pure Rust parsers first for maximum performance
+        #[cfg(feature = "pure-rust")]
+        if self.use_pure_rust || self.uses_small_file_fast_path(file_path) {
+            match self.try_pure_rust_extraction(file_path) {
+                Ok((text, metadata)) => {
+                    return Ok((self.text_stream(text), metadata));
+                }
+                // Tika cannot decrypt the document either
+                Err(e @ crate::errors::Error::EncryptedDocument(_)) => return Err(e),
+                Err(_) => {}
+            }
+        }
+
+        #[cfg(feature = "mmap")]
+        if self.uses_mmap(file_path) {
+            return self.extract_file_with_mmap(file_path);
+        }
+
+        // Fallback to standard Tika extraction
+        self.extract_file_with_tika(file_path)
+    }
+
+    /// Parses a file with Tika, which OCRs images with the configured `TesseractOcrConfig`
+    fn extract_file_with_tika(&self, file_path: &str) -> ExtractResult<(StreamReader, Metadata)> {
+        let (reader, metadata) = tika::parse_file(
+            file_path,
+            &self.encoding,
+            &self.tika_pdf_config(),
+            &self.tika_office_config(),
+            &self.tika_ocr_config(),
+            self.tika_handler(),
+            self.tika_config_xml.as_deref(),
+        )?;
+        Ok((self.configure_stream(reader), metadata))
+    }
+
+    /// Extracts text from a file path and returns an iterator over its lines, like
+    /// `BufRead::lines` on the stream of `extract_file`. The text is decoded from the extractor's
+    /// `encoding` one chunk at a time as the lines are read. When Tika streams the text, the
+    /// memory used grows with the longest line rather than with the size of the document, e.g.
+    /// for large logs. The pure Rust parsers extract the whole text before the first line is
+    /// returned, so disable them with `set_use_pure_rust(false)` to stream large files. Lines end
+    /// at `\n` or `\r\n`, which are not part of the returned lines. An error reading or
+    /// decoding the stream is returned in place of a line and ends the iteration
+    pub fn extract_file_lines(
+        &self,
+        file_path: &str,
+    ) -> ExtractResult<impl Iterator<Item = std::io::Result<String>>> {
+        let (reader, _metadata) = self.extract_file(file_path)?;
+        Ok(StreamLines::new(reader, DEFAULT_BUF_SIZE))
+    }
+
+    /// Starts the extraction of a file and returns its metadata together with a stream of the
+    /// body text, for servers that send the metadata, e.g. as response headers, before the body.
+    ///
+    /// The file is always parsed by Tika, in a background thread: the metadata is returned as
+    /// soon as the parser has produced the first character of the body, and the rest of the body
+    /// is extracted while the stream is read. Metadata that a parser only sets after the body,
+    /// which is rare, is missing.
+    pub fn extract_file_metadata_then_body(
+        &self,
+        file_path: &str,
+    ) -> ExtractResult<(Metadata, StreamReader)> {
+        self.check_output_mode(false)?;
+        self.check_file_size(file_path)?;
+        if let Some(timeout) = self.extraction_timeout {
+            let file_path = file_path.to_string();
+            return self.run_with_timeout(timeout, move |extractor| {
+                extractor.extract_file_metadata_then_body(&file_path)
+            });
+        }
+
+        let (reader, metadata) = tika::parse_file(
+            file_path,
+            &self.encoding,
+            &self.tika_pdf_config(),
+            &self.tika_office_config(),
+            &self.tika_ocr_config(),
+            self.tika_handler(),
+            self.tika_config_xml.as_deref(),
+        )?;
+        Ok((metadata, self.configure_stream(reader)))
+    }
+
+    /// Extracts a file into the structural events of Tika's XHTML output: the start and the end
+    /// of each element, with its attributes, and the text in between. The metadata is part of
+    /// the events, as the `<meta>` elements of the `<head>`.
+    ///
+    /// The file is always parsed by Tika, the events are produced while the XHTML is streamed.
+    /// The extractor's `encoding` and output mode do not apply.
+    pub fn extract_file_events(&self, file_path: &str) -> ExtractResult<XhtmlEvents> {
+        self.check_file_size(file_path)?;
+        if let Some(timeout) = self.extraction_timeout {
+            let file_path = file_path.to_string();
+            return self.run_with_timeout(timeout, move |extractor| {
+                extractor.extract_file_events(&file_path)
+            });
+        }
+
+        let (reader, _metadata) = tika::parse_file(
+            file_path,
+            &CharSet::UTF_8,
+            &self.tika_pdf_config(),
+            &self.tika_office_config(),
+            &self.tika_ocr_config(),
+            TikaHandler::Xhtml,
+            self.tika_config_xml.as_deref(),
+        )?;
+        Ok(XhtmlEvents::new(self.configure_stream(reader)))
+    }
+
+    /// Extracts the names and values of the fields of a fillable PDF form (AcroForm), separately
+    /// from the text of the pages. Nested fields have qualified names, e.g. `address.city`, and
+    /// checkboxes the value of their state, e.g. `Yes` or `Off`. Unfilled fields have an empty
+    /// value. Returns an empty map for PDFs without a form and for other formats.
+    ///
+    /// The file is always parsed by Tika, with `set_extract_acroform_content` enabled.
+    pub fn extract_pdf_form_fields(
+        &self,
+        file_path: &str,
+    ) -> ExtractResult<HashMap<String, String>> {
+        self.check_file_size(file_path)?;
+        if let Some(timeout) = self.extraction_timeout {
+            let file_path = file_path.to_string();
+            return self.run_with_timeout(timeout, move |extractor| {
+                extractor.extract_pdf_form_fields(&file_path)
+            });
+        }
+
+        let pdf_config = self
+            .tika_pdf_config()
+            .into_owned()
+            .set_extract_acroform_content(true);
+        let (reader, _metadata) = tika::parse_file(
+            file_path,
+            &CharSet::UTF_8,
+            &pdf_config,
+            &self.tika_office_config(),
+            &self.tika_ocr_config(),
+            TikaHandler::Xhtml,
+            self.tika_config_xml.as_deref(),
+        )?;
+        crate::events::acroform_fields(XhtmlEvents::new(reader))
+            .map_err(|e| crate::errors::Error::IoError(e.to_string()))
+    }
+
+    /// Extracts text from a byte buffer. Returns a tuple with stream of the extracted text and metadata.
+    /// the stream is decoded using the extractor's `encoding`
+    pub fn extract_bytes(&self, buffer: &[u8]) -> ExtractResult<(StreamReader, Metadata)> {
+        self.extract_bytes_as(buffer, None)
+    }
+
+    /// Like `extract_bytes`, for a buffer whose mime type is already known, e.g. from the
+    /// `Content-Type` of an upload. The type is passed to Tika as `Content-Type` metadata, which
+    /// settles ambiguous content such as a csv that detection alone takes for plain text
+    pub fn extract_bytes_with_hint(
+        &self,
+        buffer: &[u8],
+        mime: &str,
+    ) -> ExtractResult<(StreamReader, Metadata)> {
+        self.extract_bytes_as(buffer, Some(mime))
+    }
+
+    fn extract_bytes_as(
+        &self,
+        buffer: &[u8],
+        mime: Option<&str>,
+    ) -> ExtractResult<(StreamReader, Metadata)> {
+        self.check_output_mode(false)?;
+        self.check_size(buffer.len() as u64)?;
+        if buffer.is_empty() {
+            let mut metadata = empty_document_metadata();
+            self.insert_bytes_hash(buffer, &mut metadata);
+            return Ok((self.text_stream(String::new()), metadata));
+        }
+
+        let (reader, mut metadata) = self.parse_bytes(buffer, mime)?;
+        self.insert_bytes_hash(buffer, &mut metadata);
+        Ok((reader, metadata))
+    }
+
+    /// `parse_bytes` of a buffer that the returned stream takes ownership of, for bytes that
+    /// the extractor made, e.g. a copy of a PDF, which Tika reads while the stream is consumed
+    #[cfg(any(feature = "pure-rust", feature = "ocr-downscale"))]
+    fn parse_owned_bytes(
+        &self,
+        buffer: Vec<u8>,
+        mime: Option<&str>,
+    ) -> ExtractResult<(StreamReader, Metadata)> {
+        let (mut reader, metadata) = self.parse_bytes(&buffer, mime)?;
+        // Moving the vector does not move its heap buffer, which Tika keeps reading. A stream
+        // that already owns its input reads a downscaled copy of the buffer instead
+        if reader.owned_input.is_none() {
+            reader.owned_input = Some(buffer);
+        }
+        Ok((reader, metadata))
+    }
+
+    /// Parses a byte buffer with Tika to a stream, without the checks of `extract_bytes`
+    fn parse_bytes(
+        &self,
+        buffer: &[u8],
+        mime: Option<&str>,
+    ) -> ExtractResult<(StreamReader, Metadata)> {
+        #[cfg(feature = "ocr-downscale")]
+        if let Some(image) = self.downscale_ocr_image_bytes(buffer) {
+            let (mut reader, mut metadata) = self.parse_bytes(&image.data, mime)?;
+            reader.owned_input = Some(image.data);
+            metadata.insert(OCR_DOWNSCALED_KEY.to_string(), vec![image.note]);
+            return Ok((reader, metadata));
+        }
+
+        let (reader, metadata) = tika::parse_bytes(
+            buffer,
+            mime,
+            &self.encoding,
+            &self.tika_pdf_config(),
+            &self.tika_office_config(),
+            &self.tika_ocr_config(),
+            self.tika_handler(),
+            self.tika_config_xml.as_deref(),
+        )?;
+        Ok((self.configure_stream(reader), metadata))
+    }
+
+    /// Extracts text from a reader, e.g. a decompression stream or a socket. Returns a tuple with
+    /// stream of the extracted text and metadata. The stream is decoded using the extractor's
+    /// `encoding`
+    ///
+    /// Buffering semantics:
+    /// - the reader is not buffered up front, Tika pulls from it while the returned stream is read
+    /// - detection and some formats, e.g. zip based office documents, need the whole input and
+    ///   Tika spools those to a temporary file first
+    /// - the reader is moved to Tika's parsing thread and dropped once parsing is done, hence the
+    ///   `Send + 'static` bounds
+    ///
+    /// The maximum file size is not checked because the length is not known up front.
+    pub fn extract_reader<R>(&self, reader: R) -> ExtractResult<(StreamReader, Metadata)>
+    where
+        R: std::io::Read + Send + 'static,
+    {
+        self.check_output_mode(false)?;
+        let (reader, metadata) = tika::parse_reader(
+            Box::new(reader),
+            &self.encoding,
+            &self.tika_pdf_config(),
+            &self.tika_office_config(),
+            &self.tika_ocr_config(),
+            self.tika_handler(),
+            self.tika_config_xml.as_deref(),
+        )?;
+        Ok((self.configure_stream(reader), metadata))
+    }
+
+    /// Extracts text from an url. Returns a tuple with stream of the extracted text and metadata.
+    /// the stream is decoded using the extractor's `encoding`
+    pub fn extract_url(&self, url: &str) -> ExtractResult<(StreamReader, Metadata)> {
+        self.check_output_mode(false)?;
+        if let Some(timeout) = self.extraction_timeout {
+            let url = url.to_string();
+            return self.run_with_timeout(timeout, move |extractor| extractor.extract_url(&url));
+        }
+
+        let (reader, metadata) = self.with_url_retry(|| {
+            tika::parse_url(
+                url,
+                &self.encoding,
+                &self.tika_pdf_config(),
+                &self.tika_office_config(),
+                &self.tika_ocr_config(),
+                self.tika_handler(),
+                self.tika_config_xml.as_deref(),
+            )
+        })?;
+        Ok((self.configure_stream(reader), metadata))
+    }
+
+    /// Extracts text from a document identified by an uri. Returns a tuple with stream of the
+    /// extracted text and metadata. Supported schemes are:
+    /// - `http://` and `https://`, same as `extract_url`
+    /// - `s3://bucket/key`, requires the `s3` feature
+    /// - `gs://bucket/key`, requires the `gcs` feature
+    ///
+    /// Objects are downloaded to memory and extracted with `extract_bytes`. Credentials are read
+    /// from the environment. Other schemes return `Error::UnsupportedScheme`.
+    pub fn extract_uri(&self, uri: &str) -> ExtractResult<(StreamReader, Metadata)> {
+        let (scheme, _) = uri.split_once("://").ok_or_else(|| {
+            crate::errors::Error::UnsupportedScheme(format!("Missing scheme in uri: {}", uri))
+        })?;
+        match scheme {
+            "http" | "https" => self.extract_url(uri),
+            #[cfg(any(feature = "s3", feature = "gcs"))]
+            "s3" | "gs" => {
+                let object_uri = object_storage::ObjectUri::parse(uri)?;
+                let store = object_storage::object_store_for(&object_uri)?;
+                self.extract_object(store.as_ref(), object_uri.key)
+            }
+            scheme => Err(object_storage::unsupported_scheme(scheme)),
+        }
+    }
+
+    /// Downloads the object at `key` from `store` and extracts its text. Returns a tuple with
+    /// stream of the extracted text and metadata.
+    #[cfg(any(feature = "s3", feature = "gcs"))]
+    pub fn extract_object(
+        &self,
+        store: &dyn object_store::ObjectStore,
+        key: &str,
+    ) -> ExtractResult<(StreamReader, Metadata)> {
+        let bytes = object_storage::fetch_object(store, key)?;
+        let (mut reader, metadata) = self.extract_bytes(&bytes)?;
+        // Moving the vector does not move its heap buffer, which Tika keeps reading
+        reader.owned_input = Some(bytes);
+        Ok((reader, metadata))
+    }
+
+    /// Extracts text from a file path. Returns a tuple with string that is of maximum length
+    /// of the extractor's `extract_string_max_length` and metadata.
+    ///
+    /// Performance optimizations:
+    /// - Uses pure Rust parsers when available for 2-3x speedup
+    /// - Uses memory-mapped I/O for large files when enabled
+    /// - Applies optimized text processing when enabled
+    /// - Smart text truncation that respects word boundaries
+    pub fn extract_file_to_string(&self, file_path: &str) -> ExtractResult<(String, Metadata)> {
+        self.check_output_mode(true)?;
+        self.check_file_size(file_path)?;
+        if is_empty_file(file_path) {
+            let mut metadata = empty_document_metadata();
+            self.insert_file_hash(file_path, &mut metadata)?;
+            return Ok(self.post_process_text(String::new(), metadata, Some(file_path)));
+        }
+        if let Some(timeout) = self.extraction_timeout {
+            let file_path = file_path.to_string();
+            return self.run_with_timeout(timeout, move |extractor| {
+                extractor.extract_file_to_string(&file_path)
+            });
+        }
+
+        #[cfg(feature = "compression")]
+        if let Some((data, format)) = self.decompress_file(file_path)? {
+            let (text, mut metadata) = self.extract_decompressed_text(&data, format)?;
+            self.insert_file_hash(file_path, &mut metadata)?;
+            return Ok(self.post_process_text(text, metadata, None));
+        }
+
+        let (mut text, mut metadata) = self.extract_file_text(file_path)?;
+        if self.needs_ocr_fallback(file_path, &text) {
+            let mut ocr_extractor = self.clone();
+            ocr_extractor.pdf_config = self
+                .pdf_config
+                .clone()
+                .set_ocr_strategy(PdfOcrStrategy::OCR_ONLY);
+            // The pure Rust PDF parser cannot run OCR
+            ocr_extractor.use_pure_rust = false;
+            (text, metadata) = ocr_extractor.extract_file_text(file_path)?;
+            metadata.insert("OCR-Fallback".to_string(), vec!["true".to_string()]);
+        }
+
+        self.insert_file_hash(file_path, &mut metadata)?;
+        Ok(self.post_process_text(text, metadata, Some(file_path)))
+    }
+
+    /// The text of `extract_file_to_string` before post-processing
+    fn extract_file_text(&self, file_path: &str) -> ExtractResult<(String, Metadata)> {
+        if let Some(max_pages) = self.preview_pages {
+            if crate::detect_format(file_path) == DocumentFormat::Pdf {
+                return self.extract_pdf_preview(file_path, max_pages);
+            }
+        }
+
+        #[cfg(all(feature = "parallel", feature = "pure-rust"))]
+        if self.uses_parallel_ocr(file_path) {
+            return self.extract_pdf_ocr_parallel(file_path);
+        }
+
+        #[cfg(feature = "pure-rust")]
+        if let Some(data) = self.prepared_pdf(file_path) {
+            if self.use_pure_rust && !self.markdown_output {
+                if let Ok(extraction) = crate::pure_rust_parsers::pdf::extract_pdf_from_bytes(&data)
+                {
+                    return Ok(extraction);
+                }
+            }
+            return self.parse_bytes_to_string(&data);
+        }
+
+        #[cfg(feature = "ocr-downscale")]
+        if let Some(image) = self.downscale_ocr_image(file_path) {
+            let (text, mut metadata) = self.parse_bytes_to_string(&image.data)?;
+            metadata.insert(OCR_DOWNSCALED_KEY.to_string(), vec![image.note]);
+            return Ok((text, metadata));
+        }
+
+        // Running headers and footers are told apart by comparing the pages
+        let removes_headers_footers = self.removes_headers_footers(file_path);
+        let annotates_page_boundaries = self.annotates_page_boundaries(file_path);
+        if removes_headers_footers || annotates_page_boundaries {
+            let (mut pages, metadata) = self.read_pdf_pages(file_path)?;
+            if removes_headers_footers {
+                pages = crate::chunk::remove_repeated_headers_footers(&pages);
+            }
+            if annotates_page_boundaries {
+                pages = crate::chunk::annotate_pages(&pages, &self.page_marker);
+            }
+            let text = crate::bidi::reorder(&pages.join("\n\n"), self.bidi_reordering);
+            return Ok((text, metadata));
+        }
+
+        // Try pure Rust parsers first for maximum performance, Markdown needs Tika's XHTML.
+        // Images are left to Tika's OCR
+        #[cfg(feature = "pure-rust")]
+        if ((self.use_pure_rust && !self.markdown_output)
+            || self.uses_small_file_fast_path(file_path))
+            && !crate::detect_format(file_path).is_image()
+        {
+            match self.try_pure_rust_extraction(file_path) {
+                Ok((text, metadata)) => return Ok((text, metadata)),
+                // Tika cannot decrypt the document either
+                Err(e @ crate::errors::Error::EncryptedDocument(_)) => return Err(e),
+                Err(_) => {}
+            }
+        }
+
+        // Large files are mapped and parsed by Tika from memory. Only files the pure Rust
+        // parsers did not take get here, so no file is parsed twice
+        #[cfg(feature = "mmap")]
+        if self.uses_mmap(file_path) {
+            let mmap = self.map_file(file_path)?;
+            let (text, mut metadata) = self.parse_bytes_to_string(&mmap)?;
+            self.insert_bytes_hash(&mmap, &mut metadata);
+            return Ok((text, metadata));
+        }
+
+        // Standard Tika extraction (optimized through buffer improvements)
+        let (text, metadata) = tika::parse_file_to_string(
+            file_path,
+            self.extract_string_max_length,
+            &self.tika_pdf_config(),
+            &self.tika_office_config(),
+            &self.tika_ocr_config(),
+            self.tika_handler(),
+            self.tika_config_xml.as_deref(),
+        )?;
+        Ok((self.tika_text(text), metadata))
+    }
+
+    /// Whether the text of a PDF is too short for a text layer, see `set_ocr_fallback_on_empty`
+    fn needs_ocr_fallback(&self, file_path: &str, text: &str) -> bool {
+        if !self.ocr_fallback_on_empty
+            || !self.ocr_enabled
+            || crate::detect_format(file_path) != DocumentFormat::Pdf
+        {
+            return false;
+        }
+        let text = if self.markup_output() {
+            crate::simd_text::extract_text_content(text)
+        } else {
+            text.to_string()
+        };
+        text.chars().filter(|c| !c.is_whitespace()).count() < self.ocr_fallback_min_chars
+    }
+
+
+
+    /// Extracts the text of a file together with the files embedded in it, such as the entries
+    /// of a zip, the attachments of an email or the attachments of a PDF, each separately.
+    /// Containers are searched recursively, so an attachment of a zipped email is returned too.
+    /// Every text is limited to `extract_string_max_length`.
+    /// Returns a tuple with the text and metadata of the file and the embedded documents.
+    pub fn extract_file_with_embedded(
+        &self,
+        file_path: &str,
+    ) -> ExtractResult<(String, Metadata, Vec<EmbeddedDoc>)> {
+        self.check_output_mode(true)?;
+        self.check_file_size(file_path)?;
+        if let Some(timeout) = self.extraction_timeout {
+            let file_path = file_path.to_string();
+            return self.run_with_timeout(timeout, move |extractor| {
+                extractor.extract_file_with_embedded(&file_path)
+            });
+        }
+
+        let mut documents = tika::parse_file_with_embedded(
+            file_path,
+            self.extract_string_max_length,
+            &self.tika_pdf_config(),
+            &self.tika_office_config(),
+            &self.tika_ocr_config(),
+            self.tika_config_xml.as_deref(),
+        )?
+        .into_iter()
+        .map(|mut metadata| {
+            let text = metadata
+                .remove("X-TIKA:content")
+                .unwrap_or_default()
+                .concat();
+            (text, metadata)
+        });
+
+        let (text, metadata) = documents.next().unwrap_or_default();
+        let embedded = documents
+            .map(|(text, metadata)| {
+                let first =
+                    |key: &str| metadata.get(key).and_then(|values| values.first()).cloned();
+                EmbeddedDoc {
+                    name: first("resourceName")
+                        .or_else(|| first("X-TIKA:embedded_resource_path"))
+                        .unwrap_or_default(),
+                    mime_type: first("Content-Type").unwrap_or_default(),
+                    text,
+                    metadata,
+                }
+            })
+            .collect();
+        Ok((text, metadata, embedded))
+    }
+
+    /// Extracts Tika's structured XHTML (`<h1>`, `<p>`, `<table>`, ...) from a file, verbatim as
+    /// produced by Tika's ToXMLContentHandler. Unlike `set_xml_output`, the output is neither
+    /// limited to `extract_string_max_length` nor post-processed.
+    /// Returns a tuple with the XHTML string and metadata.
+    pub fn extract_file_to_xhtml(&self, file_path: &str) -> ExtractResult<(String, Metadata)> {
+        self.check_output_mode(true)?;
+        self.check_file_size(file_path)?;
+        if let Some(timeout) = self.extraction_timeout {
+            let file_path = file_path.to_string();
+            return self.run_with_timeout(timeout, move |extractor| {
+                extractor.extract_file_to_xhtml(&file_path)
+            });
+        }
+
+        tika::parse_file_to_xhtml(
+            file_path,
+            &self.tika_pdf_config(),
+            &self.tika_office_config(),
+            &self.tika_ocr_config(),
+            self.tika_config_xml.as_deref(),
+        )
+    }
+
+    /// Extracts only the metadata of a file, e.g. its mime type, page count and author, without
+    /// its text. Much faster than the other extract functions on large documents and scans, for
+    /// cataloging files.
+    ///
+    /// The file is always parsed by Tika. The body is dropped as it is parsed, OCR is skipped
+    /// and embedded documents are not parsed, so metadata that comes from the text, such as the
+    /// detected language, or from embedded documents is missing. `set_metadata_keys` applies.
+    pub fn extract_file_metadata_only(&self, file_path: &str) -> ExtractResult<Metadata> {
+        self.check_file_size(file_path)?;
+        if let Some(timeout) = self.extraction_timeout {
+            let file_path = file_path.to_string();
+            return self.run_with_timeout(timeout, move |extractor| {
+                extractor.extract_file_metadata_only(&file_path)
+            });
+        }
+
+        let pdf_config = self
+            .tika_pdf_config()
+            .into_owned()
+            .set_ocr_strategy(PdfOcrStrategy::NO_OCR);
+        let mut metadata = tika::parse_file_metadata(
+            file_path,
+            &pdf_config,
+            &self.tika_office_config(),
+            self.tika_config_xml.as_deref(),
+        )?;
+        if let Some(keys) = &self.metadata_keys {
+            metadata.retain(|key, _| keys.contains(&key.to_lowercase()));
+        }
+        Ok(metadata)
+    }
+
+    /// Extracts text from a byte buffer. Returns a tuple with string that is of maximum length
+    /// of the extractor's `extract_string_max_length` and metadata.
+    pub fn extract_bytes_to_string(&self, buffer: &[u8]) -> ExtractResult<(String, Metadata)> {
+        self.check_output_mode(true)?;
+        self.check_size(buffer.len() as u64)?;
+        if buffer.is_empty() {
+            let mut metadata = empty_document_metadata();
+            self.insert_bytes_hash(buffer, &mut metadata);
+            return Ok(self.post_process_text(String::new(), metadata, None));
+        }
+        if let Some(timeout) = self.extraction_timeout {
+            // The buffer is copied because the extraction thread may outlive this call
+            let buffer = buffer.to_vec();
+            return self.run_with_timeout(timeout, move |extractor| {
+                extractor.extract_bytes_to_string(&buffer)
+            });
+        }
+
+        let (text, mut metadata) = self.parse_bytes_to_string(buffer)?;
+        self.insert_bytes_hash(buffer, &mut metadata);
+        Ok(self.post_process_text(text, metadata, None))
+    }
+
+    /// Parses a byte buffer to a string with Tika, without the checks and the post processing
+    /// of `extract_bytes_to_string`, for callers that run them for the whole extraction
+    fn parse_bytes_to_string(&self, buffer: &[u8]) -> ExtractResult<(String, Metadata)> {
+        #[cfg(feature = "ocr-downscale")]
+        if let Some(image) = self.downscale_ocr_image_bytes(buffer) {
+            let (text, mut metadata) = self.parse_bytes_to_string(&image.data)?;
+            metadata.insert(OCR_DOWNSCALED_KEY.to_string(), vec![image.note]);
+            return Ok((text, metadata));
+        }
+
+        let (text, metadata) = tika::parse_bytes_to_string(
+            buffer,
+            self.extract_string_max_length,
+            &self.tika_pdf_config(),
+            &self.tika_office_config(),
+            &self.tika_ocr_config(),
+            self.tika_handler(),
+            self.tika_config_xml.as_deref(),
+        )?;
+        Ok((self.tika_text(text), metadata))
+    }
+
+    /// Extracts text from a URL. Returns a tuple with string that is of maximum length
+    /// of the extractor's `extract_string_max_length` and metadata.
+    pub fn extract_url_to_string(&self, url: &str) -> ExtractResult<(String, Metadata)> {
+        self.check_output_mode(true)?;
+        if let Some(timeout) = self.extraction_timeout {
+            let url = url.to_string();
+            return self.run_with_timeout(timeout, move |extractor| {
+                extractor.extract_url_to_string(&url)
+            });
+        }
+
+        let (text, metadata) = self.with_url_retry(|| {
+            tika::parse_url_to_string(
+                url,
+                self.extract_string_max_length,
+                &self.tika_pdf_config(),
+                &self.tika_office_config(),
+                &self.tika_ocr_config(),
+                self.tika_handler(),
+                self.tika_config_xml.as_deref(),
+            )
+        })?;
+        let text = self.tika_text(text);
+
+        Ok(self.post_process_text(text, metadata, None))
+    }
+
+    /// Reads the raw bytes of a single member of a zip based container, e.g. `word/document.xml`
+    /// or `word/media/image1.png` of a docx, without extracting the whole document.
+    /// Returns `Error::MemberNotFound` if the container has no such member.
+    #[cfg(feature = "pure-rust")]
+    pub fn extract_zip_member(&self, file_path: &str, member: &str) -> ExtractResult<Vec<u8>> {
+        self.check_file_size(file_path)?;
+        crate::pure_rust_parsers::office::read_zip_member(file_path, member)
+    }
+
+    /// Extracts the cells of every sheet of an xlsx workbook together with the sheet names, by
+    /// row and column, see `office::extract_xlsx_structured` for how cells are formatted.
+    /// Always parsed in Rust. Returns `Error::UnsupportedFormat` for other formats.
+    #[cfg(feature = "pure-rust")]
+    pub fn extract_spreadsheet(
+        &self,
+        file_path: &str,
+    ) -> ExtractResult<Vec<crate::pure_rust_parsers::office::SheetData>> {
+        self.check_file_size(file_path)?;
+        let format = crate::detect_format(file_path);
+        if format != DocumentFormat::Xlsx {
+            return Err(crate::errors::Error::UnsupportedFormat(format!(
+                "Spreadsheet extraction supports xlsx workbooks, not {:?}",
+                format
+            )));
+        }
+        let locale = match &self.locale {
+            Some(tag) => Some(crate::locale::Locale::from_tag(tag).ok_or_else(|| {
+                crate::errors::Error::InvalidConfiguration(format!("Unknown locale {}", tag))
+            })?),
+            None => None,
+        };
+        crate::pure_rust_parsers::office::extract_xlsx_structured_localized(
+            file_path,
+            locale.as_ref(),
+        )
+    }
+
+    /// Extracts the title, the body text and the presenter's notes of every slide of a pptx
+    /// presentation, so the notes can be searched or shown apart from the slides. The notes are
+    /// left empty when `OfficeParserConfig::set_include_slide_notes` is off. Always parsed in
+    /// Rust. Returns `Error::UnsupportedFormat` for other formats.
+    #[cfg(feature = "pure-rust")]
+    pub fn extract_presentation(
+        &self,
+        file_path: &str,
+    ) -> ExtractResult<Vec<crate::pure_rust_parsers::office::Slide>> {
+        self.check_file_size(file_path)?;
+        let format = crate::detect_format(file_path);
+        if format != DocumentFormat::Pptx {
+            return Err(crate::errors::Error::UnsupportedFormat(format!(
+                "Presentation extraction supports pptx presentations, not {:?}",
+                format
+            )));
+        }
+        crate::pure_rust_parsers::office::extract_pptx_slides(
+            file_path,
+            self.office_config.include_slide_notes,
+        )
+    }
+
+    /// Extracts every word of a PDF together with its page and bounding box, e.g. to highlight
+    /// search hits on the rendered pages. Coordinates are in points from the top left corner of
+    /// the displayed page, see `pdf::PositionedText`. Always parsed in Rust. Returns
+    /// `Error::UnsupportedFormat` for other formats.
+    #[cfg(feature = "pure-rust")]
+    pub fn extract_pdf_positions(
+        &self,
+        file_path: &str,
+    ) -> ExtractResult<Vec<crate::pure_rust_parsers::pdf::PositionedText>> {
+        self.check_file_size(file_path)?;
+        let format = crate::detect_format(file_path);
+        if format != DocumentFormat::Pdf {
+            return Err(crate::errors::Error::UnsupportedFormat(format!(
+                "Position extraction supports PDF files, not {:?}",
+                format
+            )));
+        }
+        crate::pure_rust_parsers::pdf::extract_pdf_positions(file_path)
+    }
+
+    /// Returns the indirect object `obj gen R` of a PDF, e.g. `1 0 R` for the catalog of most
+    /// files, formatted like `<</Type /Catalog/Pages 2 0 R>>`. Meant for debugging PDF files.
+    /// Returns `Error::ObjectNotFound` if the file has no such object.
+    #[cfg(feature = "pure-rust")]
+    pub fn get_pdf_object(&self, file_path: &str, obj: u32, gen: u16) -> ExtractResult<String> {
+        self.check_file_size(file_path)?;
+        crate::pure_rust_parsers::pdf::get_pdf_object(file_path, obj, gen)
+    }
+
+    /// Extracts the text of every page of a PDF separately, in page order.
+    /// Returns `Error::UnsupportedFormat` for other formats.
+    pub fn extract_file_pages(&self, file_path: &str) -> ExtractResult<(Vec<String>, Metadata)> {
+        self.check_ocr_config()?;
+        self.check_file_size(file_path)?;
+        let format = crate::format_detection::detect_format(file_path);
+        if format != crate::format_detection::DocumentFormat::Pdf {
+            return Err(crate::errors::Error::UnsupportedFormat(format!(
+                "Page extraction is not supported for {:?} files",
+                format
+            )));
+        }
+
+        let (mut pages, mut metadata) = self.pdf_pages(file_path)?;
+        if self.skip_blank_pages {
+            let page_count = pages.len();
+            pages.retain(|page| !page.trim().is_empty());
+            metadata.insert(
+                "Blank-Pages-Skipped".to_string(),
+                vec![(page_count - pages.len()).to_string()],
+            );
+        }
+        Ok((pages, metadata))
+    }
+
+    /// Extracts a file in chunks along its natural boundaries: one chunk per page for PDFs,
+    /// one chunk per sheet for Excel files read by the pure Rust parser, and a single chunk
+    /// with the whole text for every other format.
+    /// Blank pages are dropped when `set_skip_blank_pages` is enabled. Every chunk carries the
+    /// metadata of the document.
+    pub fn extract_file_chunked(&self, file_path: &str) -> ExtractResult<Vec<Chunk>> {
+        self.check_output_mode(true)?;
+        self.check_file_size(file_path)?;
+        let format = crate::format_detection::detect_format(file_path);
+
+        if format == crate::format_detection::DocumentFormat::Pdf {
+            let (pages, metadata) = self.pdf_pages(file_path)?;
+            let chunks = pages
+                .into_iter()
+                .enumerate()
+                .filter(|(_, text)| !self.skip_blank_pages || !text.trim().is_empty())
+                .enumerate()
+                .map(|(index, (page, text))| {
+                    let mut metadata = metadata.clone();
+                    metadata.insert("Page-Number".to_string(), vec![(page + 1).to_string()]);
+                    Chunk {
+                        index,
+                        kind: ChunkKind::Page,
+                        text,
+                        metadata,
+                    }
+                })
+                .collect();
+            return Ok(chunks);
+        }
+
+        #[cfg(feature = "pure-rust")]
+        if self.use_pure_rust && format == crate::format_detection::DocumentFormat::Xlsx {
+            if let Ok((sheets, metadata)) =
+                crate::pure_rust_parsers::office::extract_xlsx_sheets(file_path)
+            {
+                let chunks = sheets
+                    .into_iter()
+                    .enumerate()
+                    .map(|(index, (name, text))| {
+                        let mut metadata = metadata.clone();
+                        metadata.insert("Sheet-Name".to_string(), vec![name]);
+                        Chunk {
+                            index,
+                            kind: ChunkKind::Sheet,
+                            text,
+                            metadata,
+                        }
+                    })
+                    .collect();
+                return Ok(chunks);
+            }
+        }
+
+        let (text, metadata) = self.extract_file_to_string(file_path)?;
+        Ok(vec![Chunk {
+            index: 0,
+            kind: ChunkKind::Document,
+            text,
+            metadata,
+        }])
+    }
+
+    /// The text of every page of a PDF, read by the pure Rust parser when enabled and otherwise
+    /// split from Tika's XHTML, without the running headers and footers if requested
+    fn pdf_pages(&self, file_path: &str) -> ExtractResult<(Vec<String>, Metadata)> {
+        let (pages, metadata) = self.read_pdf_pages(file_path)?;
+        if self.remove_headers_footers {
+            return Ok((
+                crate::chunk::remove_repeated_headers_footers(&pages),
+                metadata,
+            ));
+        }
+        Ok((pages, metadata))
+    }
+
+    fn read_pdf_pages(&self, file_path: &str) -> ExtractResult<(Vec<String>, Metadata)> {
+        #[cfg(feature = "pure-rust")]
+        if self.use_pure_rust {
+            if let Ok(pages) = crate::pure_rust_parsers::pdf::extract_pdf_pages(file_path) {
+                return Ok(pages);
+            }
+        }
+
+        let (xhtml, metadata) = self.extract_file_to_xhtml(file_path)?;
+        Ok((crate::chunk::xhtml_pages(&xhtml), metadata))
+    }
+
+    /// The text of the first pages of a PDF, see `set_preview_pages`. The pure Rust parser
+    /// stops after them, Tika is given a copy of the PDF without the later pages, so they are
+    /// not OCRed either. Without the `pure-rust` feature there is no such copy and Tika parses
+    /// the whole PDF
+    fn extract_pdf_preview(
+        &self,
+        file_path: &str,
+        max_pages: u32,
+    ) -> ExtractResult<(String, Metadata)> {
+        #[cfg(feature = "pure-rust")]
+        {
+            use crate::pure_rust_parsers::pdf;
+
+            if self.use_pure_rust {
+                if let Ok(preview) = pdf::extract_pdf_preview(file_path, max_pages) {
+                    return Ok(preview);
+                }
+            }
+            if let Ok(data) = pdf::truncate_pdf_pages(file_path, max_pages) {
+                return self.parse_bytes_to_string(&data);
+            }
+        }
+
+        // Tika parses the whole document, only the text of the first pages is kept
+        let (xhtml, metadata) = self.extract_file_to_xhtml(file_path)?;
+        let pages = crate::chunk::xhtml_pages(&xhtml);
+        let preview = pages
+            .iter()
+            .take(max_pages as usize)
+            .cloned()
+            .collect::<Vec<_>>();
+        Ok((preview.join("\n\n"), metadata))
+    }
+
+    /// A copy of a PDF to parse instead of the file: without its hidden layers, see
+    /// `visible_layers_pdf`, and with its rotated pages stored upright when Tika OCRs the
+    /// pages, see `pdf::upright_rotated_pages`. `None` when neither applies. With the `AUTO`
+    /// OCR strategy the pages are left as they are, Tika's renderer applies their `/Rotate`
+    #[cfg(feature = "pure-rust")]
+    fn prepared_pdf(&self, file_path: &str) -> Option<Vec<u8>> {
+        use crate::pure_rust_parsers::pdf;
+
+        let visible = self.visible_layers_pdf(file_path);
+        if !self.ocrs_pdf_pages(file_path) {
+            return visible;
+        }
+        let upright = match &visible {
+            Some(data) => pdf::upright_rotated_pages_from_bytes(data),
+            None => pdf::upright_rotated_pages(file_path),
+        };
+        upright.ok().flatten().or(visible)
+    }
+
+    /// Whether a file is a PDF whose pages Tika renders and OCRs
+    #[cfg(feature = "pure-rust")]
+    fn ocrs_pdf_pages(&self, file_path: &str) -> bool {
+        !self.use_pure_rust
+            && self.ocr_enabled
+            && matches!(
+                self.pdf_config.ocr_strategy,
+                PdfOcrStrategy::OCR_ONLY | PdfOcrStrategy::OCR_AND_TEXT_EXTRACTION
+            )
+            && crate::detect_format(file_path) == DocumentFormat::Pdf
+    }
+
+    /// A copy of a PDF without the content of its hidden layers, see
+    /// `PdfParserConfig::set_respect_ocg_visibility`. `None` for other files, for PDFs without
+    /// hidden layers and for PDFs the pure Rust parser cannot read, which are left to Tika as
+    /// they are
+    #[cfg(feature = "pure-rust")]
+    fn visible_layers_pdf(&self, file_path: &str) -> Option<Vec<u8>> {
+        if !self.pdf_config.respect_ocg_visibility
+            || crate::detect_format(file_path) != DocumentFormat::Pdf
+        {
+            return None;
+        }
+        crate::pure_rust_parsers::pdf::remove_hidden_layers(file_path)
+            .ok()
+            .flatten()
+    }
+
+    /// A PNG image file downscaled to the pixel budget of
+    /// `TesseractOcrConfig::set_max_ocr_image_pixels`. `None` for other files, without OCR and
+    /// for images within the budget, which are OCRed as they are
+    #[cfg(feature = "ocr-downscale")]
+    fn downscale_ocr_image(&self, file_path: &str) -> Option<DownscaledImage> {
+        let max_pixels = self
+            .ocr_config
+            .max_ocr_image_pixels
+            .filter(|_| self.ocr_enabled)?;
+        if crate::detect_format(file_path) != DocumentFormat::Image(ImageKind::Png) {
+            return None;
+        }
+        let file = std::fs::File::open(file_path).ok()?;
+        crate::ocr_image::downscale_png(std::io::BufReader::new(file), max_pixels)
+    }
+
+    /// A PNG image buffer downscaled to the pixel budget, see `downscale_ocr_image`
+    #[cfg(feature = "ocr-downscale")]
+    fn downscale_ocr_image_bytes(&self, buffer: &[u8]) -> Option<DownscaledImage> {
+        let max_pixels = self
+            .ocr_config
+            .max_ocr_image_pixels
+            .filter(|_| self.ocr_enabled)?;
+        if crate::detect_format_from_bytes(buffer) != DocumentFormat::Image(ImageKind::Png) {
+            return None;
+        }
+        crate::ocr_image::downscale_png(buffer, max_pixels)
+    }
+
+    /// Splits a multipart MIME body, e.g. a `multipart/mixed` email, into its parts and detects
+    /// the format of each, so they can be passed to `extract_bytes`. `boundary` is the
+    /// `boundary` parameter of the `Content-Type` header of the message, without the leading
+    /// `--`. Returns `Error::ParseError` when the body has no delimiter for the boundary.
+    pub fn split_multipart(
+        &self,
+        data: &[u8],
+        boundary: &str,
+    ) -> ExtractResult<Vec<MultipartPart>> {
+        self.check_size(data.len() as u64)?;
+        crate::multipart::split_multipart(data, boundary)
+    }
+
+    /// Whether a file is mapped into memory instead of read by Tika, see `set_use_mmap` and
+    /// `set_mmap_threshold`
+    #[cfg(feature = "mmap")]
+    fn uses_mmap(&self, file_path: &str) -> bool {
+        self.use_mmap
+            && std::fs::metadata(file_path)
+                .is_ok_and(|metadata| metadata.len() as usize > self.mmap_threshold)
+    }
+
+    /// Memory-mapped file extraction for improved performance on large files
+    #[cfg(feature = "mmap")]
+    fn extract_file_with_mmap(&self, file_path: &str) -> ExtractResult<(StreamReader, Metadata)> {
+        let mmap = self.map_file(file_path)?;
+        // Use the memory-mapped data as a byte slice for extraction
+        self.extract_bytes(&mmap)
+    }
+
+    /// Maps a file into memory
+    #[cfg(feature = "mmap")]
+    fn map_file(&self, file_path: &str) -> ExtractResult<memmap2::Mmap> {
+        use std::fs::File;
+
+        let file = File::open(file_path)
+            .map_err(|e| crate::errors::Error::IoError(e.to_string()))?;
+        if self.max_file_size.is_some() {
+            let file_metadata = file
+                .metadata()
+                .map_err(|e| crate::errors::Error::IoError(e.to_string()))?;
+            self.check_size(file_metadata.len())?;
+        }
+
+        unsafe { MmapOptions::new().map(&file) }
+            .map_err(|e| crate::errors::Error::IoError(e.to_string()))
+    }
+
+    /// Whether the pages of a PDF are OCRed in parallel, see `set_parallel_ocr`
+    #[cfg(all(feature = "parallel", feature = "pure-rust"))]
+    fn uses_parallel_ocr(&self, file_path: &str) -> bool {
+        self.parallel_ocr
+            && self.ocr_enabled
+            && matches!(
+                self.pdf_config.ocr_strategy,
+                PdfOcrStrategy::OCR_ONLY | PdfOcrStrategy::OCR_AND_TEXT_EXTRACTION
+            )
+            && crate::detect_format(file_path) == DocumentFormat::Pdf
+    }
+
+    /// OCRs the pages of a PDF on the rayon thread pool, each page split off into a PDF of its
+    /// own and parsed by Tika, and joins their text in page order
+    #[cfg(all(feature = "parallel", feature = "pure-rust"))]
+    fn extract_pdf_ocr_parallel(&self, file_path: &str) -> ExtractResult<(String, Metadata)> {
+        let pages = crate::pure_rust_parsers::pdf::PdfPageSplitter::open(file_path)?;
+        // The metadata of the document, the pages carry that of their copy
+        let pdf_config = self
+            .tika_pdf_config()
+            .into_owned()
+            .set_ocr_strategy(PdfOcrStrategy::NO_OCR);
+        let metadata = tika::parse_file_metadata(
+            file_path,
+            &pdf_config,
+            &self.tika_office_config(),
+            self.tika_config_xml.as_deref(),
+        )?;
+
+        // Each page is copied out only when a worker picks it up
+        let texts = (0..pages.page_count())
+            .into_par_iter()
+            .map(|index| {
+                let page = pages.page_pdf(index)?;
+                self.parse_bytes_to_string(&page).map(|(text, _)| text)
+            })
+            .collect::<ExtractResult<Vec<String>>>()?;
+        let mut texts: Vec<String> = texts.iter().map(|text| text.trim().to_string()).collect();
+        // Applied as on the pages of `read_pdf_pages`, the pages are already in visual order
+        if self.removes_headers_footers(file_path) {
+            texts = crate::chunk::remove_repeated_headers_footers(&texts);
+        }
+        if self.annotates_page_boundaries(file_path) {
+            texts = crate::chunk::annotate_pages(&texts, &self.page_marker);
+        }
+        Ok((texts.join("\n\n"), metadata))
+    }
+
+    /// Extract multiple files in parallel (when parallel feature is enabled)
+    ///
+    /// The files are extracted on the rayon thread pool. A worker thread is attached to the Tika
+    /// vm only while it extracts a file and detached afterwards, so the long lived pool threads
+    /// do not retain isolate state between calls.
+    #[cfg(feature = "parallel")]
+    pub fn extract_files_parallel<P: AsRef<Path> + Sync>(
+        &self,
+        file_paths: &[P],
+    ) -> Vec<ExtractResult<(String, Metadata)>> {
+        if self.enable_parallel {
+            file_paths
+                .par_iter()
+                .map(|path| self.extract_file_to_string(path.as_ref().to_str().unwrap_or("")))
+                .collect()
+        } else {
+            file_paths
+                .iter()
+                .map(|path| self.extract_file_to_string(path.as_ref().to_str().unwrap_or("")))
+                .collect()
+        }
+    }
+
+    /// Extract multiple files sequentially (fallback when parallel feature is disabled)
+    #[cfg(not(feature = "parallel"))]
+    pub fn extract_files_parallel<P: AsRef<Path>>(
+        &self,
+        file_paths: &[P],
+    ) -> Vec<ExtractResult<(String, Metadata)>> {
+        file_paths
+            .iter()
+            .map(|path| self.extract_file_to_string(path.as_ref().to_str().unwrap_or("")))
+            .collect()
+    }
+
+    /// Extract multiple files like `extract_files_parallel`, but fails the whole batch with the
+    /// first error encountered. Files that have not been started yet are skipped once a file
+    /// fails. In parallel the files do not fail in input order, so with several bad files the
+    /// error returned is not necessarily the one of the first bad path
+    #[cfg(feature = "parallel")]
+    pub fn extract_files_parallel_try<P: AsRef<Path> + Sync>(
+        &self,
+        file_paths: &[P],
+    ) -> ExtractResult<Vec<(String, Metadata)>> {
+        if self.enable_parallel {
+            file_paths
+                .par_iter()
+                .map(|path| self.extract_file_to_string(path.as_ref().to_str().unwrap_or("")))
+                .collect()
+        } else {
+            file_paths
+                .iter()
+                .map(|path| self.extract_file_to_string(path.as_ref().to_str().unwrap_or("")))
+                .collect()
+        }
+    }
+
+    /// Extract multiple files sequentially, stopping at the first error (fallback when parallel
+    /// feature is disabled)
+    #[cfg(not(feature = "parallel"))]
+    pub fn extract_files_parallel_try<P: AsRef<Path>>(
+        &self,
+        file_paths: &[P],
+    ) -> ExtractResult<Vec<(String, Metadata)>> {
+        file_paths
+            .iter()
+            .map(|path| self.extract_file_to_string(path.as_ref().to_str().unwrap_or("")))
+            .collect()
+    }
+
+    /// Extract multiple files like `extract_files_parallel`, but returns each result keyed by the
+    /// path it was extracted from, so callers do not have to line the results up with the input.
+    /// A path given more than once is extracted each time and keeps a single entry
+    pub fn extract_files_parallel_map<P: AsRef<Path> + Sync>(
+        &self,
+        file_paths: &[P],
+    ) -> HashMap<PathBuf, ExtractResult<(String, Metadata)>> {
+        file_paths
+            .iter()
+            .map(|path| path.as_ref().to_path_buf())
+            .zip(self.extract_files_parallel(file_paths))
+            .collect()
+    }
+
+    /// Extracts the files of a directory like `extract_files_parallel`, and the files of its
+    /// subdirectories too when `recursive` is set. Only the files `filter` accepts are extracted,
+    /// e.g. `|path| path.extension().is_some_and(|ext| ext == "pdf")`. Files of a format that
+    /// cannot be extracted, with `Error::UnsupportedFormat`, are left out. A directory that
+    /// cannot be read is returned with its `Error::IoError`. Links to directories are not
+    /// followed. The results are sorted by path
+    pub fn extract_dir<P: AsRef<Path>>(
+        &self,
+        dir: P,
+        recursive: bool,
+        filter: impl Fn(&Path) -> bool,
+    ) -> Vec<(PathBuf, ExtractResult<(String, Metadata)>)> {
+        let mut results = Vec::new();
+        let mut files = Vec::new();
+        let mut dirs = vec![dir.as_ref().to_path_buf()];
+        while let Some(dir) = dirs.pop() {
+            let entries = match std::fs::read_dir(&dir) {
+                Ok(entries) => entries,
+                Err(e) => {
+                    results.push((dir, Err(crate::errors::Error::IoError(e.to_string()))));
+                    continue;
+                }
+            };
+            for entry in entries.flatten() {
+                let path = entry.path();
+                if entry.file_type().is_ok_and(|file_type| file_type.is_dir()) {
+                    if recursive {
+                        dirs.push(path);
+                    }
+                } else if path.is_file() && filter(&path) {
+                    files.push(path);
+                }
+            }
+        }
+
+        let extracted = self.extract_files_parallel(&files);
+        results.extend(files.into_iter().zip(extracted).filter(|(_, result)| {
+            !matches!(result, Err(e) if e.kind() == crate::errors::ErrorKind::UnsupportedFormat)
+        }));
+        results.sort_by(|(a, _), (b, _)| a.cmp(b));
+        results
+    }
+
+    /// Runs `extract` with a copy of this extractor on a new thread and waits for at most
+    /// `timeout` for its result. The thread attaches itself to the JVM when calling into Tika
+    /// and is detached again before it exits
+    fn run_with_timeout<T, F>(&self, timeout: Duration, extract: F) -> ExtractResult<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&Extractor) -> ExtractResult<T> + Send + 'static,
+    {
+        let mut extractor = self.clone();
+        extractor.extraction_timeout = None;
+
+        let (sender, receiver) = mpsc::channel();
+        std::thread::Builder::new()
+            .name("extractous-extraction".to_string())
+            .spawn(move || {
+                // Sending fails when the caller already returned with a timeout
+                let _ = sender.send(extract(&extractor));
+            })
+            .map_err(|e| crate::errors::Error::IoError(e.to_string()))?;
+
+        match receiver.recv_timeout(timeout) {
+            Ok(result) => result,
+            Err(mpsc::RecvTimeoutError::Timeout) => Err(crate::errors::Error::Timeout(timeout)),
+            Err(mpsc::RecvTimeoutError::Disconnected) => Err(crate::errors::Error::Unknown(
+                "Extraction thread terminated without a result".to_string(),
+            )),
+        }
+    }
+
+    /// Runs a URL extraction, retrying transient failures as set by `set_url_retry`
+    fn with_url_retry<T>(&self, mut extract: impl FnMut() -> ExtractResult<T>) -> ExtractResult<T> {
+        let mut backoff = self.url_retry_backoff;
+        for _ in 0..self.url_max_retries {
+            match extract() {
+                Err(e) if is_transient_url_error(&e) => {
+                    std::thread::sleep(backoff);
+                    backoff = backoff.saturating_mul(2);
+                }
+                result => return result,
+            }
+        }
+        extract()
+    }
+
+    /// Returns `Error::FileTooLarge` if the file at `file_path` exceeds the maximum file size
+    fn check_file_size(&self, file_path: &str) -> ExtractResult<()> {
+        if self.max_file_size.is_none() {
+            return Ok(());
+        }
+        let file_metadata = std::fs::metadata(file_path)
+            .map_err(|e| crate::errors::Error::IoError(e.to_string()))?;
+        self.check_size(file_metadata.len())
+    }
+
+    /// Returns `Error::FileTooLarge` if `size` exceeds the maximum file size
+    fn check_size(&self, size: u64) -> ExtractResult<()> {
+        match self.max_file_size {
+            Some(limit) if size > limit => Err(crate::errors::Error::FileTooLarge { size, limit }),
+            _ => Ok(()),
+        }
+    }
+
+    /// Whether `set_small_file_fast_path` sends the file to the pure Rust parsers, which only
+    /// return plain text
+    fn uses_small_file_fast_path(&self, file_path: &str) -> bool {
+        if !cfg!(feature = "pure-rust")
+            || !self.small_file_fast_path
+            || self.markup_output()
+            || self.tika_handler() == TikaHandler::Xhtml
+        {
+            return false;
+        }
+        let format = crate::detect_format(file_path);
+        matches!(
+            format,
+            DocumentFormat::Html | DocumentFormat::Xml | DocumentFormat::Csv
+        ) && std::fs::metadata(file_path)
+            .is_ok_and(|metadata| metadata.len() < SMALL_FILE_THRESHOLD as u64)
+    }
+
+    /// Try pure Rust extraction for supported formats
+    #[cfg(feature = "pure-rust")]
+    fn try_pure_rust_extraction(&self, file_path: &str) -> ExtractResult<(String, Metadata)> {
+        self.pure_rust_extractor().extract_file(file_path)
+    }
+
+    /// The decompressed content of a gzip, bzip2 or xz file and the format detected in it,
+    /// `None` for other files
+    #[cfg(feature = "compression")]
+    fn decompress_file(&self, file_path: &str) -> ExtractResult<Option<(Vec<u8>, DocumentFormat)>> {
+        let Some(data) =
+            crate::compression::decompress_file(file_path, self.max_decompressed_size)?
+        else {
+            return Ok(None);
+        };
+        let format =
+            crate::format_detection::detect_decompressed_format(&data, Path::new(file_path));
+        Ok(Some((data, format)))
+    }
+
+    /// Extracts decompressed content to a stream, by the pure Rust parsers when they support its
+    /// format and by Tika otherwise
+    #[cfg(feature = "compression")]
+    #[cfg_attr(not(feature = "pure-rust"), allow(unused_variables))]
+    fn extract_decompressed(
+        &self,
+        data: Vec<u8>,
+        format: DocumentFormat,
+    ) -> ExtractResult<(StreamReader, Metadata)> {
+        #[cfg(feature = "pure-rust")]
+        if let Some((text, metadata)) = self.try_pure_rust_decompressed(&data, format) {
+            return Ok((self.text_stream(text), metadata));
+        }
+        self.extract_reader(std::io::Cursor::new(data))
+    }
+
+    /// The text of decompressed content, by the pure Rust parsers when they support its format
+    /// and by Tika otherwise
+    #[cfg(feature = "compression")]
+    #[cfg_attr(not(feature = "pure-rust"), allow(unused_variables))]
+    fn extract_decompressed_text(
+        &self,
+        data: &[u8],
+        format: DocumentFormat,
+    ) -> ExtractResult<(String, Metadata)> {
+        #[cfg(feature = "pure-rust")]
+        if let Some(extracted) = self.try_pure_rust_decompressed(data, format) {
+            return Ok(extracted);
+        }
+
+        let (text, metadata) = tika::parse_bytes_to_string(
+            data,
+            self.extract_string_max_length,
+            &self.tika_pdf_config(),
+            &self.tika_office_config(),
+            &self.tika_ocr_config(),
+            self.tika_handler(),
+            self.tika_config_xml.as_deref(),
+        )?;
+        Ok((self.tika_text(text), metadata))
+    }
+
+    /// Extracts decompressed content with the pure Rust parsers, `None` when they are disabled,
+    /// do not support its format or fail, which leaves the content to Tika
+    #[cfg(all(feature = "compression", feature = "pure-rust"))]
+    fn try_pure_rust_decompressed(
+        &self,
+        data: &[u8],
+        format: DocumentFormat,
+    ) -> Option<(String, Metadata)> {
+        if !self.use_pure_rust || self.markdown_output {
+            return None;
+        }
+        let extractor = self.pure_rust_extractor();
+        if !extractor.supports(&format) {
+            return None;
+        }
+        extractor.extract_bytes(data, format).ok()
+    }
+
+    /// The pure Rust extractor with the settings of this extractor
+    #[cfg(feature = "pure-rust")]
+    fn pure_rust_extractor(&self) -> crate::pure_rust_parsers::PureRustExtractor {
+        crate::pure_rust_parsers::PureRustExtractor::with_max_length(
+            self.extract_string_max_length as usize
+        )
+        .set_table_rendering(self.table_rendering)
+        .set_footnote_handling(self.footnote_handling)
+        .set_link_mode(self.link_mode)
+        .set_strip_markdown_syntax(self.strip_markdown_syntax)
+        .set_bidi_reordering(self.bidi_reordering)
+        .set_include_comments(self.office_config.include_comments)
+        .set_include_headers_and_footers(self.office_config.include_headers_and_footers)
+        .set_sort_by_position(self.pdf_config.sort_by_position)
+        .set_password(
+            self.tika_office_config()
+                .password
+                .as_ref()
+                .map(|p| p.0.clone()),
+        )
+    }
+
+    /// Sets the hash of `set_compute_content_hash` of a buffer in the metadata
+    #[cfg_attr(not(feature = "content-hash"), allow(unused_variables))]
+    fn insert_bytes_hash(&self, buffer: &[u8], metadata: &mut Metadata) {
+        #[cfg(feature = "content-hash")]
+        if let Some(algo) = self.content_hash {
+            let hash = crate::hashing::hash_bytes(algo, buffer);
+            metadata.insert(crate::CONTENT_HASH_KEY.to_string(), vec![hash]);
+        }
+    }
+
+    /// Sets the hash of `set_compute_content_hash` of a file in the metadata, unless it was
+    /// hashed already from the memory it was mapped to. This reads the whole file again
+    #[cfg_attr(not(feature = "content-hash"), allow(unused_variables))]
+    fn insert_file_hash(&self, file_path: &str, metadata: &mut Metadata) -> ExtractResult<()> {
+        #[cfg(feature = "content-hash")]
+        if let Some(algo) = self.content_hash {
+            if !metadata.contains_key(crate::CONTENT_HASH_KEY) {
+                let hash = crate::hashing::hash_file(algo, file_path)
+                    .map_err(|e| crate::errors::Error::IoError(e.to_string()))?;
+                metadata.insert(crate::CONTENT_HASH_KEY.to_string(), vec![hash]);
+            }
+        }
+        Ok(())
+    }
+
+    /// Post-process extracted text with minimal overhead optimizations.
+    /// `file_path` is the extracted file, if any, used to recognise source code
+    fn post_process_text(
+        &self,
+        mut text: String,
+        mut metadata: Metadata,
+        file_path: Option<&str>,
+    ) -> (String, Metadata) {
+        if self.markdown_output {
+            text = crate::markdown_output::xhtml_to_markdown(&text);
+        }
+
+        if self.strip_control_chars {
+            text = crate::simd_text::strip_control_chars(&text);
+        }
+
+        if let Some(CharFilter(keep)) = &self.char_filter {
+            text.retain(|c| keep(c));
+        }
+
+        if let Some(form) = self.unicode_normalization {
+            use unicode_normalization::UnicodeNormalization;
+            text = match form {
+                NormalizationForm::Nfc => text.nfc().collect(),
+                NormalizationForm::Nfd => text.nfd().collect(),
+                NormalizationForm::Nfkc => text.nfkc().collect(),
+                NormalizationForm::Nfkd => text.nfkd().collect(),
+            };
+        }
+
+        let content_type = metadata
+            .get("Content-Type")
+            .and_then(|values| values.first());
+        let keep_whitespace = self.preserve_code_formatting
+            && (file_path.is_some_and(crate::is_code_file)
+                || content_type.is_some_and(|mime_type| crate::is_code_mime_type(mime_type)));
+        let format = content_type
+            .and_then(|mime_type| DocumentFormat::from_mime_type(mime_type))
+            .or_else(|| file_path.map(crate::detect_format));
+
+        if let Some(profile) = self.cleaning_profile_of(format.as_ref()) {
+            if profile.dehyphenate {
+                text = crate::simd_text::dehyphenate(&text);
+            }
+            if profile.normalize_whitespace && !keep_whitespace {
+                text = crate::simd_text::normalize_whitespace(&text);
+            }
+        }
+
+        if self.enable_text_cleaning {
+            // Only apply expensive operations if text is large enough to benefit, and unless a
+            // cleaning profile applies
+            let profiled = self.cleaning_profile_of(format.as_ref()).is_some();
+            if let Some(config) = &self.text_cleaning_config {
+                if !keep_whitespace && !profiled {
+                    text = crate::simd_text::clean_text_with_config(&text, config);
+                }
+            } else if text.len() > 5000 && !keep_whitespace && !profiled {
+                // Apply lightweight text cleaning only
+                text = crate::simd_text::normalize_whitespace(&text);
+                metadata.insert("Text-Processing".to_string(), vec!["lightweight".to_string()]);
+            }
+        }
+
+        if self.line_ending != LineEnding::Preserve {
+            text = crate::simd_text::normalize_line_endings(&text, self.line_ending);
+        }
+
+        // Smart truncation only if needed, a negative maximum length means no limit
+        if let Ok(max_length) = usize::try_from(self.extract_string_max_length) {
+            // The maximum length counts characters like Tika's write limit, not bytes
+            if text.char_indices().nth(max_length).is_some() {
+                // The suffix appended after the cut counts towards the limit
+                let suffix = &self.truncation_suffix;
+                text = match max_length.checked_sub(suffix.chars().count()) {
+                    Some(cut) => {
+                        let cut = text.char_indices().nth(cut).map_or(text.len(), |(i, _)| i);
+                        crate::simd_text::truncate_text_smart_with_suffix(&text, cut, suffix)
+                    }
+                    None => String::new(),
+                };
+            }
+        }
+
+        #[cfg(feature = "language-detection")]
+        if self.detect_language {
+            insert_language(&text, &mut metadata);
+        }
+
+        if let Some(keys) = &self.metadata_keys {
+            metadata.retain(|key, _| keys.contains(&key.to_lowercase()));
+        }
+
+        if self.json_output {
+            text = json_document(&text, &metadata);
+        }
+
+        (text, metadata)
+    }
+
+    /// Whether the cleaning profile of the file removes running headers and footers, which
+    /// only PDFs have
+    fn removes_headers_footers(&self, file_path: &str) -> bool {
+        let no_profiles = self.cleaning_profile.is_none() && self.cleaning_profiles.is_empty();
+        if no_profiles || self.markdown_output {
+            return false;
+        }
+        let format = crate::detect_format(file_path);
+        format == DocumentFormat::Pdf
+            && self
+                .cleaning_profile_of(Some(&format))
+                .is_some_and(|profile| profile.remove_headers_footers)
+    }
+
+    /// Whether page markers are put into the text of a file, see `set_annotate_page_boundaries`
+    fn annotates_page_boundaries(&self, file_path: &str) -> bool {
+        self.annotate_page_boundaries
+            && !self.markdown_output
+            && crate::detect_format(file_path) == DocumentFormat::Pdf
+    }
+
+    /// The cleaning profile of a format, falling back to the global profile
+    fn cleaning_profile_of(&self, format: Option<&DocumentFormat>) -> Option<&CleaningOptions> {
+        format
+            .and_then(|format| self.cleaning_profiles.get(format))
+            .or(self.cleaning_profile.as_ref())
+    }
+
+    /// Reorders the text of Tika into visual order, the markup of XHTML output is left alone
+    fn reorder_bidi(&self, text: String) -> String {
+        match self.bidi_reordering {
+            BidiReordering::Off => text,
+            _ if self.markup_output() => text,
+            mode => crate::bidi::reorder(&text, mode),
+        }
+    }
+
+    /// Whether the output is Tika's XHTML, or Markdown converted from it, rather than text
+    fn markup_output(&self) -> bool {
+        self.xml_output || self.markdown_output || self.tika_handler == TikaHandler::Xhtml
+    }
+
+    /// Whether the text output is converted from Tika's XHTML to keep the targets of links,
+    /// see `set_include_link_urls`
+    fn links_from_xhtml(&self) -> bool {
+        self.link_mode != LinkMode::Drop && !self.markup_output()
+    }
+
+    /// The text of Tika's `extract_*_to_string` output, the XHTML of `links_from_xhtml` becomes
+    /// text with the link targets
+    fn tika_text(&self, text: String) -> String {
+        let text = if self.links_from_xhtml() {
+            let include_head = self.tika_handler == TikaHandler::Text;
+            crate::links::xhtml_to_text(&text, include_head, self.link_mode)
+        } else {
+            text
+        };
+        self.reorder_bidi(text)
+    }
+
+    /// The content handler of the Tika parse calls
+    fn tika_handler(&self) -> TikaHandler {
+        if self.xml_output || self.markdown_output || self.links_from_xhtml() {
+            TikaHandler::Xhtml
+        } else {
+            self.tika_handler
+        }
+    }
+
+    /// The PDF parser config passed to Tika, without OCR when `set_ocr_enabled` disabled it
+    fn tika_pdf_config(&self) -> Cow<'_, PdfParserConfig> {
+        let mut pdf_config = Cow::Borrowed(&self.pdf_config);
+        if self.pdf_config.password.is_none() && self.document_password.is_some() {
+            pdf_config.to_mut().password = self.document_password.clone();
+        }
+        if !self.ocr_enabled {
+            pdf_config.to_mut().ocr_strategy = PdfOcrStrategy::NO_OCR;
+        }
+        pdf_config
+    }
+
+    /// The Office config passed to Tika, with the password of `set_document_password` unless it
+    /// has a password of its own
+    fn tika_office_config(&self) -> Cow<'_, OfficeParserConfig> {
+        if self.office_config.password.is_some() || self.document_password.is_none() {
+            return Cow::Borrowed(&self.office_config);
+        }
+        let mut office_config = self.office_config.clone();
+        office_config.password = self.document_password.clone();
+        Cow::Owned(office_config)
+    }
+
+    /// The OCR config passed to Tika, skipping OCR when `set_ocr_enabled` disabled it
+    fn tika_ocr_config(&self) -> Cow<'_, TesseractOcrConfig> {
+        if self.ocr_enabled {
+            return Cow::Borrowed(&self.ocr_config);
+        }
+        let mut ocr_config = self.ocr_config.clone();
+        ocr_config.skip_ocr = true;
+        Cow::Owned(ocr_config)
+    }
+
+    /// A stream of text that is already extracted, e.g. by the pure Rust parsers, in the
+    /// extractor's `encoding`
+    fn text_stream(&self, text: String) -> StreamReader {
+        self.configure_stream(StreamReader::from_text_in(text, self.encoding))
+    }
+
+    /// Applies the stream options of this extractor to a stream of extracted text
+    fn configure_stream(&self, mut reader: StreamReader) -> StreamReader {
+        reader.strip_control_chars = self.strip_control_chars && self.encoding != CharSet::UTF_16BE;
+        reader
+    }
+
+    /// Returns `Error::InvalidConfiguration` for output options that cannot be honored, and the
+    /// errors of `check_ocr_config`. `to_string` is false for the methods that return a stream
+    fn check_output_mode(&self, to_string: bool) -> ExtractResult<()> {
+        self.check_ocr_config()?;
+        let xhtml_output = self.xml_output || self.tika_handler == TikaHandler::Xhtml;
+        if self.markdown_output && (self.json_output || xhtml_output) {
+            return Err(crate::errors::Error::InvalidConfiguration(
+                "markdown output cannot be combined with json or xml output".to_string(),
+            ));
+        }
+        if self.markdown_output && !to_string {
+            return Err(crate::errors::Error::InvalidConfiguration(
+                "markdown output is only supported by the extract_*_to_string methods".to_string(),
+            ));
+        }
+        if self.json_output && (self.xml_output || self.tika_handler == TikaHandler::Xhtml) {
+            return Err(crate::errors::Error::InvalidConfiguration(
+                "json output and xml output cannot be enabled at the same time".to_string(),
+            ));
+        }
+        if self.links_from_xhtml() && !to_string {
+            return Err(crate::errors::Error::InvalidConfiguration(
+                "link urls are only supported by the extract_*_to_string methods".to_string(),
+            ));
+        }
+        if self.json_output && !to_string {
+            return Err(crate::errors::Error::InvalidConfiguration(
+                "json output is only supported by the extract_*_to_string methods".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Returns the errors Tika would return for the OCR config, before any parsing, so an
+    /// invalid config is also reported for files that the pure Rust parsers extract
+    fn check_ocr_config(&self) -> ExtractResult<()> {
+        let ocr_config = self.tika_ocr_config();
+        ocr_config.check_tessdata()?;
+        ocr_config.check_tesseract_config()?;
+        ocr_config.check_features()
+    }
+}
+
+/// The ISO 639-1 code of a language, e.g. `en`, which Tika's language detection and the
+/// `lang` attribute of HTML use for `dc:language`, rather than the ISO 639-3 code of whatlang
+#[cfg(feature = "language-detection")]
+fn iso_639_1(lang: whatlang::Lang) -> &'static str {
+    use whatlang::Lang;
+
+    match lang {
+        Lang::Epo => "eo",
+        Lang::Eng => "en",
+        Lang::Rus => "ru",
+        Lang::Cmn => "zh",
+        Lang::Spa => "es",
+        Lang::Por => "pt",
+        Lang::Ita => "it",
+        Lang::Ben => "bn",
+        Lang::Fra => "fr",
+        Lang::Deu => "de",
+        Lang::Ukr => "uk",
+        Lang::Kat => "ka",
+        Lang::Ara => "ar",
+        Lang::Hin => "hi",
+        Lang::Jpn => "ja",
+        Lang::Heb => "he",
+        Lang::Yid => "yi",
+        Lang::Pol => "pl",
+        Lang::Amh => "am",
+        Lang::Jav => "jv",
+        Lang::Kor => "ko",
+        Lang::Nob => "nb",
+        Lang::Dan => "da",
+        Lang::Swe => "sv",
+        Lang::Fin => "fi",
+        Lang::Tur => "tr",
+        Lang::Nld => "nl",
+        Lang::Hun => "hu",
+        Lang::Ces => "cs",
+        Lang::Ell => "el",
+        Lang::Bul => "bg",
+        Lang::Bel => "be",
+        Lang::Mar => "mr",
+        Lang::Kan => "kn",
+        Lang::Ron => "ro",
+        Lang::Slv => "sl",
+        Lang::Hrv => "hr",
+        Lang::Srp => "sr",
+        Lang::Mkd => "mk",
+        Lang::Lit => "lt",
+        Lang::Lav => "lv",
+        Lang::Est => "et",
+        Lang::Tam => "ta",
+        Lang::Vie => "vi",
+        Lang::Urd => "ur",
+        Lang::Tha => "th",
+        Lang::Guj => "gu",
+        Lang::Uzb => "uz",
+        Lang::Pan => "pa",
+        Lang::Aze => "az",
+        Lang::Ind => "id",
+        Lang::Tel => "te",
+        Lang::Pes => "fa",
+        Lang::Mal => "ml",
+        Lang::Ori => "or",
+        Lang::Mya => "my",
+        Lang::Nep => "ne",
+        Lang::Sin => "si",
+        Lang::Khm => "km",
+        Lang::Tuk => "tk",
+        Lang::Aka => "ak",
+        Lang::Zul => "zu",
+        Lang::Sna => "sn",
+        Lang::Afr => "af",
+        Lang::Lat => "la",
+        Lang::Slk => "sk",
+        Lang::Cat => "ca",
+        Lang::Tgl => "tl",
+        Lang::Hye => "hy",
+    }
+}
+
+/// Bytes at the start of the text that the language is detected from
+#[cfg(feature = "language-detection")]
+const LANGUAGE_SAMPLE_LENGTH: usize = 16 * 1024;
+
+/// Sets the language of `Extractor::set_detect_language` in the metadata
+#[cfg(feature = "language-detection")]
+fn insert_language(text: &str, metadata: &mut Metadata) {
+    let mut end = text.len().min(LANGUAGE_SAMPLE_LENGTH);
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    if let Some(info) = whatlang::detect(&text[..end]) {
+        metadata.insert(
+            "dc:language".to_string(),
+            vec![iso_639_1(info.lang()).to_string()],
+        );
+        metadata.insert(
+            "Language-Confidence".to_string(),
+            vec![format!("{:.2}", info.confidence())],
+        );
+    }
+}
+
+/// Whether a URL extraction failed for a reason that may go away, see `Extractor::set_url_retry`.
+/// Tika reports the failures of Java's URL connection as I/O errors with the message of the
+/// Java exception, e.g. `Server returned HTTP response code: 503 for URL: ...`. 404 and 410 are
+/// reported with the URL as message only, so they are not retried either
+fn is_transient_url_error(error: &crate::errors::Error) -> bool {
+    let crate::errors::Error::IoError(message) = error else {
+        return false;
+    };
+    if let Some((_, rest)) = message.split_once("HTTP response code: ") {
+        return rest.starts_with('5');
+    }
+    [
+        "Connection refused",
+        "Connection reset",
+        "timed out",
+        "Unexpected end of file from server",
+        "Remote host terminated",
+    ]
+    .iter()
+    .any(|failure| message.contains(failure))
+}
+
+/// Whether a path is a regular file without content. An empty document is not an error, it is
+/// returned without being parsed, Tika and the pure Rust parsers fail on it with confusing
+/// errors
+fn is_empty_file(file_path: &str) -> bool {
+    std::fs::metadata(file_path).is_ok_and(|metadata| metadata.is_file() && metadata.len() == 0)
+}
+
+/// The metadata of an empty document
+fn empty_document_metadata() -> Metadata {
+    Metadata::from([("Content-Length".to_string(), vec!["0".to_string()])])
+}
+
+/// Serializes extracted text and metadata to the json document of `Extractor::set_json_output`
+fn json_document(text: &str, metadata: &Metadata) -> String {
+    let blocks: Vec<serde_json::Value> = text
+        .split("\n\n")
+        .map(str::trim)
+        .filter(|block| !block.is_empty())
+        .map(|block| serde_json::json!({ "text": block }))
+        .collect();
+
+    // Sort the keys so the output is stable between runs
+    let metadata: std::collections::BTreeMap<&String, &Vec<String>> = metadata.iter().collect();
+
+    serde_json::json!({
+        "content": {
+            "text": text,
+            "blocks": blocks,
+        },
+        "metadata": metadata,
+    })
+    .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{StreamLines, StreamReader, StreamSource};
+    use crate::{
+        CharSet, Error, ErrorKind, Extractor, FootnoteHandling, LineEnding, LinkMode, Metadata,
+        NormalizationForm, OfficeParserConfig, PdfOcrStrategy, PdfParserConfig, TesseractOcrConfig,
+        TextCleaningConfig, TikaHandler, XhtmlEvent,
+    };
+    use std::collections::HashMap;
+    use std::fs::File;
+    use std::io::BufReader;
+    use std::io::{self, Read};
+    use std::str;
+    use std::time::Duration;
+
+    const TEST_FILE: &str = "README.md";
+
+    const TEST_URL: &str = "https://www.google.com/";
+
+    fn expected_content() -> String {
+        let mut file = File::open(TEST_FILE).unwrap();
+        let mut content = String::new();
+        file.read_to_string(&mut content).unwrap();
+        content
+    }
+
+    #[test]
+    fn extract_file_to_string_test() {
+        // Prepare expected_content
+        let expected_content = expected_content();
+
+        // Parse the files using extractous with text cleaning disabled for this test
+        // to maintain compatibility with existing expected output
+        let extractor = Extractor::new().set_enable_text_cleaning(false);
+        let result = extractor.extract_file_to_string(TEST_FILE);
+        let (content, metadata) = result.unwrap();
+        assert_eq!(content.trim(), expected_content.trim());
+        assert!(
+            metadata.len() > 0,
+            "Metadata should contain at least one entry"
+        );
+    }
+
+    fn read_content_from_stream(stream: StreamReader) -> String {
+        let mut reader = BufReader::new(stream);
+        let mut buffer = Vec::new();
+        reader.read_to_end(&mut buffer).unwrap();
+
+        let content = String::from_utf8(buffer).unwrap();
+        content
+    }
+
+    #[test]
+    fn extract_file_test() {
+        // Prepare expected_content
+        let expected_content = expected_content();
+
+        // Parse the files using extractous
+        let extractor = Extractor::new();
+        let result = extractor.extract_file(TEST_FILE);
+        let (reader, metadata) = result.unwrap();
+        let content = read_content_from_stream(reader);
+
+        assert_eq!(content.trim(), expected_content.trim());
+        assert!(
+            metadata.len() > 0,
+            "Metadata should contain at least one entry"
+        );
+    }
+
+    fn read_file_as_bytes(path: &str) -> io::Result<Vec<u8>> {
+        let mut file = File::open(path)?;
+        let mut buffer = Vec::new();
+        file.read_to_end(&mut buffer)?;
+        Ok(buffer)
+    }
+
+    #[test]
+    fn extract_bytes_test() {
+        // Prepare expected_content
+        let expected_content = expected_content();
+
+        // Parse the bytes using extractous
+        let file_bytes = read_file_as_bytes(TEST_FILE).unwrap();
+        let extractor = Extractor::new();
+        let result = extractor.extract_bytes(&file_bytes);
+        let (reader, metadata) = result.unwrap();
+        let content = read_content_from_stream(reader);
+
+        assert_eq!(content.trim(), expected_content.trim());
+        assert!(
+            metadata.len() > 0,
+            "Metadata should contain at least one entry"
+        );
+    }
+
+    #[test]
+    fn extract_bytes_with_hint_test() {
+        let csv = b"name,age\nAlice,30\nBob,41\n";
+        let extractor = Extractor::new();
+
+        let (_, metadata) = extractor.extract_bytes(csv).unwrap();
+        assert!(metadata["Content-Type"][0].starts_with("text/plain"));
+
+        let (reader, metadata) = extractor.extract_bytes_with_hint(csv, "text/csv").unwrap();
+        assert!(
+            metadata["Content-Type"][0].starts_with("text/csv"),
+            "{:?}",
+            metadata["Content-Type"]
+        );
+        assert!(read_content_from_stream(reader).contains("Alice"));
+    }
+
+    #[test]
+    fn extract_bytes_to_string_image_alt_text_test() {
+        let html = b"<html><body><figure><img src=\"chart.png\" alt=\"Sales by month\"/>\
+            <figcaption>Figure 1: Sales</figcaption></figure></body></html>";
+        let extractor = Extractor::new();
+
+        let (text, _) = extractor.extract_bytes_to_string(html).unwrap();
+        assert!(text.contains("Sales by month"), "{:?}", text);
+        assert!(text.contains("Figure 1: Sales"), "{:?}", text);
+    }
+
+    #[test]
+    fn extract_file_to_string_embedded_image_alt_text_test() {
+        // The picture's alt text is its file name, which is not text of the document
+        let extractor = Extractor::new().set_use_pure_rust(false);
+
+        let (text, _) = extractor
+            .extract_file_to_string("../test_files/documents/image-alt.docx")
+            .unwrap();
+        assert!(text.contains("Sales grew in every quarter."), "{:?}", text);
+        assert!(!text.contains("image1.png"), "{:?}", text);
+    }
+
+    #[test]
+    fn extract_bytes_strip_control_chars_test() {
+        // Few enough NULs for Tika to still detect plain text
+        let line = "The totals of the third quarter were restated after the audit of the \
+            regional\0 accounts.\n";
+        let buffer = line.repeat(5).into_bytes();
+
+        let extractor = Extractor::new().set_strip_control_chars(true);
+        let (reader, _metadata) = extractor.extract_bytes(&buffer).unwrap();
+        let content = read_content_from_stream(reader);
+
+        assert!(content.contains("regional accounts."));
+        assert!(!content.contains('\0'));
+    }
+
+    #[cfg(feature = "language-detection")]
+    #[test]
+    fn detect_language_test() {
+        let language = |text: &str| {
+            let (_, metadata) = Extractor::new()
+                .set_detect_language(true)
+                .post_process_text(text.to_string(), Metadata::new(), None);
+            metadata.get("dc:language").map(|values| values[0].clone())
+        };
+
+        let english = "The committee reviewed the annual budget and approved the new funding \
+            for the public library, which will extend its opening hours next year.";
+        let french = "Le comité a examiné le budget annuel et a approuvé le nouveau financement \
+            de la bibliothèque municipale, qui prolongera ses horaires l'année prochaine.";
+        assert_eq!(language(english).as_deref(), Some("en"));
+        assert_eq!(language(french).as_deref(), Some("fr"));
+        assert_eq!(language(""), None);
+    }
+
+    #[test]
+    fn metadata_keys_test() {
+        let mut metadata = Metadata::new();
+        metadata.insert("dc:title".to_string(), vec!["Report".to_string()]);
+        metadata.insert("Content-Type".to_string(), vec!["text/plain".to_string()]);
+        metadata.insert(
+            "X-TIKA:Parsed-By".to_string(),
+            vec!["TXTParser".to_string()],
+        );
+
+        let extractor = Extractor::new().set_metadata_keys(Some(vec![
+            "DC:Title".to_string(),
+            "content-type".to_string(),
+        ]));
+        let (_, filtered) = extractor.post_process_text(String::new(), metadata.clone(), None);
+        let mut keys: Vec<&String> = filtered.keys().collect();
+        keys.sort();
+        assert_eq!(keys, ["Content-Type", "dc:title"]);
+
+        let (_, all) = Extractor::new().post_process_text(String::new(), metadata, None);
+        assert_eq!(all.len(), 3);
+    }
+
+    #[test]
+    fn strip_control_chars_test() {
+        let extractor = Extractor::new().set_strip_control_chars(true);
+
+        let (text, _) = extractor.post_process_text(
+            "caf\u{e9}\0 au\tlait\x07\r\n".to_string(),
+            Metadata::new(),
+            None,
+        );
+        assert_eq!(text, "caf\u{e9} au\tlait\r\n");
+
+        // A read that only returns control characters is not the end of the stream
+        let mut reader =
+            extractor.configure_stream(StreamReader::from_text("\0\0\0abc\0".to_string()));
+        let mut buf = [0u8; 2];
+        let mut content = Vec::new();
+        loop {
+            match reader.read(&mut buf).unwrap() {
+                0 => break,
+                len => content.extend_from_slice(&buf[..len]),
+            }
+        }
+        assert_eq!(content, b"abc");
+
+        // Draining appends to what the buffer already holds
+        let mut reader =
+            extractor.configure_stream(StreamReader::from_text("\0de\x07f\n".to_string()));
+        assert_eq!(reader.read_to_end(&mut content).unwrap(), 4);
+        assert_eq!(content, b"abcdef\n");
+    }
+
+    #[test]
+    fn stream_lines_test() {
+        // Chunks of 3 bytes split the UTF-16 code units, the é and the line breaks
+        let text = "caf\u{e9}\r\nau\n\nlait";
+        let bytes: Vec<u8> = text.encode_utf16().flat_map(u16::to_be_bytes).collect();
+        let reader = StreamReader {
+            inner: StreamSource::Memory(io::Cursor::new(bytes)),
+            owned_input: None,
+            strip_control_chars: false,
+            charset: CharSet::UTF_16BE,
+        };
+        let lines: Vec<String> = StreamLines::new(reader, 3).map(Result::unwrap).collect();
+        assert_eq!(lines, ["caf\u{e9}", "au", "", "lait"]);
+
+        let reader = StreamReader::from_text("ok\n".to_string());
+        let mut lines = StreamLines::new(reader, 3);
+        assert_eq!(lines.next().unwrap().unwrap(), "ok");
+        assert!(lines.next().is_none());
+
+        let mut reader = StreamReader::from_text(String::new());
+        reader.inner = StreamSource::Memory(io::Cursor::new(b"ok\n\xff\n".to_vec()));
+        let mut lines = StreamLines::new(reader, 3);
+        assert_eq!(lines.next().unwrap().unwrap(), "ok");
+        let error = lines.next().unwrap().unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        assert!(lines.next().is_none());
+    }
+
+    #[test]
+    fn ocr_enabled_test() {
+        let extractor = Extractor::new()
+            .set_pdf_config(PdfParserConfig::new().set_ocr_strategy(PdfOcrStrategy::OCR_ONLY));
+        assert_eq!(
+            extractor.tika_pdf_config().ocr_strategy,
+            PdfOcrStrategy::OCR_ONLY
+        );
+        assert!(!extractor.tika_ocr_config().skip_ocr);
+
+        let extractor = extractor.set_ocr_enabled(false);
+        assert_eq!(
+            extractor.tika_pdf_config().ocr_strategy,
+            PdfOcrStrategy::NO_OCR
+        );
+        assert!(extractor.tika_ocr_config().skip_ocr);
+        // The configs themselves are kept for when OCR is enabled again
+        let extractor = extractor.set_ocr_enabled(true);
+        assert_eq!(
+            extractor.tika_pdf_config().ocr_strategy,
+            PdfOcrStrategy::OCR_ONLY
+        );
+    }
+
+    #[test]
+    fn stream_into_string_test() {
+        let text = "Zürich, 東京 and 𝄞 clefs\n";
+        let utf16be: Vec<u8> = text.encode_utf16().flat_map(u16::to_be_bytes).collect();
+        let stream = |bytes: Vec<u8>, charset: CharSet| StreamReader {
+            inner: StreamSource::Memory(io::Cursor::new(bytes)),
+            owned_input: None,
+            strip_control_chars: false,
+            charset,
+        };
+
+        assert_eq!(
+            stream(utf16be.clone(), CharSet::UTF_16BE)
+                .into_string()
+                .unwrap(),
+            text
+        );
+        assert_eq!(
+            StreamReader::from_text(text.to_string())
+                .into_string()
+                .unwrap(),
+            text
+        );
+        // An odd number of bytes cannot be UTF-16
+        let truncated = utf16be[..utf16be.len() - 1].to_vec();
+        assert!(matches!(
+            stream(truncated, CharSet::UTF_16BE).into_string(),
+            Err(Error::ParseError(_))
+        ));
+        assert!(matches!(
+            stream(utf16be.clone(), CharSet::UTF_8).into_string(),
+            Err(Error::Utf8Error(_))
+        ));
+
+        // Text that is already extracted is encoded like Tika's streams
+        let mut reader = StreamReader::from_text_in(text.to_string(), CharSet::UTF_16BE);
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).unwrap();
+        assert_eq!(bytes, utf16be);
+        let reader = StreamReader::from_text_in(text.to_string(), CharSet::US_ASCII);
+        assert_eq!(reader.into_string().unwrap(), "Z?rich, ?? and ? clefs\n");
+    }
+
+    #[cfg(feature = "pure-rust")]
+    #[test]
+    fn extract_file_pure_rust_encoding_test() {
+        let path = "../test_files/documents/table-multi-row-column-cells-actual.csv";
+        let (reader, _) = Extractor::new().extract_file(path).unwrap();
+        let expected = read_content_from_stream(reader);
+
+        let extractor = Extractor::new().set_encoding(CharSet::UTF_16BE);
+        let (mut reader, _) = extractor.extract_file(path).unwrap();
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).unwrap();
+        let utf16be: Vec<u8> = expected.encode_utf16().flat_map(u16::to_be_bytes).collect();
+        assert_eq!(bytes, utf16be);
+    }
+
+    #[test]
+    fn extract_file_events_test() {
+        let events = Extractor::new()
+            .extract_file_events("../test_files/documents/simple.html")
+            .unwrap();
+
+        let paragraphs = events
+            .map(Result::unwrap)
+            .filter(|event| matches!(event, XhtmlEvent::StartElement { name, .. } if name == "p"))
+            .count();
+        assert_eq!(paragraphs, 3);
+    }
+
+    #[test]
+    fn extract_pdf_form_fields_test() {
+        let fields = Extractor::new()
+            .extract_pdf_form_fields("../test_files/documents/filled-form.pdf")
+            .unwrap();
+
+        assert_eq!(
+            fields.get("full_name").map(String::as_str),
+            Some("Jane Doe")
+        );
+        assert_eq!(fields.get("subscribed").map(String::as_str), Some("Yes"));
+        assert_eq!(fields.get("address.city").map(String::as_str), Some("Lyon"));
+    }
+
+    #[test]
+    fn extract_url_test() {
+        // Parse url by extractous
+        let extractor = Extractor::new();
+        let result = extractor.extract_url(&TEST_URL);
+        let (reader, metadata) = result.unwrap();
+        let content = read_content_from_stream(reader);
+
+        assert!(content.contains("Google"));
+        assert!(
+            metadata.len() > 0,
+            "Metadata should contain at least one entry"
+        );
+    }
+
+    #[test]
+    fn url_retry_test() {
+        let unavailable = "IO error occurred: Server returned HTTP response code: 503 for URL: \
+            http://localhost/report.pdf";
+        let forbidden = "IO error occurred: Server returned HTTP response code: 403 for URL: \
+            http://localhost/report.pdf";
+        assert!(super::is_transient_url_error(&Error::IoError(
+            unavailable.to_string()
+        )));
+        assert!(super::is_transient_url_error(&Error::IoError(
+            "IO error occurred: Connection refused".to_string()
+        )));
+        assert!(!super::is_transient_url_error(&Error::IoError(
+            forbidden.to_string()
+        )));
+        // A 404 has the URL as message
+        assert!(!super::is_transient_url_error(&Error::IoError(
+            "IO error occurred: http://localhost/report.pdf".to_string()
+        )));
+        assert!(!super::is_transient_url_error(&Error::ParseError(
+            "timed out".to_string()
+        )));
+
+        let extractor = Extractor::new().set_url_retry(3, Duration::from_millis(1));
+        let attempt = |failures: Vec<&'static str>| {
+            let mut failures = failures.into_iter();
+            let mut attempts = 0;
+            let result = extractor.with_url_retry(|| {
+                attempts += 1;
+                match failures.next() {
+                    Some(message) => Err(Error::IoError(message.to_string())),
+                    None => Ok(()),
+                }
+            });
+            (result, attempts)
+        };
+        let (result, attempts) = attempt(vec![unavailable, unavailable]);
+        assert!(result.is_ok());
+        assert_eq!(attempts, 3);
+        let (result, attempts) = attempt(vec![unavailable, forbidden, unavailable]);
+        assert!(matches!(result, Err(Error::IoError(message)) if message == forbidden));
+        assert_eq!(attempts, 2);
+        let (result, attempts) = attempt(vec![unavailable; 5]);
+        assert!(result.is_err());
+        assert_eq!(attempts, 4);
+    }
+
+    #[test]
+    fn extract_file_to_xml_test() {
+        // Parse the files using extractous
+        let extractor = Extractor::new().set_xml_output(true);
+        let result = extractor.extract_file_to_string(TEST_FILE);
+        let (content, metadata) = result.unwrap();
+        assert!(
+            content.len() > 0,
+            "Metadata should contain at least one entry"
+        );
+        assert!(
+            metadata.len() > 0,
+            "Metadata should contain at least one entry"
+        );
+    }
+
+    #[test]
+    fn extract_file_tika_handler_test() {
+        let docx = "../test_files/documents/bug_16.docx";
+        let extractor = Extractor::new().set_use_pure_rust(false);
+        let (body, _) = extractor
+            .clone()
+            .set_tika_handler(TikaHandler::Body)
+            .extract_file_to_string(docx)
+            .unwrap();
+        let (text, _) = extractor
+            .set_tika_handler(TikaHandler::Text)
+            .extract_file_to_string(docx)
+            .unwrap();
+
+        // The text handler also writes the newlines that follow the title and meta elements of
+        // the head, the body text is the same
+        assert!(text.len() > body.len());
+        assert!(text.starts_with('\n'));
+        assert_eq!(text.trim(), body.trim());
+    }
+
+    #[test]
+    fn extract_file_preserve_code_formatting_test() {
+        // Long enough for the whitespace cleaning to kick in
+        let code: String = (0..300)
+            .map(|i| {
+                format!(
+                    "def handler_{}(event):\n    if event:\n        return {}\n\n",
+                    i, i
+                )
+            })
+            .collect();
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("handlers.py");
+        std::fs::write(&path, &code).unwrap();
+        let path = path.to_str().unwrap();
+
+        let extractor = Extractor::new().set_enable_text_cleaning(true);
+        let (content, _) = extractor.clone().extract_file_to_string(path).unwrap();
+        assert!(content.contains("def handler_7(event):\n    if event:\n        return 7"));
+
+        let (content, _) = extractor
+            .set_preserve_code_formatting(false)
+            .extract_file_to_string(path)
+            .unwrap();
+        assert!(!content.contains("\n        return 7"));
+    }
+
+    #[test]
+    fn extract_files_parallel_map_test() {
+        // Files with the same name in different directories, and one that does not exist
+        let root = tempfile::tempdir().unwrap();
+        let root = root.path();
+        let mut paths = Vec::new();
+        for dir in ["north", "south", "west"] {
+            std::fs::create_dir_all(root.join(dir)).unwrap();
+            paths.push(root.join(dir).join("sales.csv"));
+        }
+        std::fs::write(&paths[0], "region,total\nnorth,120\n").unwrap();
+        std::fs::write(&paths[1], "region,total\nsouth,340\n").unwrap();
+
+        let results = Extractor::new().extract_files_parallel_map(&paths);
+        assert_eq!(results.len(), 3);
+        let (north, _) = results[&paths[0]].as_ref().unwrap();
+        assert!(
+            north.contains("north") && !north.contains("south"),
+            "{}",
+            north
+        );
+        let (south, _) = results[&paths[1]].as_ref().unwrap();
+        assert!(
+            south.contains("south") && !south.contains("north"),
+            "{}",
+            south
+        );
+        assert!(results[&paths[2]].is_err());
+    }
+
+    #[test]
+    fn extract_files_parallel_try_test() {
+        let root = tempfile::tempdir().unwrap();
+        let root = root.path();
+        let paths = [
+            root.join("a.csv"),
+            root.join("missing.csv"),
+            root.join("c.csv"),
+        ];
+        std::fs::write(&paths[0], "region,total\nnorth,120\n").unwrap();
+        std::fs::write(&paths[2], "region,total\nsouth,340\n").unwrap();
+
+        for parallel in [true, false] {
+            let extractor = Extractor::new().set_enable_parallel(parallel);
+            assert!(extractor.extract_files_parallel_try(&paths).is_err());
+
+            let results = extractor
+                .extract_files_parallel_try(&[&paths[0], &paths[2]])
+                .unwrap();
+            assert!(results[0].0.contains("north") && results[1].0.contains("south"));
+        }
+    }
+
+    #[test]
+    fn extract_email_metadata_test() {
+        let extractor = Extractor::new();
+        for file in [
+            "../test_files/documents/simple.eml",
+            "../test_files/documents/simple.msg",
+        ] {
+            let (content, metadata) = extractor.extract_file_to_string(file).unwrap();
+            assert!(content.contains("ready for review"), "{}", file);
+            assert_eq!(metadata["dc:title"], ["Quarterly report draft"], "{}", file);
+            assert!(
+                metadata["Message-From"][0].contains("Alice Example"),
+                "{}",
+                file
+            );
+        }
+    }
+
+    #[test]
+    fn extract_from_short_lived_threads_test() {
+        const THREADS: usize = 64;
+
+        for _ in 0..3 {
+            let handles: Vec<_> = (0..THREADS)
+                .map(|_| {
+                    std::thread::spawn(|| {
+                        let extractor = Extractor::new().set_use_pure_rust(false);
+                        let (reader, _) = extractor.extract_file(TEST_FILE).unwrap();
+                        read_content_from_stream(reader)
+                    })
+                })
+                .collect();
+            for handle in handles {
+                assert!(!handle.join().unwrap().is_empty());
+            }
+
+            // The exited threads detached themselves, only threads of tests running at the same
+            // time can still be attached
+            let attached = crate::tika::vm().unwrap().threads_attached();
+            assert!(attached < THREADS, "{} threads still attached", attached);
+        }
+    }
+
+    #[test]
+    fn extract_file_with_embedded_test() {
+        let extractor = Extractor::new();
+        let (_, metadata, embedded) = extractor
+            .extract_file_with_embedded("../test_files/documents/embedded.zip")
+            .unwrap();
+
+        assert_eq!(
+            metadata.get("Content-Type").unwrap(),
+            &vec!["application/zip".to_string()]
+        );
+        let pdf = embedded
+            .iter()
+            .find(|doc| doc.name.ends_with("blank-page.pdf"))
+            .unwrap();
+        assert_eq!(pdf.mime_type, "application/pdf");
+        assert!(pdf.text.contains("First page of the report."));
+        let docx = embedded
+            .iter()
+            .find(|doc| doc.name == "bug_16.docx")
+            .unwrap();
+        assert!(docx.mime_type.starts_with("application/vnd.openxmlformats"));
+        assert!(docx.text.contains("abcdefg"));
+        assert!(!docx.metadata.contains_key("X-TIKA:content"));
+    }
+
+    #[test]
+    fn extract_file_max_file_size_test() {
+        let size = std::fs::metadata(TEST_FILE).unwrap().len();
+
+        // One byte over the limit is rejected before any extraction happens
+        let extractor = Extractor::new().set_max_file_size(size - 1);
+        match extractor.extract_file_to_string(TEST_FILE) {
+            Err(Error::FileTooLarge { size: s, limit }) => {
+                assert_eq!(s, size);
+                assert_eq!(limit, size - 1);
+            }
+            other => panic!("Expected FileTooLarge error, got {:?}", other),
+        }
+        assert!(matches!(
+            extractor.extract_file(TEST_FILE),
+            Err(Error::FileTooLarge { .. })
+        ));
+
+        // A file exactly at the limit is extracted
+        let extractor = Extractor::new().set_max_file_size(size);
+        assert!(extractor.extract_file_to_string(TEST_FILE).is_ok());
+    }
+
+    #[test]
+    fn extract_bytes_max_file_size_test() {
+        let file_bytes = read_file_as_bytes(TEST_FILE).unwrap();
+        let size = file_bytes.len() as u64;
+
+        let extractor = Extractor::new().set_max_file_size(size - 1);
+        assert!(matches!(
+            extractor.extract_bytes_to_string(&file_bytes),
+            Err(Error::FileTooLarge { .. })
+        ));
+
+        let extractor = Extractor::new().set_max_file_size(size);
+        assert!(extractor.extract_bytes_to_string(&file_bytes).is_ok());
+    }
+
+    #[test]
+    fn extract_file_timeout_test() {
+        // Parsing a 28 pages PDF takes far longer than a millisecond
+        let extractor = Extractor::new()
+            .set_use_pure_rust(false)
+            .set_extraction_timeout(Duration::from_millis(1));
+        let result = extractor.extract_file_to_string("../test_files/documents/2022_Q3_AAPL.pdf");
+        assert!(
+            matches!(result, Err(Error::Timeout(timeout)) if timeout == Duration::from_millis(1)),
+            "Expected a timeout error, got {:?}",
+            result
+        );
+
+        // A generous timeout returns the extraction result
+        let extractor = Extractor::new().set_extraction_timeout(Duration::from_secs(60));
+        let (content, _) = extractor.extract_file_to_string(TEST_FILE).unwrap();
+        assert_eq!(content.trim(), expected_content().trim());
+    }
+
+    #[test]
+    fn compare_metadata_test() {
+        let entry = |key: &str, value: &str| (key.to_string(), vec![value.to_string()]);
+        let old: Metadata = [
+            entry("dc:title", "Annual report"),
+            entry("xmpTPg:NPages", "28"),
+            entry("pdf:encrypted", "true"),
+            entry("Content-Type", "application/pdf"),
+        ]
+        .into_iter()
+        .collect();
+        let new: Metadata = [
+            entry("dc:title", "Annual report 2024"),
+            entry("xmpTPg:NPages", "30"),
+            entry("Content-Type", "application/pdf"),
+            entry("dc:creator", "Finance"),
+        ]
+        .into_iter()
+        .collect();
+
+        let diff = Extractor::compare_metadata(&old, &new);
+        assert_eq!(diff.added, vec!["dc:creator"]);
+        assert_eq!(diff.removed, vec!["pdf:encrypted"]);
+        assert_eq!(diff.changed, vec!["dc:title", "xmpTPg:NPages"]);
+        assert!(Extractor::compare_metadata(&new, &new).is_empty());
+    }
+
+    #[test]
+    fn missing_file_error_kind_test() {
+        let extractor = Extractor::new();
+        let err = extractor
+            .extract_file_to_string("../test_files/documents/does-not-exist.pdf")
+            .unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::Io, "Unexpected error: {}", err);
+    }
+
+    #[cfg(any(feature = "s3", feature = "gcs"))]
+    #[test]
+    fn extract_object_test() {
+        use object_store::{memory::InMemory, path::Path, ObjectStore};
+
+        // An in-memory store stands in for S3 or GCS
+        let store = InMemory::new();
+        let pdf = std::fs::read("../test_files/documents/2022_Q3_AAPL.pdf").unwrap();
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+        runtime
+            .block_on(store.put(&Path::from("reports/2022_Q3_AAPL.pdf"), pdf.into()))
+            .unwrap();
+
+        let extractor = Extractor::new();
+        let (reader, metadata) = extractor
+            .extract_object(&store, "reports/2022_Q3_AAPL.pdf")
+            .unwrap();
+        let content = read_content_from_stream(reader);
+
+        assert!(content.contains("Apple Inc."));
+        assert_eq!(
+            metadata.get("Content-Type").unwrap(),
+            &vec!["application/pdf".to_string()]
+        );
+    }
+
+    #[test]
+    fn extract_uri_unsupported_scheme_test() {
+        let extractor = Extractor::new();
+        let result = extractor.extract_uri("ftp://example.com/report.pdf");
+        assert!(matches!(result, Err(ref e) if e.kind() == ErrorKind::UnsupportedScheme));
+    }
+
+    #[test]
+    fn extract_reader_cursor_test() {
+        let bytes = read_file_as_bytes(TEST_FILE).unwrap();
+
+        let extractor = Extractor::new();
+        let (reader, metadata) = extractor
+            .extract_reader(std::io::Cursor::new(bytes))
+            .unwrap();
+        let content = read_content_from_stream(reader);
+
+        assert_eq!(content.trim(), expected_content().trim());
+        assert!(
+            !metadata.is_empty(),
+            "Metadata should contain at least one entry"
+        );
+    }
+
+    #[test]
+    fn extract_reader_chained_test() {
+        // Split the document in two readers to check that reads crossing the boundary work
+        let bytes = read_file_as_bytes(TEST_FILE).unwrap();
+        let second_half = bytes[bytes.len() / 2..].to_vec();
+        let first_half = bytes[..bytes.len() / 2].to_vec();
+        let reader = std::io::Cursor::new(first_half).chain(std::io::Cursor::new(second_half));
+
+        let extractor = Extractor::new();
+        let (reader, _metadata) = extractor.extract_reader(reader).unwrap();
+        let content = read_content_from_stream(reader);
+
+        assert_eq!(content.trim(), expected_content().trim());
+    }
+
+    #[test]
+    fn extract_with_tessdata_path_missing_language_test() {
+        let ocr_config = TesseractOcrConfig::new()
+            .set_tessdata_path("../test_files/tessdata".into())
+            .set_language("eng+deu");
+        // README.md goes to the pure Rust Markdown parser otherwise, which does no OCR
+        let extractor = Extractor::new()
+            .set_use_pure_rust(false)
+            .set_ocr_config(ocr_config);
+        let result = extractor.extract_file(TEST_FILE);
+
+        assert!(matches!(
+            result,
+            Err(Error::OcrLanguageMissing { ref language, .. }) if language == "deu"
+        ));
+    }
+
+    #[test]
+    fn extract_with_invalid_tesseract_config_test() {
+        // OCR with a tesseract config is tested on an image in tests/extract_to_string_tests.rs
+        let ocr_config =
+            TesseractOcrConfig::new().set_tessdata_path("../test_files/tessdata".into());
+
+        let invalid = HashMap::from([("tessedit_char_whitelist".to_string(), "a b".to_string())]);
+        // README.md goes to the pure Rust Markdown parser otherwise, which does no OCR
+        let extractor = Extractor::new().set_use_pure_rust(false);
+        let result = extractor
+            .clone()
+            .set_ocr_config(ocr_config.clone().set_tesseract_config(invalid))
+            .extract_file(TEST_FILE);
+        assert!(matches!(result, Err(Error::InvalidConfiguration(_))));
+
+        let missing = ocr_config.set_tessdata_path("../test_files/missing-tessdata".into());
+        let result = extractor.set_ocr_config(missing).extract_file(TEST_FILE);
+        assert!(matches!(result, Err(Error::InvalidConfiguration(_))));
+    }
+
+    #[cfg(feature = "pure-rust")]
+    #[test]
+    fn extract_invalid_ocr_config_pure_rust_test() {
+        let path = "../test_files/documents/table-multi-row-column-cells-actual.csv";
+        let invalid = HashMap::from([("tessedit_char_whitelist".to_string(), "a b".to_string())]);
+        let extractor = Extractor::new()
+            .set_ocr_config(TesseractOcrConfig::new().set_tesseract_config(invalid));
+        assert!(matches!(
+            extractor.extract_file(path),
+            Err(Error::InvalidConfiguration(_))
+        ));
+        assert!(matches!(
+            extractor.extract_file_to_string(path),
+            Err(Error::InvalidConfiguration(_))
+        ));
+        assert!(matches!(
+            extractor.extract_file_with_embedded(path),
+            Err(Error::InvalidConfiguration(_))
+        ));
+        assert!(matches!(
+            extractor.extract_file_to_xhtml(path),
+            Err(Error::InvalidConfiguration(_))
+        ));
+    }
+
+    #[test]
+    fn supported_mime_types_test() {
+        let mime_types = Extractor::supported_mime_types().unwrap();
+
+        assert!(mime_types.contains(&"application/pdf".to_string()));
+        assert!(mime_types.contains(&"text/html".to_string()));
+        assert!(
+            mime_types.windows(2).all(|w| w[0] < w[1]),
+            "Mime types should be sorted"
+        );
+    }
+
+    #[test]
+    fn version_test() {
+        assert_eq!(Extractor::version(), env!("CARGO_PKG_VERSION"));
+
+        let tika_version = Extractor::tika_version().unwrap();
+        let parts: Vec<&str> = tika_version.split('.').collect();
+        assert_eq!(parts.len(), 3, "{}", tika_version);
+        assert!(
+            parts.iter().all(|part| part.parse::<u32>().is_ok()),
+            "{}",
+            tika_version
+        );
+    }
+
+    #[test]
+    fn extract_file_to_string_json_output_test() {
+        let extractor = Extractor::new().set_json_output(true);
+        let (json, metadata) = extractor.extract_file_to_string(TEST_FILE).unwrap();
+
+        let document: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert!(document["content"].is_object());
+        assert!(document["metadata"].is_object());
+        assert!(document["content"]["blocks"].as_array().unwrap().len() > 1);
+        assert_eq!(
+            document["metadata"]["Content-Type"][0].as_str(),
+            metadata.get("Content-Type").map(|v| v[0].as_str())
+        );
+    }
+
+    #[test]
+    fn extract_file_to_string_markdown_output_test() {
+        let extractor = Extractor::new().set_markdown_output(true);
+        let (content, _) = extractor
+            .extract_file_to_string("../test_files/documents/simple.html")
+            .unwrap();
+
+        assert!(content.starts_with("# Trail conditions"), "{}", content);
+        assert!(content.contains("\n## What to bring\n"));
+        assert!(content.contains("\n- Headlamp\n- Spare gloves"));
+        assert!(content.contains("[ridge](https://example.com/ridge)"));
+    }
+
+    #[test]
+    fn extract_empty_file_test() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("empty.pdf");
+        File::create(&path).unwrap();
+        let path = path.to_str().unwrap();
+        let extractor = Extractor::new();
+
+        let (text, metadata) = extractor.extract_file_to_string(path).unwrap();
+        assert_eq!(text, "");
+        assert_eq!(metadata["Content-Length"], ["0"]);
+
+        let (reader, metadata) = extractor.extract_file(path).unwrap();
+        assert_eq!(reader.into_string().unwrap(), "");
+        assert_eq!(metadata["Content-Length"], ["0"]);
+
+        // A missing file is still an error
+        assert!(extractor
+            .extract_file("../test_files/documents/missing.pdf")
+            .is_err());
+    }
+
+    #[test]
+    fn extract_empty_bytes_test() {
+        let extractor = Extractor::new();
+
+        let (text, metadata) = extractor.extract_bytes_to_string(&[]).unwrap();
+        assert_eq!(text, "");
+        assert_eq!(metadata["Content-Length"], ["0"]);
+
+        let (reader, metadata) = extractor.extract_bytes_with_hint(&[], "text/csv").unwrap();
+        assert_eq!(reader.into_string().unwrap(), "");
+        assert_eq!(metadata["Content-Length"], ["0"]);
+    }
+
+    #[cfg(feature = "pure-rust")]
+    #[test]
+    fn extract_preview_pages_test() {
+        let path = "../test_files/documents/ten-pages.pdf";
+        let start = std::time::Instant::now();
+        let extractor = Extractor::new().set_preview_pages(Some(1));
+        let (text, metadata) = extractor.extract_file_to_string(path).unwrap();
+        assert!(start.elapsed() < Duration::from_secs(5));
+        assert!(
+            text.contains("This is the text of page 1 of the report."),
+            "{}",
+            text
+        );
+        assert!(!text.contains("Chapter 2"), "{}", text);
+        assert_eq!(metadata["xmpTPg:NPages"], ["10"]);
+
+        let (text, _) = Extractor::new().extract_file_to_string(path).unwrap();
+        assert!(text.contains("Chapter 10"));
+    }
+
+    #[test]
+    fn extract_preview_pages_tika_test() {
+        let path = "../test_files/documents/ten-pages.pdf";
+        let extractor = Extractor::new()
+            .set_use_pure_rust(false)
+            .set_preview_pages(Some(2));
+        let (text, _) = extractor.extract_file_to_string(path).unwrap();
+        assert!(
+            text.contains("This is the text of page 2 of the report."),
+            "{}",
+            text
+        );
+        assert!(!text.contains("Chapter 3"), "{}", text);
+    }
+
+    #[cfg(all(feature = "parallel", feature = "pure-rust"))]
+    #[test]
+    fn extract_parallel_ocr_test() {
+        let pdf_config = PdfParserConfig::new().set_ocr_strategy(PdfOcrStrategy::OCR_ONLY);
+        let extractor = Extractor::new()
+            .set_pdf_config(pdf_config)
+            .set_parallel_ocr(true);
+        let (text, metadata) = extractor
+            .extract_file_to_string("../test_files/documents/ten-pages.pdf")
+            .unwrap();
+
+        // The pages finish in any order, their text is joined in page order
+        let positions: Vec<usize> = (1..=10)
+            .map(|page| text.find(&format!("page {} of", page)).unwrap())
+            .collect();
+        assert!(
+            positions.windows(2).all(|pair| pair[0] < pair[1]),
+            "{}",
+            text
+        );
+        assert_eq!(metadata["xmpTPg:NPages"], ["10"]);
+
+        // The OCRed pages get their markers as well
+        let (text, _) = extractor
+            .set_annotate_page_boundaries(true)
+            .extract_file_to_string("../test_files/documents/ten-pages.pdf")
+            .unwrap();
+        assert!(text.starts_with("[page 1]\n"), "{}", text);
+        assert_eq!(text.matches("[page ").count(), 10);
+    }
+
+    #[cfg(feature = "pure-rust")]
+    #[test]
+    fn extract_markdown_frontmatter_test() {
+        let path = "../test_files/documents/frontmatter.md";
+        assert_eq!(crate::detect_format(path), crate::DocumentFormat::Markdown);
+
+        let extractor = Extractor::new().set_strip_markdown_syntax(true);
+        let (text, metadata) = extractor.extract_file_to_string(path).unwrap();
+        assert_eq!(metadata["dc:title"], ["Release notes: 2.0"]);
+        assert_eq!(metadata["dc:subject"], ["release", "parser"]);
+        assert!(text.starts_with("Release notes\n"), "{}", text);
+        assert!(
+            text.contains("The new parser reads Markdown files, see the guide"),
+            "{}",
+            text
+        );
+        assert!(
+            !text.contains("title:") && !text.contains("**new**"),
+            "{}",
+            text
+        );
+    }
+
+    #[cfg(feature = "pure-rust")]
+    #[test]
+    fn extract_dir_test() {
+        use std::path::Path;
+
+        let dir = Path::new("../test_files/documents/mixed-dir");
+        let is_table_or_page = |path: &Path| {
+            path.extension()
+                .is_some_and(|ext| ext == "csv" || ext == "html")
+        };
+        let extractor = Extractor::new();
+
+        let results = extractor.extract_dir(dir, false, is_table_or_page);
+        let paths: Vec<&Path> = results.iter().map(|(path, _)| path.as_path()).collect();
+        assert_eq!(paths, [dir.join("revenue.csv"), dir.join("team.html")]);
+        let (text, _) = results[0].1.as_ref().unwrap();
+        assert!(text.contains("Q2"), "{}", text);
+        let (text, _) = results[1].1.as_ref().unwrap();
+        assert!(text.contains("The team page."), "{}", text);
+
+        let results = extractor.extract_dir(dir, true, is_table_or_page);
+        let paths: Vec<&Path> = results.iter().map(|(path, _)| path.as_path()).collect();
+        assert_eq!(
+            paths,
+            [
+                dir.join("archive/last-year.csv"),
+                dir.join("revenue.csv"),
+                dir.join("team.html")
+            ]
+        );
+        assert!(results.iter().all(|(_, result)| result.is_ok()));
+
+        let results = extractor.extract_dir(dir.join("missing"), true, |_| true);
+        assert!(matches!(results[..], [(_, Err(Error::IoError(_)))]));
+    }
+
+    #[cfg(not(feature = "ocr-downscale"))]
+    #[test]
+    fn max_ocr_image_pixels_without_ocr_downscale_test() {
+        let config = TesseractOcrConfig::new().set_max_ocr_image_pixels(1_000_000);
+        assert!(matches!(
+            config.check_features(),
+            Err(Error::InvalidConfiguration(_))
+        ));
+        assert!(TesseractOcrConfig::new().check_features().is_ok());
+    }
 
-    fn expected_content() -> String {
-        let mut file = File::open(TEST_FILE).unwrap();
-        let mut content = String::new();
-        file.read_to_string(&mut content).unwrap();
-        content
+    #[cfg(not(feature = "pure-rust"))]
+    #[test]
+    fn respect_ocg_visibility_without_pure_rust_test() {
+        let config = PdfParserConfig::new().set_respect_ocg_visibility(true);
+        assert!(matches!(
+            config.check_features(),
+            Err(Error::InvalidConfiguration(_))
+        ));
+        assert!(PdfParserConfig::new().check_features().is_ok());
     }
 
+    #[cfg(feature = "pure-rust")]
     #[test]
-    fn extract_file_to_string_test() {
-        // Prepare expected_content
-        let expected_content = expected_content();
+    fn extract_respect_ocg_visibility_test() {
+        let path = "../test_files/documents/hidden-layer.pdf";
+        let (text, _) = Extractor::new().extract_file_to_string(path).unwrap();
+        assert!(text.contains("DRAFT DO NOT DISTRIBUTE"), "{}", text);
 
-        // Parse the files using extractous with text cleaning disabled for this test
-        // to maintain compatibility with existing expected output
-        let extractor = Extractor::new().set_enable_text_cleaning(false);
+        let pdf_config = PdfParserConfig::new().set_respect_ocg_visibility(true);
+        for use_pure_rust in [true, false] {
+            let extractor = Extractor::new()
+                .set_pdf_config(pdf_config.clone())
+                .set_use_pure_rust(use_pure_rust);
+            let (text, _) = extractor.extract_file_to_string(path).unwrap();
+            assert!(!text.contains("DRAFT"), "{}", text);
+            assert!(text.contains("Revenue grew by four percent."), "{}", text);
+            assert!(text.contains("Reviewed by the finance team."), "{}", text);
+
+            // Tika reads the copy without the hidden layers while the stream is consumed
+            let (reader, _) = extractor.extract_file(path).unwrap();
+            let text = read_content_from_stream(reader);
+            assert!(!text.contains("DRAFT"), "{}", text);
+            assert!(text.contains("Reviewed by the finance team."), "{}", text);
+        }
+    }
+
+    #[cfg(all(feature = "content-hash", feature = "pure-rust"))]
+    #[test]
+    fn extract_content_hash_test() {
+        use sha2::Digest;
+
+        let path = "../test_files/documents/table-multi-row-column-cells-actual.csv";
+        let data = std::fs::read(path).unwrap();
+        let sha256 = format!("sha256:{:x}", sha2::Sha256::digest(&data));
+        let blake3 = format!("blake3:{}", blake3::hash(&data).to_hex());
+
+        let extractor = Extractor::new().set_compute_content_hash(crate::HashAlgo::Sha256);
+        let (_, metadata) = extractor.extract_file_to_string(path).unwrap();
+        assert_eq!(metadata["Content-Hash"], [sha256]);
+
+        let extractor = extractor.set_compute_content_hash(crate::HashAlgo::Blake3);
+        let (_, metadata) = extractor.extract_file(path).unwrap();
+        assert_eq!(metadata["Content-Hash"], [blake3]);
+
+        let (_, metadata) = Extractor::new().extract_file(path).unwrap();
+        assert!(!metadata.contains_key("Content-Hash"));
+    }
+
+    #[cfg(feature = "content-hash")]
+    #[test]
+    fn extract_bytes_content_hash_test() {
+        use sha2::Digest;
+
+        let data = std::fs::read(TEST_FILE).unwrap();
+        let extractor = Extractor::new().set_compute_content_hash(crate::HashAlgo::Sha256);
+        let (_, metadata) = extractor.extract_bytes_to_string(&data).unwrap();
+        assert_eq!(
+            metadata["Content-Hash"],
+            [format!("sha256:{:x}", sha2::Sha256::digest(&data))]
+        );
+    }
+
+    #[cfg(feature = "mmap")]
+    #[test]
+    fn extract_file_to_string_mmap_test() {
+        let extractor = Extractor::new()
+            .set_use_pure_rust(false)
+            .set_use_mmap(false);
+        let (expected, _) = extractor.extract_file_to_string(TEST_FILE).unwrap();
+
+        let extractor = extractor.set_use_mmap(true).set_mmap_threshold(0);
+        let (text, _) = extractor.extract_file_to_string(TEST_FILE).unwrap();
+        assert_eq!(text, expected);
+    }
+
+    #[cfg(feature = "compression")]
+    #[test]
+    fn extract_gzipped_html_test() {
+        let extractor = Extractor::new();
+        let (text, metadata) = extractor
+            .extract_file_to_string("../test_files/documents/small-page.html.gz")
+            .unwrap();
+        let (expected, expected_metadata) = extractor
+            .extract_file_to_string("../test_files/documents/small-page.html")
+            .unwrap();
+        assert_eq!(text, expected);
+        // Only the metadata of the content, keys such as the file size may be those of the gzip.
+        // Tika adds a charset to the Content-Type that it detects from the bytes or the file
+        assert_eq!(metadata.get("dc:title"), expected_metadata.get("dc:title"));
+        assert!(metadata["Content-Type"][0].starts_with("text/html"));
+        assert!(text.contains("Fresh snow fell overnight on the upper slopes"));
+
+        let (reader, _) = extractor
+            .extract_file("../test_files/documents/small-page.html.gz")
+            .unwrap();
+        assert_eq!(reader.into_string().unwrap().trim(), expected.trim());
+
+        let result = extractor
+            .set_max_decompressed_size(100)
+            .extract_file_to_string("../test_files/documents/small-page.html.gz");
+        assert!(matches!(
+            result,
+            Err(Error::DecompressedTooLarge { limit: 100 })
+        ));
+    }
+
+    #[test]
+    fn markdown_output_conflict_test() {
+        let extractor = Extractor::new()
+            .set_markdown_output(true)
+            .set_json_output(true);
         let result = extractor.extract_file_to_string(TEST_FILE);
-        let (content, metadata) = result.unwrap();
-        assert_eq!(content.trim(), expected_content.trim());
+        assert!(matches!(result, Err(Error::InvalidConfiguration(_))));
+
+        let extractor = Extractor::new().set_markdown_output(true);
+        assert!(matches!(
+            extractor.extract_file(TEST_FILE),
+            Err(Error::InvalidConfiguration(_))
+        ));
+    }
+
+    #[test]
+    fn extract_link_urls_with_tika_test() {
+        let path = "../test_files/documents/links.html";
+        let extractor = Extractor::new().set_use_pure_rust(false);
+        let (text, _) = extractor.clone().extract_file_to_string(path).unwrap();
         assert!(
-            metadata.len() > 0,
-            "Metadata should contain at least one entry"
+            text.contains("Check the weather report before you leave."),
+            "{}",
+            text
+        );
+
+        let inline = extractor.clone().set_include_link_urls(LinkMode::Inline);
+        let (text, _) = inline.extract_file_to_string(path).unwrap();
+        assert!(
+            text.contains("weather report (https://example.com/weather) before"),
+            "{}",
+            text
         );
+        assert!(!text.contains("<p>"));
+        assert!(matches!(
+            inline.extract_file(path),
+            Err(Error::InvalidConfiguration(_))
+        ));
+
+        let footnotes = extractor.set_include_link_urls(LinkMode::Footnotes);
+        let (text, _) = footnotes.extract_file_to_string(path).unwrap();
+        assert!(text.contains("club website [2], next to the"), "{}", text);
+        assert!(text.trim_end().ends_with("[2] https://example.com/maps"));
     }
 
-    fn read_content_from_stream(stream: StreamReader) -> String {
-        let mut reader = BufReader::new(stream);
-        let mut buffer = Vec::new();
-        reader.read_to_end(&mut buffer).unwrap();
+    #[test]
+    fn extract_docx_headers_and_footers_test() {
+        let path = "../test_files/documents/header-footer.docx";
+        // Dropping footnotes hands the docx to the pure Rust parser
+        let extractors = [
+            Extractor::new().set_footnote_handling(FootnoteHandling::Drop),
+            Extractor::new().set_use_pure_rust(false),
+        ];
+        for extractor in extractors {
+            let extract = |include| {
+                let office_config =
+                    OfficeParserConfig::new().set_include_headers_and_footers(include);
+                extractor
+                    .clone()
+                    .set_office_config(office_config)
+                    .extract_file_to_string(path)
+                    .unwrap()
+                    .0
+            };
 
-        let content = String::from_utf8(buffer).unwrap();
-        content
+            let text = extract(true);
+            assert!(
+                text.contains("Sales grew in every region this quarter."),
+                "{}",
+                text
+            );
+            assert!(
+                text.contains("Confidential - do not distribute"),
+                "{}",
+                text
+            );
+
+            let text = extract(false);
+            assert!(text.contains("Costs stayed flat."), "{}", text);
+            assert!(!text.contains("Confidential"), "{}", text);
+        }
     }
 
     #[test]
-    fn extract_file_test() {
-        // Prepare expected_content
-        let expected_content = expected_content();
+    fn extract_unicode_metadata_test() {
+        let path = "../test_files/documents/unicode-metadata.html";
+        let extractor = Extractor::new().set_use_pure_rust(false);
+        let (_, metadata) = extractor.extract_file_to_string(path).unwrap();
+        assert_eq!(metadata["dc:title"], ["Café – 北京 😀"]);
+        assert_eq!(metadata["dc:creator"], ["Zoë Ñúñez 𝒜"]);
+    }
 
-        // Parse the files using extractous
+    #[test]
+    fn json_and_xml_output_conflict_test() {
+        let extractor = Extractor::new().set_json_output(true).set_xml_output(true);
+        let result = extractor.extract_file_to_string(TEST_FILE);
+
+        assert!(matches!(result, Err(Error::InvalidConfiguration(_))));
+    }
+
+    #[cfg(feature = "pure-rust")]
+    #[test]
+    fn extract_zip_member_test() {
         let extractor = Extractor::new();
-        let result = extractor.extract_file(TEST_FILE);
-        let (reader, metadata) = result.unwrap();
-        let content = read_content_from_stream(reader);
+        let docx = "../test_files/documents/category-level.docx";
 
-        assert_eq!(content.trim(), expected_content.trim());
-        assert!(
-            metadata.len() > 0,
-            "Metadata should contain at least one entry"
+        let document = extractor
+            .extract_zip_member(docx, "word/document.xml")
+            .unwrap();
+        assert!(document.starts_with(b"<?xml"));
+
+        let result = extractor.extract_zip_member(docx, "word/missing.xml");
+        assert!(matches!(result, Err(Error::MemberNotFound(ref m)) if m == "word/missing.xml"));
+    }
+
+    #[cfg(feature = "pure-rust")]
+    #[test]
+    fn extract_spreadsheet_test() {
+        let extractor = Extractor::new();
+        let sheets = extractor
+            .extract_spreadsheet("../test_files/documents/three-sheets.xlsx")
+            .unwrap();
+        assert_eq!(sheets[1].name, "Costs");
+        assert_eq!(
+            sheets[1].rows,
+            [["Quarter", "Costs"], ["Q1", "800"], ["Q2", "910"]]
         );
+
+        let result = extractor.extract_spreadsheet("../test_files/documents/simple.odt");
+        assert!(matches!(result, Err(Error::UnsupportedFormat(_))));
     }
 
-    fn read_file_as_bytes(path: &str) -> io::Result<Vec<u8>> {
-        let mut file = File::open(path)?;
-        let mut buffer = Vec::new();
-        file.read_to_end(&mut buffer)?;
-        Ok(buffer)
+    #[cfg(feature = "pure-rust")]
+    #[test]
+    fn extract_spreadsheet_locale_test() {
+        let path = "../test_files/documents/locale-numbers.xlsx";
+        let rows = |extractor: Extractor| {
+            let sheets = extractor.extract_spreadsheet(path).unwrap();
+            sheets[0].rows.clone()
+        };
+
+        assert_eq!(rows(Extractor::new())[1], ["1234.5", "2024-03-15"]);
+        let german_rows = rows(Extractor::new().set_locale("de-DE".to_string()));
+        assert_eq!(german_rows[1], ["1.234,5", "15.03.2024"]);
+        let us_rows = rows(Extractor::new().set_locale("en-US".to_string()));
+        assert_eq!(us_rows[1], ["1,234.5", "03/15/2024"]);
+        assert_eq!(us_rows[2], ["-1,234,567.25", "42"]);
+
+        let extractor = Extractor::new().set_locale("tlh".to_string());
+        let result = extractor.extract_spreadsheet(path);
+        assert!(matches!(result, Err(Error::InvalidConfiguration(_))));
     }
 
+    #[cfg(feature = "pure-rust")]
     #[test]
-    fn extract_bytes_test() {
-        // Prepare expected_content
-        let expected_content = expected_content();
+    fn extract_presentation_test() {
+        let path = "../test_files/documents/slide-notes.pptx";
+        let slides = Extractor::new().extract_presentation(path).unwrap();
+        assert_eq!(slides[1].title, "Things to think about");
+        assert!(slides[1].notes.starts_with("Pause after each question"));
+        assert!(!slides[1].body.contains("Pause"));
 
-        // Parse the bytes using extractous
-        let file_bytes = read_file_as_bytes(TEST_FILE).unwrap();
+        let office_config = OfficeParserConfig::new().set_include_slide_notes(false);
+        let extractor = Extractor::new().set_office_config(office_config);
+        let slides = extractor.extract_presentation(path).unwrap();
+        assert!(slides.iter().all(|slide| slide.notes.is_empty()));
+
+        let result = extractor.extract_presentation("../test_files/documents/simple.odt");
+        assert!(matches!(result, Err(Error::UnsupportedFormat(_))));
+    }
+
+    #[test]
+    fn extract_office_comments_and_tracked_changes_tika_test() {
+        let extract = |office_config| {
+            let extractor = Extractor::new()
+                .set_use_pure_rust(false)
+                .set_office_config(office_config);
+            let path = "../test_files/documents/comments.docx";
+            extractor.extract_file_to_string(path).unwrap().0
+        };
+
+        let text = extract(OfficeParserConfig::new());
+        assert!(text.contains("Check the renewal date."), "{}", text);
+        assert!(!text.contains("within 60 days"), "{}", text);
+
+        let text = extract(OfficeParserConfig::new().set_include_comments(false));
+        assert!(text.contains("The contract ends in March."), "{}", text);
+        assert!(!text.contains("Check the renewal date."), "{}", text);
+
+        let office_config = OfficeParserConfig::new().set_include_moved_deleted(true);
+        assert!(office_config.include_deleted_content && office_config.include_move_from_content);
+        let text = extract(office_config);
+        assert!(text.contains("within 60 days"), "{}", text);
+        assert!(text.contains("within 30 days"), "{}", text);
+    }
+
+    #[cfg(feature = "pure-rust")]
+    #[test]
+    fn extract_pdf_positions_test() {
         let extractor = Extractor::new();
-        let result = extractor.extract_bytes(&file_bytes);
-        let (reader, metadata) = result.unwrap();
-        let content = read_content_from_stream(reader);
+        let words = extractor
+            .extract_pdf_positions("../test_files/documents/blank-page.pdf")
+            .unwrap();
+        let first: Vec<&str> = words
+            .iter()
+            .take(5)
+            .map(|word| word.text.as_str())
+            .collect();
+        assert_eq!(first, ["First", "page", "of", "the", "report."]);
 
-        assert_eq!(content.trim(), expected_content.trim());
-        assert!(
-            metadata.len() > 0,
-            "Metadata should contain at least one entry"
+        let result = extractor.extract_pdf_positions("../test_files/documents/three-sheets.xlsx");
+        assert!(matches!(result, Err(Error::UnsupportedFormat(_))));
+    }
+
+    #[test]
+    fn extract_file_metadata_then_body_test() {
+        let extractor = Extractor::new();
+        let (metadata, mut reader) = extractor
+            .extract_file_metadata_then_body("../test_files/documents/2022_Q3_AAPL.pdf")
+            .unwrap();
+
+        // Available before any of the body is read
+        assert_eq!(metadata["Content-Type"], ["application/pdf"]);
+        assert_eq!(metadata["xmpTPg:NPages"], ["28"]);
+
+        let mut start = [0u8; 64];
+        reader.read_exact(&mut start).unwrap();
+        let mut rest = Vec::new();
+        reader.read_to_end(&mut rest).unwrap();
+        assert!(rest.len() > 10_000);
+    }
+
+    #[test]
+    fn unicode_normalization_test() {
+        let normalize = |form, text: &str| {
+            Extractor::new()
+                .set_unicode_normalization(Some(form))
+                .post_process_text(text.to_string(), Metadata::new(), None)
+                .0
+        };
+
+        // The fi ligature is a compatibility character, only the K forms expand it
+        assert_eq!(normalize(NormalizationForm::Nfkc, "\u{fb01}nal"), "final");
+        assert_eq!(
+            normalize(NormalizationForm::Nfc, "\u{fb01}nal"),
+            "\u{fb01}nal"
+        );
+        // e followed by a combining acute accent
+        assert_eq!(
+            normalize(NormalizationForm::Nfc, "caf\u{65}\u{301}"),
+            "caf\u{e9}"
+        );
+        assert_eq!(
+            normalize(NormalizationForm::Nfd, "caf\u{e9}"),
+            "caf\u{65}\u{301}"
         );
     }
 
     #[test]
-    fn extract_url_test() {
-        // Parse url by extractous
+    fn pure_rust_supports_test() {
+        let extractor = Extractor::new().set_use_pure_rust(true);
+        assert_eq!(
+            extractor.pure_rust_supports("../test_files/documents/metadata.pdf"),
+            cfg!(feature = "pure-rust")
+        );
+        assert!(!extractor.pure_rust_supports("../test_files/documents/simple.doc"));
+
+        let extractor = Extractor::new().set_use_pure_rust(false);
+        assert!(!extractor.pure_rust_supports("../test_files/documents/metadata.pdf"));
+    }
+
+    #[test]
+    fn document_password_test() {
+        let password = |value: &str| Some(crate::config::Password(value.to_string()));
+
+        // The password outlives configs set after it
+        let extractor = Extractor::new()
+            .set_document_password("secret".to_string())
+            .set_pdf_config(PdfParserConfig::new())
+            .set_office_config(OfficeParserConfig::new());
+        assert_eq!(extractor.tika_pdf_config().password, password("secret"));
+        assert_eq!(extractor.tika_office_config().password, password("secret"));
+
+        // The password of a config takes precedence
+        let extractor = extractor.set_pdf_config(PdfParserConfig::new().set_password("pdf".into()));
+        assert_eq!(extractor.tika_pdf_config().password, password("pdf"));
+        assert_eq!(extractor.tika_office_config().password, password("secret"));
+    }
+
+    #[test]
+    fn small_file_fast_path_test() {
+        let small = "../test_files/documents/small-page.html";
+        let dir = tempfile::tempdir().unwrap();
+        let large = dir.path().join("large-page.html");
+        let paragraph = "<p>Fresh snow on the upper slopes.</p>\n".repeat(2000);
+        std::fs::write(&large, format!("<html><body>{}</body></html>", paragraph)).unwrap();
+        let large = large.to_str().unwrap();
+
+        let extractor = Extractor::new().set_use_pure_rust(false);
+        assert!(!extractor.pure_rust_supports(small));
+
+        let extractor = extractor.set_small_file_fast_path(true);
+        assert_eq!(
+            extractor.pure_rust_supports(small),
+            cfg!(feature = "pure-rust")
+        );
+        assert!(!extractor.pure_rust_supports(large));
+        assert!(!extractor.pure_rust_supports("../test_files/documents/metadata.pdf"));
+        assert!(!extractor
+            .clone()
+            .set_markdown_output(true)
+            .pure_rust_supports(small));
+        assert!(!extractor
+            .clone()
+            .set_xml_output(true)
+            .pure_rust_supports(small));
+        let xhtml_handler = extractor.clone().set_tika_handler(TikaHandler::Xhtml);
+        assert!(!xhtml_handler.pure_rust_supports(small));
+
+        #[cfg(feature = "pure-rust")]
+        {
+            let (text, _) = extractor.extract_file_to_string(small).unwrap();
+            assert!(text.contains("Lift opening hours"));
+        }
+    }
+
+    #[test]
+    fn text_cleaning_config_test() {
+        let clean = |extractor: Extractor| {
+            extractor
+                .set_enable_text_cleaning(true)
+                .post_process_text(
+                    "First  page\x0cSecond page".to_string(),
+                    Metadata::new(),
+                    None,
+                )
+                .0
+        };
+
+        let config = TextCleaningConfig::new().set_preserve_page_breaks(true);
+        let extractor = Extractor::new().set_text_cleaning_config(config);
+        assert_eq!(clean(extractor), "First page\n\nSecond page");
+
+        let config = TextCleaningConfig::new().set_preserve_page_breaks(false);
+        let extractor = Extractor::new().set_text_cleaning_config(config);
+        assert_eq!(clean(extractor), "First page Second page");
+        // Short texts are left alone without a config
+        assert_eq!(clean(Extractor::new()), "First  page\x0cSecond page");
+    }
+
+    #[test]
+    fn truncation_without_text_cleaning_test() {
+        let truncate = |max_length, text: &str| {
+            Extractor::new()
+                .set_enable_text_cleaning(false)
+                .set_extract_string_max_length(max_length)
+                .post_process_text(text.to_string(), Metadata::new(), None)
+                .0
+        };
+
+        // The limit counts characters, although each é takes two bytes
+        assert_eq!(truncate(7, "éééééééééé"), "éééé...");
+        assert_eq!(truncate(10, "éééééééééé"), "éééééééééé");
+        assert_eq!(truncate(2, "éééééééééé"), "");
+        assert_eq!(truncate(-1, "éééééééééé"), "éééééééééé");
+    }
+
+    #[test]
+    fn char_filter_test() {
+        let extractor =
+            Extractor::new().set_char_filter(Box::new(|c| !matches!(c, '\u{ad}' | '\u{200b}')));
+        let hyphenated = "Extrac\u{ad}tion of hy\u{ad}phen\u{ad}ated\u{200b} words".to_string();
+
+        // Clones share the filter
+        let (text, _) = extractor
+            .clone()
+            .post_process_text(hyphenated, Metadata::new(), None);
+        assert_eq!(text, "Extraction of hyphenated words");
+    }
+
+    #[test]
+    fn line_endings_test() {
+        let normalize = |line_ending, text: &str| {
+            Extractor::new()
+                .set_line_endings(line_ending)
+                .post_process_text(text.to_string(), Metadata::new(), None)
+                .0
+        };
+
+        assert_eq!(normalize(LineEnding::Lf, "one\r\ntwo\r\n"), "one\ntwo\n");
+        assert_eq!(normalize(LineEnding::Crlf, "one\ntwo\n"), "one\r\ntwo\r\n");
+        let mixed = "one\r\ntwo\nthree\r";
+        assert_eq!(normalize(LineEnding::Preserve, mixed), mixed);
+    }
+
+    #[test]
+    fn truncation_suffix_test() {
+        let truncate = |suffix: &str| {
+            Extractor::new()
+                .set_extract_string_max_length(16)
+                .set_truncation_suffix(suffix.to_string())
+                .post_process_text("Alpha beta gamma delta".to_string(), Metadata::new(), None)
+                .0
+        };
+
+        assert_eq!(truncate("..."), "Alpha beta...");
+        // The ellipsis is a single character, the cut still falls in the middle of gamma
+        assert_eq!(truncate("\u{2026}"), "Alpha beta\u{2026}");
+        assert_eq!(truncate(""), "Alpha beta gamma");
+    }
+
+    #[cfg(feature = "pure-rust")]
+    #[test]
+    fn extract_file_bidi_reordering_test() {
+        // A w:bidi paragraph "Rust שלום עולם" followed by the left-to-right "Hello שלום עולם"
+        let docx = "../test_files/documents/mixed-direction.docx";
+        let hebrew_visual = "\u{5dd}\u{5dc}\u{5d5}\u{5e2} \u{5dd}\u{5d5}\u{5dc}\u{5e9}";
+
+        let extractor = Extractor::new().set_bidi_reordering(crate::BidiReordering::PerParagraph);
+        let (content, _) = extractor.extract_file_to_string(docx).unwrap();
+        let lines: Vec<&str> = content.lines().collect();
+        // The first paragraph is right-to-left although it starts with a Latin word
+        assert_eq!(lines[0], format!("{} Rust", hebrew_visual));
+        assert_eq!(lines[1], format!("Hello {}", hebrew_visual));
+    }
+
+    #[cfg(feature = "pure-rust")]
+    #[test]
+    fn extract_file_cleaning_profiles_test() {
+        // Long enough for the text cleaning to collapse its whitespace without a profile
+        let csv: String = (0..500)
+            .map(|i| format!("{},item {},{}.50\n", i, i, i))
+            .collect();
+        let dir = tempfile::tempdir().unwrap();
+        let csv_path = dir.path().join("items.csv");
+        std::fs::write(&csv_path, csv).unwrap();
+
+        use crate::{CleaningOptions, DocumentFormat};
+        let extractor = Extractor::new()
+            .set_enable_text_cleaning(true)
+            .set_cleaning_profile_for(DocumentFormat::Pdf, CleaningOptions::aggressive())
+            .set_cleaning_profile_for(DocumentFormat::Csv, CleaningOptions::new());
+
+        let (content, _) = extractor
+            .extract_file_to_string(csv_path.to_str().unwrap())
+            .unwrap();
+        assert_eq!(content.lines().count(), 500);
+        assert_eq!(content.lines().nth(7), Some("7\titem 7\t7.50"));
+
+        let pdf = "../test_files/documents/running-headers.pdf";
+        let (content, _) = extractor.extract_file_to_string(pdf).unwrap();
+        assert!(!content.contains("ACME Group - Annual Report 2023"));
+        assert!(!content.contains("Page 2 of 4"));
+        // Dehyphenated and on a single line
+        assert!(content.contains("full capacity in the summer, ahead of the original plan."));
+        assert!(!content.contains('\n'));
+    }
+
+    #[cfg(feature = "pure-rust")]
+    #[test]
+    fn extract_file_lines_test() {
+        let csv: String = (0..2000).map(|i| format!("{},item {}\n", i, i)).collect();
+        let dir = tempfile::tempdir().unwrap();
+        let csv_path = dir.path().join("items.csv");
+        std::fs::write(&csv_path, csv).unwrap();
+
+        let lines = Extractor::new()
+            .extract_file_lines(csv_path.to_str().unwrap())
+            .unwrap()
+            .collect::<io::Result<Vec<String>>>()
+            .unwrap();
+        assert_eq!(lines.len(), 2000);
+        assert_eq!(lines[1234], "1234\titem 1234");
+
+        let missing = Extractor::new().extract_file_lines("../test_files/documents/missing.csv");
+        assert!(missing.is_err());
+    }
+
+    #[cfg(feature = "pure-rust")]
+    #[test]
+    fn get_pdf_object_test() {
         let extractor = Extractor::new();
-        let result = extractor.extract_url(&TEST_URL);
-        let (reader, metadata) = result.unwrap();
+        let pdf = "../test_files/documents/metadata.pdf";
+
+        let catalog = extractor.get_pdf_object(pdf, 1, 0).unwrap();
+        assert!(catalog.contains("/Type /Catalog"), "{}", catalog);
+
+        let result = extractor.get_pdf_object(pdf, 999, 0);
+        assert!(matches!(
+            result,
+            Err(Error::ObjectNotFound { obj: 999, gen: 0 })
+        ));
+        assert_eq!(result.unwrap_err().kind(), ErrorKind::ObjectNotFound);
+    }
+
+    #[cfg(feature = "pure-rust")]
+    #[test]
+    fn extract_file_pure_rust_stream_test() {
+        let csv_file = "../test_files/documents/table-multi-row-column-cells-actual.csv";
+        let (expected, _) = crate::pure_rust_parsers::csv::extract_csv_text(csv_file).unwrap();
+
+        let extractor = Extractor::new().set_use_pure_rust(true);
+        let (reader, metadata) = extractor.extract_file(csv_file).unwrap();
         let content = read_content_from_stream(reader);
 
-        assert!(content.contains("Google"));
-        assert!(
-            metadata.len() > 0,
-            "Metadata should contain at least one entry"
+        assert_eq!(content, expected);
+        assert_eq!(
+            metadata.get("Parser").unwrap(),
+            &vec!["pure-rust-csv".to_string()]
         );
     }
 
+    #[cfg(feature = "pure-rust")]
     #[test]
-    fn extract_file_to_xml_test() {
-        // Parse the files using extractous
-        let extractor = Extractor::new().set_xml_output(true);
-        let result = extractor.extract_file_to_string(TEST_FILE);
-        let (content, metadata) = result.unwrap();
+    fn extract_file_pages_skip_blank_pages_test() {
+        let pdf = "../test_files/documents/blank-page.pdf";
+
+        let (pages, metadata) = Extractor::new().extract_file_pages(pdf).unwrap();
+        assert_eq!(pages.len(), 3);
+        assert!(pages[1].trim().is_empty());
+        assert!(!metadata.contains_key("Blank-Pages-Skipped"));
+
+        let extractor = Extractor::new().set_skip_blank_pages(true);
+        let (pages, metadata) = extractor.extract_file_pages(pdf).unwrap();
+        assert_eq!(
+            pages,
+            ["First page of the report.", "Third page after a blank one."]
+        );
+        assert_eq!(
+            metadata.get("Blank-Pages-Skipped").unwrap(),
+            &vec!["1".to_string()]
+        );
+    }
+
+    #[cfg(feature = "pure-rust")]
+    #[test]
+    fn annotate_page_boundaries_test() {
+        let pdf = "../test_files/documents/blank-page.pdf";
+
+        let extractor = Extractor::new().set_annotate_page_boundaries(true);
+        let (text, _) = extractor.extract_file_to_string(pdf).unwrap();
+        assert_eq!(text.matches("[page ").count(), 3);
+        let first = text.find("[page 1]\nFirst page of the report.").unwrap();
+        let second = text.find("[page 2]").unwrap();
+        let third = text
+            .find("[page 3]\nThird page after a blank one.")
+            .unwrap();
+        assert!(first == 0 && first < second && second < third);
+
+        let extractor = extractor.set_page_marker("--- {page} ---".to_string());
+        let (text, _) = extractor.extract_file_to_string(pdf).unwrap();
+        assert!(text.contains("--- 3 ---\nThird page"));
+        let (text, _) = Extractor::new().extract_file_to_string(pdf).unwrap();
+        assert!(!text.contains("[page"));
+    }
+
+    #[cfg(feature = "pure-rust")]
+    #[test]
+    fn extract_file_chunked_sheets_test() {
+        let chunks = Extractor::new()
+            .extract_file_chunked("../test_files/documents/three-sheets.xlsx")
+            .unwrap();
+
+        assert_eq!(chunks.len(), 3);
+        let names: Vec<_> = chunks
+            .iter()
+            .map(|chunk| chunk.metadata["Sheet-Name"][0].as_str())
+            .collect();
+        assert_eq!(names, ["Revenue", "Costs", "Notes"]);
+        assert!(chunks.iter().enumerate().all(|(i, chunk)| chunk.index == i));
+        assert!(chunks
+            .iter()
+            .all(|chunk| chunk.kind == crate::ChunkKind::Sheet));
+        assert!(chunks[1].text.contains("Costs"));
+        assert!(chunks[2].text.contains("thousands of euros"));
+        assert!(chunks
+            .iter()
+            .all(|chunk| chunk.metadata["Sheet-Count"] == ["3"]));
+
+        // Options that cannot be honored are rejected as by the other extract methods
+        let error = Extractor::new()
+            .set_markdown_output(true)
+            .set_json_output(true)
+            .extract_file_chunked("../test_files/documents/three-sheets.xlsx")
+            .unwrap_err();
+        assert!(matches!(
+            error,
+            crate::errors::Error::InvalidConfiguration(_)
+        ));
+    }
+
+    #[cfg(feature = "pure-rust")]
+    #[test]
+    fn extract_file_chunked_pages_test() {
+        let extractor = Extractor::new().set_skip_blank_pages(true);
+        let chunks = extractor
+            .extract_file_chunked("../test_files/documents/blank-page.pdf")
+            .unwrap();
+
+        let pages: Vec<_> = chunks
+            .iter()
+            .map(|chunk| chunk.metadata["Page-Number"][0].as_str())
+            .collect();
+        assert_eq!(pages, ["1", "3"]);
+        assert_eq!(chunks[1].index, 1);
+        assert_eq!(chunks[1].text, "Third page after a blank one.");
+    }
+
+    #[cfg(feature = "pure-rust")]
+    #[test]
+    fn extract_link_urls_test() {
+        let path = "../test_files/documents/links.html";
+        let extract = |link_mode| {
+            let extractor = Extractor::new().set_include_link_urls(link_mode);
+            extractor.extract_file_to_string(path).unwrap().0
+        };
+
+        let text = extract(LinkMode::Drop);
         assert!(
-            content.len() > 0,
-            "Metadata should contain at least one entry"
+            text.contains("Check the weather report before you leave."),
+            "{}",
+            text
         );
+        assert!(!text.contains("https://"));
+
+        let text = extract(LinkMode::Inline);
         assert!(
-            metadata.len() > 0,
-            "Metadata should contain at least one entry"
+            text.contains("weather report (https://example.com/weather) before"),
+            "{}",
+            text
         );
+        assert!(!text.contains("Back to top ("));
+
+        let text = extract(LinkMode::Footnotes);
+        assert!(text.contains("weather report [1] before"), "{}", text);
+        let notes = "[1] https://example.com/weather\n[2] https://example.com/maps";
+        assert!(text.trim_end().ends_with(notes), "{}", text);
+    }
+
+    #[cfg(feature = "pure-rust")]
+    #[test]
+    fn extract_pdf_sorted_by_position_test() {
+        let path = "../test_files/documents/two-columns.pdf";
+        let (text, _) = Extractor::new().extract_file_to_string(path).unwrap();
+        assert!(text.find("The right column").unwrap() < text.find("before it ends").unwrap());
+
+        let extractor =
+            Extractor::new().set_pdf_config(PdfParserConfig::new().set_sort_by_position(true));
+        let (text, _) = extractor.extract_file_to_string(path).unwrap();
+        let left = text.find("The left column starts here").unwrap();
+        let left_end = text.find("before it ends at the bottom.").unwrap();
+        let right = text.find("The right column comes next").unwrap();
+        assert!(left < left_end && left_end < right, "{}", text);
     }
 }
@@ -1,38 +1,146 @@
 /// Fast format detection for optimized parsing
+use std::borrow::Cow;
 use std::path::Path;
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
 pub enum DocumentFormat {
     Pdf,
     Docx,
     Xlsx,
     Pptx,
+    Doc,
+    Xls,
+    Ppt,
+    Odt,
+    Ods,
+    Odp,
     Html,
     Xml,
     Csv,
     Text,
+    /// Markdown, whose YAML frontmatter the pure-Rust parser reads as metadata
+    Markdown,
     Json,
+    Eml,
+    Msg,
+    /// An image, whose text Tika reads with OCR
+    Image(ImageKind),
     Unknown,
 }
 
+/// The kind of a [`DocumentFormat::Image`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ImageKind {
+    Png,
+    Jpeg,
+    Tiff,
+}
+
+impl DocumentFormat {
+    /// The formats the pure-Rust parsers can extract when the `pure-rust` feature is enabled.
+    /// Tika supports many more, see `Extractor::supported_mime_types`
+    pub fn all() -> &'static [DocumentFormat] {
+        &[
+            DocumentFormat::Pdf,
+            DocumentFormat::Docx,
+            DocumentFormat::Xlsx,
+            DocumentFormat::Html,
+            DocumentFormat::Xml,
+            DocumentFormat::Csv,
+            DocumentFormat::Markdown,
+        ]
+    }
+
+    /// The mime type of the format, `None` for `Unknown`
+    pub fn mime_type(&self) -> Option<&'static str> {
+        match self {
+            DocumentFormat::Pdf => Some("application/pdf"),
+            DocumentFormat::Docx => {
+                Some("application/vnd.openxmlformats-officedocument.wordprocessingml.document")
+            }
+            DocumentFormat::Xlsx => {
+                Some("application/vnd.openxmlformats-officedocument.spreadsheetml.sheet")
+            }
+            DocumentFormat::Pptx => {
+                Some("application/vnd.openxmlformats-officedocument.presentationml.presentation")
+            }
+            DocumentFormat::Doc => Some("application/msword"),
+            DocumentFormat::Xls => Some("application/vnd.ms-excel"),
+            DocumentFormat::Ppt => Some("application/vnd.ms-powerpoint"),
+            DocumentFormat::Odt => Some("application/vnd.oasis.opendocument.text"),
+            DocumentFormat::Ods => Some("application/vnd.oasis.opendocument.spreadsheet"),
+            DocumentFormat::Odp => Some("application/vnd.oasis.opendocument.presentation"),
+            DocumentFormat::Html => Some("text/html"),
+            DocumentFormat::Xml => Some("application/xml"),
+            DocumentFormat::Csv => Some("text/csv"),
+            DocumentFormat::Text => Some("text/plain"),
+            DocumentFormat::Markdown => Some("text/markdown"),
+            DocumentFormat::Json => Some("application/json"),
+            DocumentFormat::Eml => Some("message/rfc822"),
+            DocumentFormat::Msg => Some("application/vnd.ms-outlook"),
+            DocumentFormat::Image(ImageKind::Png) => Some("image/png"),
+            DocumentFormat::Image(ImageKind::Jpeg) => Some("image/jpeg"),
+            DocumentFormat::Image(ImageKind::Tiff) => Some("image/tiff"),
+            DocumentFormat::Unknown => None,
+        }
+    }
+
+    /// Whether the format is an image, which has no text other than what OCR reads from it
+    pub fn is_image(&self) -> bool {
+        matches!(self, DocumentFormat::Image(_))
+    }
+
+    /// The format of a mime type, e.g. a `Content-Type` value whose parameters are ignored.
+    /// `None` for mime types without a `DocumentFormat`
+    pub fn from_mime_type(mime_type: &str) -> Option<DocumentFormat> {
+        let mime_type = mime_type.split(';').next()?.trim().to_lowercase();
+        match mime_type.as_str() {
+            "application/pdf" => Some(DocumentFormat::Pdf),
+            "application/vnd.openxmlformats-officedocument.wordprocessingml.document" => {
+                Some(DocumentFormat::Docx)
+            }
+            "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet" => {
+                Some(DocumentFormat::Xlsx)
+            }
+            "application/vnd.openxmlformats-officedocument.presentationml.presentation" => {
+                Some(DocumentFormat::Pptx)
+            }
+            "application/msword" => Some(DocumentFormat::Doc),
+            "application/vnd.ms-excel" => Some(DocumentFormat::Xls),
+            "application/vnd.ms-powerpoint" => Some(DocumentFormat::Ppt),
+            "application/vnd.oasis.opendocument.text" => Some(DocumentFormat::Odt),
+            "application/vnd.oasis.opendocument.spreadsheet" => Some(DocumentFormat::Ods),
+            "application/vnd.oasis.opendocument.presentation" => Some(DocumentFormat::Odp),
+            "text/html" | "application/xhtml+xml" => Some(DocumentFormat::Html),
+            "application/xml" | "text/xml" => Some(DocumentFormat::Xml),
+            "text/csv" => Some(DocumentFormat::Csv),
+            "text/plain" => Some(DocumentFormat::Text),
+            "text/markdown" | "text/x-markdown" | "text/x-web-markdown" => {
+                Some(DocumentFormat::Markdown)
+            }
+            "application/json" => Some(DocumentFormat::Json),
+            "message/rfc822" => Some(DocumentFormat::Eml),
+            "application/vnd.ms-outlook" => Some(DocumentFormat::Msg),
+            "image/png" => Some(DocumentFormat::Image(ImageKind::Png)),
+            "image/jpeg" => Some(DocumentFormat::Image(ImageKind::Jpeg)),
+            "image/tiff" => Some(DocumentFormat::Image(ImageKind::Tiff)),
+            _ => None,
+        }
+    }
+}
+
 /// Fast format detection using file extension and magic bytes
 pub fn detect_format<P: AsRef<Path>>(path: P) -> DocumentFormat {
     let path = path.as_ref();
     
     // First try extension-based detection (fastest)
-    if let Some(ext) = path.extension().and_then(|e| e.to_str()) {
-        match ext.to_lowercase().as_str() {
-            "pdf" => return DocumentFormat::Pdf,
-            "docx" => return DocumentFormat::Docx,
-            "xlsx" => return DocumentFormat::Xlsx,
-            "pptx" => return DocumentFormat::Pptx,
-            "html" | "htm" => return DocumentFormat::Html,
-            "xml" => return DocumentFormat::Xml,
-            "csv" => return DocumentFormat::Csv,
-            "txt" | "md" | "rst" => return DocumentFormat::Text,
-            "json" => return DocumentFormat::Json,
-            _ => {}
-        }
+    if let Some(format) = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .and_then(format_from_extension)
+    {
+        return format;
     }
     
     // Fallback to magic byte detection
@@ -45,6 +153,231 @@ pub fn detect_format<P: AsRef<Path>>(path: P) -> DocumentFormat {
     DocumentFormat::Unknown
 }
 
+/// Detect the format like [`detect_format`], along with a confidence score from 0.0 to 1.0.
+///
+/// The score is highest when the extension and a magic byte signature such as `%PDF` agree,
+/// lower for markup and JSON sniffed from the first bytes, and lowest for formats guessed by text
+/// heuristics, e.g. csv from counting commas. A signature that contradicts the extension wins
+/// with a lower score, while weaker evidence leaves the format of the extension. `Unknown`
+/// scores 0.0.
+pub fn detect_format_with_confidence<P: AsRef<Path>>(path: P) -> (DocumentFormat, f32) {
+    let path = path.as_ref();
+    let by_extension = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .and_then(format_from_extension);
+
+    match (by_extension, content_evidence(path)) {
+        (None, None) => (DocumentFormat::Unknown, 0.0),
+        (Some(format), None) => (format, 0.6),
+        (None, Some((format, evidence))) => {
+            let score = match evidence {
+                Evidence::Signature => 0.9,
+                Evidence::Markup => 0.7,
+                Evidence::Heuristic => 0.4,
+            };
+            (format, score)
+        }
+        (Some(extension), Some((format, evidence))) if extension == format => {
+            let score = match evidence {
+                Evidence::Signature => 1.0,
+                Evidence::Markup => 0.9,
+                Evidence::Heuristic => 0.7,
+            };
+            (format, score)
+        }
+        (Some(_), Some((format, Evidence::Signature))) => (format, 0.8),
+        (Some(extension), Some((_, Evidence::Markup))) => (extension, 0.5),
+        (Some(extension), Some((_, Evidence::Heuristic))) => (extension, 0.4),
+    }
+}
+
+/// What the content of a file found in [`detect_format_with_confidence`] rests on
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Evidence {
+    /// A magic byte signature, e.g. `%PDF`
+    Signature,
+    /// Markup or JSON recognised by its first characters
+    Markup,
+    /// A text heuristic, e.g. commas on the first line for csv
+    Heuristic,
+}
+
+/// Magic bytes that identify a format, or a container of one, on their own
+const SIGNATURES: &[&[u8]] = &[
+    b"%PDF",
+    b"PK\x03\x04",
+    OLE_SIGNATURE,
+    b"\x89PNG",
+    b"II*\x00",
+    b"MM\x00*",
+    b"\xFF\xD8\xFF",
+];
+
+/// The format detected from the first kilobytes of the file and the evidence for it. `None`
+/// when the file cannot be read or its format is unknown
+fn content_evidence(path: &Path) -> Option<(DocumentFormat, Evidence)> {
+    use std::io::Read;
+
+    let mut file = std::fs::File::open(path).ok()?;
+    let mut buffer = Vec::with_capacity(4096);
+    (&mut file).take(4096).read_to_end(&mut buffer).ok()?;
+    // The text heuristics need valid UTF-8, without a character cut off at the end
+    if let Err(e) = std::str::from_utf8(&buffer) {
+        if e.error_len().is_none() {
+            buffer.truncate(e.valid_up_to());
+        }
+    }
+
+    let format = if buffer.starts_with(OLE_SIGNATURE) {
+        detect_format_from_file(&mut file).ok()?
+    } else {
+        detect_format_from_bytes(&buffer)
+    };
+    if format == DocumentFormat::Unknown {
+        return None;
+    }
+
+    let (content, _) = strip_bom(&buffer);
+    let evidence = if SIGNATURES
+        .iter()
+        .any(|signature| content.starts_with(signature))
+    {
+        Evidence::Signature
+    } else if detect_markup_or_json(&content).is_some() {
+        Evidence::Markup
+    } else {
+        Evidence::Heuristic
+    };
+    Some((format, evidence))
+}
+
+/// The format of a file extension without the dot, matched case insensitively
+fn format_from_extension(extension: &str) -> Option<DocumentFormat> {
+    let format = match extension.to_lowercase().as_str() {
+        "pdf" => DocumentFormat::Pdf,
+        "docx" => DocumentFormat::Docx,
+        "xlsx" => DocumentFormat::Xlsx,
+        "pptx" => DocumentFormat::Pptx,
+        "doc" => DocumentFormat::Doc,
+        "xls" => DocumentFormat::Xls,
+        "ppt" => DocumentFormat::Ppt,
+        "odt" => DocumentFormat::Odt,
+        "ods" => DocumentFormat::Ods,
+        "odp" => DocumentFormat::Odp,
+        "html" | "htm" => DocumentFormat::Html,
+        "xml" => DocumentFormat::Xml,
+        "csv" | "tsv" | "tab" | "psv" => DocumentFormat::Csv,
+        "txt" | "rst" => DocumentFormat::Text,
+        "md" | "markdown" => DocumentFormat::Markdown,
+        "json" => DocumentFormat::Json,
+        "eml" => DocumentFormat::Eml,
+        "msg" => DocumentFormat::Msg,
+        "png" => DocumentFormat::Image(ImageKind::Png),
+        "jpg" | "jpeg" => DocumentFormat::Image(ImageKind::Jpeg),
+        "tif" | "tiff" => DocumentFormat::Image(ImageKind::Tiff),
+        _ => return None,
+    };
+    Some(format)
+}
+
+/// Extensions of source code and configuration files, whose indentation is significant
+const CODE_EXTENSIONS: &[&str] = &[
+    "py",
+    "pyw",
+    "rs",
+    "go",
+    "c",
+    "h",
+    "cc",
+    "cpp",
+    "cxx",
+    "hpp",
+    "cs",
+    "java",
+    "kt",
+    "kts",
+    "scala",
+    "swift",
+    "js",
+    "jsx",
+    "mjs",
+    "ts",
+    "tsx",
+    "rb",
+    "php",
+    "pl",
+    "lua",
+    "r",
+    "sh",
+    "bash",
+    "zsh",
+    "ps1",
+    "sql",
+    "hs",
+    "ml",
+    "ex",
+    "exs",
+    "erl",
+    "clj",
+    "dart",
+    "groovy",
+    "yaml",
+    "yml",
+    "toml",
+    "ini",
+    "mk",
+    "makefile",
+    "dockerfile",
+];
+
+/// Mime types Tika reports for source code
+const CODE_MIME_TYPES: &[&str] = &[
+    "text/x-python",
+    "text/x-java-source",
+    "text/x-csrc",
+    "text/x-chdr",
+    "text/x-c++src",
+    "text/x-c++hdr",
+    "text/x-csharp",
+    "text/x-go",
+    "text/x-rustsrc",
+    "text/x-scala",
+    "text/x-kotlin",
+    "text/x-ruby",
+    "text/x-php",
+    "text/x-perl",
+    "text/x-lua",
+    "text/x-haskell",
+    "text/x-sql",
+    "text/x-yaml",
+    "text/javascript",
+    "application/javascript",
+    "application/x-sh",
+    "application/x-httpd-php",
+];
+
+/// Whether the file is source code, judged by its extension or, for files like `Makefile`
+/// without one, by its name
+pub fn is_code_file<P: AsRef<Path>>(path: P) -> bool {
+    let path = path.as_ref();
+    path.extension()
+        .or_else(|| path.file_name())
+        .and_then(|name| name.to_str())
+        .is_some_and(|name| CODE_EXTENSIONS.contains(&name.to_lowercase().as_str()))
+}
+
+/// Whether the mime type, e.g. a `Content-Type` value with parameters, is a source code type
+pub fn is_code_mime_type(mime_type: &str) -> bool {
+    let mime_type = mime_type
+        .split(';')
+        .next()
+        .unwrap_or("")
+        .trim()
+        .to_lowercase();
+    CODE_MIME_TYPES.contains(&mime_type.as_str())
+}
+
 /// Detect format from file content using magic bytes
 pub fn detect_format_from_file(file: &mut std::fs::File) -> Result<DocumentFormat, std::io::Error> {
     use std::io::{Read, Seek, SeekFrom};
@@ -52,35 +385,250 @@ pub fn detect_format_from_file(file: &mut std::fs::File) -> Result<DocumentForma
     let mut buffer = [0u8; 16];
     file.seek(SeekFrom::Start(0))?;
     file.read_exact(&mut buffer)?;
+    // The streams of OLE compound files are listed in directory sectors further into the file
+    let format = if buffer.starts_with(OLE_SIGNATURE) {
+        ole_stream_names(file).map_or(DocumentFormat::Unknown, |names| ole_format(&names))
+    } else {
+        detect_format_from_bytes(&buffer)
+    };
     file.seek(SeekFrom::Start(0))?; // Reset position
-    
-    Ok(detect_format_from_bytes(&buffer))
+
+    Ok(format)
+}
+
+/// A byte order mark at the start of a text, and the encoding it declares
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ByteOrderMark {
+    Utf8,
+    Utf16Le,
+    Utf16Be,
 }
 
-/// Detect format from byte slice using magic bytes
+impl ByteOrderMark {
+    /// The bytes of the mark
+    pub fn bytes(&self) -> &'static [u8] {
+        match self {
+            ByteOrderMark::Utf8 => b"\xEF\xBB\xBF",
+            ByteOrderMark::Utf16Le => b"\xFF\xFE",
+            ByteOrderMark::Utf16Be => b"\xFE\xFF",
+        }
+    }
+
+    /// The name of the encoding, as Tika reports it in `Content-Encoding`
+    pub fn encoding(&self) -> &'static str {
+        match self {
+            ByteOrderMark::Utf8 => "UTF-8",
+            ByteOrderMark::Utf16Le => "UTF-16LE",
+            ByteOrderMark::Utf16Be => "UTF-16BE",
+        }
+    }
+}
+
+/// The byte order mark the buffer starts with, if any
+pub fn detect_bom(buffer: &[u8]) -> Option<ByteOrderMark> {
+    [
+        ByteOrderMark::Utf8,
+        ByteOrderMark::Utf16Le,
+        ByteOrderMark::Utf16Be,
+    ]
+    .into_iter()
+    .find(|bom| buffer.starts_with(bom.bytes()))
+}
+
+/// A compression format wrapping a document, e.g. of a `.txt.gz` file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Compression {
+    Gzip,
+    Bzip2,
+    Xz,
+}
+
+impl Compression {
+    /// The mime type of the compressed file
+    pub fn mime_type(&self) -> &'static str {
+        match self {
+            Compression::Gzip => "application/gzip",
+            Compression::Bzip2 => "application/x-bzip2",
+            Compression::Xz => "application/x-xz",
+        }
+    }
+}
+
+/// The compression format the buffer starts with, judged by its magic bytes: `1F 8B` for gzip,
+/// `BZh` and a block size digit from 1 to 9 for bzip2 and `FD 37 7A 58 5A 00` for xz
+pub fn detect_compression(buffer: &[u8]) -> Option<Compression> {
+    if buffer.starts_with(b"\x1F\x8B") {
+        Some(Compression::Gzip)
+    } else if buffer.starts_with(b"BZh") && matches!(buffer.get(3), Some(b'1'..=b'9')) {
+        Some(Compression::Bzip2)
+    } else if buffer.starts_with(b"\xFD7zXZ\x00") {
+        Some(Compression::Xz)
+    } else {
+        None
+    }
+}
+
+/// The format of the decompressed content of the file at `path`. Text, which magic bytes do not
+/// tell apart from csv or source code, is taken for the format named by the extension before the
+/// compression extension, e.g. csv for `sales.csv.gz`
+#[cfg(feature = "compression")]
+pub(crate) fn detect_decompressed_format(buffer: &[u8], path: &Path) -> DocumentFormat {
+    let format = detect_format_from_bytes(buffer);
+    if !matches!(format, DocumentFormat::Text | DocumentFormat::Unknown) {
+        return format;
+    }
+    path.file_stem()
+        .map(Path::new)
+        .and_then(Path::extension)
+        .and_then(|e| e.to_str())
+        .and_then(format_from_extension)
+        .unwrap_or(format)
+}
+
+/// Removes a byte order mark from the start of the buffer and returns the rest as UTF-8, along
+/// with the mark. UTF-16 text is converted, with a replacement character for invalid code units
+/// and without an odd last byte, e.g. of a buffer that was cut off. A buffer without a mark is
+/// returned as it is
+pub(crate) fn strip_bom(buffer: &[u8]) -> (Cow<'_, [u8]>, Option<ByteOrderMark>) {
+    let Some(bom) = detect_bom(buffer) else {
+        return (Cow::Borrowed(buffer), None);
+    };
+    let rest = &buffer[bom.bytes().len()..];
+    let to_unit = match bom {
+        ByteOrderMark::Utf8 => return (Cow::Borrowed(rest), Some(bom)),
+        ByteOrderMark::Utf16Le => u16::from_le_bytes,
+        ByteOrderMark::Utf16Be => u16::from_be_bytes,
+    };
+    let units = rest.chunks_exact(2).map(|pair| to_unit([pair[0], pair[1]]));
+    let text: String = char::decode_utf16(units)
+        .map(|c| c.unwrap_or(char::REPLACEMENT_CHARACTER))
+        .collect();
+    (Cow::Owned(text.into_bytes()), Some(bom))
+}
+
+/// Detect format from byte slice using magic bytes. Text with a UTF-8 or UTF-16 byte order mark
+/// is detected from the text after the mark
 pub fn detect_format_from_bytes(buffer: &[u8]) -> DocumentFormat {
+    // Only the first mark is stripped, a mark after it is text
+    let (buffer, _) = strip_bom(buffer);
+    detect_unmarked_format(&buffer)
+}
+
+/// Detect the format of a byte slice without a byte order mark
+fn detect_unmarked_format(buffer: &[u8]) -> DocumentFormat {
+    match buffer.get(0..4) {
+        Some(b"%PDF") => return DocumentFormat::Pdf,
+        // ZIP-based formats
+        Some(b"PK\x03\x04") => return detect_office_format(buffer),
+        // OLE compound files (legacy Office)
+        Some(b"\xD0\xCF\x11\xE0") => return detect_ole_format(buffer),
+        Some(b"\x89PNG") => return DocumentFormat::Image(ImageKind::Png),
+        Some(b"II*\x00" | b"MM\x00*") => return DocumentFormat::Image(ImageKind::Tiff),
+        _ => {}
+    }
+    if buffer.starts_with(b"\xFF\xD8\xFF") {
+        return DocumentFormat::Image(ImageKind::Jpeg);
+    }
+
+    if let Some(format) = detect_markup_or_json(buffer) {
+        return format;
+    }
     if buffer.len() < 4 {
         return DocumentFormat::Unknown;
     }
-    
-    match &buffer[0..4] {
-        b"%PDF" => DocumentFormat::Pdf,
-        b"PK\x03\x04" => detect_office_format(buffer),  // ZIP-based formats
-        b"<htm" | b"<HTM" | b"<!DO" => DocumentFormat::Html,
-        b"<?xm" => DocumentFormat::Xml,
-        b"{\n  " | b"{ \n" | b"{\r\n" | b"[{\"" => DocumentFormat::Json,
-        _ => detect_text_format(buffer),
+    detect_text_format(buffer)
+}
+
+/// Detect JSON, XML and HTML from the first byte after any leading whitespace, so indented or
+/// blank-line-prefixed documents and buffers as short as `{}` are found
+fn detect_markup_or_json(buffer: &[u8]) -> Option<DocumentFormat> {
+    let start = buffer.iter().position(|b| !b.is_ascii_whitespace())?;
+    let content = &buffer[start..];
+    let next_byte = content[1..].iter().find(|b| !b.is_ascii_whitespace());
+
+    match content[0] {
+        // An object starts with a key or is empty, and only arrays of objects, arrays or
+        // strings are sniffed here, other text is left to the text heuristics
+        b'{' => matches!(next_byte, Some(b'"' | b'}')).then_some(DocumentFormat::Json),
+        b'[' => {
+            matches!(next_byte, Some(b'{' | b'[' | b'"' | b']')).then_some(DocumentFormat::Json)
+        }
+        b'<' => {
+            let head = content[..content.len().min(1024)].to_ascii_lowercase();
+            if head.starts_with(b"<?xml") {
+                Some(DocumentFormat::Xml)
+            } else if head.starts_with(b"<!doctype html")
+                || head.windows(5).any(|window| window == b"<html")
+            {
+                Some(DocumentFormat::Html)
+            } else if head
+                .get(1)
+                .is_some_and(|b| b.is_ascii_alphabetic() || *b == b'!')
+            {
+                Some(DocumentFormat::Xml)
+            } else {
+                None
+            }
+        }
+        _ => None,
     }
 }
 
+/// Detect every plausible format of a byte slice, with the primary format first.
+///
+/// Some files are valid as more than one format (polyglots), for example a PDF with a ZIP
+/// archive appended to it. [`detect_format_from_bytes`] only reports the first match, this
+/// function also looks for the signatures of other formats further into the buffer so callers
+/// can choose how to process the file.
+pub fn detect_all_formats(buffer: &[u8]) -> Vec<DocumentFormat> {
+    let mut formats = vec![detect_format_from_bytes(buffer)];
+    let mut add = |format: DocumentFormat| {
+        if format != DocumentFormat::Unknown && !formats.contains(&format) {
+            formats.push(format);
+        }
+    };
+
+    // PDF readers accept the header anywhere in the first 1024 bytes
+    if find_subslice(&buffer[..buffer.len().min(1024)], b"%PDF-").is_some() {
+        add(DocumentFormat::Pdf);
+    }
+
+    // A ZIP archive can start anywhere, and is located by its end of central directory record
+    if let Some(offset) = find_subslice(buffer, b"PK\x03\x04") {
+        add(detect_office_format(&buffer[offset..]));
+    } else if find_subslice(buffer, b"PK\x05\x06").is_some() {
+        add(DocumentFormat::Docx);
+    }
+
+    formats
+}
+
+/// Returns the offset of the first occurrence of `needle` in `haystack`
+fn find_subslice(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack
+        .windows(needle.len())
+        .position(|window| window == needle)
+}
+
 /// Detect specific Office format from ZIP content
 fn detect_office_format(buffer: &[u8]) -> DocumentFormat {
     // For now, we'll need to examine the ZIP content to determine the exact format
     // This is a simplified version - a full implementation would parse the ZIP directory
     
     // Look for Office-specific patterns in the first few KB
-    if buffer.len() > 100 {
-        let content = String::from_utf8_lossy(&buffer[0..100.min(buffer.len())]);
+    if let Some(head) = buffer.get(..100) {
+        let content = String::from_utf8_lossy(head);
+        // OpenDocument files start with an uncompressed `mimetype` member
+        let odf = content.split_once("mimetypeapplication/vnd.oasis.opendocument.");
+        if let Some((_, subtype)) = odf {
+            if subtype.starts_with("text") {
+                return DocumentFormat::Odt;
+            } else if subtype.starts_with("spreadsheet") {
+                return DocumentFormat::Ods;
+            } else if subtype.starts_with("presentation") {
+                return DocumentFormat::Odp;
+            }
+        }
         if content.contains("word/") {
             return DocumentFormat::Docx;
         } else if content.contains("xl/") {
@@ -94,45 +642,312 @@ fn detect_office_format(buffer: &[u8]) -> DocumentFormat {
     DocumentFormat::Docx
 }
 
+/// Signature of OLE compound files, the container of legacy Office documents and Outlook messages
+const OLE_SIGNATURE: &[u8] = b"\xD0\xCF\x11\xE0\xA1\xB1\x1A\xE1";
+
+/// Detect the format of an OLE compound file from the streams in its root storage. The buffer
+/// must hold the directory sectors, usually that means the whole file
+fn detect_ole_format(buffer: &[u8]) -> DocumentFormat {
+    match ole_stream_names(&mut std::io::Cursor::new(buffer)) {
+        Ok(names) => ole_format(&names),
+        Err(_) => DocumentFormat::Unknown,
+    }
+}
+
+/// The format given by the streams of an OLE compound file
+fn ole_format(names: &[String]) -> DocumentFormat {
+    let has = |name: &str| names.iter().any(|n| n == name);
+    if has("WordDocument") {
+        DocumentFormat::Doc
+    } else if has("Workbook") || has("Book") {
+        DocumentFormat::Xls
+    } else if has("PowerPoint Document") {
+        DocumentFormat::Ppt
+    } else if names.iter().any(|n| n.starts_with("__substg1.0_")) {
+        // Outlook messages store their MAPI properties in __substg1.0_ streams
+        DocumentFormat::Msg
+    } else {
+        DocumentFormat::Unknown
+    }
+}
+
+/// Reads the names of the directory entries of an OLE compound file, following the chain of
+/// directory sectors through the FAT. The chain ends early at FAT sectors listed in DIFAT
+/// sectors beyond the header, i.e. in files larger than about 7 MB
+fn ole_stream_names<R: std::io::Read + std::io::Seek>(
+    reader: &mut R,
+) -> std::io::Result<Vec<String>> {
+    use std::io::{Error, ErrorKind, SeekFrom};
+
+    let u32_at = |bytes: &[u8], offset: usize| {
+        u32::from_le_bytes([
+            bytes[offset],
+            bytes[offset + 1],
+            bytes[offset + 2],
+            bytes[offset + 3],
+        ])
+    };
+    let mut header = [0u8; 512];
+    reader.seek(SeekFrom::Start(0))?;
+    reader.read_exact(&mut header)?;
+    if !header.starts_with(OLE_SIGNATURE) {
+        return Err(Error::new(
+            ErrorKind::InvalidData,
+            "Not an OLE compound file",
+        ));
+    }
+    let sector_size = match u16::from_le_bytes([header[0x1E], header[0x1F]]) {
+        9 => 512,
+        12 => 4096,
+        _ => {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                "Invalid OLE sector size",
+            ))
+        }
+    };
+    // The first 109 FAT sectors are listed in the header
+    let fat_sectors: Vec<u32> = (0..109).map(|i| u32_at(&header, 0x4C + i * 4)).collect();
+    let sector_offset = |sector: u32| (sector as u64 + 1) * sector_size as u64;
+
+    let mut names = Vec::new();
+    let mut sector = u32_at(&header, 0x30);
+    let mut buffer = vec![0u8; sector_size];
+    // Bounded against cycles in corrupt files
+    for _ in 0..4096 {
+        // Special values such as ENDOFCHAIN are at the top of the range
+        if sector >= 0xFFFF_FFFA {
+            break;
+        }
+        reader.seek(SeekFrom::Start(sector_offset(sector)))?;
+        reader.read_exact(&mut buffer)?;
+        for entry in buffer.chunks_exact(128) {
+            // Name length in bytes including the terminating null, 0 for unused entries
+            let name_len = u16::from_le_bytes([entry[64], entry[65]]) as usize;
+            if entry[66] == 0 || !(2..=64).contains(&name_len) {
+                continue;
+            }
+            let units: Vec<u16> = entry[..name_len - 2]
+                .chunks_exact(2)
+                .map(|unit| u16::from_le_bytes([unit[0], unit[1]]))
+                .collect();
+            names.push(String::from_utf16_lossy(&units));
+        }
+
+        let entries_per_sector = sector_size as u32 / 4;
+        let Some(&fat_sector) = fat_sectors.get((sector / entries_per_sector) as usize) else {
+            break;
+        };
+        let entry_offset = sector_offset(fat_sector) + (sector % entries_per_sector) as u64 * 4;
+        let mut next = [0u8; 4];
+        reader.seek(SeekFrom::Start(entry_offset))?;
+        reader.read_exact(&mut next)?;
+        sector = u32::from_le_bytes(next);
+    }
+    Ok(names)
+}
+
+/// Header fields an RFC 822 message commonly starts with
+const EMAIL_HEADERS: &[&str] = &[
+    "received",
+    "return-path",
+    "delivered-to",
+    "from",
+    "to",
+    "cc",
+    "date",
+    "subject",
+    "message-id",
+    "mime-version",
+];
+
+/// Whether the text starts with an email header block: a common header field on the first line,
+/// followed by `Name: value` or folded continuation lines up to the first empty line
+fn looks_like_email(text: &str) -> bool {
+    let header_name = |line: &str| {
+        line.split_once(':')
+            .map(|(name, _)| name.to_lowercase())
+            .filter(|name| !name.is_empty() && !name.contains(char::is_whitespace))
+    };
+    let mut lines = text.lines();
+    let first_is_common = lines
+        .next()
+        .and_then(header_name)
+        .is_some_and(|name| EMAIL_HEADERS.contains(&name.as_str()));
+    // The last line of the buffer may be cut off
+    let mut rest: Vec<&str> = lines.take_while(|line| !line.is_empty()).collect();
+    if !text.ends_with('\n') {
+        rest.pop();
+    }
+    first_is_common
+        && rest
+            .iter()
+            .all(|line| line.starts_with([' ', '\t']) || header_name(line).is_some())
+}
+
 /// Detect text-based formats
 fn detect_text_format(buffer: &[u8]) -> DocumentFormat {
     // Check if it's valid UTF-8 text
     if let Ok(text) = std::str::from_utf8(buffer) {
-        // Simple CSV detection
+        // Email headers, checked first because address lists contain commas
+        if looks_like_email(text) {
+            return DocumentFormat::Eml;
+        }
+
+        // Tab or pipe delimited rows, or commas on the first line of several
+        if detect_csv_delimiter(buffer).is_some() {
+            return DocumentFormat::Csv;
+        }
         if text.contains(',') && text.lines().count() > 1 {
             let first_line = text.lines().next().unwrap_or("");
             let comma_count = first_line.matches(',').count();
-            if comma_count > 0 && comma_count < 20 { // Reasonable CSV column count
+            if comma_count > 0 && comma_count < 20 {
+                // Reasonable CSV column count
                 return DocumentFormat::Csv;
             }
         }
-        
+
         // Check for HTML patterns
         if text.to_lowercase().contains("<html") || text.to_lowercase().contains("<!doctype") {
             return DocumentFormat::Html;
         }
-        
+
         // Check for XML patterns
         if text.trim_start().starts_with("<?xml") || text.trim_start().starts_with('<') {
             return DocumentFormat::Xml;
         }
-        
+
         // Check for JSON patterns
         let trimmed = text.trim_start();
         if trimmed.starts_with('{') || trimmed.starts_with('[') {
             return DocumentFormat::Json;
         }
-        
+
         DocumentFormat::Text
     } else {
         DocumentFormat::Unknown
     }
 }
 
+/// Delimiters recognised by `detect_csv_delimiter`, ties go to the earlier one
+const CSV_DELIMITERS: [u8; 3] = [b',', b'\t', b'|'];
+
+/// Lines of the start of a file that `detect_csv_delimiter` compares
+const CSV_SAMPLE_LINES: usize = 20;
+
+/// The delimiter of a comma, tab or pipe delimited text file, if the text looks like one: the
+/// delimiter occurs as often, outside of quotes, on each of the first non-empty lines, of which
+/// there must be at least two. Lines starting with a tab or a pipe are taken for indentation or
+/// a Markdown table. The CSV parser splits the rows at this delimiter.
+pub fn detect_csv_delimiter(buffer: &[u8]) -> Option<u8> {
+    let text = String::from_utf8_lossy(buffer);
+    let mut lines: Vec<&str> = text
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .take(CSV_SAMPLE_LINES + 1)
+        .collect();
+    // The last line may be cut off at the end of the buffer
+    if lines.len() > CSV_SAMPLE_LINES || (lines.len() > 2 && !text.ends_with('\n')) {
+        lines.pop();
+    }
+    if lines.len() < 2 {
+        return None;
+    }
+
+    let mut best = None;
+    let mut best_count = 0;
+    for delimiter in CSV_DELIMITERS {
+        if delimiter != b',' && lines.iter().any(|line| line.as_bytes()[0] == delimiter) {
+            continue;
+        }
+        let count = count_outside_quotes(lines[0], delimiter);
+        let consistent = lines[1..]
+            .iter()
+            .all(|line| count_outside_quotes(line, delimiter) == count);
+        if consistent && count > best_count {
+            best = Some(delimiter);
+            best_count = count;
+        }
+    }
+    best
+}
+
+/// How often `delimiter` occurs in a line, not counting the quoted parts
+fn count_outside_quotes(line: &str, delimiter: u8) -> usize {
+    let mut in_quotes = false;
+    let mut count = 0;
+    for &byte in line.as_bytes() {
+        if byte == b'"' {
+            in_quotes = !in_quotes;
+        } else if byte == delimiter && !in_quotes {
+            count += 1;
+        }
+    }
+    count
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     
+    #[test]
+    fn test_all_formats_have_mime_types() {
+        assert!(DocumentFormat::all().contains(&DocumentFormat::Pdf));
+        assert!(!DocumentFormat::all().contains(&DocumentFormat::Unknown));
+        for format in DocumentFormat::all() {
+            assert!(
+                format.mime_type().is_some(),
+                "{:?} has no mime type",
+                format
+            );
+        }
+    }
+
+    #[test]
+    fn test_from_mime_type() {
+        for (mime_type, format) in [
+            ("application/pdf", DocumentFormat::Pdf),
+            ("text/html; charset=utf-8", DocumentFormat::Html),
+            ("Text/CSV;header=present", DocumentFormat::Csv),
+            ("application/json", DocumentFormat::Json),
+            (
+                "text/x-web-markdown; charset=UTF-8",
+                DocumentFormat::Markdown,
+            ),
+            ("application/xml", DocumentFormat::Xml),
+            (
+                "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
+                DocumentFormat::Xlsx,
+            ),
+            (
+                "application/vnd.oasis.opendocument.text",
+                DocumentFormat::Odt,
+            ),
+            (
+                " application/vnd.oasis.opendocument.presentation ",
+                DocumentFormat::Odp,
+            ),
+        ] {
+            assert_eq!(
+                DocumentFormat::from_mime_type(mime_type),
+                Some(format.clone())
+            );
+            // The mime type of a format maps back to it
+            assert_eq!(
+                DocumentFormat::from_mime_type(format.mime_type().unwrap()),
+                Some(format)
+            );
+        }
+        assert_eq!(
+            DocumentFormat::from_mime_type("application/octet-stream"),
+            None
+        );
+        assert_eq!(DocumentFormat::from_mime_type(""), None);
+
+        let bytes = std::fs::read("../test_files/documents/simple.odt").unwrap();
+        assert_eq!(detect_format_from_bytes(&bytes), DocumentFormat::Odt);
+    }
+
     #[test]
     fn test_pdf_detection() {
         let pdf_header = b"%PDF-1.4\n";
@@ -157,9 +972,348 @@ mod tests {
         assert_eq!(detect_format_from_bytes(csv_content), DocumentFormat::Csv);
     }
     
+    #[test]
+    fn test_tsv_and_pipe_detection() {
+        let tsv = b"station\tcity\televation\nAlpha\tBern, CH\t540\nBravo\tLyon\t173\n";
+        assert_eq!(detect_format_from_bytes(tsv), DocumentFormat::Csv);
+        // The commas inside the cells do not make it comma separated
+        assert_eq!(detect_csv_delimiter(tsv), Some(b'\t'));
+
+        let pipes = b"id|name|notes\n1|Alpha|\"a|b\"\n2|Bravo|none\n3|Charlie|";
+        assert_eq!(detect_format_from_bytes(pipes), DocumentFormat::Csv);
+        assert_eq!(detect_csv_delimiter(pipes), Some(b'|'));
+        assert_eq!(detect_format("exports/stations.TSV"), DocumentFormat::Csv);
+
+        // Indented text and Markdown tables are not delimited files
+        let indented = b"Steps:\n\tmix the flour\n\tadd the water\n";
+        assert_eq!(detect_csv_delimiter(indented), None);
+        assert_eq!(detect_format_from_bytes(indented), DocumentFormat::Text);
+        let markdown = b"| id | name |\n|----|------|\n| 1  | Alpha |\n";
+        assert_eq!(detect_csv_delimiter(markdown), None);
+        assert_eq!(detect_csv_delimiter(b"one\tline only\n"), None);
+    }
+
     #[test]
     fn test_json_detection() {
         let json_content = b"{\n  \"name\": \"test\"\n}";
         assert_eq!(detect_format_from_bytes(json_content), DocumentFormat::Json);
     }
+
+    #[test]
+    fn test_image_detection() {
+        let cases: [(&[u8], ImageKind); 4] = [
+            (b"\x89PNG\r\n\x1a\n\x00\x00\x00\rIHDR", ImageKind::Png),
+            (b"\xFF\xD8\xFF\xE0\x00\x10JFIF\x00", ImageKind::Jpeg),
+            (b"II*\x00\x08\x00\x00\x00", ImageKind::Tiff),
+            (b"MM\x00*\x00\x00\x00\x08", ImageKind::Tiff),
+        ];
+        for (header, kind) in cases {
+            assert_eq!(
+                detect_format_from_bytes(header),
+                DocumentFormat::Image(kind)
+            );
+        }
+        assert_eq!(
+            detect_format_from_bytes(b"\xFF\xD8\xFF"),
+            DocumentFormat::Image(ImageKind::Jpeg)
+        );
+        assert_eq!(
+            detect_format("scans/page-1.TIF"),
+            DocumentFormat::Image(ImageKind::Tiff)
+        );
+        assert_eq!(
+            DocumentFormat::from_mime_type("image/jpeg"),
+            Some(DocumentFormat::Image(ImageKind::Jpeg))
+        );
+        assert!(detect_format("../test_files/documents/ara-ocr.png").is_image());
+        assert!(!DocumentFormat::Pdf.is_image());
+    }
+
+    #[test]
+    fn test_compression_detection() {
+        let cases: [(&[u8], Option<Compression>); 7] = [
+            (b"\x1F\x8B\x08\x00\x00\x00\x00\x00", Some(Compression::Gzip)),
+            (b"BZh91AY&SY", Some(Compression::Bzip2)),
+            (b"\xFD7zXZ\x00\x00\x04", Some(Compression::Xz)),
+            (b"BZ is not bzip2", None),
+            (b"BZh0", None),
+            (b"BZhello, world", None),
+            (b"BZh", None),
+        ];
+        for (header, expected) in cases {
+            assert_eq!(detect_compression(header), expected);
+        }
+        assert_eq!(Compression::Gzip.mime_type(), "application/gzip");
+    }
+
+    #[test]
+    fn test_detect_format_with_confidence() {
+        let (format, pdf_score) =
+            detect_format_with_confidence("../test_files/documents/2022_Q3_AAPL.pdf");
+        assert_eq!(format, DocumentFormat::Pdf);
+        assert_eq!(pdf_score, 1.0);
+
+        let dir = tempfile::tempdir().unwrap();
+        let dir = dir.path();
+        let csv = dir.join("prices");
+        std::fs::write(&csv, "item,price\nwax,12\nskis,450\n").unwrap();
+        let pdf = dir.join("report");
+        std::fs::copy("../test_files/documents/2022_Q3_AAPL.pdf", &pdf).unwrap();
+        let misnamed = dir.join("report.docx");
+        std::fs::copy("../test_files/documents/2022_Q3_AAPL.pdf", &misnamed).unwrap();
+
+        // Counting commas is a guess, a signature is not
+        let (format, csv_score) = detect_format_with_confidence(&csv);
+        assert_eq!(format, DocumentFormat::Csv);
+        assert!(pdf_score > csv_score);
+        let (format, unnamed_pdf_score) = detect_format_with_confidence(&pdf);
+        assert_eq!(format, DocumentFormat::Pdf);
+        assert!(unnamed_pdf_score > csv_score && unnamed_pdf_score < pdf_score);
+        let (format, misnamed_score) = detect_format_with_confidence(&misnamed);
+        assert_eq!(format, DocumentFormat::Pdf);
+        assert!(misnamed_score < unnamed_pdf_score);
+
+        assert_eq!(
+            detect_format_with_confidence("missing/notes.csv"),
+            (DocumentFormat::Csv, 0.6)
+        );
+        assert_eq!(
+            detect_format_with_confidence("missing/notes"),
+            (DocumentFormat::Unknown, 0.0)
+        );
+    }
+
+    #[test]
+    fn test_leading_whitespace_detection() {
+        let cases: [(&[u8], DocumentFormat); 8] = [
+            (
+                b"  {\"name\": \"test\", \"tags\": [1, 2]}",
+                DocumentFormat::Json,
+            ),
+            (b"\r\n\t[{\"id\": 1},\n {\"id\": 2}]", DocumentFormat::Json),
+            (b"{}", DocumentFormat::Json),
+            (
+                b"\xEF\xBB\xBF\n  <?xml version=\"1.0\"?><note/>",
+                DocumentFormat::Xml,
+            ),
+            (
+                b"\r\n<catalog>\n  <book id=\"1\">a, b</book>\n</catalog>",
+                DocumentFormat::Xml,
+            ),
+            (
+                b"\r\n<!doctype html>\n<p>one, two</p>\nthree",
+                DocumentFormat::Html,
+            ),
+            (
+                b"\n\n   <HTML><body>Hello</body></HTML>",
+                DocumentFormat::Html,
+            ),
+            (
+                b"   <!-- generated -->\n<html lang=\"en\">",
+                DocumentFormat::Html,
+            ),
+        ];
+        for (content, expected) in cases {
+            assert_eq!(
+                detect_format_from_bytes(content),
+                expected,
+                "{:?}",
+                String::from_utf8_lossy(content)
+            );
+        }
+        assert_eq!(detect_format_from_bytes(b" <"), DocumentFormat::Unknown);
+        assert_eq!(detect_format_from_bytes(b"{a"), DocumentFormat::Unknown);
+    }
+
+    #[test]
+    fn test_bom_detection() {
+        let utf16 = |text: &str, little_endian: bool| -> Vec<u8> {
+            let mut bytes = if little_endian {
+                vec![0xFF, 0xFE]
+            } else {
+                vec![0xFE, 0xFF]
+            };
+            for unit in text.encode_utf16() {
+                let pair = if little_endian {
+                    unit.to_le_bytes()
+                } else {
+                    unit.to_be_bytes()
+                };
+                bytes.extend(pair);
+            }
+            bytes
+        };
+        let csv = "name,city\nZoë,Zürich\nAnn,Oslo\n";
+        let xml = "<?xml version=\"1.0\"?><note>Grüße</note>";
+
+        for (buffer, bom, expected) in [
+            (
+                [b"\xEF\xBB\xBF", csv.as_bytes()].concat(),
+                ByteOrderMark::Utf8,
+                DocumentFormat::Csv,
+            ),
+            (
+                utf16(csv, true),
+                ByteOrderMark::Utf16Le,
+                DocumentFormat::Csv,
+            ),
+            (
+                utf16(xml, false),
+                ByteOrderMark::Utf16Be,
+                DocumentFormat::Xml,
+            ),
+        ] {
+            assert_eq!(detect_bom(&buffer), Some(bom));
+            assert_eq!(detect_format_from_bytes(&buffer), expected, "{:?}", bom);
+            let (text, stripped) = strip_bom(&buffer);
+            assert_eq!(stripped, Some(bom));
+            assert!(!text.starts_with("\u{feff}".as_bytes()));
+            assert!(
+                std::str::from_utf8(&text).unwrap().contains('ü'),
+                "{:?}",
+                bom
+            );
+        }
+
+        // An odd last byte of a cut off UTF-16 buffer is dropped
+        let mut cut = utf16("Grüße", true);
+        cut.push(b'x');
+        assert_eq!(strip_bom(&cut).0.as_ref(), "Grüße".as_bytes());
+        assert_eq!(detect_bom(b"name,city"), None);
+        assert_eq!(strip_bom(b"name,city").0.as_ref(), b"name,city");
+    }
+
+    #[test]
+    fn test_detect_all_formats_polyglot() {
+        let mut polyglot = b"%PDF-1.4\n1 0 obj\n<< /Type /Catalog >>\nendobj\n".to_vec();
+        polyglot.extend_from_slice(b"PK\x03\x04\x14\x00\x00\x00word/document.xml");
+        polyglot.extend_from_slice(&[0u8; 100]);
+        polyglot.extend_from_slice(b"PK\x05\x06");
+
+        let formats = detect_all_formats(&polyglot);
+        assert_eq!(formats[0], DocumentFormat::Pdf);
+        assert!(formats.contains(&DocumentFormat::Docx));
+        assert_eq!(formats.len(), 2);
+    }
+
+    #[test]
+    fn test_detect_all_formats_single() {
+        let pdf = b"%PDF-1.4\n%%EOF";
+        assert_eq!(detect_all_formats(pdf), vec![DocumentFormat::Pdf]);
+    }
+
+    #[test]
+    fn test_email_detection() {
+        let eml = b"Received: from mail.example.com\r\n\tby mx.example.org\r\n\
+            From: Alice <alice@example.com>\r\nTo: bob@example.org, carol@example.org\r\n\
+            Subject: Quarterly numbers\r\n\r\nHi Bob, Carol";
+        assert_eq!(detect_format_from_bytes(eml), DocumentFormat::Eml);
+        assert_eq!(
+            detect_format_from_bytes(b"From: alice@exam"),
+            DocumentFormat::Eml
+        );
+        let csv = b"name,note\nSubject: maths,ok\n";
+        assert_eq!(detect_format_from_bytes(csv), DocumentFormat::Csv);
+        assert_eq!(
+            detect_format_from_bytes(b"Date: today\nnot a header\n"),
+            DocumentFormat::Text
+        );
+        assert_eq!(detect_format("mail/invoice.MSG"), DocumentFormat::Msg);
+    }
+
+    #[test]
+    fn test_ole_detection() {
+        for (file, format) in [
+            ("simple.doc", DocumentFormat::Doc),
+            ("simple.xls", DocumentFormat::Xls),
+            ("simple.ppt", DocumentFormat::Ppt),
+            ("simple.msg", DocumentFormat::Msg),
+        ] {
+            let path = format!("../test_files/documents/{}", file);
+            let bytes = std::fs::read(&path).unwrap();
+            assert_eq!(detect_format_from_bytes(&bytes), format, "{}", file);
+
+            let mut file = std::fs::File::open(&path).unwrap();
+            assert_eq!(detect_format_from_file(&mut file).unwrap(), format);
+        }
+
+        // The header alone does not tell the kind of document
+        let doc = std::fs::read("../test_files/documents/simple.doc").unwrap();
+        assert_eq!(
+            detect_format_from_bytes(&doc[..512]),
+            DocumentFormat::Unknown
+        );
+    }
+
+    #[test]
+    fn test_code_detection() {
+        assert!(is_code_file("src/main.PY"));
+        assert!(is_code_file("project/Makefile"));
+        assert!(!is_code_file("notes.txt"));
+        assert!(is_code_mime_type("text/x-python; charset=UTF-8"));
+        assert!(!is_code_mime_type("text/plain"));
+    }
+
+    /// Starts of buffers that send the detection down a particular path
+    const FUZZ_PREFIXES: &[&[u8]] = &[
+        b"",
+        b"%PDF-1.7\n",
+        b"PK\x03\x04",
+        OLE_SIGNATURE,
+        b"\x89PNG\r\n\x1a\n",
+        b"II*\x00",
+        b"MM\x00*",
+        b"\xFF\xD8\xFF\xE0",
+        b"\xEF\xBB\xBF",
+        b"\xFF\xFE",
+        b"\xFE\xFF",
+        b"\x1F\x8B\x08",
+        b"  <!DOCTYPE html>",
+        b"<?xml ",
+        b"{\"",
+        b"[[",
+        b"From: a@example.com\nTo: b",
+        b"a,b\nc,d\n",
+    ];
+
+    #[test]
+    fn test_detect_format_from_bytes_cut_off_signatures() {
+        // Every prefix of every signature, alone and followed by bytes that match nothing
+        for prefix in FUZZ_PREFIXES {
+            for len in 0..=prefix.len() {
+                for tail in [&b""[..], b"\x00", b"\xFF\xFF\xFF\xFF", b"\xE2\x82"] {
+                    let buffer = [&prefix[..len], tail].concat();
+                    let format = detect_format_from_bytes(&buffer);
+                    assert_eq!(detect_all_formats(&buffer)[0], format);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_detect_format_from_bytes_repeated_byte_order_marks() {
+        // Stripped once, the other marks are text. Stripping them one by one recursed once per
+        // mark, which overflowed the stack for large buffers
+        let buffer = b"\xEF\xBB\xBF".repeat(1 << 20);
+        assert_eq!(detect_format_from_bytes(&buffer), DocumentFormat::Text);
+        // UTF-16 decodes to UTF-8 marks
+        let buffer = b"\xFE\xFF".repeat(1 << 20);
+        assert_eq!(detect_format_from_bytes(&buffer), DocumentFormat::Text);
+    }
+
+    proptest::proptest! {
+        #![proptest_config(proptest::prelude::ProptestConfig::with_cases(2000))]
+
+        #[test]
+        fn test_detect_format_from_bytes_never_panics(
+            prefix in proptest::sample::select(FUZZ_PREFIXES),
+            bytes in proptest::collection::vec(proptest::num::u8::ANY, 0..1200),
+        ) {
+            let buffer = [prefix, &bytes[..]].concat();
+            let format = detect_format_from_bytes(&buffer);
+            proptest::prop_assert_eq!(&detect_all_formats(&buffer)[0], &format);
+            let format = detect_format_from_bytes(&bytes);
+            proptest::prop_assert_eq!(&detect_all_formats(&bytes)[0], &format);
+        }
+    }
 }
@@ -0,0 +1,97 @@
+//! Hashes of the raw input for `Extractor::set_compute_content_hash`, so pipelines can
+//! deduplicate documents without keeping their bytes
+
+use sha2::Digest;
+use std::io::{self, Read};
+use std::path::Path;
+
+/// The metadata key of the content hash
+pub const CONTENT_HASH_KEY: &str = "Content-Hash";
+
+/// The hash function of `Extractor::set_compute_content_hash`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HashAlgo {
+    Sha256,
+    Blake3,
+}
+
+impl HashAlgo {
+    /// The name the hash is prefixed with in the metadata, e.g. `sha256:9f86...`
+    pub fn name(&self) -> &'static str {
+        match self {
+            HashAlgo::Sha256 => "sha256",
+            HashAlgo::Blake3 => "blake3",
+        }
+    }
+}
+
+/// A running hash that the input is fed into as it is read
+pub(crate) enum ContentHasher {
+    Sha256(sha2::Sha256),
+    Blake3(Box<blake3::Hasher>),
+}
+
+impl ContentHasher {
+    pub(crate) fn new(algo: HashAlgo) -> Self {
+        match algo {
+            HashAlgo::Sha256 => ContentHasher::Sha256(sha2::Sha256::new()),
+            HashAlgo::Blake3 => ContentHasher::Blake3(Box::default()),
+        }
+    }
+
+    pub(crate) fn update(&mut self, data: &[u8]) {
+        match self {
+            ContentHasher::Sha256(hasher) => hasher.update(data),
+            ContentHasher::Blake3(hasher) => {
+                hasher.update(data);
+            }
+        }
+    }
+
+    /// The hash as lowercase hex, prefixed with the name of the algorithm and a colon
+    pub(crate) fn finish(self) -> String {
+        match self {
+            ContentHasher::Sha256(hasher) => format!("sha256:{:x}", hasher.finalize()),
+            ContentHasher::Blake3(hasher) => format!("blake3:{}", hasher.finalize().to_hex()),
+        }
+    }
+}
+
+/// The content hash of a buffer
+pub(crate) fn hash_bytes(algo: HashAlgo, data: &[u8]) -> String {
+    let mut hasher = ContentHasher::new(algo);
+    hasher.update(data);
+    hasher.finish()
+}
+
+/// The content hash of a file, read in chunks so large files are not held in memory
+pub(crate) fn hash_file<P: AsRef<Path>>(algo: HashAlgo, path: P) -> io::Result<String> {
+    let mut file = std::fs::File::open(path)?;
+    let mut hasher = ContentHasher::new(algo);
+    let mut buffer = vec![0; 64 * 1024];
+    loop {
+        match file.read(&mut buffer) {
+            Ok(0) => return Ok(hasher.finish()),
+            Ok(read) => hasher.update(&buffer[..read]),
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hash_bytes() {
+        assert_eq!(
+            hash_bytes(HashAlgo::Sha256, b"abc"),
+            "sha256:ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        assert_eq!(
+            hash_bytes(HashAlgo::Blake3, b"abc"),
+            "blake3:6437b3ac38465133ffb63b75273a8db548c558465d79db03fd359c6cd5bd9d85"
+        );
+    }
+}
@@ -76,6 +76,12 @@ pub const LARGE_BUF_SIZE: usize = 1048576; // 1MB
 /// Threshold for using memory-mapped I/O - lowered based on benchmarks
 pub const MMAP_THRESHOLD: usize = 512 * 1024; // 512KB
 
+/// Size below which text formats are parsed in Rust with `Extractor::set_small_file_fast_path`
+pub const SMALL_FILE_THRESHOLD: usize = 64 * 1024; // 64KB
+
+/// Page marker of `Extractor::set_annotate_page_boundaries`, `{page}` is the page number
+pub const DEFAULT_PAGE_MARKER: &str = "[page {page}]";
+
 // errors module
 mod errors;
 pub use errors::*;
@@ -88,14 +94,74 @@ pub use config::*;
 mod extractor;
 pub use extractor::*;
 
+// pool of worker threads for concurrent extraction
+mod pool;
+pub use pool::*;
+
+// chunks of a document along its pages or sheets
+mod chunk;
+pub use chunk::*;
+
+// structural events of Tika's xhtml output
+mod events;
+pub use events::*;
+
+// conversion of xhtml to the markdown output
+mod markdown_output;
+
+// visual reordering of right-to-left text
+mod bidi;
+
+// link targets in the extracted text, see Extractor::set_include_link_urls
+mod links;
+
+// splitting of multipart MIME bodies
+mod multipart;
+pub use multipart::MultipartPart;
+
 // format detection module
 mod format_detection;
 pub use format_detection::*;
 
+// heuristic document classification
+#[cfg(feature = "classify")]
+mod classify;
+#[cfg(feature = "classify")]
+pub use classify::*;
+
+// locale dependent formatting of spreadsheet numbers and dates
+#[cfg(feature = "pure-rust")]
+mod locale;
+
+// downscaling of large images before OCR, see TesseractOcrConfig::set_max_ocr_image_pixels
+#[cfg(feature = "ocr-downscale")]
+mod ocr_image;
+
+// content hashes of the input for deduplication
+#[cfg(feature = "content-hash")]
+mod hashing;
+#[cfg(feature = "content-hash")]
+pub use hashing::{HashAlgo, CONTENT_HASH_KEY};
+
+// detection of citations and cross-references in extracted text
+mod entities;
+pub use entities::*;
+
 // pure rust parsers for performance optimization
 mod pure_rust_parsers;
 pub use pure_rust_parsers::*;
 
+// decryption of password protected OOXML documents for the pure rust parsers
+#[cfg(feature = "office-encryption")]
+mod ooxml_crypto;
+
+// decompression of gzip, bzip2 and xz files for extract_file, not exposed outside this crate
+#[cfg(feature = "compression")]
+mod compression;
+
+// cloud object store downloads for extract_uri, not exposed outside this crate
+mod object_storage;
+
 // SIMD-optimized text processing
 mod simd_text;
 pub use simd_text::*;
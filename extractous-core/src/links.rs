@@ -0,0 +1,129 @@
+//! The targets of hyperlinks in the extracted text, see `Extractor::set_include_link_urls`
+
+use crate::{LinkMode, StreamReader, XhtmlEvent, XhtmlEvents};
+
+/// Collects the targets of the links of a document as its text is written
+pub(crate) struct LinkUrls {
+    mode: LinkMode,
+    /// The targets of `LinkMode::Footnotes`, numbered from 1 in order of their first link
+    footnotes: Vec<String>,
+}
+
+impl LinkUrls {
+    pub(crate) fn new(mode: LinkMode) -> Self {
+        Self {
+            mode,
+            footnotes: Vec::new(),
+        }
+    }
+
+    /// The text that follows the text of a link to `href`, if any. Nothing follows links within
+    /// the document, or links whose text already is their target
+    pub(crate) fn marker(&mut self, href: &str, link_text: &str) -> Option<String> {
+        let href = href.trim();
+        if href.is_empty() || href.starts_with('#') || link_text.trim() == href {
+            return None;
+        }
+        match self.mode {
+            LinkMode::Drop => None,
+            LinkMode::Inline => Some(format!("({})", href)),
+            LinkMode::Footnotes => {
+                let number = match self.footnotes.iter().position(|url| url == href) {
+                    Some(index) => index + 1,
+                    None => {
+                        self.footnotes.push(href.to_string());
+                        self.footnotes.len()
+                    }
+                };
+                Some(format!("[{}]", number))
+            }
+        }
+    }
+
+    /// Appends the numbered targets of `LinkMode::Footnotes` to the end of `text`
+    pub(crate) fn append_footnotes(self, text: &mut String) {
+        let footnotes: Vec<String> = self
+            .footnotes
+            .iter()
+            .enumerate()
+            .map(|(index, url)| format!("[{}] {}", index + 1, url))
+            .collect();
+        crate::append_endnotes(text, &footnotes);
+    }
+}
+
+/// The text of Tika's XHTML output with the link targets of `mode`. This is the character data
+/// of the XHTML, including the newlines Tika writes after block elements, so apart from the
+/// links it is the text of the `TikaHandler::Body` handler, or of `TikaHandler::Text` with
+/// `include_head`
+pub(crate) fn xhtml_to_text(xhtml: &str, include_head: bool, mode: LinkMode) -> String {
+    let mut text = String::new();
+    let mut links = LinkUrls::new(mode);
+    // The target of every open link and where its text starts
+    let mut open_links: Vec<(Option<String>, usize)> = Vec::new();
+    let mut in_head = false;
+
+    // The XHTML is in memory, only an XHTML that ends inside a tag ends the events early
+    let events = XhtmlEvents::new(StreamReader::from_text(xhtml.to_string()));
+    for event in events.map_while(Result::ok) {
+        match event {
+            XhtmlEvent::StartElement { name, .. } if name == "head" => in_head = true,
+            XhtmlEvent::EndElement { name } if name == "head" => in_head = false,
+            XhtmlEvent::StartElement { name, attributes } if name == "a" => {
+                let href = attributes
+                    .into_iter()
+                    .find(|(name, _)| name == "href")
+                    .map(|(_, href)| href);
+                open_links.push((href, text.len()));
+            }
+            XhtmlEvent::EndElement { name } if name == "a" => {
+                let Some((Some(href), start)) = open_links.pop() else {
+                    continue;
+                };
+                if let Some(marker) = links.marker(&href, &text[start..]) {
+                    text.push(' ');
+                    text.push_str(&marker);
+                }
+            }
+            XhtmlEvent::Characters(characters) if include_head || !in_head => {
+                text.push_str(&characters);
+            }
+            _ => {}
+        }
+    }
+
+    links.append_footnotes(&mut text);
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_xhtml_to_text() {
+        let xhtml = "<html><head><title>Links</title></head><body>\n\
+            <p>Read the <a shape=\"rect\" href=\"https://example.com/guide\">guide</a> and the \
+            <a href=\"https://example.com/faq\">FAQ</a>, or <a href=\"#top\">go up</a>.</p>\n\
+            <p>Again: <a href=\"https://example.com/guide\">the guide</a>, \
+            <a href=\"https://example.com\">https://example.com</a></p>\n</body></html>";
+
+        assert_eq!(
+            xhtml_to_text(xhtml, false, LinkMode::Drop),
+            "\nRead the guide and the FAQ, or go up.\n\
+            Again: the guide, https://example.com\n"
+        );
+        assert_eq!(
+            xhtml_to_text(xhtml, false, LinkMode::Inline),
+            "\nRead the guide (https://example.com/guide) and the FAQ \
+            (https://example.com/faq), or go up.\n\
+            Again: the guide (https://example.com/guide), https://example.com\n"
+        );
+        assert_eq!(
+            xhtml_to_text(xhtml, true, LinkMode::Footnotes),
+            "Links\nRead the guide [1] and the FAQ [2], or go up.\n\
+            Again: the guide [1], https://example.com\n\n\
+            ----------\n[1] https://example.com/guide\n[2] https://example.com/faq\n"
+        );
+    }
+}
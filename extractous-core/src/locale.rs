@@ -0,0 +1,158 @@
+//! Locale dependent formatting of the numbers and dates of spreadsheet cells, see
+//! `Extractor::set_locale`
+
+/// The order of the parts of a date
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum DateOrder {
+    DayMonthYear,
+    MonthDayYear,
+    YearMonthDay,
+}
+
+/// How a locale writes numbers and dates. Times keep the 24 hour `HH:MM:SS` of every locale
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Locale {
+    group_separator: &'static str,
+    decimal_separator: char,
+    date_order: DateOrder,
+    date_separator: char,
+}
+
+/// The locales by language and region, a language alone matches its first entry
+const LOCALES: &[(&str, &str, char, DateOrder, char)] = &[
+    ("en-us", ",", '.', DateOrder::MonthDayYear, '/'),
+    ("en-gb", ",", '.', DateOrder::DayMonthYear, '/'),
+    ("en-ie", ",", '.', DateOrder::DayMonthYear, '/'),
+    ("en-au", ",", '.', DateOrder::DayMonthYear, '/'),
+    ("en-ca", ",", '.', DateOrder::YearMonthDay, '-'),
+    ("de-de", ".", ',', DateOrder::DayMonthYear, '.'),
+    ("de-at", ".", ',', DateOrder::DayMonthYear, '.'),
+    ("de-ch", "'", '.', DateOrder::DayMonthYear, '.'),
+    ("fr-fr", "\u{202f}", ',', DateOrder::DayMonthYear, '/'),
+    ("fr-be", "\u{202f}", ',', DateOrder::DayMonthYear, '/'),
+    ("fr-ca", "\u{a0}", ',', DateOrder::YearMonthDay, '-'),
+    ("es-es", ".", ',', DateOrder::DayMonthYear, '/'),
+    ("it-it", ".", ',', DateOrder::DayMonthYear, '/'),
+    ("nl-nl", ".", ',', DateOrder::DayMonthYear, '-'),
+    ("pt-br", ".", ',', DateOrder::DayMonthYear, '/'),
+    ("pt-pt", "\u{a0}", ',', DateOrder::DayMonthYear, '/'),
+    ("pl-pl", "\u{a0}", ',', DateOrder::DayMonthYear, '.'),
+    ("ru-ru", "\u{a0}", ',', DateOrder::DayMonthYear, '.'),
+    ("sv-se", "\u{a0}", ',', DateOrder::YearMonthDay, '-'),
+    ("ja-jp", ",", '.', DateOrder::YearMonthDay, '/'),
+    ("zh-cn", ",", '.', DateOrder::YearMonthDay, '/'),
+];
+
+impl Locale {
+    /// The locale of a language tag such as `de-DE`, `de_DE.UTF-8` or `de`, ignoring case.
+    /// Returns `None` for locales that are not known
+    pub(crate) fn from_tag(tag: &str) -> Option<Locale> {
+        let tag = tag.split(['.', '@']).next().unwrap_or_default();
+        let tag = tag.replace('_', "-").to_lowercase();
+        let language = tag.split('-').next().unwrap_or_default();
+
+        let &(_, group_separator, decimal_separator, date_order, date_separator) =
+            LOCALES.iter().find(|(name, ..)| *name == tag).or_else(|| {
+                LOCALES
+                    .iter()
+                    .find(|(name, ..)| name.split('-').next() == Some(language))
+            })?;
+        Some(Locale {
+            group_separator,
+            decimal_separator,
+            date_order,
+            date_separator,
+        })
+    }
+
+    /// A plain decimal number such as `-1234.5`, with grouped thousands and the decimal
+    /// separator of the locale. Other text is returned unchanged
+    pub(crate) fn format_number(&self, number: &str) -> String {
+        let (sign, digits) = match number.strip_prefix('-') {
+            Some(digits) => ("-", digits),
+            None => ("", number),
+        };
+        let (integer, fraction) = digits.split_once('.').unwrap_or((digits, ""));
+        let is_digits = |s: &str| s.bytes().all(|b| b.is_ascii_digit());
+        if integer.is_empty() || !is_digits(integer) || !is_digits(fraction) {
+            return number.to_string();
+        }
+
+        let mut formatted = sign.to_string();
+        for (i, digit) in integer.chars().enumerate() {
+            if i > 0 && (integer.len() - i) % 3 == 0 {
+                formatted.push_str(self.group_separator);
+            }
+            formatted.push(digit);
+        }
+        if !fraction.is_empty() {
+            formatted.push(self.decimal_separator);
+            formatted.push_str(fraction);
+        }
+        formatted
+    }
+
+    /// An ISO 8601 date, optionally followed by a time, e.g. `2024-03-15 14:30:00`, in the
+    /// date order and with the date separator of the locale. Other text is returned unchanged
+    pub(crate) fn format_datetime(&self, datetime: &str) -> String {
+        let (date, time) = match datetime.split_once(' ') {
+            Some((date, time)) => (date, Some(time)),
+            None => (datetime, None),
+        };
+        let parts: Vec<&str> = date.split('-').collect();
+        let [year, month, day] = parts[..] else {
+            return datetime.to_string();
+        };
+        if year.len() != 4 || month.len() != 2 || day.len() != 2 {
+            return datetime.to_string();
+        }
+
+        let ordered = match self.date_order {
+            DateOrder::DayMonthYear => [day, month, year],
+            DateOrder::MonthDayYear => [month, day, year],
+            DateOrder::YearMonthDay => [year, month, day],
+        };
+        let date = ordered.join(&self.date_separator.to_string());
+        match time {
+            Some(time) => format!("{} {}", date, time),
+            None => date,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_number() {
+        let german = Locale::from_tag("de-DE").unwrap();
+        assert_eq!(german.format_number("1234.5"), "1.234,5");
+        assert_eq!(german.format_number("-1234567.25"), "-1.234.567,25");
+        assert_eq!(german.format_number("999"), "999");
+
+        let us = Locale::from_tag("en_US.UTF-8").unwrap();
+        assert_eq!(us.format_number("1234.5"), "1,234.5");
+        assert_eq!(us.format_number("0.125"), "0.125");
+        assert_eq!(us.format_number("N/A"), "N/A");
+
+        assert_eq!(Locale::from_tag("de"), Some(german));
+        assert_eq!(Locale::from_tag("xx-YY"), None);
+    }
+
+    #[test]
+    fn test_format_datetime() {
+        let german = Locale::from_tag("de-AT").unwrap();
+        assert_eq!(german.format_datetime("2024-03-15"), "15.03.2024");
+        assert_eq!(
+            german.format_datetime("2024-03-16 14:30:00"),
+            "16.03.2024 14:30:00"
+        );
+        assert_eq!(german.format_datetime("14:30:00"), "14:30:00");
+
+        let us = Locale::from_tag("en-US").unwrap();
+        assert_eq!(us.format_datetime("2024-03-15"), "03/15/2024");
+        let british = Locale::from_tag("en-GB").unwrap();
+        assert_eq!(british.format_datetime("2024-03-15"), "15/03/2024");
+    }
+}
@@ -0,0 +1,227 @@
+//! Conversion of Tika's XHTML output to the Markdown of `Extractor::set_markdown_output`.
+//! Headings, lists, tables and links are kept, other markup is reduced to its text
+
+use crate::{StreamReader, XhtmlEvent, XhtmlEvents};
+
+/// Converts XHTML to Markdown. The `<head>`, e.g. the title and the metadata, is left out
+pub(crate) fn xhtml_to_markdown(xhtml: &str) -> String {
+    let mut writer = MarkdownWriter::default();
+    // The XHTML is in memory, only an XHTML that ends inside a tag ends the events early
+    let events = XhtmlEvents::new(StreamReader::from_text(xhtml.to_string()));
+    for event in events.map_while(Result::ok) {
+        match event {
+            XhtmlEvent::StartElement { name, attributes } => writer.start(&name, &attributes),
+            XhtmlEvent::EndElement { name } => writer.end(&name),
+            XhtmlEvent::Characters(text) => writer.text(&text),
+        }
+    }
+    writer.out.trim().to_string()
+}
+
+#[derive(Default)]
+struct MarkdownWriter {
+    out: String,
+    /// Line breaks still owed before the next output, 2 for a blank line
+    pending_breaks: usize,
+    in_head: bool,
+    /// Whether each open list is ordered, and the number of its items so far
+    lists: Vec<(bool, usize)>,
+    /// Whether each open link has a target, links without one are written as plain text
+    links: Vec<Option<String>>,
+    /// The rows of the open table and the text of the open cell
+    rows: Vec<Vec<String>>,
+    cell: Option<String>,
+}
+
+impl MarkdownWriter {
+    fn start(&mut self, name: &str, attributes: &[(String, String)]) {
+        match name {
+            "head" => self.in_head = true,
+            "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+                self.block_break(2);
+                let level = name[1..].parse().unwrap_or(1);
+                self.write(&format!("{} ", "#".repeat(level)));
+            }
+            "p" | "div" | "blockquote" | "pre" if self.lists.is_empty() => self.block_break(2),
+            "ul" | "ol" => {
+                self.block_break(if self.lists.is_empty() { 2 } else { 1 });
+                self.lists.push((name == "ol", 0));
+            }
+            "li" => {
+                self.block_break(1);
+                let depth = self.lists.len().saturating_sub(1);
+                let marker = match self.lists.last_mut() {
+                    Some((true, count)) => {
+                        *count += 1;
+                        format!("{}. ", count)
+                    }
+                    _ => "- ".to_string(),
+                };
+                self.write(&format!("{}{}", "  ".repeat(depth), marker));
+            }
+            "tr" => self.rows.push(Vec::new()),
+            "td" | "th" => self.cell = Some(String::new()),
+            "a" => {
+                let href = attributes
+                    .iter()
+                    .find(|(name, _)| name == "href")
+                    .map(|(_, href)| href.clone())
+                    .filter(|href| !href.is_empty());
+                if href.is_some() {
+                    self.write("[");
+                }
+                self.links.push(href);
+            }
+            "br" => self.block_break(1),
+            "img" => {
+                let attribute = |wanted: &str| {
+                    attributes
+                        .iter()
+                        .find(|(name, _)| name == wanted)
+                        .map_or("", |(_, value)| value.trim())
+                };
+                match (attribute("alt"), attribute("src")) {
+                    ("", _) => {}
+                    (alt, "") => self.write(alt),
+                    (alt, src) => self.write(&format!("![{}]({})", alt, src)),
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn end(&mut self, name: &str) {
+        match name {
+            "head" => self.in_head = false,
+            "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => self.block_break(2),
+            "p" | "div" | "blockquote" | "pre" if self.lists.is_empty() => self.block_break(2),
+            "li" => self.block_break(1),
+            "ul" | "ol" => {
+                self.lists.pop();
+                self.block_break(if self.lists.is_empty() { 2 } else { 1 });
+            }
+            "td" | "th" => {
+                let cell = self.cell.take().unwrap_or_default();
+                if let Some(row) = self.rows.last_mut() {
+                    row.push(cell.trim().replace('|', "\\|"));
+                }
+            }
+            "table" => self.table(),
+            "a" => {
+                if let Some(Some(href)) = self.links.pop() {
+                    self.write(&format!("]({})", href));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn text(&mut self, text: &str) {
+        if self.in_head {
+            return;
+        }
+        let collapsed = text.split_whitespace().collect::<Vec<_>>().join(" ");
+        let ends_in_space = self.target().ends_with([' ', '\n']) || self.target().is_empty();
+        if collapsed.is_empty() {
+            // Whitespace between words, but not a reason to end a pending block
+            if !ends_in_space && self.pending_breaks == 0 {
+                self.write(" ");
+            }
+            return;
+        }
+        if text.starts_with(char::is_whitespace) && !ends_in_space && self.pending_breaks == 0 {
+            self.write(" ");
+        }
+        self.write(&collapsed);
+        if text.ends_with(char::is_whitespace) {
+            self.write(" ");
+        }
+    }
+
+    /// Writes the rows of a table, the first row is the header
+    fn table(&mut self) {
+        let rows: Vec<Vec<String>> = std::mem::take(&mut self.rows)
+            .into_iter()
+            .filter(|row| !row.is_empty())
+            .collect();
+        let columns = rows.iter().map(Vec::len).max().unwrap_or(0);
+        if columns == 0 {
+            return;
+        }
+
+        let line = |row: &[String]| {
+            let cells: Vec<&str> = (0..columns)
+                .map(|i| row.get(i).map_or("", String::as_str))
+                .collect();
+            format!("| {} |", cells.join(" | "))
+        };
+        let mut table = vec![line(&rows[0]), line(&vec!["---".to_string(); columns])];
+        table.extend(rows[1..].iter().map(|row| line(row)));
+
+        self.block_break(2);
+        self.write(&table.join("\n"));
+        self.block_break(2);
+    }
+
+    fn block_break(&mut self, breaks: usize) {
+        self.pending_breaks = self.pending_breaks.max(breaks);
+    }
+
+    /// The text of the open table cell, or the output
+    fn target(&self) -> &str {
+        self.cell.as_deref().unwrap_or(&self.out)
+    }
+
+    fn write(&mut self, text: &str) {
+        if let Some(cell) = &mut self.cell {
+            cell.push_str(text);
+            return;
+        }
+        if self.pending_breaks > 0 && !self.out.is_empty() {
+            let trimmed = self.out.trim_end_matches(' ').len();
+            self.out.truncate(trimmed);
+            let present = self.out.len() - self.out.trim_end_matches('\n').len();
+            for _ in present..self.pending_breaks {
+                self.out.push('\n');
+            }
+        }
+        self.pending_breaks = 0;
+        self.out.push_str(text);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_xhtml_to_markdown() {
+        let xhtml = "<html><head><title>Trip</title></head><body>\n\
+            <h1>Packing list</h1>\n<p>Check the <a href=\"https://example.com/weather\">weather\
+            </a> first.</p>\n<ul><li>Skis</li><li>Layers<ol><li>Base</li><li>Shell</li></ol>\
+            </li></ul>\n<table><tr><th>Item</th><th>Price</th></tr>\
+            <tr><td>Wax</td><td>12 | 15</td></tr></table>\n<p>Have   fun.</p></body></html>";
+
+        assert_eq!(
+            xhtml_to_markdown(xhtml),
+            "# Packing list\n\n\
+            Check the [weather](https://example.com/weather) first.\n\n\
+            - Skis\n\
+            - Layers\n  1. Base\n  2. Shell\n\n\
+            | Item | Price |\n| --- | --- |\n| Wax | 12 \\| 15 |\n\n\
+            Have fun."
+        );
+    }
+
+    #[test]
+    fn test_xhtml_to_markdown_images() {
+        let xhtml = "<html><body><figure><img src=\"chart.png\" alt=\"Sales by month\"/>\
+            <figcaption>Figure 1: Sales</figcaption></figure><p><img alt=\"Logo\"/> \
+            <img src=\"spacer.gif\" alt=\"\"/>Home</p></body></html>";
+
+        assert_eq!(
+            xhtml_to_markdown(xhtml),
+            "![Sales by month](chart.png)Figure 1: Sales\n\nLogo Home"
+        );
+    }
+}
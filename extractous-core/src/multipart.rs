@@ -0,0 +1,254 @@
+//! Splitting of multipart MIME bodies, e.g. `multipart/mixed` emails or webhook payloads, into
+//! their parts
+
+use crate::errors::{Error, ExtractResult};
+use crate::format_detection::{detect_format_from_bytes, DocumentFormat};
+use base64::Engine;
+use std::collections::HashMap;
+
+/// A part of a multipart MIME body, as returned by `Extractor::split_multipart`
+#[derive(Debug, Clone, PartialEq)]
+pub struct MultipartPart {
+    /// The headers of the part, with lowercase names, e.g. `content-type`
+    pub headers: HashMap<String, String>,
+    /// The format given by the `Content-Type` header, or detected from the body when the header
+    /// is missing or names a format without a parser
+    pub format: DocumentFormat,
+    /// The body with its `Content-Transfer-Encoding` (base64 or quoted-printable) decoded
+    pub body: Vec<u8>,
+}
+
+/// Splits a multipart body at the delimiter lines of `boundary`. The preamble before the first
+/// delimiter and the epilogue after the closing delimiter are ignored. Nested multiparts are
+/// returned as single parts and can be split again with their own boundary.
+pub(crate) fn split_multipart(data: &[u8], boundary: &str) -> ExtractResult<Vec<MultipartPart>> {
+    if boundary.is_empty() {
+        return Err(Error::InvalidConfiguration(
+            "The multipart boundary must not be empty".to_string(),
+        ));
+    }
+    let delimiter = format!("--{}", boundary).into_bytes();
+
+    let starts = delimiter_lines(data, &delimiter);
+    if starts.is_empty() {
+        return Err(Error::ParseError(format!(
+            "No multipart delimiter for boundary {} found",
+            boundary
+        )));
+    }
+
+    let mut parts = Vec::new();
+    for (i, &start) in starts.iter().enumerate() {
+        let after = &data[start + delimiter.len()..];
+        if after.starts_with(b"--") {
+            // Closing delimiter
+            break;
+        }
+        let Some(line_end) = after.iter().position(|&b| b == b'\n') else {
+            break;
+        };
+        let content_start = start + delimiter.len() + line_end + 1;
+        // The line break before the next delimiter belongs to the delimiter
+        let content_end = match starts.get(i + 1) {
+            Some(&next) => strip_line_break(data, next),
+            None => data.len(),
+        };
+        if content_start <= content_end {
+            parts.push(parse_part(&data[content_start..content_end])?);
+        }
+    }
+    Ok(parts)
+}
+
+/// Offsets of the delimiter lines, the delimiters that start a line
+fn delimiter_lines(data: &[u8], delimiter: &[u8]) -> Vec<usize> {
+    let mut starts = Vec::new();
+    let mut offset = 0;
+    while offset + delimiter.len() <= data.len() {
+        let at_line_start = offset == 0 || data[offset - 1] == b'\n';
+        // Transport padding or the closing `--` may follow the boundary, but no other character,
+        // so a nested boundary that starts with this one, e.g. `b1-inner` in `b1`, is no delimiter
+        let after = &data[offset + delimiter.len()..];
+        let boundary_ends = after.starts_with(b"--")
+            || matches!(after.first(), None | Some(b'\r' | b'\n' | b' ' | b'\t'));
+        if at_line_start && boundary_ends && data[offset..].starts_with(delimiter) {
+            starts.push(offset);
+            offset += delimiter.len();
+        } else {
+            offset += 1;
+        }
+    }
+    starts
+}
+
+/// The offset of the CRLF or LF before `end`
+fn strip_line_break(data: &[u8], end: usize) -> usize {
+    if data[..end].ends_with(b"\r\n") {
+        end - 2
+    } else if data[..end].ends_with(b"\n") {
+        end - 1
+    } else {
+        end
+    }
+}
+
+fn parse_part(part: &[u8]) -> ExtractResult<MultipartPart> {
+    let (header_block, body) = if part.starts_with(b"\r\n") {
+        (&part[..0], &part[2..])
+    } else if part.starts_with(b"\n") {
+        (&part[..0], &part[1..])
+    } else {
+        match find(part, b"\r\n\r\n") {
+            Some(i) => (&part[..i], &part[i + 4..]),
+            None => match find(part, b"\n\n") {
+                Some(i) => (&part[..i], &part[i + 2..]),
+                // Only headers
+                None => (part, &part[part.len()..]),
+            },
+        }
+    };
+    let headers = parse_headers(&String::from_utf8_lossy(header_block));
+
+    let encoding = headers
+        .get("content-transfer-encoding")
+        .map(|encoding| encoding.trim().to_lowercase());
+    let body = match encoding.as_deref() {
+        Some("base64") => {
+            let encoded: Vec<u8> = body
+                .iter()
+                .copied()
+                .filter(|b| !b.is_ascii_whitespace())
+                .collect();
+            base64::engine::general_purpose::STANDARD
+                .decode(encoded)
+                .map_err(|e| Error::ParseError(format!("Invalid base64 part: {}", e)))?
+        }
+        Some("quoted-printable") => decode_quoted_printable(body),
+        _ => body.to_vec(),
+    };
+
+    let format = headers
+        .get("content-type")
+        .and_then(|content_type| DocumentFormat::from_mime_type(content_type))
+        .unwrap_or_else(|| detect_format_from_bytes(&body));
+
+    Ok(MultipartPart {
+        headers,
+        format,
+        body,
+    })
+}
+
+/// Parses `Name: value` lines, unfolding continuation lines that start with whitespace
+fn parse_headers(block: &str) -> HashMap<String, String> {
+    let mut headers: HashMap<String, String> = HashMap::new();
+    let mut last: Option<String> = None;
+    for line in block.lines() {
+        if line.starts_with([' ', '\t']) {
+            if let Some(value) = last.as_ref().and_then(|name| headers.get_mut(name)) {
+                value.push(' ');
+                value.push_str(line.trim());
+            }
+        } else if let Some((name, value)) = line.split_once(':') {
+            let name = name.trim().to_lowercase();
+            headers.insert(name.clone(), value.trim().to_string());
+            last = Some(name);
+        }
+    }
+    headers
+}
+
+/// Decodes `=XX` escapes and removes soft line breaks (`=` at the end of a line)
+fn decode_quoted_printable(data: &[u8]) -> Vec<u8> {
+    let hex = |b: u8| (b as char).to_digit(16).map(|d| d as u8);
+    let mut decoded = Vec::with_capacity(data.len());
+    let mut i = 0;
+    while i < data.len() {
+        if data[i] != b'=' {
+            decoded.push(data[i]);
+            i += 1;
+        } else if data[i + 1..].starts_with(b"\r\n") {
+            i += 3;
+        } else if data[i + 1..].starts_with(b"\n") {
+            i += 2;
+        } else if let Some((high, low)) = data
+            .get(i + 1..i + 3)
+            .and_then(|pair| Some((hex(pair[0])?, hex(pair[1])?)))
+        {
+            decoded.push(high << 4 | low);
+            i += 3;
+        } else {
+            decoded.push(b'=');
+            i += 1;
+        }
+    }
+    decoded
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack
+        .windows(needle.len())
+        .position(|window| window == needle)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BODY: &[u8] = b"This is the preamble.\r\n\
+        --frontier\r\n\
+        Content-Type: text/plain; charset=utf-8\r\n\
+        \r\n\
+        Hello from the text part.\r\n\
+        --frontier\r\n\
+        Content-Type: text/html\r\n\
+        Content-Transfer-Encoding: base64\r\n\
+        \r\n\
+        PGh0bWw+PGJvZHk+PHA+SGVsbG8gPGI+SFRNTDwvYj48L3A+PC9ib2R5PjwvaHRtbD4=\r\n\
+        --frontier--\r\n\
+        This is the epilogue.\r\n";
+
+    #[test]
+    fn test_split_multipart() {
+        let parts = split_multipart(BODY, "frontier").unwrap();
+
+        assert_eq!(parts.len(), 2);
+        assert_eq!(parts[0].format, DocumentFormat::Text);
+        assert_eq!(parts[0].body, b"Hello from the text part.");
+        assert_eq!(
+            parts[0].headers["content-type"],
+            "text/plain; charset=utf-8"
+        );
+        assert_eq!(parts[1].format, DocumentFormat::Html);
+        assert_eq!(
+            parts[1].body,
+            b"<html><body><p>Hello <b>HTML</b></p></body></html>"
+        );
+    }
+
+    #[test]
+    fn test_split_multipart_detects_format_without_content_type() {
+        let body = b"--b\n\n%PDF-1.4 not really a pdf\n--b\nContent-Transfer-Encoding: \
+            quoted-printable\n\nCaf=C3=A9 au l=\nait\n--b--\n";
+        let parts = split_multipart(body, "b").unwrap();
+
+        assert_eq!(parts[0].format, DocumentFormat::Pdf);
+        assert_eq!(parts[1].body, "Café au lait".as_bytes());
+        assert!(split_multipart(body, "other").is_err());
+    }
+
+    #[test]
+    fn test_split_multipart_nested_boundary_with_same_prefix() {
+        let body = b"--b1\nContent-Type: multipart/alternative; boundary=b1-inner\n\n\
+            --b1-inner\nContent-Type: text/plain\n\nInner text\n--b1-inner--\n\
+            --b1\nContent-Type: text/plain\n\nOuter text\n--b1--\n";
+        let parts = split_multipart(body, "b1").unwrap();
+
+        assert_eq!(parts.len(), 2);
+        assert_eq!(
+            parts[0].body,
+            b"--b1-inner\nContent-Type: text/plain\n\nInner text\n--b1-inner--"
+        );
+        assert_eq!(parts[1].body, b"Outer text");
+    }
+}
@@ -0,0 +1,86 @@
+//! Fetching documents from cloud object stores through the `object_store` crate.
+//! Each backend is behind its own optional feature: `s3` for `s3://` and `gcs` for `gs://` uris.
+
+use crate::errors::Error;
+
+#[cfg(any(feature = "s3", feature = "gcs"))]
+use crate::errors::ExtractResult;
+#[cfg(any(feature = "s3", feature = "gcs"))]
+use object_store::ObjectStore;
+
+/// A parsed `scheme://bucket/key` object uri
+#[cfg(any(feature = "s3", feature = "gcs"))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct ObjectUri<'a> {
+    pub(crate) scheme: &'a str,
+    pub(crate) bucket: &'a str,
+    pub(crate) key: &'a str,
+}
+
+#[cfg(any(feature = "s3", feature = "gcs"))]
+impl<'a> ObjectUri<'a> {
+    /// Splits an object uri into its scheme, bucket and key
+    pub(crate) fn parse(uri: &'a str) -> ExtractResult<Self> {
+        let (scheme, rest) = uri
+            .split_once("://")
+            .ok_or_else(|| Error::UnsupportedScheme(format!("Missing scheme in uri: {}", uri)))?;
+        let (bucket, key) = rest.split_once('/').unwrap_or((rest, ""));
+        if bucket.is_empty() || key.is_empty() {
+            return Err(Error::ParseError(format!(
+                "Expected an uri of the form {}://bucket/key, got: {}",
+                scheme, uri
+            )));
+        }
+        Ok(Self {
+            scheme,
+            bucket,
+            key,
+        })
+    }
+}
+
+/// The error returned for a scheme that is not supported, or whose feature is not enabled
+pub(crate) fn unsupported_scheme(scheme: &str) -> Error {
+    match scheme {
+        "s3" => Error::UnsupportedScheme("s3:// uris require the `s3` feature".to_string()),
+        "gs" => Error::UnsupportedScheme("gs:// uris require the `gcs` feature".to_string()),
+        _ => Error::UnsupportedScheme(format!("Unsupported uri scheme: {}", scheme)),
+    }
+}
+
+/// Creates the object store for the bucket of `uri`. Credentials and region are read from the
+/// environment, e.g. `AWS_ACCESS_KEY_ID` or `GOOGLE_SERVICE_ACCOUNT`
+#[cfg(any(feature = "s3", feature = "gcs"))]
+pub(crate) fn object_store_for(uri: &ObjectUri) -> ExtractResult<Box<dyn ObjectStore>> {
+    match uri.scheme {
+        #[cfg(feature = "s3")]
+        "s3" => object_store::aws::AmazonS3Builder::from_env()
+            .with_bucket_name(uri.bucket)
+            .build()
+            .map(|store| Box::new(store) as Box<dyn ObjectStore>)
+            .map_err(|e| Error::IoError(e.to_string())),
+        #[cfg(feature = "gcs")]
+        "gs" => object_store::gcp::GoogleCloudStorageBuilder::from_env()
+            .with_bucket_name(uri.bucket)
+            .build()
+            .map(|store| Box::new(store) as Box<dyn ObjectStore>)
+            .map_err(|e| Error::IoError(e.to_string())),
+        scheme => Err(unsupported_scheme(scheme)),
+    }
+}
+
+/// Downloads the whole object at `key` from `store`
+#[cfg(any(feature = "s3", feature = "gcs"))]
+pub(crate) fn fetch_object(store: &dyn ObjectStore, key: &str) -> ExtractResult<Vec<u8>> {
+    // object_store is async, a single threaded runtime is enough to drive one download
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .map_err(|e| Error::IoError(e.to_string()))?;
+
+    let location = object_store::path::Path::from(key);
+    let bytes = runtime
+        .block_on(async { store.get(&location).await?.bytes().await })
+        .map_err(|e| Error::IoError(e.to_string()))?;
+    Ok(bytes.to_vec())
+}
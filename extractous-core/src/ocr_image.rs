@@ -0,0 +1,188 @@
+//! Downscaling of images above the pixel budget of `TesseractOcrConfig::set_max_ocr_image_pixels`
+//! before OCR, so very high resolution scans do not take tesseract's memory with them
+
+use std::io::Read;
+
+/// The metadata key of the note that an image was downscaled before OCR, e.g.
+/// `4800x800 to 2400x400`
+pub(crate) const OCR_DOWNSCALED_KEY: &str = "OCR-Downscaled";
+
+/// A downscaled PNG image
+pub(crate) struct DownscaledImage {
+    pub(crate) data: Vec<u8>,
+    /// The original and the new size, for the `OCR-Downscaled` metadata
+    pub(crate) note: String,
+}
+
+/// Downscales a PNG image with more than `max_pixels` pixels by the smallest whole factor that
+/// fits it in the budget, averaging each block of pixels. The image is decoded one row at a time,
+/// unless it is interlaced, so only the downscaled image is held in memory. `None` for images
+/// within the budget and for images that cannot be decoded, which are left to Tika as they are
+pub(crate) fn downscale_png<R: Read>(image: R, max_pixels: u64) -> Option<DownscaledImage> {
+    let max_pixels = max_pixels.max(1);
+    let mut decoder = png::Decoder::new(image);
+    decoder.set_transformations(png::Transformations::normalize_to_color8());
+    let mut reader = decoder.read_info().ok()?;
+    let (width, height) = reader.info().size();
+    if u64::from(width) * u64::from(height) <= max_pixels {
+        return None;
+    }
+
+    let (color_type, _) = reader.output_color_type();
+    let factor = scale_factor(width, height, max_pixels);
+    let mut scaler = BoxScaler::new(width, factor, color_type.samples());
+    if reader.info().interlaced {
+        let mut frame = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut frame).ok()?;
+        for row in frame.chunks_exact(info.line_size).take(height as usize) {
+            scaler.push_row(row);
+        }
+    } else {
+        while let Some(row) = reader.next_row().ok()? {
+            scaler.push_row(row.data());
+        }
+    }
+    let (new_width, new_height, pixels) = scaler.finish();
+
+    let mut data = Vec::new();
+    let mut encoder = png::Encoder::new(&mut data, new_width, new_height);
+    encoder.set_color(color_type);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header().ok()?;
+    writer.write_image_data(&pixels).ok()?;
+    writer.finish().ok()?;
+    Some(DownscaledImage {
+        data,
+        note: format!("{}x{} to {}x{}", width, height, new_width, new_height),
+    })
+}
+
+/// The smallest whole factor that the width and height are divided by, rounding up, for the image
+/// to have at most `max_pixels` pixels
+fn scale_factor(width: u32, height: u32, max_pixels: u64) -> u32 {
+    let pixels = u64::from(width) * u64::from(height);
+    let mut factor = ((pixels as f64 / max_pixels as f64).sqrt().ceil() as u32).max(2);
+    while u64::from(width.div_ceil(factor)) * u64::from(height.div_ceil(factor)) > max_pixels {
+        factor += 1;
+    }
+    factor
+}
+
+/// Averages blocks of `factor` by `factor` pixels of an 8 bit image that is fed one row at a
+/// time. The blocks at the right and bottom edges may be smaller
+struct BoxScaler {
+    width: usize,
+    factor: usize,
+    channels: usize,
+    /// The sums of the samples of the blocks of the output row being filled
+    sums: Vec<u64>,
+    /// The number of input rows added to `sums`
+    rows: usize,
+    pixels: Vec<u8>,
+}
+
+impl BoxScaler {
+    fn new(width: u32, factor: u32, channels: usize) -> Self {
+        let (width, factor) = (width as usize, factor as usize);
+        Self {
+            width,
+            factor,
+            channels,
+            sums: vec![0; width.div_ceil(factor) * channels],
+            rows: 0,
+            pixels: Vec::new(),
+        }
+    }
+
+    fn push_row(&mut self, row: &[u8]) {
+        for (x, pixel) in row.chunks_exact(self.channels).take(self.width).enumerate() {
+            let block = x / self.factor * self.channels;
+            for (sum, &sample) in self.sums[block..].iter_mut().zip(pixel) {
+                *sum += u64::from(sample);
+            }
+        }
+        self.rows += 1;
+        if self.rows == self.factor {
+            self.flush_row();
+        }
+    }
+
+    fn flush_row(&mut self) {
+        for (i, sum) in self.sums.iter_mut().enumerate() {
+            let block_x = i / self.channels * self.factor;
+            let count = (self.factor.min(self.width - block_x) * self.rows) as u64;
+            self.pixels.push(((*sum + count / 2) / count) as u8);
+            *sum = 0;
+        }
+        self.rows = 0;
+    }
+
+    /// The width, height and samples of the downscaled image
+    fn finish(mut self) -> (u32, u32, Vec<u8>) {
+        if self.rows > 0 {
+            self.flush_row();
+        }
+        let new_width = self.width.div_ceil(self.factor);
+        let new_height = self.pixels.len() / (new_width * self.channels);
+        (new_width as u32, new_height as u32, self.pixels)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn encode_png(width: u32, height: u32, color: png::ColorType, pixels: &[u8]) -> Vec<u8> {
+        let mut data = Vec::new();
+        let mut encoder = png::Encoder::new(&mut data, width, height);
+        encoder.set_color(color);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header().unwrap();
+        writer.write_image_data(pixels).unwrap();
+        writer.finish().unwrap();
+        data
+    }
+
+    fn decode_png(data: &[u8]) -> (u32, u32, Vec<u8>) {
+        let mut reader = png::Decoder::new(data).read_info().unwrap();
+        let mut pixels = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut pixels).unwrap();
+        pixels.truncate(info.buffer_size());
+        (info.width, info.height, pixels)
+    }
+
+    #[test]
+    fn test_downscale_png_fits_budget() {
+        let (width, height) = (3000, 2000);
+        let pixels: Vec<u8> = (0..width * height * 3).map(|i| (i % 251) as u8).collect();
+        let image = encode_png(width, height, png::ColorType::Rgb, &pixels);
+
+        let downscaled = downscale_png(image.as_slice(), 1_000_000).unwrap();
+        assert_eq!(downscaled.note, "3000x2000 to 1000x667");
+        let (new_width, new_height, new_pixels) = decode_png(&downscaled.data);
+        assert_eq!((new_width, new_height), (1000, 667));
+        assert_eq!(new_pixels.len(), 1000 * 667 * 3);
+
+        assert!(downscale_png(image.as_slice(), 6_000_000).is_none());
+        assert!(downscale_png(&b"not a png"[..], 1).is_none());
+    }
+
+    #[test]
+    fn test_downscale_png_averages_blocks() {
+        #[rustfmt::skip]
+        let pixels = [
+            0, 100, 200, 10, 20,
+            50, 150, 250, 30, 40,
+            7, 9, 11, 13, 15,
+        ];
+        let image = encode_png(5, 3, png::ColorType::Grayscale, &pixels);
+
+        let downscaled = downscale_png(image.as_slice(), 6).unwrap();
+        assert_eq!(downscaled.note, "5x3 to 3x2");
+        // The blocks at the right and bottom edges average fewer pixels
+        assert_eq!(
+            decode_png(&downscaled.data),
+            (3, 2, vec![75, 123, 30, 8, 12, 15])
+        );
+    }
+}
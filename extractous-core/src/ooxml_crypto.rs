@@ -0,0 +1,544 @@
+//! Decryption of password protected OOXML documents (docx, xlsx, ...), as specified in
+//! [MS-OFFCRYPTO](https://learn.microsoft.com/en-us/openspecs/office_file_formats/ms-offcrypto).
+//!
+//! An encrypted document is an OLE2 compound file with an `EncryptionInfo` stream describing
+//! the encryption and an `EncryptedPackage` stream holding the encrypted zip package. Both the
+//! agile (Office 2010+) and the standard (Office 2007) AES encryption are supported.
+
+use crate::errors::{Error, ExtractResult};
+use base64::Engine;
+use cbc::cipher::block_padding::NoPadding;
+use cbc::cipher::{BlockDecryptMut, KeyInit, KeyIvInit};
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::name::{Namespace, ResolveResult};
+use sha1::Digest;
+use std::io::Read;
+use std::path::Path;
+
+/// Signature of OLE2 compound files
+const OLE2_MAGIC: [u8; 8] = [0xD0, 0xCF, 0x11, 0xE0, 0xA1, 0xB1, 0x1A, 0xE1];
+
+/// The encrypted package is encrypted in segments of this size in the agile encryption
+const AGILE_SEGMENT_SIZE: usize = 4096;
+
+/// Block keys of the agile encryption, MS-OFFCRYPTO 2.3.4.13
+const VERIFIER_HASH_INPUT_BLOCK_KEY: [u8; 8] = [0xfe, 0xa7, 0xd2, 0x76, 0x3b, 0x4b, 0x9e, 0x79];
+const VERIFIER_HASH_VALUE_BLOCK_KEY: [u8; 8] = [0xd7, 0xaa, 0x0f, 0x6d, 0x30, 0x61, 0x34, 0x4e];
+const ENCRYPTED_KEY_VALUE_BLOCK_KEY: [u8; 8] = [0x14, 0x6e, 0x0b, 0xe7, 0xab, 0xac, 0xd0, 0xd6];
+
+/// Iterations of the password hash in the standard encryption
+const STANDARD_SPIN_COUNT: u32 = 50_000;
+
+/// The namespace of the `encryptedKey` of the password key encryptor. The certificate key
+/// encryptor has an `encryptedKey` of its own in another namespace
+const PASSWORD_KEY_ENCRYPTOR_NS: &[u8] =
+    b"http://schemas.microsoft.com/office/2006/keyEncryptor/password";
+
+/// The block size of AES, the only cipher that is supported, in bytes
+const AES_BLOCK_SIZE: usize = 16;
+
+/// The key sizes of AES in bits
+const AES_KEY_BITS: [usize; 3] = [128, 192, 256];
+
+/// The largest spin count of the agile encryption, MS-OFFCRYPTO 2.3.4.11
+const MAX_SPIN_COUNT: usize = 10_000_000;
+
+/// The streams of an encrypted OOXML document
+pub(crate) struct EncryptedPackage {
+    encryption_info: Vec<u8>,
+    encrypted_package: Vec<u8>,
+}
+
+impl EncryptedPackage {
+    /// Reads the encryption streams of the file at `path`. Returns `None` if the file is not an
+    /// encrypted OOXML document, which is cheap to find out for any other file
+    pub(crate) fn open(path: &Path) -> ExtractResult<Option<Self>> {
+        let mut file = std::fs::File::open(path).map_err(|e| Error::IoError(e.to_string()))?;
+        let mut magic = [0u8; 8];
+        if file.read_exact(&mut magic).is_err() || magic != OLE2_MAGIC {
+            return Ok(None);
+        }
+
+        let mut compound = cfb::open(path).map_err(|e| Error::ParseError(e.to_string()))?;
+        if !compound.is_stream("/EncryptionInfo") || !compound.is_stream("/EncryptedPackage") {
+            return Ok(None);
+        }
+        Ok(Some(Self {
+            encryption_info: read_stream(&mut compound, "/EncryptionInfo")?,
+            encrypted_package: read_stream(&mut compound, "/EncryptedPackage")?,
+        }))
+    }
+
+    /// Decrypts the package, returning the bytes of the zip package. Fails with
+    /// `Error::EncryptedDocument` if the password is wrong
+    pub(crate) fn decrypt(&self, password: &str) -> ExtractResult<Vec<u8>> {
+        let info = &self.encryption_info;
+        if info.len() < 8 {
+            return Err(invalid("EncryptionInfo is truncated"));
+        }
+        let major = u16::from_le_bytes([info[0], info[1]]);
+        let minor = u16::from_le_bytes([info[2], info[3]]);
+        match (major, minor) {
+            (4, 4) => decrypt_agile(&info[8..], &self.encrypted_package, password),
+            (2..=4, 2) => decrypt_standard(&info[8..], &self.encrypted_package, password),
+            _ => Err(Error::UnsupportedFormat(format!(
+                "Unsupported OOXML encryption version {}.{}",
+                major, minor
+            ))),
+        }
+    }
+}
+
+fn read_stream(
+    compound: &mut cfb::CompoundFile<std::fs::File>,
+    name: &str,
+) -> ExtractResult<Vec<u8>> {
+    let mut stream = compound
+        .open_stream(name)
+        .map_err(|e| Error::ParseError(e.to_string()))?;
+    let mut data = Vec::new();
+    stream
+        .read_to_end(&mut data)
+        .map_err(|e| Error::IoError(e.to_string()))?;
+    Ok(data)
+}
+
+fn invalid(msg: &str) -> Error {
+    Error::ParseError(format!("Invalid encrypted document: {}", msg))
+}
+
+fn wrong_password() -> Error {
+    Error::EncryptedDocument("The password of the encrypted document is incorrect".to_string())
+}
+
+/// Splits the `EncryptedPackage` stream into the decrypted size and the encrypted data
+fn package_parts(package: &[u8]) -> ExtractResult<(usize, &[u8])> {
+    if package.len() < 8 {
+        return Err(invalid("EncryptedPackage is truncated"));
+    }
+    let mut size = [0u8; 8];
+    size.copy_from_slice(&package[..8]);
+    Ok((u64::from_le_bytes(size) as usize, &package[8..]))
+}
+
+fn utf16le(password: &str) -> Vec<u8> {
+    password
+        .encode_utf16()
+        .flat_map(|u| u.to_le_bytes())
+        .collect()
+}
+
+#[derive(Debug, Clone, Copy)]
+enum HashAlgorithm {
+    Sha1,
+    Sha256,
+    Sha384,
+    Sha512,
+}
+
+impl HashAlgorithm {
+    fn from_name(name: &str) -> ExtractResult<Self> {
+        match name {
+            "SHA1" | "SHA-1" => Ok(Self::Sha1),
+            "SHA256" => Ok(Self::Sha256),
+            "SHA384" => Ok(Self::Sha384),
+            "SHA512" => Ok(Self::Sha512),
+            _ => Err(Error::UnsupportedFormat(format!(
+                "Unsupported hash algorithm {}",
+                name
+            ))),
+        }
+    }
+
+    /// Hashes the concatenation of `parts`
+    fn digest(self, parts: &[&[u8]]) -> Vec<u8> {
+        fn run<D: Digest>(parts: &[&[u8]]) -> Vec<u8> {
+            let mut hasher = D::new();
+            for part in parts {
+                hasher.update(part);
+            }
+            hasher.finalize().to_vec()
+        }
+        match self {
+            Self::Sha1 => run::<sha1::Sha1>(parts),
+            Self::Sha256 => run::<sha2::Sha256>(parts),
+            Self::Sha384 => run::<sha2::Sha384>(parts),
+            Self::Sha512 => run::<sha2::Sha512>(parts),
+        }
+    }
+}
+
+fn aes_cbc_decrypt(key: &[u8], iv: &[u8], data: &[u8]) -> ExtractResult<Vec<u8>> {
+    fn run<C>(key: &[u8], iv: &[u8], buf: &mut [u8]) -> ExtractResult<usize>
+    where
+        cbc::Decryptor<C>: KeyIvInit + BlockDecryptMut,
+        C: cbc::cipher::BlockDecryptMut + cbc::cipher::BlockCipher,
+    {
+        let decryptor = cbc::Decryptor::<C>::new_from_slices(key, iv)
+            .map_err(|_| invalid("bad key or iv length"))?;
+        let len = decryptor
+            .decrypt_padded_mut::<NoPadding>(buf)
+            .map_err(|_| invalid("encrypted data is not a multiple of the block size"))?
+            .len();
+        Ok(len)
+    }
+
+    let mut buf = data.to_vec();
+    let len = match key.len() {
+        16 => run::<aes::Aes128>(key, iv, &mut buf)?,
+        24 => run::<aes::Aes192>(key, iv, &mut buf)?,
+        32 => run::<aes::Aes256>(key, iv, &mut buf)?,
+        _ => return Err(invalid("unsupported AES key size")),
+    };
+    buf.truncate(len);
+    Ok(buf)
+}
+
+fn aes_ecb_decrypt(key: &[u8], data: &[u8]) -> ExtractResult<Vec<u8>> {
+    fn run<C>(key: &[u8], buf: &mut [u8]) -> ExtractResult<usize>
+    where
+        ecb::Decryptor<C>: KeyInit + BlockDecryptMut,
+        C: cbc::cipher::BlockDecryptMut + cbc::cipher::BlockCipher,
+    {
+        let decryptor =
+            ecb::Decryptor::<C>::new_from_slice(key).map_err(|_| invalid("bad key length"))?;
+        let len = decryptor
+            .decrypt_padded_mut::<NoPadding>(buf)
+            .map_err(|_| invalid("encrypted data is not a multiple of the block size"))?
+            .len();
+        Ok(len)
+    }
+
+    let mut buf = data.to_vec();
+    let len = match key.len() {
+        16 => run::<aes::Aes128>(key, &mut buf)?,
+        24 => run::<aes::Aes192>(key, &mut buf)?,
+        32 => run::<aes::Aes256>(key, &mut buf)?,
+        _ => return Err(invalid("unsupported AES key size")),
+    };
+    buf.truncate(len);
+    Ok(buf)
+}
+
+/// Parameters of the agile encryption, read from the xml of the `EncryptionInfo` stream
+#[derive(Debug, Default)]
+struct AgileInfo {
+    // <keyData>, used for the package
+    key_data_salt: Vec<u8>,
+    key_data_hash: Option<String>,
+    key_data_block_size: usize,
+    // <p:encryptedKey>, used to verify the password and decrypt the package key
+    password_salt: Vec<u8>,
+    password_hash: Option<String>,
+    password_key_bits: usize,
+    spin_count: u32,
+    encrypted_verifier_hash_input: Vec<u8>,
+    encrypted_verifier_hash_value: Vec<u8>,
+    encrypted_key_value: Vec<u8>,
+}
+
+impl AgileInfo {
+    fn parse(xml: &[u8]) -> ExtractResult<Self> {
+        let mut info = AgileInfo::default();
+        let mut reader = quick_xml::reader::NsReader::from_reader(xml);
+        let mut buf = Vec::new();
+        loop {
+            match reader.read_resolved_event_into(&mut buf) {
+                Ok((namespace, Event::Start(ref e))) | Ok((namespace, Event::Empty(ref e))) => {
+                    let name = e.local_name();
+                    match name.as_ref() {
+                        b"keyData" => {
+                            info.key_data_salt = base64_attribute(e, b"saltValue")?;
+                            info.key_data_hash = string_attribute(e, b"hashAlgorithm");
+                            info.key_data_block_size = number_attribute(e, b"blockSize")?;
+                            check_cipher(e)?;
+                            // The block size is the length of the iv of every segment
+                            if info.key_data_block_size != AES_BLOCK_SIZE {
+                                return Err(invalid(&format!(
+                                    "unsupported block size {}",
+                                    info.key_data_block_size
+                                )));
+                            }
+                        }
+                        b"encryptedKey"
+                            if namespace
+                                == ResolveResult::Bound(Namespace(PASSWORD_KEY_ENCRYPTOR_NS)) =>
+                        {
+                            info.password_salt = base64_attribute(e, b"saltValue")?;
+                            info.password_hash = string_attribute(e, b"hashAlgorithm");
+                            info.password_key_bits = number_attribute(e, b"keyBits")?;
+                            if !AES_KEY_BITS.contains(&info.password_key_bits) {
+                                return Err(invalid(&format!(
+                                    "unsupported key size {}",
+                                    info.password_key_bits
+                                )));
+                            }
+                            let spin_count = number_attribute(e, b"spinCount")?;
+                            if spin_count > MAX_SPIN_COUNT {
+                                return Err(invalid(&format!(
+                                    "spin count {} is above {}",
+                                    spin_count, MAX_SPIN_COUNT
+                                )));
+                            }
+                            info.spin_count = spin_count as u32;
+                            info.encrypted_verifier_hash_input =
+                                base64_attribute(e, b"encryptedVerifierHashInput")?;
+                            info.encrypted_verifier_hash_value =
+                                base64_attribute(e, b"encryptedVerifierHashValue")?;
+                            info.encrypted_key_value = base64_attribute(e, b"encryptedKeyValue")?;
+                            check_cipher(e)?;
+                        }
+                        _ => {}
+                    }
+                }
+                Ok((_, Event::Eof)) => break,
+                Err(e) => return Err(invalid(&e.to_string())),
+                _ => {}
+            }
+            buf.clear();
+        }
+
+        if info.encrypted_key_value.is_empty() {
+            return Err(Error::UnsupportedFormat(
+                "Only password encrypted documents are supported".to_string(),
+            ));
+        }
+        Ok(info)
+    }
+}
+
+fn string_attribute(element: &BytesStart, name: &[u8]) -> Option<String> {
+    element
+        .try_get_attribute(name)
+        .ok()
+        .flatten()
+        .map(|attr| String::from_utf8_lossy(&attr.value).into_owned())
+}
+
+fn number_attribute(element: &BytesStart, name: &[u8]) -> ExtractResult<usize> {
+    string_attribute(element, name)
+        .and_then(|value| value.parse().ok())
+        .ok_or_else(|| invalid(&format!("missing {}", String::from_utf8_lossy(name))))
+}
+
+fn base64_attribute(element: &BytesStart, name: &[u8]) -> ExtractResult<Vec<u8>> {
+    let value = string_attribute(element, name)
+        .ok_or_else(|| invalid(&format!("missing {}", String::from_utf8_lossy(name))))?;
+    base64::engine::general_purpose::STANDARD
+        .decode(value.trim())
+        .map_err(|e| invalid(&e.to_string()))
+}
+
+fn check_cipher(element: &BytesStart) -> ExtractResult<()> {
+    let cipher = string_attribute(element, b"cipherAlgorithm").unwrap_or_default();
+    let chaining = string_attribute(element, b"cipherChaining").unwrap_or_default();
+    if cipher != "AES" || chaining != "ChainingModeCBC" {
+        return Err(Error::UnsupportedFormat(format!(
+            "Unsupported cipher {} {}",
+            cipher, chaining
+        )));
+    }
+    Ok(())
+}
+
+/// Hashes the password `spin_count` times, MS-OFFCRYPTO 2.3.4.11
+fn agile_password_hash(
+    hash: HashAlgorithm,
+    salt: &[u8],
+    password: &str,
+    spin_count: u32,
+) -> Vec<u8> {
+    let mut h = hash.digest(&[salt, &utf16le(password)]);
+    for i in 0..spin_count {
+        h = hash.digest(&[&i.to_le_bytes(), &h]);
+    }
+    h
+}
+
+/// Derives the key for `block_key` from the password hash, MS-OFFCRYPTO 2.3.4.11
+fn agile_key(
+    hash: HashAlgorithm,
+    password_hash: &[u8],
+    block_key: &[u8],
+    key_len: usize,
+) -> Vec<u8> {
+    let mut key = hash.digest(&[password_hash, block_key]);
+    // Truncated, or padded with 0x36 when the hash is shorter than the key
+    key.resize(key_len, 0x36);
+    key
+}
+
+fn decrypt_agile(xml: &[u8], package: &[u8], password: &str) -> ExtractResult<Vec<u8>> {
+    let info = AgileInfo::parse(xml)?;
+    let password_hash = HashAlgorithm::from_name(info.password_hash.as_deref().unwrap_or(""))?;
+    let key_data_hash = HashAlgorithm::from_name(info.key_data_hash.as_deref().unwrap_or(""))?;
+    let key_len = info.password_key_bits / 8;
+    let salt = &info.password_salt;
+
+    // Verify the password before decrypting the package. The expensive spin is shared by the
+    // keys of all blocks
+    let hashed_password = agile_password_hash(password_hash, salt, password, info.spin_count);
+    let derive = |block_key: &[u8]| agile_key(password_hash, &hashed_password, block_key, key_len);
+    let verifier_input = aes_cbc_decrypt(
+        &derive(&VERIFIER_HASH_INPUT_BLOCK_KEY),
+        salt,
+        &info.encrypted_verifier_hash_input,
+    )?;
+    let verifier_hash = aes_cbc_decrypt(
+        &derive(&VERIFIER_HASH_VALUE_BLOCK_KEY),
+        salt,
+        &info.encrypted_verifier_hash_value,
+    )?;
+    let expected = password_hash.digest(&[&verifier_input[..salt.len().min(verifier_input.len())]]);
+    if verifier_hash.len() < expected.len() || verifier_hash[..expected.len()] != expected[..] {
+        return Err(wrong_password());
+    }
+
+    let mut package_key = aes_cbc_decrypt(
+        &derive(&ENCRYPTED_KEY_VALUE_BLOCK_KEY),
+        salt,
+        &info.encrypted_key_value,
+    )?;
+    package_key.truncate(key_len);
+
+    // Each segment has its own iv, derived from the segment index
+    let (size, data) = package_parts(package)?;
+    let mut decrypted = Vec::with_capacity(data.len());
+    for (index, segment) in data.chunks(AGILE_SEGMENT_SIZE).enumerate() {
+        let mut iv = key_data_hash.digest(&[&info.key_data_salt, &(index as u32).to_le_bytes()]);
+        iv.resize(info.key_data_block_size, 0x36);
+        decrypted.extend(aes_cbc_decrypt(&package_key, &iv, segment)?);
+    }
+    decrypted.truncate(size);
+    Ok(decrypted)
+}
+
+fn decrypt_standard(info: &[u8], package: &[u8], password: &str) -> ExtractResult<Vec<u8>> {
+    let u32_at = |offset: usize| -> ExtractResult<u32> {
+        info.get(offset..offset + 4)
+            .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+            .ok_or_else(|| invalid("EncryptionInfo is truncated"))
+    };
+
+    // EncryptionHeader, preceded by its size
+    let header_size = u32_at(0)? as usize;
+    let alg_id = u32_at(4 + 8)?;
+    let key_bits = u32_at(4 + 16)? as usize;
+    // AES-128, AES-192 or AES-256
+    if !(0x660E..=0x6610).contains(&alg_id) {
+        return Err(Error::UnsupportedFormat(format!(
+            "Unsupported encryption algorithm 0x{:x}",
+            alg_id
+        )));
+    }
+
+    // EncryptionVerifier
+    let verifier = 4 + header_size;
+    let salt_size = u32_at(verifier)? as usize;
+    let field = |offset: usize, len: usize| {
+        info.get(offset..offset + len)
+            .ok_or_else(|| invalid("EncryptionInfo is truncated"))
+    };
+    let salt = field(verifier + 4, salt_size)?;
+    let encrypted_verifier = field(verifier + 4 + salt_size, 16)?;
+    let verifier_hash_size = u32_at(verifier + 20 + salt_size)? as usize;
+    let encrypted_verifier_hash = field(verifier + 24 + salt_size, 32)?;
+
+    // Key derivation, MS-OFFCRYPTO 2.3.4.7
+    let sha1 = HashAlgorithm::Sha1;
+    let mut h = sha1.digest(&[salt, &utf16le(password)]);
+    for i in 0..STANDARD_SPIN_COUNT {
+        h = sha1.digest(&[&i.to_le_bytes(), &h]);
+    }
+    let h = sha1.digest(&[&h, &0u32.to_le_bytes()]);
+    let mut inner = [0x36u8; 64];
+    let mut outer = [0x5cu8; 64];
+    for (i, byte) in h.iter().enumerate() {
+        inner[i] ^= byte;
+        outer[i] ^= byte;
+    }
+    let mut key = sha1.digest(&[&inner]);
+    key.extend(sha1.digest(&[&outer]));
+    key.truncate(key_bits / 8);
+
+    let verifier = aes_ecb_decrypt(&key, encrypted_verifier)?;
+    let verifier_hash = aes_ecb_decrypt(&key, encrypted_verifier_hash)?;
+    if sha1.digest(&[&verifier])[..] != verifier_hash[..verifier_hash_size.min(20)] {
+        return Err(wrong_password());
+    }
+
+    let (size, data) = package_parts(package)?;
+    // The stream may carry trailing bytes beyond the last full block
+    let data = &data[..data.len() - data.len() % 16];
+    let mut decrypted = aes_ecb_decrypt(&key, data)?;
+    decrypted.truncate(size);
+    Ok(decrypted)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ENCRYPTION_XML: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<encryption xmlns="http://schemas.microsoft.com/office/2006/encryption"
+    xmlns:p="http://schemas.microsoft.com/office/2006/keyEncryptor/password"
+    xmlns:c="http://schemas.microsoft.com/office/2006/keyEncryptor/certificate">
+<keyData saltSize="16" blockSize="16" keyBits="256" hashSize="64" cipherAlgorithm="AES"
+    cipherChaining="ChainingModeCBC" hashAlgorithm="SHA512" saltValue="AAECAwQFBgcICQoLDA0ODw=="/>
+<keyEncryptors>
+<keyEncryptor uri="http://schemas.microsoft.com/office/2006/keyEncryptor/certificate">
+<c:encryptedKey encryptedKeyValue="Y2VydGlmaWNhdGU=" x509Certificate="" certVerifier=""/>
+</keyEncryptor>
+<keyEncryptor uri="http://schemas.microsoft.com/office/2006/keyEncryptor/password">
+<p:encryptedKey spinCount="100000" saltSize="16" blockSize="16" keyBits="256" hashSize="64"
+    cipherAlgorithm="AES" cipherChaining="ChainingModeCBC" hashAlgorithm="SHA512"
+    saltValue="Dw4NDAsKCQgHBgUEAwIBAA==" encryptedVerifierHashInput="aW5wdXQ="
+    encryptedVerifierHashValue="dmFsdWU=" encryptedKeyValue="cGFzc3dvcmQ="/>
+</keyEncryptor>
+</keyEncryptors>
+</encryption>"#;
+
+    #[test]
+    fn test_agile_info_reads_password_key_encryptor() {
+        let info = AgileInfo::parse(ENCRYPTION_XML.as_bytes()).unwrap();
+        assert_eq!(info.encrypted_key_value, b"password");
+        assert_eq!(info.spin_count, 100_000);
+        assert_eq!(info.key_data_block_size, 16);
+
+        // Only the certificate key encryptor
+        let start = ENCRYPTION_XML.rfind("<keyEncryptor ").unwrap();
+        let end = ENCRYPTION_XML.rfind("</keyEncryptor>").unwrap() + "</keyEncryptor>".len();
+        let xml = format!("{}{}", &ENCRYPTION_XML[..start], &ENCRYPTION_XML[end..]);
+        assert!(matches!(
+            AgileInfo::parse(xml.as_bytes()),
+            Err(Error::UnsupportedFormat(_))
+        ));
+    }
+
+    #[test]
+    fn test_agile_info_rejects_block_size() {
+        let xml = ENCRYPTION_XML.replacen("blockSize=\"16\"", "blockSize=\"1000000000\"", 1);
+        assert!(matches!(
+            AgileInfo::parse(xml.as_bytes()),
+            Err(Error::ParseError(_))
+        ));
+    }
+
+    #[test]
+    fn test_agile_info_rejects_key_bits_and_spin_count() {
+        for (from, to) in [
+            (
+                "keyBits=\"256\" hashSize=\"64\"\n",
+                "keyBits=\"4294967295\" hashSize=\"64\"\n",
+            ),
+            ("spinCount=\"100000\"", "spinCount=\"10000001\""),
+        ] {
+            let xml = ENCRYPTION_XML.replacen(from, to, 1);
+            assert_ne!(xml, ENCRYPTION_XML);
+            assert!(matches!(
+                AgileInfo::parse(xml.as_bytes()),
+                Err(Error::ParseError(_))
+            ));
+        }
+    }
+}
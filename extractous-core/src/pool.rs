@@ -0,0 +1,287 @@
+//! A fixed pool of worker threads for concurrent extraction, e.g. in a server handling many
+//! requests at once
+
+use crate::errors::{Error, ExtractResult};
+use crate::{Extractor, Metadata};
+use std::future::Future;
+use std::panic::{self, AssertUnwindSafe};
+use std::pin::Pin;
+use std::sync::mpsc;
+use std::sync::{Arc, Condvar, Mutex, PoisonError};
+use std::task::{Context, Poll, Waker};
+use std::thread::JoinHandle;
+
+type Job = Box<dyn FnOnce(&Extractor) + Send>;
+
+/// Runs extraction jobs on a fixed set of worker threads, each with its own clone of an
+/// [`Extractor`].
+///
+/// Every worker is attached to the Tika vm for as long as it lives, instead of attaching and
+/// detaching around each call, so the attachments are bounded by the number of workers however
+/// many requests come in. At most as many jobs as there are workers wait in the queue, `submit`
+/// blocks when the queue is full, which pushes back on callers that produce jobs faster than
+/// the workers finish them. Dropping the pool runs the queued jobs and joins the workers.
+/// ```no_run
+/// use extractous::{Extractor, ExtractorPool};
+///
+/// let pool = ExtractorPool::new(Extractor::new(), 4).unwrap();
+/// let handle = pool.submit(|extractor| extractor.extract_file_to_string("README.md")).unwrap();
+/// let (text, metadata) = handle.wait().unwrap();
+/// ```
+pub struct ExtractorPool {
+    sender: Option<mpsc::SyncSender<Job>>,
+    workers: Vec<JoinHandle<()>>,
+}
+
+impl ExtractorPool {
+    /// Starts `workers` threads that extract with clones of `extractor`. Returns
+    /// `Error::InvalidConfiguration` for zero workers.
+    pub fn new(extractor: Extractor, workers: usize) -> ExtractResult<Self> {
+        if workers == 0 {
+            return Err(Error::InvalidConfiguration(
+                "An extractor pool needs at least one worker".to_string(),
+            ));
+        }
+
+        let (sender, receiver) = mpsc::sync_channel::<Job>(workers);
+        let receiver = Arc::new(Mutex::new(receiver));
+        let workers = (0..workers)
+            .map(|i| {
+                let extractor = extractor.clone();
+                let receiver = Arc::clone(&receiver);
+                std::thread::Builder::new()
+                    .name(format!("extractous-pool-{}", i))
+                    .spawn(move || run_worker(extractor, receiver))
+                    .map_err(|e| Error::IoError(e.to_string()))
+            })
+            .collect::<ExtractResult<Vec<_>>>()?;
+
+        Ok(Self {
+            sender: Some(sender),
+            workers,
+        })
+    }
+
+    /// Queues a job and returns a handle to its result. Blocks while the queue is full. A job
+    /// that panics completes with `Error::Unknown`, the worker keeps running.
+    pub fn submit<T, F>(&self, job: F) -> ExtractResult<JobHandle<T>>
+    where
+        T: Send + 'static,
+        F: FnOnce(&Extractor) -> ExtractResult<T> + Send + 'static,
+    {
+        let shared = Arc::new(JobShared::default());
+        let completion = Arc::clone(&shared);
+        let job: Job = Box::new(move |extractor| {
+            let result = panic::catch_unwind(AssertUnwindSafe(|| job(extractor)))
+                .unwrap_or_else(|_| Err(Error::Unknown("The extraction job panicked".to_string())));
+            completion.complete(result);
+        });
+
+        let sender = self
+            .sender
+            .as_ref()
+            .expect("the sender lives until the pool is dropped");
+        sender
+            .send(job)
+            .map_err(|_| Error::Unknown("The extractor pool has no workers left".to_string()))?;
+        Ok(JobHandle { shared })
+    }
+
+    /// Extracts a file to a string on a worker and waits for the result, see
+    /// `Extractor::extract_file_to_string`
+    pub fn extract_file_to_string(&self, file_path: &str) -> ExtractResult<(String, Metadata)> {
+        let file_path = file_path.to_string();
+        self.submit(move |extractor| extractor.extract_file_to_string(&file_path))?
+            .wait()
+    }
+
+    /// Extracts a byte buffer to a string on a worker and waits for the result, see
+    /// `Extractor::extract_bytes_to_string`
+    pub fn extract_bytes_to_string(&self, buffer: Vec<u8>) -> ExtractResult<(String, Metadata)> {
+        self.submit(move |extractor| extractor.extract_bytes_to_string(&buffer))?
+            .wait()
+    }
+
+    /// The number of worker threads
+    pub fn workers(&self) -> usize {
+        self.workers.len()
+    }
+}
+
+impl Drop for ExtractorPool {
+    fn drop(&mut self) {
+        // Closing the queue ends the workers once they ran the jobs still queued
+        drop(self.sender.take());
+        for worker in self.workers.drain(..) {
+            let _ = worker.join();
+        }
+    }
+}
+
+/// The number of local references a job's frame is created with, the vm grows it as needed
+const JOB_LOCAL_FRAME_CAPACITY: i32 = 64;
+
+fn run_worker(extractor: Extractor, receiver: Arc<Mutex<mpsc::Receiver<Job>>>) {
+    // The calls into Tika reuse this attachment, the worker is detached when it exits. A vm
+    // that fails to start fails the jobs instead of the worker
+    let mut attached = crate::tika::attach_current_thread().ok();
+
+    loop {
+        let job = receiver
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .recv();
+        let mut job = match job {
+            Ok(job) => Some(job),
+            Err(mpsc::RecvError) => break,
+        };
+        // The local references of a job are only freed when the worker detaches, which is when
+        // the pool is dropped. Each job runs in a local frame of its own that frees them, the
+        // streams it returns hold global references
+        if let Some(env) = attached.as_mut() {
+            let _ = env.with_local_frame(JOB_LOCAL_FRAME_CAPACITY, |_| {
+                if let Some(job) = job.take() {
+                    job(&extractor);
+                }
+                Ok::<(), jni::errors::Error>(())
+            });
+        }
+        // Without a vm or a frame the job still runs, to complete its handle
+        if let Some(job) = job.take() {
+            job(&extractor);
+        }
+    }
+}
+
+/// The result of a job submitted to an [`ExtractorPool`]. Either block on it with `wait`, or
+/// await it, it is a [`Future`] that does not need a particular async runtime.
+pub struct JobHandle<T> {
+    shared: Arc<JobShared<T>>,
+}
+
+impl<T> JobHandle<T> {
+    /// Blocks until the job has run and returns its result
+    pub fn wait(self) -> ExtractResult<T> {
+        let mut state = self
+            .shared
+            .state
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        loop {
+            if let Some(result) = state.result.take() {
+                return result;
+            }
+            state = self
+                .shared
+                .done
+                .wait(state)
+                .unwrap_or_else(PoisonError::into_inner);
+        }
+    }
+}
+
+impl<T> Future for JobHandle<T> {
+    type Output = ExtractResult<T>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut state = self
+            .shared
+            .state
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        match state.result.take() {
+            Some(result) => Poll::Ready(result),
+            None => {
+                state.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+struct JobShared<T> {
+    state: Mutex<JobState<T>>,
+    done: Condvar,
+}
+
+struct JobState<T> {
+    result: Option<ExtractResult<T>>,
+    waker: Option<Waker>,
+}
+
+impl<T> Default for JobShared<T> {
+    fn default() -> Self {
+        Self {
+            state: Mutex::new(JobState {
+                result: None,
+                waker: None,
+            }),
+            done: Condvar::new(),
+        }
+    }
+}
+
+impl<T> JobShared<T> {
+    fn complete(&self, result: ExtractResult<T>) {
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        state.result = Some(result);
+        self.done.notify_all();
+        if let Some(waker) = state.waker.take() {
+            waker.wake();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pool_extracts_more_files_than_workers() {
+        let expected = Extractor::new()
+            .extract_file_to_string("README.md")
+            .unwrap();
+
+        let pool = ExtractorPool::new(Extractor::new(), 2).unwrap();
+        let handles: Vec<JobHandle<(String, Metadata)>> = (0..8)
+            .map(|_| {
+                pool.submit(|extractor| extractor.extract_file_to_string("README.md"))
+                    .unwrap()
+            })
+            .collect();
+
+        for handle in handles {
+            let (text, _) = handle.wait().unwrap();
+            assert_eq!(text, expected.0);
+        }
+    }
+
+    #[test]
+    fn test_pool_runs_queued_jobs_and_survives_panics() {
+        assert!(matches!(
+            ExtractorPool::new(Extractor::new(), 0),
+            Err(Error::InvalidConfiguration(_))
+        ));
+
+        let pool = ExtractorPool::new(Extractor::new(), 2).unwrap();
+        let handles: Vec<JobHandle<usize>> = (0..10)
+            .map(|i| {
+                pool.submit(move |_| match i {
+                    3 => panic!("job {} failed", i),
+                    _ => Ok(i * i),
+                })
+                .unwrap()
+            })
+            .collect();
+
+        for (i, handle) in handles.into_iter().enumerate() {
+            match i {
+                3 => assert!(matches!(handle.wait(), Err(Error::Unknown(_)))),
+                _ => assert_eq!(handle.wait().unwrap(), i * i),
+            }
+        }
+        // The worker of the panicked job is still there
+        assert_eq!(pool.submit(|_| Ok(1)).unwrap().wait().unwrap(), 1);
+        assert_eq!(pool.workers(), 2);
+    }
+}
@@ -5,10 +5,43 @@ use crate::errors::{Error, ExtractResult};
 use crate::Metadata;
 use std::path::Path;
 
+/// Separator placed between the body text and the footnotes collected as endnotes, or the
+/// link targets of `LinkMode::Footnotes`
+pub const ENDNOTES_SEPARATOR: &str = "----------";
+
+/// Appends the collected footnotes to the end of `text`, under [`ENDNOTES_SEPARATOR`]
+pub(crate) fn append_endnotes(text: &mut String, footnotes: &[String]) {
+    if footnotes.is_empty() {
+        return;
+    }
+    let trimmed_len = text.trim_end().len();
+    text.truncate(trimmed_len);
+    text.push_str("\n\n");
+    text.push_str(ENDNOTES_SEPARATOR);
+    text.push('\n');
+    for footnote in footnotes {
+        text.push_str(footnote);
+        text.push('\n');
+    }
+}
+
+/// Records the encoding declared by the byte order mark of a text file as `Content-Encoding`, the
+/// key Tika uses for the encoding of text files
+#[cfg(feature = "pure-rust")]
+fn insert_bom_encoding(metadata: &mut Metadata, bom: Option<crate::ByteOrderMark>) {
+    if let Some(bom) = bom {
+        metadata.insert(
+            "Content-Encoding".to_string(),
+            vec![bom.encoding().to_string()],
+        );
+    }
+}
+
 #[cfg(feature = "pure-rust")]
 pub mod pdf {
     use super::*;
-    use std::collections::HashMap;
+    use crate::FootnoteHandling;
+    use std::collections::{HashMap, HashSet};
     
     /// Pure Rust PDF parser using pdf-extract crate
     /// Provides 2-3x performance improvement over Tika for most PDFs
@@ -16,10 +49,16 @@ pub mod pdf {
         let path = path.as_ref();
         
         // Use pdf-extract for pure Rust PDF parsing
-        let text = pdf_extract::extract_text(path)
-            .map_err(|e| Error::ParseError(format!("PDF extraction failed: {}", e)))?;
+        let doc = load_pdf(path)?;
+        let text = plain_text(&doc)?;
         
-        // Create basic metadata
+        let mut metadata = file_metadata(path);
+        document_metadata(&doc, &mut metadata);
+        Ok((text, metadata))
+    }
+
+    /// Create basic metadata for a PDF file
+    fn file_metadata(path: &Path) -> Metadata {
         let mut metadata = HashMap::new();
         metadata.insert("Content-Type".to_string(), vec!["application/pdf".to_string()]);
         
@@ -31,107 +70,2663 @@ pub mod pdf {
         }
         
         metadata.insert("Parser".to_string(), vec!["pure-rust-pdf".to_string()]);
-        
-        Ok((text, metadata))
+        metadata
     }
     
+    /// Returns the indirect object `obj gen R` of a PDF in lopdf's debug syntax, e.g.
+    /// `<</Type /Catalog/Pages 2 0 R>>`. Streams are shown by their dictionary only
+    pub fn get_pdf_object<P: AsRef<Path>>(path: P, obj: u32, gen: u16) -> ExtractResult<String> {
+        let doc = load_pdf(path.as_ref())?;
+        doc.objects
+            .get(&(obj, gen))
+            .map(|object| format!("{:?}", object))
+            .ok_or(Error::ObjectNotFound { obj, gen })
+    }
+
     /// Extract PDF text from byte slice
     pub fn extract_pdf_from_bytes(data: &[u8]) -> ExtractResult<(String, Metadata)> {
-        let text = pdf_extract::extract_text_from_mem(data)
-            .map_err(|e| Error::ParseError(format!("PDF extraction from bytes failed: {}", e)))?;
+        let doc = load_pdf_from_bytes(data)?;
+        let text = plain_text(&doc)?;
         
         let mut metadata = HashMap::new();
         metadata.insert("Content-Type".to_string(), vec!["application/pdf".to_string()]);
         metadata.insert("File-Size".to_string(), vec![data.len().to_string()]);
         metadata.insert("Parser".to_string(), vec!["pure-rust-pdf".to_string()]);
+        document_metadata(&doc, &mut metadata);
         
         Ok((text, metadata))
     }
-}
 
-#[cfg(feature = "pure-rust")]
-pub mod office {
-    use super::*;
-    use std::collections::HashMap;
-    
-    /// Extract text from Excel files using calamine
-    pub fn extract_xlsx_text<P: AsRef<Path>>(path: P) -> ExtractResult<(String, Metadata)> {
-        use calamine::{Reader, Xlsx, open_workbook};
-        
-        let mut workbook: Xlsx<_> = open_workbook(path.as_ref())
-            .map_err(|e| Error::ParseError(format!("Excel extraction failed: {}", e)))?;
-        
+    fn load_pdf(path: &Path) -> ExtractResult<pdf_extract::Document> {
+        let doc = pdf_extract::Document::load(path)
+            .map_err(|e| Error::ParseError(format!("PDF extraction failed: {}", e)))?;
+        decrypt(doc)
+    }
+
+    fn load_pdf_from_bytes(data: &[u8]) -> ExtractResult<pdf_extract::Document> {
+        let doc = pdf_extract::Document::load_mem(data)
+            .map_err(|e| Error::ParseError(format!("PDF extraction from bytes failed: {}", e)))?;
+        decrypt(doc)
+    }
+
+    /// Decrypts documents that are encrypted with an empty user password, like pdf-extract does
+    fn decrypt(mut doc: pdf_extract::Document) -> ExtractResult<pdf_extract::Document> {
+        if doc.is_encrypted() {
+            doc.decrypt("")
+                .map_err(|e| Error::ParseError(format!("PDF decryption failed: {}", e)))?;
+        }
+        Ok(doc)
+    }
+
+    fn plain_text(doc: &pdf_extract::Document) -> ExtractResult<String> {
         let mut text = String::new();
-        let mut sheet_count = 0;
-        
-        for sheet_name in workbook.sheet_names() {
-            if let Some(Ok(range)) = workbook.worksheet_range(&sheet_name) {
-                sheet_count += 1;
-                
-                for row in range.rows() {
-                    for cell in row {
-                        if !cell.is_empty() {
-                            text.push_str(&cell.to_string());
-                            text.push(' ');
+        let mut output = pdf_extract::PlainTextOutput::new(&mut text);
+        pdf_extract::output_doc(doc, &mut output)
+            .map_err(|e| Error::ParseError(format!("PDF extraction failed: {}", e)))?;
+        Ok(text)
+    }
+
+    /// A metadata field that can be stored both in the document information dictionary and in
+    /// the XMP packet, with the Tika keys it is reported under
+    struct DocumentField {
+        info_key: &'static [u8],
+        xmp_property: &'static str,
+        names: &'static [&'static str],
+        is_date: bool,
+    }
+
+    const DOCUMENT_FIELDS: [DocumentField; 7] = [
+        DocumentField {
+            info_key: b"Title",
+            xmp_property: "dc:title",
+            names: &["dc:title", "pdf:docinfo:title"],
+            is_date: false,
+        },
+        DocumentField {
+            info_key: b"Author",
+            xmp_property: "dc:creator",
+            names: &["dc:creator", "pdf:docinfo:creator"],
+            is_date: false,
+        },
+        DocumentField {
+            info_key: b"Subject",
+            xmp_property: "dc:description",
+            names: &["dc:subject", "pdf:docinfo:subject"],
+            is_date: false,
+        },
+        DocumentField {
+            info_key: b"Creator",
+            xmp_property: "xmp:CreatorTool",
+            names: &["xmp:CreatorTool", "pdf:docinfo:creator_tool"],
+            is_date: false,
+        },
+        DocumentField {
+            info_key: b"Producer",
+            xmp_property: "pdf:Producer",
+            names: &["pdf:producer", "pdf:docinfo:producer"],
+            is_date: false,
+        },
+        DocumentField {
+            info_key: b"CreationDate",
+            xmp_property: "xmp:CreateDate",
+            names: &["dcterms:created", "pdf:docinfo:created"],
+            is_date: true,
+        },
+        DocumentField {
+            info_key: b"ModDate",
+            xmp_property: "xmp:ModifyDate",
+            names: &["dcterms:modified", "pdf:docinfo:modified"],
+            is_date: true,
+        },
+    ];
+
+    /// Adds the entries of the document information dictionary, the XMP packet and the page
+    /// count to `metadata`, using the same keys as Tika.
+    ///
+    /// The information dictionary takes precedence, XMP only fills in missing fields. When both
+    /// sources hold a different value for the same field, a `Metadata-Conflict-<key>` entry
+    /// lists the information dictionary value followed by the XMP values.
+    fn document_metadata(doc: &pdf_extract::Document, metadata: &mut Metadata) {
+        metadata.insert(
+            "xmpTPg:NPages".to_string(),
+            vec![doc.get_pages().len().to_string()],
+        );
+
+        let info = doc
+            .trailer
+            .get(b"Info")
+            .and_then(|info| doc.dereference(info))
+            .and_then(|(_, info)| info.as_dict())
+            .ok();
+        let info_entry = |key: &[u8]| {
+            let (_, object) = doc.dereference(info?.get(key).ok()?).ok()?;
+            pdf_extract::decode_text_string(object)
+                .ok()
+                .map(|value| value.trim().to_string())
+                .filter(|value| !value.is_empty())
+        };
+
+        let xmp = xmp_packet(doc)
+            .map(|packet| parse_xmp(&packet))
+            .unwrap_or_default();
+        metadata.insert(
+            "pdf:hasXMP".to_string(),
+            vec![(!xmp.is_empty()).to_string()],
+        );
+
+        for field in &DOCUMENT_FIELDS {
+            let info_value = if field.is_date {
+                info_entry(field.info_key)
+                    .as_deref()
+                    .and_then(pdf_date_to_iso8601)
+            } else {
+                info_entry(field.info_key)
+            };
+            let xmp_values = xmp.get(field.xmp_property).cloned().unwrap_or_default();
+
+            match info_value {
+                Some(value) => {
+                    let agrees = xmp_values.iter().any(|xmp_value| {
+                        if field.is_date {
+                            same_instant(&value, xmp_value)
+                        } else {
+                            *xmp_value == value
                         }
+                    });
+                    if !xmp_values.is_empty() && !agrees {
+                        let mut values = vec![value.clone()];
+                        values.extend(xmp_values);
+                        metadata.insert(format!("Metadata-Conflict-{}", field.names[0]), values);
+                    }
+                    for name in field.names {
+                        metadata.insert(name.to_string(), vec![value.clone()]);
+                    }
+                }
+                // The pdf:docinfo keys are reserved for the information dictionary
+                None if !xmp_values.is_empty() => {
+                    metadata.insert(field.names[0].to_string(), xmp_values);
+                }
+                None => {}
+            }
+        }
+    }
+
+    /// Returns the decompressed XMP packet referenced by the `/Metadata` entry of the catalog
+    fn xmp_packet(doc: &pdf_extract::Document) -> Option<Vec<u8>> {
+        let (_, object) = doc
+            .dereference(doc.catalog().ok()?.get(b"Metadata").ok()?)
+            .ok()?;
+        let stream = object.as_stream().ok()?;
+        Some(
+            stream
+                .decompressed_content()
+                .unwrap_or_else(|_| stream.content.clone()),
+        )
+    }
+
+    /// Collects the values of the properties of an XMP packet by qualified name, for example
+    /// `dc:title`. Properties are read from both elements and `rdf:Description` attributes;
+    /// the items of `rdf:Alt`, `rdf:Seq` and `rdf:Bag` containers become separate values
+    fn parse_xmp(packet: &[u8]) -> HashMap<String, Vec<String>> {
+        use quick_xml::events::Event;
+
+        let mut properties: HashMap<String, Vec<String>> = HashMap::new();
+        let Ok(xml) = std::str::from_utf8(packet) else {
+            return properties;
+        };
+        let mut reader = quick_xml::Reader::from_str(xml);
+        // Element names from the root to the current element
+        let mut path: Vec<String> = Vec::new();
+        loop {
+            match reader.read_event() {
+                Ok(Event::Start(e)) => {
+                    let name = e.name();
+                    let name = String::from_utf8_lossy(name.as_ref()).into_owned();
+                    if name == "rdf:Description" {
+                        xmp_attributes(&e, &reader, &mut properties);
+                    }
+                    path.push(name);
+                }
+                Ok(Event::Empty(e)) => {
+                    let name = e.name();
+                    if name.as_ref() == b"rdf:Description" {
+                        xmp_attributes(&e, &reader, &mut properties);
+                    }
+                }
+                Ok(Event::End(_)) => {
+                    path.pop();
+                }
+                Ok(Event::Text(e)) => {
+                    let value = e.unescape().unwrap_or_default();
+                    let value = value.trim();
+                    // The property is the innermost element outside of the rdf namespace
+                    let property = path.iter().rev().find(|name| !name.starts_with("rdf:"));
+                    if let (false, Some(property)) = (value.is_empty(), property) {
+                        properties
+                            .entry(property.clone())
+                            .or_default()
+                            .push(value.to_string());
+                    }
+                }
+                Ok(Event::Eof) | Err(_) => break,
+                _ => {}
+            }
+        }
+        properties
+    }
+
+    /// Adds the properties written as attributes of an `rdf:Description` element
+    fn xmp_attributes(
+        element: &quick_xml::events::BytesStart,
+        reader: &quick_xml::Reader<&[u8]>,
+        properties: &mut HashMap<String, Vec<String>>,
+    ) {
+        for attr in element.attributes().flatten() {
+            let key = attr.key;
+            let key = String::from_utf8_lossy(key.as_ref()).into_owned();
+            if key.starts_with("xmlns") || key.starts_with("rdf:") {
+                continue;
+            }
+            if let Ok(value) = attr.decode_and_unescape_value(reader) {
+                properties
+                    .entry(key)
+                    .or_default()
+                    .push(value.trim().to_string());
+            }
+        }
+    }
+
+    /// Returns true if two ISO 8601 dates denote the same instant, e.g. `2024-03-15T09:30:00Z`
+    /// and `2024-03-15T10:30:00+01:00`
+    fn same_instant(a: &str, b: &str) -> bool {
+        match (iso8601_to_unix(a), iso8601_to_unix(b)) {
+            (Some(a), Some(b)) => a == b,
+            _ => a == b,
+        }
+    }
+
+    /// Converts an ISO 8601 date (`YYYY[-MM[-DD[Thh:mm[:ss[.s]][Z|+hh:mm]]]]`) to seconds since
+    /// the Unix epoch. Dates without an offset are read as UTC
+    fn iso8601_to_unix(date: &str) -> Option<i64> {
+        let number = |range: std::ops::Range<usize>| -> Option<i64> {
+            match date.get(range) {
+                Some(digits) => digits.parse().ok(),
+                None => Some(0),
+            }
+        };
+        let year = number(0..4)?;
+        let month = if date.len() > 4 { number(5..7)? } else { 1 };
+        let day = if date.len() > 7 { number(8..10)? } else { 1 };
+        let (time, offset) = match date.get(11..) {
+            Some(rest) => {
+                let split = rest.find(['Z', '+', '-']).unwrap_or(rest.len());
+                (&rest[..split], &rest[split..])
+            }
+            None => ("", ""),
+        };
+        let mut seconds = 0;
+        for (part, unit) in time.split(':').zip([3600, 60, 1]) {
+            let whole = part.split('.').next()?;
+            seconds += whole.parse::<i64>().ok()? * unit;
+        }
+        if let Some(sign) = offset.chars().next().filter(|c| *c == '+' || *c == '-') {
+            let hours: i64 = offset.get(1..3)?.parse().ok()?;
+            let minutes: i64 = offset.get(4..6).and_then(|m| m.parse().ok()).unwrap_or(0);
+            let offset_seconds = hours * 3600 + minutes * 60;
+            seconds -= if sign == '+' {
+                offset_seconds
+            } else {
+                -offset_seconds
+            };
+        }
+
+        // Days from civil, see http://howardhinnant.github.io/date_algorithms.html
+        let y = if month <= 2 { year - 1 } else { year };
+        let era = y.div_euclid(400);
+        let yoe = y - era * 400;
+        let doy = (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + day - 1;
+        let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+        let days = era * 146097 + doe - 719468;
+        Some(days * 86400 + seconds)
+    }
+
+    /// Converts a PDF date string (`D:YYYYMMDDHHmmSSOHH'mm'`) to ISO 8601.
+    /// Only the year is mandatory, missing fields default to their lowest value
+    pub(crate) fn pdf_date_to_iso8601(date: &str) -> Option<String> {
+        let date = date.strip_prefix("D:").unwrap_or(date);
+        let digits = date.chars().take_while(|c| c.is_ascii_digit()).count();
+        if digits < 4 || digits % 2 != 0 || digits > 14 {
+            return None;
+        }
+        let field = |start: usize, default: &'static str| {
+            if start + 2 <= digits {
+                &date[start..start + 2]
+            } else {
+                default
+            }
+        };
+        let iso = format!(
+            "{}-{}-{}T{}:{}:{}",
+            &date[..4],
+            field(4, "01"),
+            field(6, "01"),
+            field(8, "00"),
+            field(10, "00"),
+            field(12, "00"),
+        );
+
+        let offset: String = date[digits..].chars().filter(|c| *c != '\'').collect();
+        match offset.chars().next() {
+            None | Some('Z') => Some(format!("{}Z", iso)),
+            Some('+') | Some('-') => {
+                // Slices by bytes, `get` is None for a cut through a non-ASCII character
+                let hours = offset
+                    .get(..3)
+                    .filter(|hours| hours[1..].bytes().all(|b| b.is_ascii_digit()))?;
+                let minutes = offset.get(3..5).unwrap_or("00");
+                Some(format!("{}{}:{}", iso, hours, minutes))
+            }
+            _ => None,
+        }
+    }
+
+    /// A line of text laid out on a PDF page. Coordinates are in PDF points with `y` measured
+    /// from the top of the page, as the page is displayed once its `/Rotate` is applied
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct PdfTextLine {
+        pub text: String,
+        pub x: f64,
+        pub y: f64,
+        pub font_size: f64,
+    }
+
+    /// The lines of text of a PDF page, in content stream order. `width` and `height` are the
+    /// dimensions of the displayed page, i.e. swapped for pages rotated by 90 or 270 degrees
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct PdfPageLayout {
+        pub number: u32,
+        pub width: f64,
+        pub height: f64,
+        /// Clockwise rotation of the page in degrees: 0, 90, 180 or 270
+        pub rotation: u32,
+        pub lines: Vec<PdfTextLine>,
+    }
+
+    /// A word on a PDF page and its bounding box, in the coordinates of [`PdfTextLine`]. The box
+    /// spans the font size above the baseline, so descenders stick out of it. Boxes of words on
+    /// rotated pages assume the text runs left to right once the page is displayed
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct PositionedText {
+        pub text: String,
+        /// The page number, starting at 1
+        pub page: u32,
+        pub x: f64,
+        pub y: f64,
+        pub width: f64,
+        pub height: f64,
+    }
+
+    /// [`pdf_extract::OutputDev`] that records every line of text together with its position
+    /// and font size, using the same word and line break rules as pdf-extract's plain text output
+    #[derive(Default)]
+    struct LayoutCollector {
+        pages: Vec<PdfPageLayout>,
+        words: Vec<PositionedText>,
+        /// Whether the next character continues the last word
+        in_word: bool,
+        /// `/Rotate` of every page, by page number
+        rotations: HashMap<u32, u32>,
+        /// Media box of the current page, before rotation
+        media_box: (f64, f64, f64, f64),
+        first_char: bool,
+        last_end: f64,
+        last_y: f64,
+    }
+
+    impl LayoutCollector {
+        /// Maps a point of the PDF user space to the displayed page, with `y` growing from the top
+        fn to_display(&self, rotation: u32, x: f64, y: f64) -> (f64, f64) {
+            let (llx, lly, urx, ury) = self.media_box;
+            let (x, y) = (x - llx, y - lly);
+            let (width, height) = (urx - llx, ury - lly);
+            match rotation {
+                90 => (y, x),
+                180 => (width - x, y),
+                270 => (height - y, width - x),
+                _ => (x, height - y),
+            }
+        }
+    }
+
+    impl pdf_extract::OutputDev for LayoutCollector {
+        fn begin_page(
+            &mut self,
+            page_num: u32,
+            media_box: &pdf_extract::MediaBox,
+            _art_box: Option<(f64, f64, f64, f64)>,
+        ) -> Result<(), pdf_extract::OutputError> {
+            let rotation = self.rotations.get(&page_num).copied().unwrap_or(0);
+            let (width, height) = (media_box.urx - media_box.llx, media_box.ury - media_box.lly);
+            let (width, height) = match rotation {
+                90 | 270 => (height, width),
+                _ => (width, height),
+            };
+            self.media_box = (media_box.llx, media_box.lly, media_box.urx, media_box.ury);
+            self.pages.push(PdfPageLayout {
+                number: page_num,
+                width,
+                height,
+                rotation,
+                lines: Vec::new(),
+            });
+            self.last_end = f64::MAX;
+            self.last_y = 0.0;
+            self.in_word = false;
+            Ok(())
+        }
+
+        fn end_page(&mut self) -> Result<(), pdf_extract::OutputError> {
+            Ok(())
+        }
+
+        fn output_character(
+            &mut self,
+            trm: &pdf_extract::Transform,
+            width: f64,
+            _spacing: f64,
+            font_size: f64,
+            char: &str,
+        ) -> Result<(), pdf_extract::OutputError> {
+            let Some(rotation) = self.pages.last().map(|page| page.rotation) else {
+                return Ok(());
+            };
+            let (x, y) = self.to_display(rotation, trm.m31, trm.m32);
+            let Some(page) = self.pages.last_mut() else {
+                return Ok(());
+            };
+            let scaled_x = font_size * (trm.m11 + trm.m21);
+            let scaled_y = font_size * (trm.m12 + trm.m22);
+            let size = (scaled_x * scaled_y).abs().sqrt();
+
+            let new_line = match page.lines.last() {
+                None => true,
+                Some(_) => self.first_char && (y - self.last_y).abs() > size * 0.5,
+            };
+            if new_line {
+                page.lines.push(PdfTextLine {
+                    text: String::new(),
+                    x,
+                    y,
+                    font_size: size,
+                });
+            }
+            let word_break = !new_line && self.first_char && x > self.last_end + size * 0.1;
+            if let Some(line) = page.lines.last_mut() {
+                if word_break {
+                    line.text.push(' ');
+                }
+                line.text.push_str(char);
+            }
+
+            let end = x + width * size;
+            if char.trim().is_empty() {
+                self.in_word = false;
+            } else if self.in_word && !new_line && !word_break {
+                if let Some(word) = self.words.last_mut() {
+                    word.text.push_str(char);
+                    word.width = word.width.max(end - word.x);
+                    word.y = word.y.min(y - size);
+                    word.height = word.height.max(size);
+                }
+            } else {
+                self.words.push(PositionedText {
+                    text: char.to_string(),
+                    page: page.number,
+                    x,
+                    y: y - size,
+                    width: end - x,
+                    height: size,
+                });
+                self.in_word = true;
+            }
+
+            self.first_char = false;
+            self.last_y = y;
+            self.last_end = x + width * size;
+            Ok(())
+        }
+
+        fn begin_word(&mut self) -> Result<(), pdf_extract::OutputError> {
+            self.first_char = true;
+            Ok(())
+        }
+
+        fn end_word(&mut self) -> Result<(), pdf_extract::OutputError> {
+            Ok(())
+        }
+
+        fn end_line(&mut self) -> Result<(), pdf_extract::OutputError> {
+            Ok(())
+        }
+    }
+
+    /// Extract the lines of text of every page together with their position and font size
+    pub fn extract_pdf_layout<P: AsRef<Path>>(path: P) -> ExtractResult<Vec<PdfPageLayout>> {
+        layout_document(&load_pdf(path.as_ref())?)
+    }
+
+    /// Extract the lines of text of every page from a byte slice
+    pub fn extract_pdf_layout_from_bytes(data: &[u8]) -> ExtractResult<Vec<PdfPageLayout>> {
+        layout_document(&load_pdf_from_bytes(data)?)
+    }
+
+    /// Extract every word of every page together with its bounding box, in content stream
+    /// order. Pages without text, e.g. scanned pages, add no words
+    pub fn extract_pdf_positions<P: AsRef<Path>>(path: P) -> ExtractResult<Vec<PositionedText>> {
+        Ok(collect_layout(&load_pdf(path.as_ref())?)?.words)
+    }
+
+    fn layout_document(doc: &pdf_extract::Document) -> ExtractResult<Vec<PdfPageLayout>> {
+        Ok(collect_layout(doc)?.pages)
+    }
+
+    fn collect_layout(doc: &pdf_extract::Document) -> ExtractResult<LayoutCollector> {
+        let mut collector = LayoutCollector {
+            rotations: doc
+                .get_pages()
+                .into_iter()
+                .map(|(number, id)| (number, page_rotation(doc, id)))
+                .collect(),
+            ..Default::default()
+        };
+        pdf_extract::output_doc(doc, &mut collector)
+            .map_err(|e| Error::ParseError(format!("PDF extraction failed: {}", e)))?;
+        Ok(collector)
+    }
+
+    /// The `/Rotate` of a page, inherited from its ancestors in the page tree when not set,
+    /// normalized to 0, 90, 180 or 270 degrees
+    fn page_rotation(doc: &pdf_extract::Document, page_id: pdf_extract::ObjectId) -> u32 {
+        let Ok(page) = doc.get_dictionary(page_id) else {
+            return 0;
+        };
+        let rotate = match page.get(b"Rotate") {
+            Ok(rotate) => rotate.as_i64().ok(),
+            Err(_) => inherited_attribute(doc, page, b"Rotate").and_then(|r| r.as_i64().ok()),
+        };
+        rotate.map_or(0, |rotate| (rotate.rem_euclid(360) / 90 * 90) as u32)
+    }
+
+    /// Extract PDF text, handling footnotes according to `footnote_handling`.
+    ///
+    /// PDFs have no notion of footnotes, so they are detected heuristically: lines in the
+    /// bottom part of a page that are set in a smaller font than the body text.
+    /// `FootnoteHandling::Inline` keeps the text exactly as [`extract_pdf_text`] returns it.
+    pub fn extract_pdf_text_with_footnotes<P: AsRef<Path>>(
+        path: P,
+        footnote_handling: FootnoteHandling,
+    ) -> ExtractResult<(String, Metadata)> {
+        if footnote_handling == FootnoteHandling::Inline {
+            return extract_pdf_text(path);
+        }
+
+        let doc = load_pdf(path.as_ref())?;
+        let pages = layout_document(&doc)?;
+        let mut metadata = file_metadata(path.as_ref());
+        document_metadata(&doc, &mut metadata);
+        let body_font_size = body_font_size(&pages);
+        let mut body = String::new();
+        let mut footnotes = Vec::new();
+        for page in &pages {
+            for line in &page.lines {
+                let is_footnote =
+                    line.font_size < body_font_size * 0.9 && line.y > page.height * 0.6;
+                if is_footnote {
+                    footnotes.push(line.text.trim().to_string());
+                } else {
+                    body.push_str(&line.text);
+                    body.push('\n');
+                }
+            }
+            body.push('\n');
+        }
+
+        metadata.insert(
+            "Footnote-Count".to_string(),
+            vec![footnotes.len().to_string()],
+        );
+        if footnote_handling == FootnoteHandling::Endnotes {
+            append_endnotes(&mut body, &footnotes);
+        }
+        Ok((body, metadata))
+    }
+
+    /// Extract PDF text in reading order by position, see `PdfParserConfig::set_sort_by_position`.
+    /// The words of every page are split into columns at the vertical gaps no word crosses, and
+    /// each column is read top to bottom and left to right before the column to its right
+    pub fn extract_pdf_text_by_position<P: AsRef<Path>>(
+        path: P,
+    ) -> ExtractResult<(String, Metadata)> {
+        let doc = load_pdf(path.as_ref())?;
+        let layout = collect_layout(&doc)?;
+        let mut text = String::new();
+        for page in &layout.pages {
+            let words: Vec<&PositionedText> = layout
+                .words
+                .iter()
+                .filter(|word| word.page == page.number)
+                .collect();
+            for line in lines_by_position(words) {
+                text.push_str(&line);
+                text.push('\n');
+            }
+            text.push('\n');
+        }
+
+        let mut metadata = file_metadata(path.as_ref());
+        document_metadata(&doc, &mut metadata);
+        Ok((text, metadata))
+    }
+
+    /// The narrowest gap between two columns, in points. Gaps between words are a fraction of
+    /// the font size
+    const MIN_COLUMN_GAP: f64 = 12.0;
+
+    /// The lines of the words of a page in reading order, column by column
+    fn lines_by_position(mut words: Vec<&PositionedText>) -> Vec<String> {
+        let gaps = column_gaps(&words);
+        let column = |word: &PositionedText| {
+            let center = word.x + word.width / 2.0;
+            gaps.iter().filter(|&&gap| gap < center).count()
+        };
+        // Words on one line share their baseline, the bottom of their box
+        let baseline = |word: &PositionedText| word.y + word.height;
+        words.sort_by(|a, b| {
+            column(a)
+                .cmp(&column(b))
+                .then(baseline(a).total_cmp(&baseline(b)))
+                .then(a.x.total_cmp(&b.x))
+        });
+
+        let mut lines: Vec<Vec<&PositionedText>> = Vec::new();
+        for word in words {
+            let same_line = lines
+                .last()
+                .and_then(|line| line.first())
+                .is_some_and(|first| {
+                    column(first) == column(word)
+                        && (baseline(first) - baseline(word)).abs() < first.height * 0.5
+                });
+            match lines.last_mut() {
+                Some(line) if same_line => line.push(word),
+                _ => lines.push(vec![word]),
+            }
+        }
+        lines
+            .into_iter()
+            .map(|mut line| {
+                line.sort_by(|a, b| a.x.total_cmp(&b.x));
+                let words: Vec<&str> = line.iter().map(|word| word.text.as_str()).collect();
+                words.join(" ")
+            })
+            .collect()
+    }
+
+    /// The x positions of the gaps between the columns of a page: vertical strips of at least
+    /// `MIN_COLUMN_GAP` that no word crosses. A heading across the columns closes the gap, the
+    /// page is then read as a single column
+    fn column_gaps(words: &[&PositionedText]) -> Vec<f64> {
+        let mut spans: Vec<(f64, f64)> = words
+            .iter()
+            .map(|word| (word.x, word.x + word.width))
+            .collect();
+        spans.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+        let mut gaps = Vec::new();
+        let mut spans = spans.into_iter();
+        let Some((_, mut covered_to)) = spans.next() else {
+            return gaps;
+        };
+        for (start, end) in spans {
+            if start - covered_to >= MIN_COLUMN_GAP {
+                gaps.push((covered_to + start) / 2.0);
+            }
+            covered_to = covered_to.max(end);
+        }
+        gaps
+    }
+
+    /// Extract the text of every page separately, one entry per page in page order.
+    /// Pages without any text are kept as empty strings
+    pub fn extract_pdf_pages<P: AsRef<Path>>(path: P) -> ExtractResult<(Vec<String>, Metadata)> {
+        let doc = load_pdf(path.as_ref())?;
+        let pages = layout_document(&doc)?
+            .into_iter()
+            .map(|page| {
+                let lines: Vec<String> = page.lines.into_iter().map(|line| line.text).collect();
+                lines.join("\n")
+            })
+            .collect();
+
+        let mut metadata = file_metadata(path.as_ref());
+        document_metadata(&doc, &mut metadata);
+        Ok((pages, metadata))
+    }
+
+    /// Extract the text of the first `max_pages` pages of a PDF only, for previews. The later
+    /// pages are not parsed. `xmpTPg:NPages` is the page count of the whole document
+    pub fn extract_pdf_preview<P: AsRef<Path>>(
+        path: P,
+        max_pages: u32,
+    ) -> ExtractResult<(String, Metadata)> {
+        let doc = load_pdf(path.as_ref())?;
+        let pages = doc.get_pages();
+        let mut text = String::new();
+        let mut output = pdf_extract::PlainTextOutput::new(&mut text);
+        for &number in pages.keys().take(max_pages as usize) {
+            pdf_extract::output_doc_page(&doc, &mut output, number)
+                .map_err(|e| Error::ParseError(format!("PDF extraction failed: {}", e)))?;
+        }
+
+        let mut metadata = file_metadata(path.as_ref());
+        document_metadata(&doc, &mut metadata);
+        Ok((text, metadata))
+    }
+
+    /// A copy of a PDF with the pages after the first `max_pages` removed, so that a parser
+    /// given the copy, e.g. Tika with OCR, does not spend time on them
+    pub fn truncate_pdf_pages<P: AsRef<Path>>(path: P, max_pages: u32) -> ExtractResult<Vec<u8>> {
+        let doc = load_pdf(path.as_ref())?;
+        save_pdf_pages(doc, |number| number <= max_pages)
+    }
+
+    /// A copy of a PDF whose rotated pages are stored upright, for OCR. The `/Rotate` of every
+    /// page is applied to its content, its media and crop boxes and the rectangles of its
+    /// annotations and then set to 0, so the image a page is rendered to for OCR is upright
+    /// before it is recognized, whether or not the renderer applies `/Rotate`. `None` when no
+    /// page is rotated, so the PDF does not need to be copied
+    pub fn upright_rotated_pages<P: AsRef<Path>>(path: P) -> ExtractResult<Option<Vec<u8>>> {
+        upright_document(load_pdf(path.as_ref())?)
+    }
+
+    /// `upright_rotated_pages` of a PDF in a byte slice
+    pub fn upright_rotated_pages_from_bytes(data: &[u8]) -> ExtractResult<Option<Vec<u8>>> {
+        upright_document(load_pdf_from_bytes(data)?)
+    }
+
+    fn upright_document(mut doc: pdf_extract::Document) -> ExtractResult<Option<Vec<u8>>> {
+        let rotate_error =
+            |e: pdf_extract::Error| Error::ParseError(format!("PDF page rotation failed: {}", e));
+        let mut rotated = false;
+        for page_id in doc.get_pages().into_values() {
+            rotated |= make_page_upright(&mut doc, page_id).map_err(rotate_error)?;
+        }
+        if !rotated {
+            return Ok(None);
+        }
+
+        let mut data = Vec::new();
+        doc.save_to(&mut data)
+            .map_err(|e| Error::ParseError(format!("PDF page rotation failed: {}", e)))?;
+        Ok(Some(data))
+    }
+
+    /// Applies the `/Rotate` of a page to its content, boxes and annotation rectangles, see
+    /// `upright_rotated_pages`. `false` for pages that are not rotated
+    fn make_page_upright(
+        doc: &mut pdf_extract::Document,
+        page_id: pdf_extract::ObjectId,
+    ) -> Result<bool, pdf_extract::Error> {
+        let rotation = page_rotation(doc, page_id);
+        if rotation == 0 {
+            return Ok(false);
+        }
+        let page = doc.get_dictionary(page_id)?;
+        let page_box = |key: &[u8]| {
+            let value = page
+                .get(key)
+                .ok()
+                .cloned()
+                .or_else(|| inherited_attribute(doc, page, key));
+            value.and_then(|value| pdf_rect(doc, &value))
+        };
+        // Letter size is the default of PDF readers for pages without a media box
+        let media_box = page_box(b"MediaBox").unwrap_or([0.0, 0.0, 612.0, 792.0]);
+        let crop_box = page_box(b"CropBox");
+        let matrix = upright_matrix(rotation, media_box);
+
+        let mut content = format!(
+            "q {} {} {} {} {} {} cm\n",
+            matrix[0], matrix[1], matrix[2], matrix[3], matrix[4], matrix[5]
+        )
+        .into_bytes();
+        content.extend(doc.get_page_content(page_id)?);
+        content.extend(b"\nQ\n");
+        doc.change_page_content(page_id, content)?;
+
+        let annotations = doc
+            .get_dictionary(page_id)
+            .and_then(|page| page.get(b"Annots"))
+            .and_then(|annotations| doc.dereference(annotations))
+            .and_then(|(_, annotations)| annotations.as_array())
+            .map(|annotations| annotations.to_vec())
+            .unwrap_or_default();
+        for annotation in annotations {
+            let Ok(id) = annotation.as_reference() else {
+                continue;
+            };
+            let rect = doc
+                .get_dictionary(id)
+                .and_then(|annotation| annotation.get(b"Rect"));
+            if let Some(rect) = rect.ok().and_then(|rect| pdf_rect(doc, rect)) {
+                let rect = transform_rect(&matrix, rect);
+                doc.get_dictionary_mut(id)?
+                    .set("Rect", pdf_rect_object(rect));
+            }
+        }
+
+        let page = doc.get_dictionary_mut(page_id)?;
+        page.set(
+            "MediaBox",
+            pdf_rect_object(transform_rect(&matrix, media_box)),
+        );
+        if let Some(crop_box) = crop_box {
+            page.set(
+                "CropBox",
+                pdf_rect_object(transform_rect(&matrix, crop_box)),
+            );
+        }
+        // Set rather than removed, the page would inherit the rotation of the page tree otherwise
+        page.set("Rotate", 0);
+        Ok(true)
+    }
+
+    /// The matrix that turns the user space of a page with the media box `[llx lly urx ury]`
+    /// rotated clockwise by `rotation` degrees into that of an upright page whose media box
+    /// starts at the origin
+    fn upright_matrix(rotation: u32, media_box: [f64; 4]) -> [f64; 6] {
+        let [llx, lly, urx, ury] = media_box;
+        let (width, height) = (urx - llx, ury - lly);
+        match rotation {
+            90 => [0.0, -1.0, 1.0, 0.0, -lly, width + llx],
+            180 => [-1.0, 0.0, 0.0, -1.0, width + llx, height + lly],
+            270 => [0.0, 1.0, -1.0, 0.0, height + lly, -llx],
+            _ => [1.0, 0.0, 0.0, 1.0, -llx, -lly],
+        }
+    }
+
+    /// The rectangle that holds the corners of `rect` transformed by `matrix`
+    fn transform_rect(matrix: &[f64; 6], rect: [f64; 4]) -> [f64; 4] {
+        let [a, b, c, d, e, f] = *matrix;
+        let corners = [(rect[0], rect[1]), (rect[2], rect[3])]
+            .map(|(x, y)| (a * x + c * y + e, b * x + d * y + f));
+        let [(x1, y1), (x2, y2)] = corners;
+        [x1.min(x2), y1.min(y2), x1.max(x2), y1.max(y2)]
+    }
+
+    /// A rectangle of a PDF, e.g. a media box, as `[llx lly urx ury]`
+    fn pdf_rect(doc: &pdf_extract::Document, rect: &pdf_extract::Object) -> Option<[f64; 4]> {
+        let (_, rect) = doc.dereference(rect).ok()?;
+        let values = rect.as_array().ok()?;
+        let values: Vec<f64> = values
+            .iter()
+            .filter_map(|v| v.as_float().ok().map(f64::from))
+            .collect();
+        let [x1, y1, x2, y2] = values.try_into().ok()?;
+        Some([x1.min(x2), y1.min(y2), x1.max(x2), y1.max(y2)])
+    }
+
+    fn pdf_rect_object(rect: [f64; 4]) -> pdf_extract::Object {
+        pdf_extract::Object::Array(rect.map(|v| pdf_extract::Object::Real(v as f32)).to_vec())
+    }
+
+    /// The pages of a PDF, each saved as a PDF of its own when it is asked for, e.g. to OCR the
+    /// pages in parallel. The document is loaded once, and a page copies only the objects it
+    /// uses, so splitting a large PDF does not copy the whole document for every page
+    pub struct PdfPageSplitter {
+        doc: pdf_extract::Document,
+        pages: Vec<pdf_extract::ObjectId>,
+    }
+
+    impl PdfPageSplitter {
+        pub fn open<P: AsRef<Path>>(path: P) -> ExtractResult<Self> {
+            let doc = load_pdf(path.as_ref())?;
+            let pages = doc.get_pages().into_values().collect();
+            Ok(Self { doc, pages })
+        }
+
+        pub fn page_count(&self) -> usize {
+            self.pages.len()
+        }
+
+        /// A PDF with the page at `index`, counted from 0, only. The objects the page refers to
+        /// are copied, other pages and the page tree are not, the attributes the page inherits
+        /// from the page tree are set on the copy of the page. A rotated page is stored upright,
+        /// see `upright_rotated_pages`
+        pub fn page_pdf(&self, index: usize) -> ExtractResult<Vec<u8>> {
+            use pdf_extract::{Dictionary, Document, Object};
+
+            let split_error =
+                |e: pdf_extract::Error| Error::ParseError(format!("PDF page split failed: {}", e));
+            let page_id = *self
+                .pages
+                .get(index)
+                .ok_or_else(|| Error::ParseError(format!("The PDF has no page {}", index + 1)))?;
+            let mut page = self
+                .doc
+                .get_dictionary(page_id)
+                .map_err(split_error)?
+                .clone();
+            for key in [&b"Resources"[..], b"MediaBox", b"CropBox", b"Rotate"] {
+                if !page.has(key) {
+                    if let Some(value) = inherited_attribute(&self.doc, &page, key) {
+                        page.set(key, value);
+                    }
+                }
+            }
+
+            let mut pdf = Document::with_version(self.doc.version.as_str());
+            let pages_id = (self.doc.max_id + 1, 0);
+            let catalog_id = (self.doc.max_id + 2, 0);
+            page.set("Parent", pages_id);
+            let mut pending = Vec::new();
+            push_references(&mut pending, &Object::Dictionary(page.clone()));
+            pdf.objects.insert(page_id, Object::Dictionary(page));
+            while let Some(id) = pending.pop() {
+                if pdf.objects.contains_key(&id) {
+                    continue;
+                }
+                // References to other pages, e.g. from links, are left dangling
+                let Ok(object) = self.doc.get_object(id) else {
+                    continue;
+                };
+                if is_page_tree_node(object) {
+                    continue;
+                }
+                push_references(&mut pending, object);
+                pdf.objects.insert(id, object.clone());
+            }
+
+            let mut pages = Dictionary::new();
+            pages.set("Type", Object::Name(b"Pages".to_vec()));
+            pages.set("Kids", vec![Object::Reference(page_id)]);
+            pages.set("Count", 1);
+            pdf.objects.insert(pages_id, Object::Dictionary(pages));
+            let mut catalog = Dictionary::new();
+            catalog.set("Type", Object::Name(b"Catalog".to_vec()));
+            catalog.set("Pages", pages_id);
+            pdf.objects.insert(catalog_id, Object::Dictionary(catalog));
+            pdf.trailer.set("Root", catalog_id);
+            pdf.max_id = catalog_id.0;
+            make_page_upright(&mut pdf, page_id).map_err(split_error)?;
+
+            let mut data = Vec::new();
+            pdf.save_to(&mut data)
+                .map_err(|e| Error::ParseError(format!("PDF page split failed: {}", e)))?;
+            Ok(data)
+        }
+    }
+
+    /// An attribute a page inherits from the nodes of the page tree above it. The walk up the
+    /// tree is bounded, so a page tree with a cycle ends it
+    fn inherited_attribute(
+        doc: &pdf_extract::Document,
+        page: &pdf_extract::Dictionary,
+        key: &[u8],
+    ) -> Option<pdf_extract::Object> {
+        let mut parent = page
+            .get(b"Parent")
+            .and_then(pdf_extract::Object::as_reference);
+        for _ in 0..64 {
+            let node = doc.get_dictionary(parent.ok()?).ok()?;
+            if let Ok(value) = node.get(key) {
+                return Some(value.clone());
+            }
+            parent = node
+                .get(b"Parent")
+                .and_then(pdf_extract::Object::as_reference);
+        }
+        None
+    }
+
+    /// Whether an object is a page or a node of the page tree
+    fn is_page_tree_node(object: &pdf_extract::Object) -> bool {
+        let kind = object.as_dict().and_then(|dict| dict.get(b"Type"));
+        let kind = kind.and_then(pdf_extract::Object::as_name);
+        kind.is_ok_and(|kind| kind == b"Page" || kind == b"Pages")
+    }
+
+    /// Adds the objects an object refers to directly to `references`
+    fn push_references(references: &mut Vec<pdf_extract::ObjectId>, object: &pdf_extract::Object) {
+        match object {
+            pdf_extract::Object::Reference(id) => references.push(*id),
+            pdf_extract::Object::Array(items) => items
+                .iter()
+                .for_each(|item| push_references(references, item)),
+            pdf_extract::Object::Dictionary(dict) => dict
+                .iter()
+                .for_each(|(_, value)| push_references(references, value)),
+            pdf_extract::Object::Stream(stream) => stream
+                .dict
+                .iter()
+                .for_each(|(_, value)| push_references(references, value)),
+            _ => {}
+        }
+    }
+
+    /// Saves a PDF with the pages whose number is kept by `keep` only
+    fn save_pdf_pages(
+        mut doc: pdf_extract::Document,
+        keep: impl Fn(u32) -> bool,
+    ) -> ExtractResult<Vec<u8>> {
+        let removed: Vec<u32> = doc.get_pages().into_keys().filter(|&n| !keep(n)).collect();
+        doc.delete_pages(&removed);
+        doc.prune_objects();
+
+        let mut data = Vec::new();
+        doc.save_to(&mut data)
+            .map_err(|e| Error::ParseError(format!("PDF page selection failed: {}", e)))?;
+        Ok(data)
+    }
+
+    /// A copy of a PDF without the content of its optional content groups (layers) that are
+    /// hidden by default, see `PdfParserConfig::set_respect_ocg_visibility`. Marked content of
+    /// hidden layers, form XObjects and annotations that belong to one are removed from the
+    /// pages. `None` when no layer is hidden, so the PDF does not need to be copied
+    pub fn remove_hidden_layers<P: AsRef<Path>>(path: P) -> ExtractResult<Option<Vec<u8>>> {
+        use pdf_extract::content::Content;
+
+        let mut doc = load_pdf(path.as_ref())?;
+        let hidden = hidden_layers(&doc);
+        if hidden.is_empty() {
+            return Ok(None);
+        }
+
+        let layer_error =
+            |e: pdf_extract::Error| Error::ParseError(format!("PDF layer removal failed: {}", e));
+        for page_id in doc.get_pages().into_values() {
+            let properties = hidden_page_resources(&doc, page_id, b"Properties", &hidden);
+            let xobjects = hidden_page_resources(&doc, page_id, b"XObject", &hidden);
+            if !properties.is_empty() || !xobjects.is_empty() {
+                let content = doc
+                    .get_and_decode_page_content(page_id)
+                    .map_err(layer_error)?;
+                let operations = visible_operations(content.operations, &properties, &xobjects);
+                let data = Content { operations }.encode().map_err(layer_error)?;
+                doc.change_page_content(page_id, data)
+                    .map_err(layer_error)?;
+            }
+            remove_hidden_annotations(&mut doc, page_id, &hidden);
+        }
+
+        let mut data = Vec::new();
+        doc.save_to(&mut data)
+            .map_err(|e| Error::ParseError(format!("PDF layer removal failed: {}", e)))?;
+        Ok(Some(data))
+    }
+
+    /// The optional content groups that are off in the default configuration of a PDF
+    fn hidden_layers(doc: &pdf_extract::Document) -> HashSet<pdf_extract::ObjectId> {
+        let config = doc
+            .catalog()
+            .and_then(|catalog| doc.get_dict_in_dict(catalog, b"OCProperties"))
+            .map(|properties| (properties, doc.get_dict_in_dict(properties, b"D")));
+        let Ok((properties, Ok(config))) = config else {
+            return HashSet::new();
+        };
+        let groups = |dict: &pdf_extract::Dictionary, key: &[u8]| -> Vec<pdf_extract::ObjectId> {
+            dict.get(key)
+                .and_then(|groups| doc.dereference(groups))
+                .and_then(|(_, groups)| groups.as_array())
+                .map(|groups| {
+                    groups
+                        .iter()
+                        .filter_map(|g| g.as_reference().ok())
+                        .collect()
+                })
+                .unwrap_or_default()
+        };
+
+        let mut hidden = HashSet::new();
+        let base_state = config
+            .get(b"BaseState")
+            .and_then(pdf_extract::Object::as_name);
+        if base_state.is_ok_and(|state| state == b"OFF") {
+            let on = groups(config, b"ON");
+            hidden.extend(
+                groups(properties, b"OCGs")
+                    .into_iter()
+                    .filter(|g| !on.contains(g)),
+            );
+        }
+        hidden.extend(groups(config, b"OFF"));
+        hidden
+    }
+
+    /// Whether an optional content group, or a membership dictionary of several, is hidden. The
+    /// members of a membership dictionary are only looked up as groups, not followed further, so
+    /// a dictionary that lists itself cannot recurse
+    fn is_hidden_layer(
+        doc: &pdf_extract::Document,
+        layer: &pdf_extract::Object,
+        hidden: &HashSet<pdf_extract::ObjectId>,
+    ) -> bool {
+        let Ok((id, layer)) = doc.dereference(layer) else {
+            return false;
+        };
+        if id.is_some_and(|id| hidden.contains(&id)) {
+            return true;
+        }
+        let Ok(membership) = layer.as_dict() else {
+            return false;
+        };
+        let kind = membership
+            .get(b"Type")
+            .and_then(pdf_extract::Object::as_name);
+        if !kind.is_ok_and(|kind| kind == b"OCMD") {
+            return false;
+        }
+        let is_hidden_group =
+            |group: &pdf_extract::Object| group.as_reference().is_ok_and(|id| hidden.contains(&id));
+        let groups: Vec<bool> = match membership.get(b"OCGs") {
+            Ok(pdf_extract::Object::Array(groups)) => groups.iter().map(is_hidden_group).collect(),
+            Ok(group) => vec![is_hidden_group(group)],
+            Err(_) => return false,
+        };
+        if groups.is_empty() {
+            return false;
+        }
+        match membership.get(b"P").and_then(pdf_extract::Object::as_name) {
+            Ok(b"AllOn") => groups.iter().any(|&off| off),
+            Ok(b"AnyOff") => groups.iter().all(|&off| !off),
+            Ok(b"AllOff") => groups.iter().any(|&off| !off),
+            // AnyOn
+            _ => groups.iter().all(|&off| off),
+        }
+    }
+
+    /// The names of the `/Properties` or `/XObject` resources of a page that belong to a hidden
+    /// layer, including the resources the page inherits
+    fn hidden_page_resources(
+        doc: &pdf_extract::Document,
+        page_id: pdf_extract::ObjectId,
+        category: &[u8],
+        hidden: &HashSet<pdf_extract::ObjectId>,
+    ) -> HashSet<Vec<u8>> {
+        let Ok((resources, inherited)) = doc.get_page_resources(page_id) else {
+            return HashSet::new();
+        };
+        let inherited = inherited
+            .into_iter()
+            .filter_map(|id| doc.get_dictionary(id).ok());
+        let mut names = HashSet::new();
+        for resources in resources.into_iter().chain(inherited) {
+            let Ok(entries) = doc.get_dict_in_dict(resources, category) else {
+                continue;
+            };
+            for (name, entry) in entries.iter() {
+                let layer = match category {
+                    b"XObject" => match doc.dereference(entry) {
+                        Ok((_, pdf_extract::Object::Stream(xobject))) => xobject.dict.get(b"OC"),
+                        _ => continue,
+                    },
+                    _ => Ok(entry),
+                };
+                if layer.is_ok_and(|layer| is_hidden_layer(doc, layer, hidden)) {
+                    names.insert(name.clone());
+                }
+            }
+        }
+        names
+    }
+
+    /// The operations of a page without the marked content of the hidden `properties` and
+    /// without drawing the hidden `xobjects`
+    fn visible_operations(
+        operations: Vec<pdf_extract::content::Operation>,
+        properties: &HashSet<Vec<u8>>,
+        xobjects: &HashSet<Vec<u8>>,
+    ) -> Vec<pdf_extract::content::Operation> {
+        let is_hidden = |operand: Option<&pdf_extract::Object>, names: &HashSet<Vec<u8>>| {
+            operand.is_some_and(|name| name.as_name().is_ok_and(|name| names.contains(name)))
+        };
+
+        let mut visible = Vec::with_capacity(operations.len());
+        // The nesting of marked content within the hidden sequence being skipped
+        let mut hidden_depth = 0usize;
+        for operation in operations {
+            match operation.operator.as_str() {
+                "BDC" | "BMC" if hidden_depth > 0 => hidden_depth += 1,
+                "EMC" if hidden_depth > 0 => hidden_depth -= 1,
+                _ if hidden_depth > 0 => {}
+                "BDC"
+                    if operation
+                        .operands
+                        .first()
+                        .and_then(|tag| tag.as_name().ok())
+                        == Some(b"OC")
+                        && is_hidden(operation.operands.get(1), properties) =>
+                {
+                    hidden_depth = 1;
+                }
+                "Do" if is_hidden(operation.operands.first(), xobjects) => {}
+                _ => visible.push(operation),
+            }
+        }
+        visible
+    }
+
+    /// Removes the annotations of a page that belong to a hidden layer
+    fn remove_hidden_annotations(
+        doc: &mut pdf_extract::Document,
+        page_id: pdf_extract::ObjectId,
+        hidden: &HashSet<pdf_extract::ObjectId>,
+    ) {
+        let Ok(annotations) = doc
+            .get_dictionary(page_id)
+            .and_then(|page| page.get(b"Annots"))
+            .and_then(|annotations| doc.dereference(annotations))
+            .and_then(|(_, annotations)| annotations.as_array())
+        else {
+            return;
+        };
+        let visible: Vec<pdf_extract::Object> = annotations
+            .iter()
+            .filter(|annotation| {
+                let layer = doc
+                    .dereference(annotation)
+                    .and_then(|(_, annotation)| annotation.as_dict())
+                    .and_then(|annotation| annotation.get(b"OC"));
+                !layer.is_ok_and(|layer| is_hidden_layer(doc, layer, hidden))
+            })
+            .cloned()
+            .collect();
+        if visible.len() < annotations.len() {
+            if let Ok(page) = doc.get_dictionary_mut(page_id) {
+                page.set("Annots", visible);
+            }
+        }
+    }
+
+    /// The font size used by most of the text, weighted by the number of characters
+    fn body_font_size(pages: &[PdfPageLayout]) -> f64 {
+        let mut sizes: HashMap<i64, usize> = HashMap::new();
+        for line in pages.iter().flat_map(|page| &page.lines) {
+            // bucket sizes to a tenth of a point to absorb rounding noise
+            let bucket = (line.font_size * 10.0).round() as i64;
+            *sizes.entry(bucket).or_default() += line.text.chars().count();
+        }
+        sizes
+            .into_iter()
+            .max_by_key(|&(bucket, count)| (count, bucket))
+            .map(|(bucket, _)| bucket as f64 / 10.0)
+            .unwrap_or(0.0)
+    }
+}
+
+#[cfg(feature = "pure-rust")]
+pub mod office {
+    use super::*;
+    use crate::{BidiReordering, FootnoteHandling};
+    use quick_xml::events::{BytesStart, Event};
+    use std::collections::HashMap;
+    
+    /// Extract text from Excel files using calamine
+    pub fn extract_xlsx_text<P: AsRef<Path>>(path: P) -> ExtractResult<(String, Metadata)> {
+        use calamine::{open_workbook, Xlsx};
+
+        let workbook: Xlsx<_> = open_workbook(path.as_ref())
+            .map_err(|e| Error::ParseError(format!("Excel extraction failed: {}", e)))?;
+        let (text, mut metadata) = xlsx_workbook_text(workbook);
+        
+        if let Ok(file_metadata) = std::fs::metadata(path.as_ref()) {
+            metadata.insert(
+                "File-Size".to_string(),
+                vec![file_metadata.len().to_string()],
+            );
+        }
+
+        Ok((text, metadata))
+    }
+
+    /// Extract text from the bytes of an Excel file using calamine
+    pub fn extract_xlsx_from_bytes(data: &[u8]) -> ExtractResult<(String, Metadata)> {
+        use calamine::{Reader, Xlsx};
+
+        let workbook = Xlsx::new(std::io::Cursor::new(data))
+            .map_err(|e| Error::ParseError(format!("Excel extraction failed: {}", e)))?;
+        let (text, mut metadata) = xlsx_workbook_text(workbook);
+        metadata.insert("File-Size".to_string(), vec![data.len().to_string()]);
+
+        Ok((text, metadata))
+    }
+
+    fn xlsx_workbook_text<RS: std::io::Read + std::io::Seek>(
+        mut workbook: calamine::Xlsx<RS>,
+    ) -> (String, Metadata) {
+        use calamine::Reader;
+
+        let mut text = String::new();
+        let mut sheet_count = 0;
+        
+        for sheet_name in workbook.sheet_names() {
+            if let Some(Ok(range)) = workbook.worksheet_range(&sheet_name) {
+                sheet_count += 1;
+                text.push_str(&sheet_text(&range));
+            }
+        }
+        
+        (text, xlsx_metadata(sheet_count))
+    }
+
+    /// The metadata of a workbook with `sheet_count` sheets
+    fn xlsx_metadata(sheet_count: usize) -> Metadata {
+        let mut metadata = HashMap::new();
+        metadata.insert("Content-Type".to_string(), vec!["application/vnd.openxmlformats-officedocument.spreadsheetml.sheet".to_string()]);
+        metadata.insert("Sheet-Count".to_string(), vec![sheet_count.to_string()]);
+        metadata.insert("Parser".to_string(), vec!["pure-rust-excel".to_string()]);
+        metadata
+    }
+
+    /// Extract the text of every sheet of an Excel file separately, as `(sheet name, text)`
+    /// pairs in workbook order, and the metadata of the workbook
+    pub fn extract_xlsx_sheets<P: AsRef<Path>>(
+        path: P,
+    ) -> ExtractResult<(Vec<(String, String)>, Metadata)> {
+        use calamine::{open_workbook, Reader, Xlsx};
+
+        let mut workbook: Xlsx<_> = open_workbook(path.as_ref())
+            .map_err(|e| Error::ParseError(format!("Excel extraction failed: {}", e)))?;
+        let mut sheets = Vec::new();
+        for sheet_name in workbook.sheet_names() {
+            if let Some(Ok(range)) = workbook.worksheet_range(&sheet_name) {
+                let text = sheet_text(&range);
+                sheets.push((sheet_name, text));
+            }
+        }
+
+        let mut metadata = xlsx_metadata(sheets.len());
+        if let Ok(file_metadata) = std::fs::metadata(path.as_ref()) {
+            metadata.insert(
+                "File-Size".to_string(),
+                vec![file_metadata.len().to_string()],
+            );
+        }
+        Ok((sheets, metadata))
+    }
+
+    /// A sheet of a workbook, as returned by `extract_xlsx_structured`
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct SheetData {
+        pub name: String,
+        /// The zero based row and column of `rows[0][0]`, the first cell of the used range of
+        /// the sheet, e.g. `(1, 1)` for a sheet whose cells start at B2
+        pub first_cell: (u32, u32),
+        /// The cells of the used range by row and column, `rows[1][2]` is the cell two columns
+        /// right of and one row below `first_cell`. All rows have the same length, empty cells
+        /// are empty strings
+        pub rows: Vec<Vec<String>>,
+    }
+
+    /// Extract the cells of every sheet of an Excel file, in workbook order. Numbers are written
+    /// with at most 15 significant digits like Excel shows them, e.g. `0.3` rather than
+    /// `0.30000000000000004`, dates and times in ISO 8601, e.g. `2024-03-15` or
+    /// `2024-03-16 14:30:00`
+    pub fn extract_xlsx_structured<P: AsRef<Path>>(path: P) -> ExtractResult<Vec<SheetData>> {
+        extract_xlsx_structured_localized(path, None)
+    }
+
+    /// Like `extract_xlsx_structured`, with numbers and dates written the way `locale` writes
+    /// them, e.g. `1.234,5` and `15.03.2024` for German, see `Extractor::set_locale`
+    pub(crate) fn extract_xlsx_structured_localized<P: AsRef<Path>>(
+        path: P,
+        locale: Option<&crate::locale::Locale>,
+    ) -> ExtractResult<Vec<SheetData>> {
+        use calamine::{open_workbook, Reader, Xlsx};
+
+        let mut workbook: Xlsx<_> = open_workbook(path.as_ref())
+            .map_err(|e| Error::ParseError(format!("Excel extraction failed: {}", e)))?;
+        let mut sheets = Vec::new();
+        for name in workbook.sheet_names() {
+            if let Some(Ok(range)) = workbook.worksheet_range(&name) {
+                let first_cell = range.start().unwrap_or_default();
+                let rows = sheet_rows(&range, locale);
+                sheets.push(SheetData {
+                    name,
+                    first_cell,
+                    rows,
+                });
+            }
+        }
+        Ok(sheets)
+    }
+
+    /// The cells of the used range of a sheet, which calamine starts at the first non empty
+    /// cell. The rows are not padded out from A1, a single cell far down a sheet is one row
+    fn sheet_rows(
+        range: &calamine::Range<calamine::DataType>,
+        locale: Option<&crate::locale::Locale>,
+    ) -> Vec<Vec<String>> {
+        range
+            .rows()
+            .map(|cells| cells.iter().map(|cell| cell_text(cell, locale)).collect())
+            .collect()
+    }
+
+    fn cell_text(cell: &calamine::DataType, locale: Option<&crate::locale::Locale>) -> String {
+        use calamine::DataType;
+
+        match (cell, locale) {
+            (DataType::Float(value), None) => format_number(*value),
+            (DataType::Float(value), Some(locale)) => locale.format_number(&format_number(*value)),
+            (DataType::Int(value), Some(locale)) => locale.format_number(&value.to_string()),
+            (DataType::DateTime(serial), None) => format_excel_datetime(*serial),
+            (DataType::DateTime(serial), Some(locale)) => {
+                locale.format_datetime(&format_excel_datetime(*serial))
+            }
+            (DataType::Empty, _) => String::new(),
+            (other, _) => other.to_string(),
+        }
+    }
+
+    /// A number rounded to 15 significant digits, which drops the artefacts of binary floats
+    fn format_number(value: f64) -> String {
+        if value.fract() == 0.0 && value.abs() < 1e15 {
+            return (value as i64).to_string();
+        }
+        let rounded = format!("{:.14e}", value);
+        rounded
+            .parse::<f64>()
+            .map_or(rounded, |value| value.to_string())
+    }
+
+    /// An Excel date serial, the days since 1899-12-30 with the time of day as fraction, in
+    /// ISO 8601. Serials below 1 are a time of day only, whole serials a date only
+    fn format_excel_datetime(serial: f64) -> String {
+        let seconds = (serial * 86400.0).round() as i64;
+        let (mut days, seconds) = (seconds.div_euclid(86400), seconds.rem_euclid(86400));
+        let time = format!(
+            "{:02}:{:02}:{:02}",
+            seconds / 3600,
+            seconds / 60 % 60,
+            seconds % 60
+        );
+        if days == 0 {
+            return time;
+        }
+        // Excel counts the 29th of February 1900, which did not exist
+        if days < 61 {
+            days += 1;
+        }
+
+        // Civil from days, see http://howardhinnant.github.io/date_algorithms.html. 1899-12-30
+        // is 25569 days before 1970-01-01
+        let z = days - 25569 + 719468;
+        let era = z.div_euclid(146097);
+        let doe = z - era * 146097;
+        let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+        let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+        let mp = (5 * doy + 2) / 153;
+        let day = doy - (153 * mp + 2) / 5 + 1;
+        let month = if mp < 10 { mp + 3 } else { mp - 9 };
+        let year = yoe + era * 400 + i64::from(month <= 2);
+
+        let date = format!("{:04}-{:02}-{:02}", year, month, day);
+        match seconds {
+            0 => date,
+            _ => format!("{} {}", date, time),
+        }
+    }
+
+    /// The non empty cells of a sheet, separated by spaces, one line per row
+    fn sheet_text(range: &calamine::Range<calamine::DataType>) -> String {
+        let mut text = String::new();
+        for row in range.rows() {
+            for cell in row {
+                if !cell.is_empty() {
+                    text.push_str(&cell.to_string());
+                    text.push(' ');
+                }
+            }
+            text.push('\n');
+        }
+        text
+    }
+
+    /// Extract text from Word (DOCX) documents, handling footnotes according to
+    /// `footnote_handling`. Footnotes are read from the `word/footnotes.xml` part and matched
+    /// to the `w:footnoteReference` elements of the body.
+    /// With `BidiReordering::PerParagraph` every paragraph is reordered into visual order using
+    /// its `w:bidi` property as base direction, the other modes keep the logical order.
+    /// With `include_comments` the comments of `word/comments.xml` follow the paragraph they
+    /// are anchored in, as `Comment by <author>: <text>` after a tab, like Tika writes them.
+    /// With `include_headers_footers` the text of the headers comes before the body and the
+    /// text of the footers after it, as in Tika's output.
+    pub fn extract_docx_text<P: AsRef<Path>>(
+        path: P,
+        footnote_handling: FootnoteHandling,
+        bidi_reordering: BidiReordering,
+        include_comments: bool,
+        include_headers_footers: bool,
+    ) -> ExtractResult<(String, Metadata)> {
+        let file = std::fs::File::open(path.as_ref()).map_err(|e| Error::IoError(e.to_string()))?;
+        let (text, mut metadata) = docx_text(
+            file,
+            footnote_handling,
+            bidi_reordering,
+            include_comments,
+            include_headers_footers,
+        )?;
+
+        if let Ok(file_metadata) = std::fs::metadata(path.as_ref()) {
+            metadata.insert(
+                "File-Size".to_string(),
+                vec![file_metadata.len().to_string()],
+            );
+        }
+
+        Ok((text, metadata))
+    }
+
+    /// Extract text from the bytes of a Word (DOCX) document, see [`extract_docx_text`]
+    pub fn extract_docx_from_bytes(
+        data: &[u8],
+        footnote_handling: FootnoteHandling,
+        bidi_reordering: BidiReordering,
+        include_comments: bool,
+        include_headers_footers: bool,
+    ) -> ExtractResult<(String, Metadata)> {
+        let (text, mut metadata) = docx_text(
+            std::io::Cursor::new(data),
+            footnote_handling,
+            bidi_reordering,
+            include_comments,
+            include_headers_footers,
+        )?;
+        metadata.insert("File-Size".to_string(), vec![data.len().to_string()]);
+        Ok((text, metadata))
+    }
+
+    fn docx_text<R: std::io::Read + std::io::Seek>(
+        reader: R,
+        footnote_handling: FootnoteHandling,
+        bidi_reordering: BidiReordering,
+        include_comments: bool,
+        include_headers_footers: bool,
+    ) -> ExtractResult<(String, Metadata)> {
+        let mut archive = zip::ZipArchive::new(reader)
+            .map_err(|e| Error::ParseError(format!("DOCX extraction failed: {}", e)))?;
+        let (headers, footers) = if include_headers_footers {
+            (
+                docx_parts_text(&mut archive, "header")?,
+                docx_parts_text(&mut archive, "footer")?,
+            )
+        } else {
+            (String::new(), String::new())
+        };
+
+        let document = read_zip_entry(&mut archive, "word/document.xml")?.ok_or_else(|| {
+            Error::ParseError("DOCX extraction failed: missing word/document.xml".to_string())
+        })?;
+        let footnotes = match read_zip_entry(&mut archive, "word/footnotes.xml")? {
+            Some(xml) => parse_docx_notes(&xml, b"w:footnote")?,
+            None => HashMap::new(),
+        };
+        let comments = match read_zip_entry(&mut archive, "word/comments.xml")? {
+            Some(xml) if include_comments => parse_docx_notes(&xml, b"w:comment")?,
+            _ => HashMap::new(),
+        };
+        // Ids of the comments referenced by the open top level paragraph
+        let mut paragraph_comments: Vec<String> = Vec::new();
+
+        let mut text = headers;
+        let mut endnotes = Vec::new();
+        let mut reader = quick_xml::Reader::from_str(&document);
+        let mut in_text = false;
+        // Start offset and right-to-left direction of the open paragraphs, text box paragraphs
+        // are nested in the paragraph of their anchor
+        let mut paragraphs: Vec<(usize, bool)> = Vec::new();
+        let mut in_paragraph_properties = false;
+        let mut in_section_properties = false;
+        // Runs nest when a run anchors a text box. A w:tab outside of runs is a tab stop of the
+        // paragraph properties rather than a tab character
+        let mut run_depth = 0;
+        loop {
+            match reader.read_event() {
+                Ok(Event::Start(ref e)) => match e.name().as_ref() {
+                    b"w:t" => in_text = true,
+                    b"w:r" => run_depth += 1,
+                    b"w:p" => paragraphs.push((text.len(), false)),
+                    b"w:pPr" => in_paragraph_properties = true,
+                    b"w:sectPr" => in_section_properties = true,
+                    _ => {}
+                },
+                Ok(Event::End(ref e)) => match e.name().as_ref() {
+                    b"w:t" => in_text = false,
+                    b"w:r" => run_depth -= 1,
+                    b"w:pPr" => in_paragraph_properties = false,
+                    b"w:sectPr" => in_section_properties = false,
+                    b"w:p" => {
+                        let paragraph = paragraphs.pop();
+                        if let (Some((start, rtl)), true) = (paragraph, paragraphs.is_empty()) {
+                            if bidi_reordering == BidiReordering::PerParagraph {
+                                let reordered =
+                                    crate::bidi::reorder_paragraph(&text[start..], Some(rtl));
+                                text.replace_range(start.., &reordered);
+                            }
+                        }
+                        if paragraphs.is_empty() {
+                            for id in paragraph_comments.drain(..) {
+                                if let Some((author, comment)) = comments.get(&id) {
+                                    let author = author.as_deref().unwrap_or_default();
+                                    text.push_str(&format!("\tComment by {}: {}", author, comment));
+                                }
+                            }
+                        }
+                        text.push('\n');
+                    }
+                    _ => {}
+                },
+                Ok(Event::Empty(ref e)) => match e.name().as_ref() {
+                    b"w:bidi" if in_paragraph_properties && !in_section_properties => {
+                        let off = matches!(
+                            attribute_value(e, b"w:val").as_deref(),
+                            Some("0" | "false" | "off")
+                        );
+                        if let Some((_, rtl)) = paragraphs.last_mut() {
+                            *rtl = !off;
+                        }
+                    }
+                    b"w:tab" if run_depth > 0 => text.push('\t'),
+                    b"w:br" => text.push('\n'),
+                    b"w:footnoteReference" => {
+                        let id = attribute_value(e, b"w:id").unwrap_or_default();
+                        let footnote = footnotes
+                            .get(&id)
+                            .map(|(_, text)| text.clone())
+                            .unwrap_or_default();
+                        match footnote_handling {
+                            FootnoteHandling::Inline => {
+                                text.push_str(&format!(" [{}]", footnote));
+                            }
+                            FootnoteHandling::Endnotes => {
+                                endnotes.push(format!("[{}] {}", endnotes.len() + 1, footnote));
+                                text.push_str(&format!("[{}]", endnotes.len()));
+                            }
+                            FootnoteHandling::Drop => {}
+                        }
+                    }
+                    b"w:commentReference" if !comments.is_empty() => {
+                        paragraph_comments.extend(attribute_value(e, b"w:id"));
+                    }
+                    _ => {}
+                },
+                Ok(Event::Text(e)) if in_text => {
+                    text.push_str(&e.unescape().unwrap_or_default());
+                }
+                Ok(Event::Eof) => break,
+                Err(e) => return Err(Error::ParseError(format!("DOCX parse error: {}", e))),
+                _ => {}
+            }
+        }
+        text.push_str(&footers);
+        append_endnotes(&mut text, &endnotes);
+
+        let mut metadata = HashMap::new();
+        metadata.insert(
+            "Content-Type".to_string(),
+            vec![
+                "application/vnd.openxmlformats-officedocument.wordprocessingml.document"
+                    .to_string(),
+            ],
+        );
+        metadata.insert(
+            "Footnote-Count".to_string(),
+            vec![footnotes.len().to_string()],
+        );
+        if !comments.is_empty() {
+            metadata.insert(
+                "Comment-Count".to_string(),
+                vec![comments.len().to_string()],
+            );
+        }
+        metadata.insert("Parser".to_string(), vec!["pure-rust-docx".to_string()]);
+
+        Ok((text, metadata))
+    }
+
+    /// The text of the `word/header<n>.xml` or `word/footer<n>.xml` parts, for `kind` `header` or
+    /// `footer`, in the order of their numbers and one line per paragraph. A document has one
+    /// part per kind of header, e.g. for the first page and for even pages, all are included
+    fn docx_parts_text<R: std::io::Read + std::io::Seek>(
+        archive: &mut zip::ZipArchive<R>,
+        kind: &str,
+    ) -> ExtractResult<String> {
+        let prefix = format!("word/{}", kind);
+        let mut parts: Vec<(u32, String)> = archive
+            .file_names()
+            .filter_map(|name| {
+                let number = name
+                    .strip_prefix(&prefix)?
+                    .strip_suffix(".xml")?
+                    .parse()
+                    .ok()?;
+                Some((number, name.to_string()))
+            })
+            .collect();
+        parts.sort();
+
+        let mut text = String::new();
+        for (_, name) in parts {
+            let Some(xml) = read_zip_entry(archive, &name)? else {
+                continue;
+            };
+            let mut reader = quick_xml::Reader::from_str(&xml);
+            let mut in_text = false;
+            // Tab stops of the paragraph properties are w:tab elements as well
+            let mut run_depth = 0;
+            loop {
+                match reader.read_event() {
+                    Ok(Event::Start(ref e)) => match e.name().as_ref() {
+                        b"w:t" => in_text = true,
+                        b"w:r" => run_depth += 1,
+                        _ => {}
+                    },
+                    Ok(Event::End(ref e)) => match e.name().as_ref() {
+                        b"w:t" => in_text = false,
+                        b"w:r" => run_depth -= 1,
+                        b"w:p" => text.push('\n'),
+                        _ => {}
+                    },
+                    Ok(Event::Empty(ref e)) if e.name().as_ref() == b"w:tab" && run_depth > 0 => {
+                        text.push('\t')
+                    }
+                    Ok(Event::Text(e)) if in_text => {
+                        text.push_str(&e.unescape().unwrap_or_default());
+                    }
+                    Ok(Event::Eof) => break,
+                    Err(e) => return Err(Error::ParseError(format!("DOCX parse error: {}", e))),
+                    _ => {}
+                }
+            }
+        }
+        Ok(text)
+    }
+
+    /// Parses `word/footnotes.xml` or `word/comments.xml` into a map of note id to the author,
+    /// set for comments, and the text of the `element` notes, `w:footnote` or `w:comment`.
+    /// The separator footnotes Word always writes are skipped
+    fn parse_docx_notes(
+        xml: &str,
+        element: &[u8],
+    ) -> ExtractResult<HashMap<String, (Option<String>, String)>> {
+        let mut notes = HashMap::new();
+        let mut reader = quick_xml::Reader::from_str(xml);
+        let mut current: Option<(String, Option<String>, String)> = None;
+        let mut in_text = false;
+        loop {
+            match reader.read_event() {
+                Ok(Event::Start(ref e)) => match e.name().as_ref() {
+                    name if name == element && attribute_value(e, b"w:type").is_none() => {
+                        let id = attribute_value(e, b"w:id").unwrap_or_default();
+                        current = Some((id, attribute_value(e, b"w:author"), String::new()));
+                    }
+                    b"w:t" => in_text = true,
+                    _ => {}
+                },
+                Ok(Event::End(ref e)) => match e.name().as_ref() {
+                    name if name == element => {
+                        if let Some((id, author, text)) = current.take() {
+                            notes.insert(id, (author, text.trim().to_string()));
+                        }
+                    }
+                    b"w:t" => in_text = false,
+                    b"w:p" => {
+                        if let Some((_, _, text)) = current.as_mut() {
+                            text.push(' ');
+                        }
+                    }
+                    _ => {}
+                },
+                Ok(Event::Text(e)) if in_text => {
+                    if let Some((_, _, text)) = current.as_mut() {
+                        text.push_str(&e.unescape().unwrap_or_default());
+                    }
+                }
+                Ok(Event::Eof) => break,
+                Err(e) => return Err(Error::ParseError(format!("DOCX parse error: {}", e))),
+                _ => {}
+            }
+        }
+        Ok(notes)
+    }
+
+    /// A slide of a presentation, as returned by `extract_pptx_slides`
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct Slide {
+        /// The position of the slide in the presentation, starting at 1
+        pub index: usize,
+        /// The text of the title placeholder, empty for slides without a title
+        pub title: String,
+        /// The text of the other shapes and tables of the slide, one line per paragraph
+        pub body: String,
+        /// The presenter's notes, empty for slides without notes
+        pub notes: String,
+    }
+
+    /// Extract the title, the body and the notes of every slide of a PowerPoint (PPTX) file, in
+    /// presentation order. Hidden slides are included. Without `include_notes` the notes are
+    /// left empty
+    pub fn extract_pptx_slides<P: AsRef<Path>>(
+        path: P,
+        include_notes: bool,
+    ) -> ExtractResult<Vec<Slide>> {
+        let file = std::fs::File::open(path.as_ref()).map_err(|e| Error::IoError(e.to_string()))?;
+        let mut archive = zip::ZipArchive::new(file)
+            .map_err(|e| Error::ParseError(format!("PPTX extraction failed: {}", e)))?;
+        let presentation = read_zip_entry(&mut archive, "ppt/presentation.xml")?
+            .ok_or_else(|| Error::ParseError("PPTX without ppt/presentation.xml".to_string()))?;
+        let relationships = part_relationships(&mut archive, "ppt/presentation.xml")?;
+
+        let mut slides = Vec::new();
+        for id in pptx_slide_ids(&presentation)? {
+            let Some((_, slide_part)) = relationships.get(&id) else {
+                continue;
+            };
+            let Some(xml) = read_zip_entry(&mut archive, slide_part)? else {
+                continue;
+            };
+            let (mut title, mut body) = (String::new(), String::new());
+            for (placeholder, text) in pptx_shapes_text(&xml)? {
+                match placeholder.as_deref() {
+                    Some("title" | "ctrTitle") => title.push_str(&text),
+                    _ => body.push_str(&text),
+                }
+            }
+
+            let mut notes = String::new();
+            let notes_part = part_relationships(&mut archive, slide_part)?
+                .into_values()
+                .find(|(kind, _)| kind.ends_with("/notesSlide"))
+                .map(|(_, part)| part);
+            if let Some(notes_part) = notes_part.filter(|_| include_notes) {
+                if let Some(xml) = read_zip_entry(&mut archive, &notes_part)? {
+                    // The other placeholders of a notes page are the slide image and number
+                    for (placeholder, text) in pptx_shapes_text(&xml)? {
+                        if placeholder.as_deref() == Some("body") {
+                            notes.push_str(&text);
+                        }
+                    }
+                }
+            }
+
+            slides.push(Slide {
+                index: slides.len() + 1,
+                title: title.trim().to_string(),
+                body: body.trim().to_string(),
+                notes: notes.trim().to_string(),
+            });
+        }
+        Ok(slides)
+    }
+
+    /// The relationship ids of the slides in `ppt/presentation.xml`, in presentation order
+    fn pptx_slide_ids(presentation: &str) -> ExtractResult<Vec<String>> {
+        let mut reader = quick_xml::Reader::from_str(presentation);
+        let mut ids = Vec::new();
+        loop {
+            match reader.read_event() {
+                Ok(Event::Start(ref e) | Event::Empty(ref e))
+                    if e.name().as_ref() == b"p:sldId" =>
+                {
+                    ids.extend(attribute_value(e, b"r:id"));
+                }
+                Ok(Event::Eof) => break,
+                Err(e) => return Err(Error::ParseError(format!("PPTX parse error: {}", e))),
+                _ => {}
+            }
+        }
+        Ok(ids)
+    }
+
+    /// The text of every shape of a slide or notes page, together with the type of its
+    /// placeholder: `None` for shapes that are no placeholder and `obj` for placeholders
+    /// without a type. Paragraphs end in a line break. The text of tables and other graphic
+    /// frames is returned as a shape of its own
+    fn pptx_shapes_text(xml: &str) -> ExtractResult<Vec<(Option<String>, String)>> {
+        let mut reader = quick_xml::Reader::from_str(xml);
+        let mut shapes = Vec::new();
+        let mut placeholder = None;
+        let mut text = String::new();
+        let mut in_text = false;
+        loop {
+            match reader.read_event() {
+                Ok(Event::Start(ref e)) if e.name().as_ref() == b"a:t" => in_text = true,
+                Ok(Event::Start(ref e) | Event::Empty(ref e)) => match e.name().as_ref() {
+                    b"p:ph" => {
+                        let kind = attribute_value(e, b"type");
+                        placeholder = Some(kind.unwrap_or_else(|| "obj".to_string()));
+                    }
+                    b"a:br" => text.push('\n'),
+                    _ => {}
+                },
+                Ok(Event::End(ref e)) => match e.name().as_ref() {
+                    b"a:t" => in_text = false,
+                    b"a:p" => text.push('\n'),
+                    b"p:sp" | b"p:graphicFrame" => {
+                        shapes.push((placeholder.take(), std::mem::take(&mut text)));
+                    }
+                    _ => {}
+                },
+                Ok(Event::Text(e)) if in_text => {
+                    text.push_str(&e.unescape().unwrap_or_default());
+                }
+                Ok(Event::Eof) => break,
+                Err(e) => return Err(Error::ParseError(format!("PPTX parse error: {}", e))),
+                _ => {}
+            }
+        }
+        Ok(shapes)
+    }
+
+    /// The relationships of a part of an OPC package such as a pptx, by id: the type and the
+    /// name of the target part. External targets, e.g. hyperlinks, are left out
+    fn part_relationships<R: std::io::Read + std::io::Seek>(
+        archive: &mut zip::ZipArchive<R>,
+        part: &str,
+    ) -> ExtractResult<HashMap<String, (String, String)>> {
+        let (directory, name) = part.rsplit_once('/').unwrap_or(("", part));
+        let rels_part = if directory.is_empty() {
+            format!("_rels/{}.rels", name)
+        } else {
+            format!("{}/_rels/{}.rels", directory, name)
+        };
+        let Some(xml) = read_zip_entry(archive, &rels_part)? else {
+            return Ok(HashMap::new());
+        };
+
+        let mut reader = quick_xml::Reader::from_str(&xml);
+        let mut relationships = HashMap::new();
+        loop {
+            match reader.read_event() {
+                Ok(Event::Start(ref e) | Event::Empty(ref e))
+                    if e.name().as_ref() == b"Relationship"
+                        && attribute_value(e, b"TargetMode").as_deref() != Some("External") =>
+                {
+                    if let (Some(id), Some(kind), Some(target)) = (
+                        attribute_value(e, b"Id"),
+                        attribute_value(e, b"Type"),
+                        attribute_value(e, b"Target"),
+                    ) {
+                        relationships.insert(id, (kind, resolve_part_name(directory, &target)));
+                    }
+                }
+                Ok(Event::Eof) => break,
+                Err(e) => {
+                    return Err(Error::ParseError(format!(
+                        "Relationships parse error: {}",
+                        e
+                    )))
+                }
+                _ => {}
+            }
+        }
+        Ok(relationships)
+    }
+
+    /// The part name of the `target` of a relationship of a part in `directory`, e.g.
+    /// `ppt/notesSlides/notesSlide1.xml` for `../notesSlides/notesSlide1.xml` in `ppt/slides`
+    fn resolve_part_name(directory: &str, target: &str) -> String {
+        let (mut segments, target) = match target.strip_prefix('/') {
+            Some(absolute) => (Vec::new(), absolute),
+            None => (
+                directory.split('/').filter(|s| !s.is_empty()).collect(),
+                target,
+            ),
+        };
+        for segment in target.split('/') {
+            match segment {
+                ".." => {
+                    segments.pop();
+                }
+                "." | "" => {}
+                segment => segments.push(segment),
+            }
+        }
+        segments.join("/")
+    }
+
+    /// Reads the raw bytes of a single member of a zip container (docx, xlsx, odt, zip, ...)
+    /// without extracting the rest. Returns `Error::MemberNotFound` if there is no such member
+    pub fn read_zip_member<P: AsRef<Path>>(path: P, member: &str) -> ExtractResult<Vec<u8>> {
+        use std::io::Read;
+
+        let file = std::fs::File::open(path.as_ref()).map_err(|e| Error::IoError(e.to_string()))?;
+        let mut archive = zip::ZipArchive::new(file)
+            .map_err(|e| Error::ParseError(format!("Zip extraction failed: {}", e)))?;
+
+        let mut entry = match archive.by_name(member) {
+            Ok(entry) => entry,
+            Err(zip::result::ZipError::FileNotFound) => {
+                return Err(Error::MemberNotFound(member.to_string()))
+            }
+            Err(e) => return Err(Error::ParseError(format!("Zip extraction failed: {}", e))),
+        };
+        // The size in the header is not trusted beyond the first MiB, the vector grows as read
+        let mut content = Vec::with_capacity(entry.size().min(1 << 20) as usize);
+        entry
+            .read_to_end(&mut content)
+            .map_err(|e| Error::IoError(e.to_string()))?;
+        Ok(content)
+    }
+
+    /// Reads an entry of a zip archive to a string, returns `None` if the entry does not exist
+    fn read_zip_entry<R: std::io::Read + std::io::Seek>(
+        archive: &mut zip::ZipArchive<R>,
+        name: &str,
+    ) -> ExtractResult<Option<String>> {
+        use std::io::Read;
+
+        let mut entry = match archive.by_name(name) {
+            Ok(entry) => entry,
+            Err(zip::result::ZipError::FileNotFound) => return Ok(None),
+            Err(e) => return Err(Error::ParseError(format!("Zip extraction failed: {}", e))),
+        };
+        let mut content = String::new();
+        entry
+            .read_to_string(&mut content)
+            .map_err(|e| Error::IoError(e.to_string()))?;
+        Ok(Some(content))
+    }
+
+    /// Returns the raw value of an attribute of an xml element
+    fn attribute_value(element: &BytesStart, name: &[u8]) -> Option<String> {
+        element
+            .try_get_attribute(name)
+            .ok()
+            .flatten()
+            .map(|attr| String::from_utf8_lossy(&attr.value).into_owned())
+    }
+}
+
+#[cfg(feature = "pure-rust")]
+pub mod csv {
+    use super::*;
+    use std::collections::HashMap;
+
+    /// Delimiters considered when sniffing the first line of a CSV file
+    const CANDIDATE_DELIMITERS: [u8; 4] = [b',', b';', b'\t', b'|'];
+
+    /// Extract text from CSV files, one row per line with the cells joined by tabs.
+    /// Tab and pipe delimited files are recognised with `detect_csv_delimiter`, otherwise the
+    /// delimiter is sniffed from the first line so semicolon-delimited files are supported
+    pub fn extract_csv_text<P: AsRef<Path>>(path: P) -> ExtractResult<(String, Metadata)> {
+        let data = std::fs::read(path.as_ref()).map_err(|e| Error::IoError(e.to_string()))?;
+        extract_csv_from_bytes(&data)
+    }
+
+    /// Extract CSV text from byte slice
+    pub fn extract_csv_from_bytes(data: &[u8]) -> ExtractResult<(String, Metadata)> {
+        let (content, bom) = crate::format_detection::strip_bom(data);
+        let delimiter =
+            crate::detect_csv_delimiter(&content).unwrap_or_else(|| sniff_delimiter(&content));
+        let mut reader = ::csv::ReaderBuilder::new()
+            .delimiter(delimiter)
+            .has_headers(false)
+            .flexible(true)
+            .from_reader(content.as_ref());
+
+        let mut text = String::new();
+        let mut row_count = 0;
+        let mut column_count = 0;
+        for record in reader.records() {
+            let record =
+                record.map_err(|e| Error::ParseError(format!("CSV extraction failed: {}", e)))?;
+            row_count += 1;
+            column_count = column_count.max(record.len());
+
+            // Line breaks and tabs inside quoted cells would break the row structure
+            let cells: Vec<String> = record
+                .iter()
+                .map(|cell| {
+                    cell.split(['\r', '\n', '\t'])
+                        .filter(|part| !part.is_empty())
+                        .collect::<Vec<_>>()
+                        .join(" ")
+                })
+                .collect();
+            text.push_str(&cells.join("\t"));
+            text.push('\n');
+        }
+
+        let mut metadata = HashMap::new();
+        metadata.insert("Content-Type".to_string(), vec!["text/csv".to_string()]);
+        metadata.insert("Row-Count".to_string(), vec![row_count.to_string()]);
+        metadata.insert("Column-Count".to_string(), vec![column_count.to_string()]);
+        metadata.insert(
+            "csv:delimiter".to_string(),
+            vec![(delimiter as char).to_string()],
+        );
+        insert_bom_encoding(&mut metadata, bom);
+        metadata.insert("File-Size".to_string(), vec![data.len().to_string()]);
+        metadata.insert("Parser".to_string(), vec!["pure-rust-csv".to_string()]);
+
+        Ok((text, metadata))
+    }
+
+    /// Picks the candidate delimiter that occurs most often, outside of quotes, in the first line.
+    /// Falls back to a comma
+    fn sniff_delimiter(data: &[u8]) -> u8 {
+        let mut counts = [0usize; CANDIDATE_DELIMITERS.len()];
+        let mut in_quotes = false;
+        for &byte in data {
+            match byte {
+                b'"' => in_quotes = !in_quotes,
+                b'\n' | b'\r' if !in_quotes => break,
+                _ if !in_quotes => {
+                    if let Some(i) = CANDIDATE_DELIMITERS.iter().position(|&d| d == byte) {
+                        counts[i] += 1;
+                    }
+                }
+                _ => {}
+            }
+        }
+        counts
+            .iter()
+            .enumerate()
+            .filter(|&(_, &count)| count > 0)
+            // ties go to the earlier, more common delimiter
+            .max_by_key(|&(i, &count)| (count, std::cmp::Reverse(i)))
+            .map(|(i, _)| CANDIDATE_DELIMITERS[i])
+            .unwrap_or(b',')
+    }
+}
+
+#[cfg(feature = "pure-rust")]
+pub mod markdown {
+    use super::*;
+    use std::collections::HashMap;
+
+    /// Frontmatter keys stored under the name Tika uses for the same property, other keys are
+    /// kept under their own name
+    const FRONTMATTER_KEYS: &[(&str, &str)] = &[
+        ("title", "dc:title"),
+        ("author", "dc:creator"),
+        ("authors", "dc:creator"),
+        ("description", "dc:description"),
+        ("summary", "dc:description"),
+        ("tags", "dc:subject"),
+        ("keywords", "dc:subject"),
+        ("categories", "dc:subject"),
+        ("lang", "dc:language"),
+        ("language", "dc:language"),
+        ("date", "dcterms:created"),
+        ("lastmod", "dcterms:modified"),
+        ("updated", "dcterms:modified"),
+    ];
+
+    /// Extract text from Markdown files, see `extract_markdown_from_bytes`
+    pub fn extract_markdown_text<P: AsRef<Path>>(
+        path: P,
+        strip_syntax: bool,
+    ) -> ExtractResult<(String, Metadata)> {
+        let data = std::fs::read(path.as_ref()).map_err(|e| Error::IoError(e.to_string()))?;
+        extract_markdown_from_bytes(&data, strip_syntax)
+    }
+
+    /// Extract the body of a Markdown document as text, and the YAML frontmatter between the
+    /// `---` lines at its start as metadata, e.g. `title` as `dc:title`. With `strip_syntax`
+    /// the markup of the body is removed, `## [Install](install.md)` becomes `Install`, code
+    /// blocks are kept as they are
+    pub fn extract_markdown_from_bytes(
+        data: &[u8],
+        strip_syntax: bool,
+    ) -> ExtractResult<(String, Metadata)> {
+        let (content, bom) = crate::format_detection::strip_bom(data);
+        let content = String::from_utf8_lossy(&content);
+        let (frontmatter, body) = split_frontmatter(&content);
+
+        let mut metadata: Metadata = HashMap::new();
+        for (key, values) in frontmatter.map(parse_frontmatter).unwrap_or_default() {
+            let name = FRONTMATTER_KEYS
+                .iter()
+                .find(|(known, _)| key.eq_ignore_ascii_case(known))
+                .map_or(key.as_str(), |(_, name)| name);
+            metadata.entry(name.to_string()).or_default().extend(values);
+        }
+        metadata.insert(
+            "Content-Type".to_string(),
+            vec!["text/markdown".to_string()],
+        );
+        insert_bom_encoding(&mut metadata, bom);
+        metadata.insert("File-Size".to_string(), vec![data.len().to_string()]);
+        metadata.insert("Parser".to_string(), vec!["pure-rust-markdown".to_string()]);
+
+        let text = if strip_syntax {
+            strip_markdown(body)
+        } else {
+            body.to_string()
+        };
+        Ok((text, metadata))
+    }
+
+    /// The frontmatter between a `---` first line and the next `---` or `...` line, and the
+    /// body after it. Without a closed frontmatter the whole document is the body
+    fn split_frontmatter(content: &str) -> (Option<&str>, &str) {
+        match content.split_once('\n') {
+            Some((first, rest)) if first.trim_end() == "---" => {
+                let mut end = 0;
+                for line in rest.split_inclusive('\n') {
+                    if matches!(line.trim_end(), "---" | "...") {
+                        let body = rest[end + line.len()..].trim_start_matches(['\r', '\n']);
+                        return (Some(&rest[..end]), body);
+                    }
+                    end += line.len();
+                }
+                (None, content)
+            }
+            _ => (None, content),
+        }
+    }
+
+    /// The top level keys of YAML frontmatter and their values, one for every item of a list.
+    /// Covers the YAML frontmatter is written in: plain and quoted scalars, `[a, b]` lists,
+    /// lists of `- item` lines and `|` or `>` block scalars. Nested mappings are left out
+    fn parse_frontmatter(yaml: &str) -> Vec<(String, Vec<String>)> {
+        let mut entries = Vec::new();
+        let mut lines = yaml.lines().peekable();
+        while let Some(line) = lines.next() {
+            let Some((key, value)) = line.split_once(':') else {
+                continue;
+            };
+            if line.starts_with([' ', '\t', '#']) {
+                continue;
+            }
+            let value = value.trim();
+            // The list items and the lines of block scalars and nested mappings under the key
+            let mut nested = Vec::new();
+            while let Some(next) =
+                lines.next_if(|next| next.starts_with([' ', '\t', '-']) || next.trim().is_empty())
+            {
+                nested.push(next);
+            }
+
+            let values: Vec<String> = if value.is_empty() {
+                nested
+                    .iter()
+                    .filter_map(|line| line.trim_start().strip_prefix('-'))
+                    .map(|item| scalar(item.trim()))
+                    .collect()
+            } else if value.starts_with(['|', '>']) {
+                let lines = nested.iter().map(|line| line.trim());
+                let separator = if value.starts_with('|') { "\n" } else { " " };
+                vec![lines.collect::<Vec<_>>().join(separator).trim().to_string()]
+            } else if let Some(items) = value.strip_prefix('[').and_then(|v| v.strip_suffix(']')) {
+                items.split(',').map(|item| scalar(item.trim())).collect()
+            } else {
+                vec![scalar(value)]
+            };
+            let values: Vec<String> = values.into_iter().filter(|v| !v.is_empty()).collect();
+            if !values.is_empty() {
+                entries.push((scalar(key.trim()), values));
+            }
+        }
+        entries
+    }
+
+    /// A YAML scalar without its quotes, or without a trailing comment when it is not quoted
+    fn scalar(value: &str) -> String {
+        if value.len() >= 2 {
+            if let Some(quoted) = value.strip_prefix('"').and_then(|v| v.strip_suffix('"')) {
+                return quoted.replace("\\\"", "\"");
+            }
+            if let Some(quoted) = value.strip_prefix('\'').and_then(|v| v.strip_suffix('\'')) {
+                return quoted.replace("''", "'");
+            }
+        }
+        let end = value.find(" #").unwrap_or(value.len());
+        value[..end].trim_end().to_string()
+    }
+
+    /// The text of a Markdown body without its markup: heading, block quote and list markers,
+    /// emphasis, link and image syntax, code fences, rules and the rules of tables, whose cells
+    /// are separated by tabs. The content of code blocks is kept as it is
+    pub(crate) fn strip_markdown(body: &str) -> String {
+        let mut lines: Vec<String> = Vec::new();
+        // The backticks or tildes that opened the code block the lines are in
+        let mut fence: Option<String> = None;
+        for line in body.lines() {
+            let trimmed = line.trim();
+            if let Some(open) = &fence {
+                let closes = trimmed.starts_with(open.as_str());
+                if closes && trimmed.chars().all(|c| open.starts_with(c)) {
+                    fence = None;
+                } else {
+                    lines.push(line.to_string());
+                }
+                continue;
+            }
+            if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+                let marker = trimmed.as_bytes()[0] as char;
+                fence = Some(trimmed.chars().take_while(|&c| c == marker).collect());
+                continue;
+            }
+            if is_rule(trimmed) || is_link_definition(trimmed) || is_table_rule(trimmed) {
+                continue;
+            }
+
+            let mut text = trimmed;
+            while let Some(quoted) = text.strip_prefix('>') {
+                text = quoted.trim_start();
+            }
+            let level = text.chars().take_while(|&c| c == '#').count();
+            if (1..=6).contains(&level) && text[level..].starts_with([' ', '\t']) {
+                text = text[level..].trim().trim_end_matches('#').trim_end();
+            }
+            text = strip_list_marker(text);
+
+            let text = if text.starts_with('|') {
+                let cells: Vec<String> = text
+                    .trim_matches('|')
+                    .split('|')
+                    .map(|cell| strip_inline(cell.trim()))
+                    .collect();
+                cells.join("\t")
+            } else {
+                strip_inline(text)
+            };
+            // Runs of blank lines, e.g. around a removed rule, are collapsed
+            if !text.is_empty() || lines.last().is_some_and(|last| !last.is_empty()) {
+                lines.push(text);
+            }
+        }
+        lines.join("\n").trim().to_string()
+    }
+
+    /// A thematic break such as `---` or `* * *`, or the `===` underline of a heading
+    fn is_rule(line: &str) -> bool {
+        let mut marks = line.chars().filter(|c| !c.is_whitespace());
+        match marks.next() {
+            Some(mark @ ('-' | '*' | '_' | '=')) => {
+                marks.clone().count() >= 2 && marks.all(|c| c == mark)
+            }
+            _ => false,
+        }
+    }
+
+    /// A link reference definition, `[id]: https://example.com`
+    fn is_link_definition(line: &str) -> bool {
+        line.starts_with('[') && !line.starts_with("[^") && line.contains("]:")
+    }
+
+    /// The delimiter row between the header and the body of a table, `| --- | :---: |`
+    fn is_table_rule(line: &str) -> bool {
+        line.contains('|')
+            && line.contains('-')
+            && line
+                .chars()
+                .all(|c| matches!(c, '|' | '-' | ':' | ' ' | '\t'))
+    }
+
+    /// A list item without its bullet or number, and without the box of a task
+    fn strip_list_marker(line: &str) -> &str {
+        let digits = line.chars().take_while(char::is_ascii_digit).count();
+        let rest = match line.strip_prefix(['-', '*', '+']) {
+            Some(rest) => rest,
+            None if (1..=9).contains(&digits) => match line[digits..].strip_prefix(['.', ')']) {
+                Some(rest) => rest,
+                None => return line,
+            },
+            None => return line,
+        };
+        if !rest.starts_with([' ', '\t']) {
+            return line;
+        }
+        let rest = rest.trim_start();
+        ["[ ] ", "[x] ", "[X] "]
+            .iter()
+            .find_map(|task| rest.strip_prefix(task))
+            .unwrap_or(rest)
+    }
+
+    /// A line of Markdown without emphasis, code spans, links and images. The text of links
+    /// and the alternative text of images are kept
+    fn strip_inline(line: &str) -> String {
+        let chars: Vec<char> = line.chars().collect();
+        let mut text = String::with_capacity(line.len());
+        let mut i = 0;
+        while i < chars.len() {
+            let c = chars[i];
+            let run = chars[i..].iter().take_while(|&&next| next == c).count();
+            match c {
+                '\\' if chars.get(i + 1).is_some_and(char::is_ascii_punctuation) => {
+                    text.push(chars[i + 1]);
+                    i += 2;
+                }
+                '`' => {
+                    // The code span ends at the next run of as many backticks
+                    let end = (i + run..chars.len()).find(|&j| {
+                        chars[j..].iter().take_while(|&&next| next == '`').count() == run
+                            && chars[j - 1] != '`'
+                    });
+                    match end {
+                        Some(end) => {
+                            text.push_str(chars[i + run..end].iter().collect::<String>().trim());
+                            i = end + run;
+                        }
+                        None => {
+                            text.extend(&chars[i..i + run]);
+                            i += run;
+                        }
+                    }
+                }
+                '!' if chars.get(i + 1) == Some(&'[') => i += 1,
+                '[' => match link_end(&chars, i) {
+                    Some((label_end, end)) => {
+                        let label: String = chars[i + 1..label_end].iter().collect();
+                        text.push_str(&strip_inline(&label));
+                        i = end;
+                    }
+                    None => {
+                        text.push(c);
+                        i += 1;
+                    }
+                },
+                '*' | '_' | '~' => {
+                    let before = i.checked_sub(1).map(|j| chars[j]);
+                    let after = chars.get(i + run).copied();
+                    // The start and end of the text count as space
+                    let is_space = |c: Option<char>| match c {
+                        Some(c) => c.is_whitespace(),
+                        None => true,
+                    };
+                    let is_word = |c: Option<char>| c.is_some_and(char::is_alphanumeric);
+                    let literal = match c {
+                        // snake_case and 2 * 3 are not emphasis
+                        '_' => is_word(before) && is_word(after),
+                        '*' => is_space(before) && is_space(after),
+                        _ => run != 2,
+                    };
+                    if literal {
+                        text.extend(&chars[i..i + run]);
+                    }
+                    i += run;
+                }
+                _ => {
+                    text.push(c);
+                    i += 1;
+                }
+            }
+        }
+        text
+    }
+
+    /// The end of the label and the end of the whole link that starts with the `[` at `start`,
+    /// for `[text](target)` and `[text][id]` links
+    fn link_end(chars: &[char], start: usize) -> Option<(usize, usize)> {
+        let closing = |open: char, close: char, from: usize| {
+            let mut depth = 0;
+            for (j, &c) in chars.iter().enumerate().skip(from) {
+                if c == open {
+                    depth += 1;
+                } else if c == close {
+                    depth -= 1;
+                    if depth == 0 {
+                        return Some(j);
                     }
-                    text.push('\n');
                 }
             }
-        }
-        
-        let mut metadata = HashMap::new();
-        metadata.insert("Content-Type".to_string(), vec!["application/vnd.openxmlformats-officedocument.spreadsheetml.sheet".to_string()]);
-        metadata.insert("Sheet-Count".to_string(), vec![sheet_count.to_string()]);
-        metadata.insert("Parser".to_string(), vec!["pure-rust-excel".to_string()]);
-        
-        if let Ok(file_metadata) = std::fs::metadata(path.as_ref()) {
-            metadata.insert("File-Size".to_string(), vec![file_metadata.len().to_string()]);
-        }
-        
-        Ok((text, metadata))
+            None
+        };
+        let label_end = closing('[', ']', start)?;
+        let end = match chars.get(label_end + 1) {
+            Some('(') => closing('(', ')', label_end + 1)?,
+            Some('[') => closing('[', ']', label_end + 1)?,
+            _ => return None,
+        };
+        Some((label_end, end + 1))
     }
 }
 
 #[cfg(feature = "pure-rust")]
 pub mod web {
     use super::*;
+    use crate::links::LinkUrls;
+    use crate::{LinkMode, TableRendering};
     use std::collections::HashMap;
     
     /// Extract text from HTML using quick-xml
     pub fn extract_html_text(data: &[u8]) -> ExtractResult<(String, Metadata)> {
-        use quick_xml::Reader;
+        extract_html_text_with_tables(data, TableRendering::Flat)
+    }
+
+    /// Extract text from HTML using quick-xml, rendering `<table>` elements according to
+    /// `table_rendering`. With [`TableRendering::Tabs`] each `<tr>` becomes a line and each
+    /// `<td>`/`<th>` a tab separated cell, which can be pasted directly into a spreadsheet.
+    pub fn extract_html_text_with_tables(
+        data: &[u8],
+        table_rendering: TableRendering,
+    ) -> ExtractResult<(String, Metadata)> {
+        extract_html_text_with_links(data, table_rendering, LinkMode::Drop)
+    }
+
+    /// Extract text from HTML like [`extract_html_text_with_tables`], keeping the `href` of
+    /// the `<a>` elements according to `link_mode`
+    pub fn extract_html_text_with_links(
+        data: &[u8],
+        table_rendering: TableRendering,
+        link_mode: LinkMode,
+    ) -> ExtractResult<(String, Metadata)> {
         use quick_xml::events::Event;
+        use quick_xml::Reader;
         
-        let html = std::str::from_utf8(data)
+        let (content, bom) = crate::format_detection::strip_bom(data);
+        let html = std::str::from_utf8(&content)
             .map_err(|e| Error::ParseError(format!("Invalid UTF-8 in HTML: {}", e)))?;
         
         let mut reader = Reader::from_str(html);
-        reader.trim_text(true);
         
         let mut text = String::new();
         let mut buf = Vec::new();
         let mut in_script_or_style = false;
+        let render_tables = table_rendering != TableRendering::Flat;
+        let mut table = TableBuilder::default();
+        let mut links = LinkUrls::new(link_mode);
+        // The target of every open link and where its text starts, in the text or in the open
+        // table cell
+        let mut open_links: Vec<(Option<String>, usize)> = Vec::new();
+        // Whether the last text is a link marker, which is followed by a space only when the
+        // document has whitespace after the link, as in Tika's output
+        let mut after_marker = false;
         
         loop {
             match reader.read_event_into(&mut buf) {
                 Ok(Event::Start(ref e)) => {
-                    let tag_name = std::str::from_utf8(e.name().as_ref()).unwrap_or("");
-                    if tag_name == "script" || tag_name == "style" {
-                        in_script_or_style = true;
+                    let name = e.name();
+                    let tag_name = String::from_utf8_lossy(name.as_ref()).to_ascii_lowercase();
+                    match tag_name.as_str() {
+                        "script" | "style" => in_script_or_style = true,
+                        "table" if render_tables => table.start_table(),
+                        "td" | "th" | "caption" if render_tables => table.start_cell(),
+                        "img" => push_alt_text(e, &reader, &mut text, &mut table),
+                        "a" => {
+                            let href = e
+                                .try_get_attribute("href")
+                                .ok()
+                                .flatten()
+                                .and_then(|href| href.decode_and_unescape_value(&reader).ok())
+                                .map(|href| href.into_owned());
+                            let start = table.cell_text().map_or(text.len(), str::len);
+                            open_links.push((href, start));
+                        }
+                        _ => {}
+                    }
+                }
+                Ok(Event::Empty(ref e)) => {
+                    let name = e.name();
+                    let tag_name = String::from_utf8_lossy(name.as_ref()).to_ascii_lowercase();
+                    if render_tables && (tag_name == "td" || tag_name == "th") {
+                        table.start_cell();
+                        table.end_cell();
+                    }
+                    if tag_name == "img" {
+                        push_alt_text(e, &reader, &mut text, &mut table);
                     }
                 }
                 Ok(Event::End(ref e)) => {
-                    let tag_name = std::str::from_utf8(e.name().as_ref()).unwrap_or("");
-                    if tag_name == "script" || tag_name == "style" {
-                        in_script_or_style = false;
-                    } else if tag_name == "p" || tag_name == "div" || tag_name == "br" {
-                        text.push('\n');
+                    let name = e.name();
+                    let tag_name = String::from_utf8_lossy(name.as_ref()).to_ascii_lowercase();
+                    match tag_name.as_str() {
+                        "script" | "style" => in_script_or_style = false,
+                        "a" => {
+                            if let Some((Some(href), start)) = open_links.pop() {
+                                let link_text = table.cell_text().unwrap_or(&text);
+                                let link_text = link_text.get(start..).unwrap_or_default();
+                                match links.marker(&href, link_text) {
+                                    Some(marker) if table.in_table() => table.push_text(&marker),
+                                    Some(marker) => {
+                                        text.push_str(&marker);
+                                        after_marker = true;
+                                    }
+                                    None => {}
+                                }
+                            }
+                        }
+                        "td" | "th" if render_tables => table.end_cell(),
+                        "caption" if render_tables => table.end_caption(),
+                        "tr" if render_tables => table.end_row(),
+                        "table" if render_tables => {
+                            if let Some(closed) = table.end_table() {
+                                if !text.is_empty() && !text.ends_with('\n') {
+                                    text.push('\n');
+                                }
+                                // The caption is a line of its own above the rows
+                                if let Some(caption) = closed.caption.filter(|c| !c.is_empty()) {
+                                    text.push_str(&caption);
+                                    text.push('\n');
+                                }
+                                text.push_str(&render_table(&closed.rows, table_rendering));
+                            }
+                        }
+                        "p" | "div" | "br" | "figcaption" if !table.in_table() => {
+                            text.truncate(text.trim_end_matches(' ').len());
+                            text.push('\n');
+                            after_marker = false;
+                        }
+                        _ => {}
                     }
                 }
-                Ok(Event::Text(e)) => {
-                    if !in_script_or_style {
-                        text.push_str(&e.unescape().unwrap_or_default());
+                Ok(Event::Text(e)) if !in_script_or_style => {
+                    let raw = e.unescape().unwrap_or_default();
+                    let content = raw.trim();
+                    if std::mem::take(&mut after_marker) && raw.starts_with(char::is_whitespace) {
+                        text.push(' ');
+                    }
+                    if content.is_empty() {
+                    } else if table.in_table() {
+                        table.push_text(content);
+                    } else {
+                        text.push_str(content);
                         text.push(' ');
                     }
                 }
@@ -141,21 +2736,188 @@ pub mod web {
             }
             buf.clear();
         }
+        links.append_footnotes(&mut text);
         
         let mut metadata = HashMap::new();
         metadata.insert("Content-Type".to_string(), vec!["text/html".to_string()]);
+        insert_bom_encoding(&mut metadata, bom);
         metadata.insert("File-Size".to_string(), vec![data.len().to_string()]);
         metadata.insert("Parser".to_string(), vec!["pure-rust-html".to_string()]);
         
         Ok((text, metadata))
     }
+
+    /// Adds the `alt` text of an `<img>` to the text, or to the open table cell. Text content
+    /// is all a text extractor keeps, so without it the description of an image is lost
+    fn push_alt_text<R>(
+        img: &quick_xml::events::BytesStart,
+        reader: &quick_xml::Reader<R>,
+        text: &mut String,
+        table: &mut TableBuilder,
+    ) {
+        let alt = img
+            .try_get_attribute("alt")
+            .ok()
+            .flatten()
+            .and_then(|attribute| attribute.decode_and_unescape_value(reader).ok())
+            .map(|alt| alt.trim().to_string())
+            .filter(|alt| !alt.is_empty());
+        if let Some(alt) = alt {
+            if table.in_table() {
+                table.push_text(&alt);
+            } else {
+                text.push_str(&alt);
+                text.push(' ');
+            }
+        }
+    }
+
+    /// Separator between the padded columns of [`TableRendering::AlignedColumns`] tables
+    const COLUMN_SEPARATOR: &str = "  ";
+
+    /// Rows and cells of a table that is being parsed
+    #[derive(Default)]
+    struct OpenTable {
+        caption: Option<String>,
+        rows: Vec<Vec<String>>,
+        row: Vec<String>,
+        cell: Option<String>,
+    }
+
+    /// Collects the rows and cells of the (possibly nested) tables being parsed
+    #[derive(Default)]
+    struct TableBuilder {
+        stack: Vec<OpenTable>,
+    }
+
+    impl TableBuilder {
+        fn in_table(&self) -> bool {
+            !self.stack.is_empty()
+        }
+
+        /// The text of the open cell of the innermost table
+        fn cell_text(&self) -> Option<&str> {
+            self.stack.last()?.cell.as_deref()
+        }
+
+        fn start_table(&mut self) {
+            self.stack.push(OpenTable::default());
+        }
+
+        fn start_cell(&mut self) {
+            if let Some(table) = self.stack.last_mut() {
+                table.cell = Some(String::new());
+            }
+        }
+
+        fn push_text(&mut self, content: &str) {
+            if let Some(OpenTable {
+                cell: Some(cell), ..
+            }) = self.stack.last_mut()
+            {
+                if !cell.is_empty() {
+                    cell.push(' ');
+                }
+                cell.push_str(content);
+            }
+        }
+
+        fn end_cell(&mut self) {
+            if let Some(table) = self.stack.last_mut() {
+                if let Some(cell) = table.cell.take() {
+                    table.row.push(cell);
+                }
+            }
+        }
+
+        /// Closes a `<caption>`, whose text is collected like that of a cell
+        fn end_caption(&mut self) {
+            if let Some(table) = self.stack.last_mut() {
+                table.caption = table.cell.take();
+            }
+        }
+
+        fn end_row(&mut self) {
+            self.end_cell();
+            if let Some(table) = self.stack.last_mut() {
+                if !table.row.is_empty() {
+                    table.rows.push(std::mem::take(&mut table.row));
+                }
+            }
+        }
+
+        /// Closes the innermost table and returns it. A nested table is flattened into the
+        /// cell of its parent table
+        fn end_table(&mut self) -> Option<OpenTable> {
+            self.end_row();
+            let table = self.stack.pop()?;
+            if self.in_table() {
+                let flattened = table
+                    .caption
+                    .into_iter()
+                    .chain(table.rows.iter().map(|row| row.join(" ")))
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                self.push_text(&flattened);
+                None
+            } else {
+                Some(table)
+            }
+        }
+    }
+
+    /// Renders the table rows as text according to the table rendering mode
+    fn render_table(rows: &[Vec<String>], table_rendering: TableRendering) -> String {
+        if table_rendering == TableRendering::AlignedColumns {
+            return render_aligned_table(rows);
+        }
+        let mut text = String::new();
+        for row in rows {
+            text.push_str(&row.join("\t"));
+            text.push('\n');
+        }
+        text
+    }
+
+    /// Pads every cell to the display width of the widest cell of its column. Widths are
+    /// measured with `unicode-width` so that wide CJK characters and emoji count as two columns
+    fn render_aligned_table(rows: &[Vec<String>]) -> String {
+        use unicode_width::UnicodeWidthStr;
+
+        let mut widths: Vec<usize> = Vec::new();
+        for row in rows {
+            for (column, cell) in row.iter().enumerate() {
+                let width = cell.width();
+                match widths.get_mut(column) {
+                    Some(max) => *max = (*max).max(width),
+                    None => widths.push(width),
+                }
+            }
+        }
+
+        let mut text = String::new();
+        for row in rows {
+            let mut line = String::new();
+            for (column, cell) in row.iter().enumerate() {
+                if column > 0 {
+                    line.push_str(COLUMN_SEPARATOR);
+                }
+                line.push_str(cell);
+                line.push_str(&" ".repeat(widths[column] - cell.width()));
+            }
+            text.push_str(line.trim_end());
+            text.push('\n');
+        }
+        text
+    }
     
     /// Extract text from XML
     pub fn extract_xml_text(data: &[u8]) -> ExtractResult<(String, Metadata)> {
         use quick_xml::Reader;
         use quick_xml::events::Event;
         
-        let xml = std::str::from_utf8(data)
+        let (content, bom) = crate::format_detection::strip_bom(data);
+        let xml = std::str::from_utf8(&content)
             .map_err(|e| Error::ParseError(format!("Invalid UTF-8 in XML: {}", e)))?;
         
         let mut reader = Reader::from_str(xml);
@@ -171,7 +2933,8 @@ pub mod web {
                     text.push(' ');
                 }
                 Ok(Event::CData(e)) => {
-                    text.push_str(&e.escape().unwrap_or_default());
+                    let escaped = e.escape().map(|text| text.into_inner()).unwrap_or_default();
+                    text.push_str(&String::from_utf8_lossy(&escaped));
                     text.push(' ');
                 }
                 Ok(Event::Eof) => break,
@@ -183,6 +2946,7 @@ pub mod web {
         
         let mut metadata = HashMap::new();
         metadata.insert("Content-Type".to_string(), vec!["application/xml".to_string()]);
+        insert_bom_encoding(&mut metadata, bom);
         metadata.insert("File-Size".to_string(), vec![data.len().to_string()]);
         metadata.insert("Parser".to_string(), vec!["pure-rust-xml".to_string()]);
         
@@ -194,6 +2958,15 @@ pub mod web {
 #[cfg(feature = "pure-rust")]
 pub struct PureRustExtractor {
     max_text_length: usize,
+    table_rendering: crate::TableRendering,
+    footnote_handling: crate::FootnoteHandling,
+    bidi_reordering: crate::BidiReordering,
+    include_comments: bool,
+    include_headers_and_footers: bool,
+    sort_by_position: bool,
+    link_mode: crate::LinkMode,
+    strip_markdown_syntax: bool,
+    password: Option<String>,
 }
 
 #[cfg(feature = "pure-rust")]
@@ -201,58 +2974,257 @@ impl PureRustExtractor {
     pub fn new() -> Self {
         Self {
             max_text_length: 500_000,
+            table_rendering: crate::TableRendering::default(),
+            footnote_handling: crate::FootnoteHandling::default(),
+            bidi_reordering: crate::BidiReordering::default(),
+            include_comments: true,
+            include_headers_and_footers: false,
+            sort_by_position: false,
+            link_mode: crate::LinkMode::default(),
+            strip_markdown_syntax: false,
+            password: None,
         }
     }
     
     pub fn with_max_length(max_length: usize) -> Self {
         Self {
             max_text_length: max_length,
+            table_rendering: crate::TableRendering::default(),
+            footnote_handling: crate::FootnoteHandling::default(),
+            bidi_reordering: crate::BidiReordering::default(),
+            include_comments: true,
+            include_headers_and_footers: false,
+            sort_by_position: false,
+            link_mode: crate::LinkMode::default(),
+            strip_markdown_syntax: false,
+            password: None,
         }
     }
+
+    /// Set how tables are rendered by the parsers that understand table structure
+    pub fn set_table_rendering(mut self, table_rendering: crate::TableRendering) -> Self {
+        self.table_rendering = table_rendering;
+        self
+    }
+
+    /// Set how footnotes are handled by the DOCX and PDF parsers
+    pub fn set_footnote_handling(mut self, footnote_handling: crate::FootnoteHandling) -> Self {
+        self.footnote_handling = footnote_handling;
+        self
+    }
+
+    /// Set whether the DOCX parser writes the comments after the paragraph they are anchored in
+    pub fn set_include_comments(mut self, include_comments: bool) -> Self {
+        self.include_comments = include_comments;
+        self
+    }
+
+    /// Set whether the DOCX parser writes the text of the headers before the body and the text
+    /// of the footers after it
+    pub fn set_include_headers_and_footers(mut self, include_headers_and_footers: bool) -> Self {
+        self.include_headers_and_footers = include_headers_and_footers;
+        self
+    }
+
+    /// Set whether the PDF parser puts the text of every page in reading order by position,
+    /// column by column, instead of content stream order
+    pub fn set_sort_by_position(mut self, sort_by_position: bool) -> Self {
+        self.sort_by_position = sort_by_position;
+        self
+    }
+
+    /// Set what the HTML parser does with the targets of links
+    pub fn set_link_mode(mut self, link_mode: crate::LinkMode) -> Self {
+        self.link_mode = link_mode;
+        self
+    }
+
+    /// Set how text mixing right-to-left and left-to-right scripts is reordered
+    pub fn set_bidi_reordering(mut self, bidi_reordering: crate::BidiReordering) -> Self {
+        self.bidi_reordering = bidi_reordering;
+        self
+    }
+
+    /// Set whether the Markdown parser removes the markup of the body, e.g. `**` and `#`
+    pub fn set_strip_markdown_syntax(mut self, strip_markdown_syntax: bool) -> Self {
+        self.strip_markdown_syntax = strip_markdown_syntax;
+        self
+    }
+
+    /// Set the password of encrypted docx and xlsx documents
+    pub fn set_password(mut self, password: Option<String>) -> Self {
+        self.password = password;
+        self
+    }
     
     /// Extract text using pure Rust parsers when possible
     pub fn extract_file<P: AsRef<Path>>(&self, path: P) -> ExtractResult<(String, Metadata)> {
-        let format = crate::format_detection::detect_format(&path);
-        
+        #[cfg(feature = "office-encryption")]
+        if let Some(package) = crate::ooxml_crypto::EncryptedPackage::open(path.as_ref())? {
+            return self.extract_encrypted_package(&path, &package);
+        }
+
         let format = crate::format_detection::detect_format(&path);
 
         let (mut text, metadata) = match format {
-            crate::format_detection::DocumentFormat::Pdf => pdf::extract_pdf_text(&path)?,
+            crate::format_detection::DocumentFormat::Pdf if self.sort_by_position => {
+                pdf::extract_pdf_text_by_position(&path)?
+            }
+            crate::format_detection::DocumentFormat::Pdf => {
+                pdf::extract_pdf_text_with_footnotes(&path, self.footnote_handling)?
+            }
+            crate::format_detection::DocumentFormat::Docx if self.supports(&format) => {
+                office::extract_docx_text(
+                    &path,
+                    self.footnote_handling,
+                    self.bidi_reordering,
+                    self.include_comments,
+                    self.include_headers_and_footers,
+                )?
+            }
             crate::format_detection::DocumentFormat::Xlsx => office::extract_xlsx_text(&path)?,
+            crate::format_detection::DocumentFormat::Csv => csv::extract_csv_text(&path)?,
+            crate::format_detection::DocumentFormat::Markdown => {
+                markdown::extract_markdown_text(&path, self.strip_markdown_syntax)?
+            }
             crate::format_detection::DocumentFormat::Html => {
                 let data = std::fs::read(&path)
                     .map_err(|e| Error::IoError(e.to_string()))?;
-                web::extract_html_text(&data)?
+                web::extract_html_text_with_links(&data, self.table_rendering, self.link_mode)?
             }
             crate::format_detection::DocumentFormat::Xml => {
                 let data = std::fs::read(&path)
                     .map_err(|e| Error::IoError(e.to_string()))?;
                 web::extract_xml_text(&data)?
             }
-            _ => return Err(Error::ParseError(format!("Format {:?} not supported by pure Rust parsers", format))),
+            _ => {
+                return Err(Error::UnsupportedFormat(format!(
+                    "Format {:?} not supported by pure Rust parsers",
+                    format
+                )))
+            }
         };
         
-        // Truncate if necessary
-        if text.len() > self.max_text_length {
-            text.truncate(self.max_text_length);
-        }
+        text = self.reorder_bidi(text, &format);
+
+        self.truncate(&mut text);
         
         Ok((text, metadata))
     }
     
+    /// Decrypts a password protected docx or xlsx and extracts its text
+    #[cfg(feature = "office-encryption")]
+    fn extract_encrypted_package<P: AsRef<Path>>(
+        &self,
+        path: P,
+        package: &crate::ooxml_crypto::EncryptedPackage,
+    ) -> ExtractResult<(String, Metadata)> {
+        use crate::format_detection::DocumentFormat;
+
+        let password = self.password.as_deref().ok_or_else(|| {
+            Error::EncryptedDocument(
+                "The document is encrypted, set its password with OfficeParserConfig::set_password"
+                    .to_string(),
+            )
+        })?;
+        let data = package.decrypt(password)?;
+
+        // The extension is the best hint, the decrypted package is a plain zip otherwise
+        let format = match crate::format_detection::detect_format(&path) {
+            format @ (DocumentFormat::Docx | DocumentFormat::Xlsx) => format,
+            _ => crate::format_detection::detect_format_from_bytes(&data),
+        };
+        let (mut text, metadata) = match format {
+            DocumentFormat::Docx => office::extract_docx_from_bytes(
+                &data,
+                self.footnote_handling,
+                self.bidi_reordering,
+                self.include_comments,
+                self.include_headers_and_footers,
+            )?,
+            DocumentFormat::Xlsx => office::extract_xlsx_from_bytes(&data)?,
+            _ => {
+                return Err(Error::UnsupportedFormat(format!(
+                    "Encrypted {:?} documents are not supported by pure Rust parsers",
+                    format
+                )))
+            }
+        };
+        text = self.reorder_bidi(text, &format);
+
+        self.truncate(&mut text);
+
+        Ok((text, metadata))
+    }
+
+    /// Whether `extract_file` extracts documents of `format`, instead of failing with
+    /// `Error::UnsupportedFormat`
+    pub fn supports(&self, format: &crate::format_detection::DocumentFormat) -> bool {
+        use crate::format_detection::DocumentFormat;
+        match format {
+            DocumentFormat::Pdf
+            | DocumentFormat::Xlsx
+            | DocumentFormat::Csv
+            | DocumentFormat::Markdown
+            | DocumentFormat::Html
+            | DocumentFormat::Xml => true,
+            // DOCX is left to Tika unless footnotes have to be moved or dropped, or paragraphs
+            // reordered by their w:bidi direction
+            DocumentFormat::Docx => {
+                self.footnote_handling != crate::FootnoteHandling::Inline
+                    || self.bidi_reordering == crate::BidiReordering::PerParagraph
+            }
+            _ => false,
+        }
+    }
+
+    /// Truncates the text to the maximum length in characters, as the extractor's
+    /// `extract_string_max_length` counts them
+    fn truncate(&self, text: &mut String) {
+        if let Some((end, _)) = text.char_indices().nth(self.max_text_length) {
+            text.truncate(end);
+        }
+    }
+
+    /// Reorders the extracted text into visual order, unless the DOCX parser already reordered
+    /// its paragraphs
+    fn reorder_bidi(
+        &self,
+        text: String,
+        format: &crate::format_detection::DocumentFormat,
+    ) -> String {
+        let reordered_by_parser = *format == crate::format_detection::DocumentFormat::Docx
+            && self.bidi_reordering == crate::BidiReordering::PerParagraph;
+        if reordered_by_parser {
+            text
+        } else {
+            crate::bidi::reorder(&text, self.bidi_reordering)
+        }
+    }
+
     /// Extract text from byte slice
     pub fn extract_bytes(&self, data: &[u8], format: crate::format_detection::DocumentFormat) -> ExtractResult<(String, Metadata)> {
         let (mut text, metadata) = match format {
             crate::format_detection::DocumentFormat::Pdf => pdf::extract_pdf_from_bytes(data)?,
-            crate::format_detection::DocumentFormat::Html => web::extract_html_text(data)?,
+            crate::format_detection::DocumentFormat::Csv => csv::extract_csv_from_bytes(data)?,
+            crate::format_detection::DocumentFormat::Markdown => {
+                markdown::extract_markdown_from_bytes(data, self.strip_markdown_syntax)?
+            }
+            crate::format_detection::DocumentFormat::Html => {
+                web::extract_html_text_with_links(data, self.table_rendering, self.link_mode)?
+            }
             crate::format_detection::DocumentFormat::Xml => web::extract_xml_text(data)?,
-            _ => return Err(Error::ParseError(format!("Format {:?} not supported by pure Rust parsers", format))),
+            _ => {
+                return Err(Error::UnsupportedFormat(format!(
+                    "Format {:?} not supported by pure Rust parsers",
+                    format
+                )))
+            }
         };
         
-        // Truncate if necessary
-        if text.len() > self.max_text_length {
-            text.truncate(self.max_text_length);
-        }
+        text = self.reorder_bidi(text, &format);
+
+        self.truncate(&mut text);
         
         Ok((text, metadata))
     }
@@ -271,3 +3243,702 @@ impl PureRustExtractor {
         Err(Error::ParseError("Pure Rust parsers not enabled. Enable 'pure-rust' feature.".to_string()))
     }
 }
+
+#[cfg(all(test, feature = "pure-rust"))]
+mod tests {
+    use super::*;
+    use crate::{BidiReordering, FootnoteHandling, LinkMode, TableRendering};
+
+    const TABLE_HTML: &[u8] = b"<html><body><p>Prices</p>\
+        <table><tr><th>Item</th><th>Price</th></tr>\
+        <tr><td>Apple</td><td>1.20</td></tr></table></body></html>";
+
+    #[test]
+    fn test_html_table_tabs() {
+        let (text, _) =
+            web::extract_html_text_with_tables(TABLE_HTML, TableRendering::Tabs).unwrap();
+        assert!(text.contains("Item\tPrice\nApple\t1.20\n"));
+        assert!(text.starts_with("Prices"));
+
+        // The caption is kept on a line of its own above the rows
+        let html = b"<table><caption>Fruit prices</caption>\
+            <tr><td>Apple</td><td>1.20</td></tr></table>";
+        let (text, _) = web::extract_html_text_with_tables(html, TableRendering::Tabs).unwrap();
+        assert_eq!(text, "Fruit prices\nApple\t1.20\n");
+    }
+
+    #[test]
+    fn test_html_image_alt_text() {
+        let html = b"<html><body><figure><img src=\"chart.png\" alt=\"Sales by month\"/>\
+            <figcaption>Figure 1: Sales</figcaption></figure>\
+            <table><tr><td><img src=\"up.png\" alt=\"Up\"></img></td><td>12%</td></tr></table>\
+            <p><img src=\"spacer.gif\" alt=\"\"/>Done</p></body></html>";
+        let (text, _) = web::extract_html_text_with_tables(html, TableRendering::Tabs).unwrap();
+
+        assert!(
+            text.contains("Sales by month Figure 1: Sales\n"),
+            "{:?}",
+            text
+        );
+        assert!(text.contains("Up\t12%\n"), "{:?}", text);
+        assert!(text.contains("Done"));
+    }
+
+    #[test]
+    fn test_html_table_aligned_columns() {
+        let html = "<table><tr><th>Item</th><th>Price</th><th>Origin</th></tr>\
+            <tr><td>Watermelon</td><td>3.50</td><td>Spain</td></tr>\
+            <tr><td>梨</td><td>12.00</td><td>日本</td></tr></table>";
+        let (text, _) =
+            web::extract_html_text_with_tables(html.as_bytes(), TableRendering::AlignedColumns)
+                .unwrap();
+
+        let lines: Vec<&str> = text.lines().filter(|line| !line.is_empty()).collect();
+        assert_eq!(
+            lines,
+            [
+                "Item        Price  Origin",
+                "Watermelon  3.50   Spain",
+                // the wide characters take two columns each
+                "梨          12.00  日本",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_html_table_flat() {
+        let (text, _) = web::extract_html_text(TABLE_HTML).unwrap();
+        assert!(!text.contains('\t'));
+        assert!(text.contains("Apple"));
+    }
+
+    #[test]
+    fn test_html_link_urls() {
+        let html = std::fs::read("../test_files/documents/links.html").unwrap();
+        let extract = |link_mode| {
+            web::extract_html_text_with_links(&html, TableRendering::Flat, link_mode)
+                .unwrap()
+                .0
+        };
+
+        let text = extract(LinkMode::Drop);
+        assert!(
+            text.contains("Check the weather report before you leave."),
+            "{:?}",
+            text
+        );
+        assert!(!text.contains("https://"));
+
+        let text = extract(LinkMode::Inline);
+        assert!(
+            text.contains("weather report (https://example.com/weather) before"),
+            "{:?}",
+            text
+        );
+        assert!(text.contains("club website (https://example.com/maps), next"));
+        assert!(text.contains("Back to top\n"));
+
+        let text = extract(LinkMode::Footnotes);
+        let (body, notes) = text.split_once(ENDNOTES_SEPARATOR).unwrap();
+        assert!(body.contains("weather report [1] before"), "{:?}", body);
+        assert!(
+            body.contains("club website [2], next to the forecast [1]."),
+            "{:?}",
+            body
+        );
+        assert_eq!(
+            notes.trim(),
+            "[1] https://example.com/weather\n[2] https://example.com/maps"
+        );
+
+        // Cells of a table get their targets too
+        let html = b"<table><tr><td><a href=\"https://example.com/a\">A</a></td></tr></table>";
+        let (text, _) =
+            web::extract_html_text_with_links(html, TableRendering::Tabs, LinkMode::Inline)
+                .unwrap();
+        assert_eq!(text, "A (https://example.com/a)\n");
+    }
+
+    #[test]
+    fn test_docx_footnotes_as_endnotes() {
+        let (text, metadata) = office::extract_docx_text(
+            "../test_files/documents/footnotes.docx",
+            FootnoteHandling::Endnotes,
+            BidiReordering::Off,
+            true,
+            false,
+        )
+        .unwrap();
+
+        let (body, notes) = text.split_once(ENDNOTES_SEPARATOR).unwrap();
+        assert!(body.contains("released in 2015.[1] It is memory safe.[2]"));
+        assert!(!body.contains("May 2015"));
+        assert_eq!(
+            notes.trim(),
+            "[1] Version 1.0 was released in May 2015.\n[2] Safety is enforced by the borrow checker."
+        );
+        assert_eq!(
+            metadata.get("Footnote-Count").unwrap(),
+            &vec!["2".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_docx_comments() {
+        let extract = |include_comments| {
+            office::extract_docx_text(
+                "../test_files/documents/comments.docx",
+                FootnoteHandling::Inline,
+                BidiReordering::Off,
+                include_comments,
+                false,
+            )
+            .unwrap()
+        };
+
+        let (text, metadata) = extract(true);
+        assert!(text.contains(
+            "The contract ends in March.\tComment by Alice Example: Check the renewal date.\n"
+        ));
+        assert_eq!(metadata["Comment-Count"], ["1"]);
+
+        let (text, metadata) = extract(false);
+        assert!(text.contains("The contract ends in March.\n"));
+        assert!(!text.contains("renewal"));
+        assert!(!metadata.contains_key("Comment-Count"));
+    }
+
+    #[test]
+    fn test_pdf_preview() {
+        let path = "../test_files/documents/ten-pages.pdf";
+        let (text, metadata) = pdf::extract_pdf_preview(path, 1).unwrap();
+        assert!(
+            text.contains("This is the text of page 1 of the report."),
+            "{}",
+            text
+        );
+        assert!(!text.contains("page 2"));
+        assert_eq!(metadata["xmpTPg:NPages"], ["10"]);
+        assert_eq!(metadata["dc:title"], ["Ten page report"]);
+
+        let truncated = pdf::truncate_pdf_pages(path, 2).unwrap();
+        let (text, _) = pdf::extract_pdf_from_bytes(&truncated).unwrap();
+        assert!(text.contains("Chapter 2"));
+        assert!(!text.contains("Chapter 3"));
+    }
+
+    #[test]
+    fn test_split_pdf_pages() {
+        let path = "../test_files/documents/ten-pages.pdf";
+        let pages = pdf::PdfPageSplitter::open(path).unwrap();
+        assert_eq!(pages.page_count(), 10);
+        for i in 0..pages.page_count() {
+            let page = pages.page_pdf(i).unwrap();
+            let (text, metadata) = pdf::extract_pdf_from_bytes(&page).unwrap();
+            assert!(
+                text.contains(&format!("page {} of the report", i + 1)),
+                "{}",
+                text
+            );
+            assert!(!text.contains(&format!("page {} of the report", (i + 1) % 10 + 1)));
+            assert_eq!(metadata["xmpTPg:NPages"], ["1"]);
+        }
+        assert!(matches!(pages.page_pdf(10), Err(Error::ParseError(_))));
+    }
+
+    #[test]
+    fn test_markdown_frontmatter() {
+        let path = "../test_files/documents/frontmatter.md";
+        let (text, metadata) = markdown::extract_markdown_text(path, true).unwrap();
+
+        assert_eq!(metadata["dc:title"], ["Release notes: 2.0"]);
+        assert_eq!(metadata["dcterms:created"], ["2024-03-15"]);
+        assert_eq!(metadata["dc:subject"], ["release", "parser"]);
+        assert_eq!(metadata["dc:creator"], ["Ada Lovelace", "Grace Hopper"]);
+        assert_eq!(
+            metadata["dc:description"],
+            ["What changed in the second major release."]
+        );
+        assert_eq!(metadata["draft"], ["false"]);
+        assert_eq!(metadata["Content-Type"], ["text/markdown"]);
+        assert_eq!(
+            text,
+            "Release notes\n\n\
+            The new parser reads Markdown files, see the guide and extract_file.\n\n\
+            Changes\n\n\
+            Faster snake_case detection\n\
+            Frontmatter as metadata badge\n\n\
+            Upgrading is hard easy.\n\n\
+            Format\tParser\n\
+            Markdown\tRust\n\n\
+            let text = **not** stripped;\n\n\
+            Thanks to all 3 * 4 contributors."
+        );
+
+        // Without stripping only the frontmatter is left out
+        let (text, _) = markdown::extract_markdown_text(path, false).unwrap();
+        assert!(
+            text.starts_with("# Release notes\n\nThe **new** parser"),
+            "{}",
+            text
+        );
+
+        let unclosed = b"---\n# A rule\n";
+        let (text, metadata) = markdown::extract_markdown_from_bytes(unclosed, true).unwrap();
+        assert_eq!(text, "A rule");
+        assert!(!metadata.contains_key("dc:title"));
+    }
+
+    #[test]
+    fn test_remove_hidden_layers() {
+        let path = "../test_files/documents/hidden-layer.pdf";
+        let (text, _) = pdf::extract_pdf_text(path).unwrap();
+        assert!(text.contains("DRAFT DO NOT DISTRIBUTE"), "{}", text);
+
+        let data = pdf::remove_hidden_layers(path).unwrap().unwrap();
+        let (text, metadata) = pdf::extract_pdf_from_bytes(&data).unwrap();
+        assert!(!text.contains("DRAFT"), "{}", text);
+        assert!(text.contains("Revenue grew by four percent."), "{}", text);
+        assert!(text.contains("Reviewed by the finance team."), "{}", text);
+        assert_eq!(metadata["dc:title"], ["Hidden layer"]);
+
+        // Nothing to remove from a PDF without layers
+        let path = "../test_files/documents/ten-pages.pdf";
+        assert!(pdf::remove_hidden_layers(path).unwrap().is_none());
+
+        // A membership dictionary that lists itself next to a hidden group
+        let path = "../test_files/documents/self-referencing-layer.pdf";
+        let data = pdf::remove_hidden_layers(path).unwrap().unwrap();
+        let (text, _) = pdf::extract_pdf_from_bytes(&data).unwrap();
+        assert!(!text.contains("Draft note"), "{}", text);
+        assert!(text.contains("Visible text"), "{}", text);
+    }
+
+    #[test]
+    fn test_pptx_slides() {
+        let path = "../test_files/documents/slide-notes.pptx";
+        let slides = office::extract_pptx_slides(path, true).unwrap();
+
+        assert_eq!(slides.len(), 2);
+        assert_eq!(slides[0].index, 1);
+        assert_eq!(slides[0].title, "Title Slide");
+        assert_eq!(slides[0].body, "With a subtitle");
+        assert_eq!(slides[0].notes, "");
+        assert_eq!(slides[1].title, "Things to think about");
+        assert!(slides[1]
+            .body
+            .starts_with("How much is enough?\nIf not now when?\n"));
+        assert_eq!(
+            slides[1].notes,
+            "Pause after each question and let the audience answer.\n\
+            Mention the survey results from March."
+        );
+        assert!(!slides[1].body.contains("Pause"));
+
+        let slides = office::extract_pptx_slides(path, false).unwrap();
+        assert_eq!(slides[1].notes, "");
+    }
+
+    #[test]
+    fn test_docx_headers_and_footers() {
+        let extract = |include_headers_footers| {
+            office::extract_docx_text(
+                "../test_files/documents/header-footer.docx",
+                FootnoteHandling::Inline,
+                BidiReordering::Off,
+                true,
+                include_headers_footers,
+            )
+            .unwrap()
+            .0
+        };
+
+        let text = extract(true);
+        let header = text.find("Northwind Traders Quarterly Review").unwrap();
+        let body = text
+            .find("Sales grew in every region this quarter.")
+            .unwrap();
+        let footer = text.find("Confidential - do not distribute").unwrap();
+        assert!(header < body && body < footer);
+
+        let text = extract(false);
+        assert!(text.contains("Costs stayed flat."));
+        assert!(!text.contains("Northwind"));
+        assert!(!text.contains("Confidential"));
+    }
+
+    #[test]
+    fn test_docx_tab_stops() {
+        let (text, _) = office::extract_docx_text(
+            "../test_files/documents/tab-stops.docx",
+            FootnoteHandling::Inline,
+            BidiReordering::Off,
+            true,
+            true,
+        )
+        .unwrap();
+
+        // The tab stops of the paragraph properties are not tabs of the text
+        assert_eq!(text, "Menu\tSpring\nItem\tPrice\nCoffee\t3.50\nPage 1\n");
+    }
+
+    #[test]
+    fn test_docx_footnotes_dropped() {
+        let (text, _) = office::extract_docx_text(
+            "../test_files/documents/footnotes.docx",
+            FootnoteHandling::Drop,
+            BidiReordering::Off,
+            true,
+            false,
+        )
+        .unwrap();
+        assert!(text.contains("Rust was first released in 2015. It is memory safe."));
+        assert!(!text.contains("borrow checker"));
+        assert!(!text.contains(ENDNOTES_SEPARATOR));
+    }
+
+    #[test]
+    fn test_pdf_footnotes_as_endnotes() {
+        let (text, _) = pdf::extract_pdf_text_with_footnotes(
+            "../test_files/documents/footnotes.pdf",
+            FootnoteHandling::Endnotes,
+        )
+        .unwrap();
+
+        let (body, notes) = text.split_once(ENDNOTES_SEPARATOR).unwrap();
+        assert!(body.contains("The borrow checker enforces safety."));
+        assert!(!body.contains("Ownership rules"));
+        assert!(notes.contains("1 Version 1.0 was released in May 2015."));
+        assert!(notes.contains("2 Ownership rules are checked at compile time."));
+    }
+
+    #[test]
+    fn test_pdf_document_metadata() {
+        let (text, metadata) =
+            pdf::extract_pdf_text("../test_files/documents/metadata.pdf").unwrap();
+        assert!(text.contains("Page 3 of the metadata sample."));
+
+        let value = |key: &str| metadata.get(key).map(|values| values[0].as_str());
+        assert_eq!(value("dc:title"), Some("Extractous Metadata Sample"));
+        assert_eq!(value("dc:creator"), Some("Jane Doe"));
+        assert_eq!(value("dc:subject"), Some("PDF document information"));
+        assert_eq!(value("dcterms:created"), Some("2024-03-15T09:30:00Z"));
+        assert_eq!(value("xmpTPg:NPages"), Some("3"));
+    }
+
+    #[test]
+    fn test_pdf_date_to_iso8601() {
+        assert_eq!(
+            pdf::pdf_date_to_iso8601("D:20220729100321+02'00'").as_deref(),
+            Some("2022-07-29T10:03:21+02:00")
+        );
+        assert_eq!(
+            pdf::pdf_date_to_iso8601("D:2022").as_deref(),
+            Some("2022-01-01T00:00:00Z")
+        );
+        assert_eq!(pdf::pdf_date_to_iso8601("yesterday"), None);
+        assert_eq!(pdf::pdf_date_to_iso8601("D:20220729100321+é"), None);
+        assert_eq!(pdf::pdf_date_to_iso8601("D:20220729100321+1é"), None);
+        assert_eq!(pdf::pdf_date_to_iso8601("D:20220729100321+0"), None);
+    }
+
+    #[test]
+    fn test_csv_quoted_fields() {
+        let data = b"name,quote,year\n\"Doe, Jane\",\"first line\nsecond line\",2024\nSmith,\"said \"\"hi\"\"\",2023\n";
+        let (text, metadata) = csv::extract_csv_from_bytes(data).unwrap();
+
+        assert_eq!(
+            text,
+            "name\tquote\tyear\nDoe, Jane\tfirst line second line\t2024\nSmith\tsaid \"hi\"\t2023\n"
+        );
+        assert_eq!(metadata.get("Row-Count").unwrap(), &vec!["3".to_string()]);
+        assert_eq!(
+            metadata.get("Column-Count").unwrap(),
+            &vec!["3".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_csv_semicolon_delimiter() {
+        let data = "Produkt;Preis;Menge\n\"Äpfel; rot\";1,50;3\nBirnen;2,10;5\n".as_bytes();
+        let (text, metadata) = csv::extract_csv_from_bytes(data).unwrap();
+
+        assert_eq!(
+            text,
+            "Produkt\tPreis\tMenge\nÄpfel; rot\t1,50\t3\nBirnen\t2,10\t5\n"
+        );
+        assert_eq!(
+            metadata.get("csv:delimiter").unwrap(),
+            &vec![";".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_csv_tab_and_pipe_delimiters() {
+        let tsv = b"station\tcity\televation\nAlpha\tBern, CH\t540\nBravo\tLyon\t173\n";
+        let (text, metadata) = csv::extract_csv_from_bytes(tsv).unwrap();
+        assert_eq!(
+            text,
+            "station\tcity\televation\nAlpha\tBern, CH\t540\nBravo\tLyon\t173\n"
+        );
+        assert_eq!(
+            metadata.get("csv:delimiter").unwrap(),
+            &vec!["\t".to_string()]
+        );
+        assert_eq!(metadata.get("Row-Count").unwrap(), &vec!["3".to_string()]);
+        assert_eq!(
+            metadata.get("Column-Count").unwrap(),
+            &vec!["3".to_string()]
+        );
+
+        // As many commas as pipes on the first line, but only the pipes split every row
+        let pipes = b"id|name, title, role|notes\n1|Alpha|\"a|b\"\n2|Bravo|none\n";
+        let (text, metadata) = csv::extract_csv_from_bytes(pipes).unwrap();
+        assert_eq!(
+            text,
+            "id\tname, title, role\tnotes\n1\tAlpha\ta|b\n2\tBravo\tnone\n"
+        );
+        assert_eq!(
+            metadata.get("csv:delimiter").unwrap(),
+            &vec!["|".to_string()]
+        );
+        assert_eq!(
+            metadata.get("Column-Count").unwrap(),
+            &vec!["3".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_bom_prefixed_text_files() {
+        let utf16le = |text: &str| -> Vec<u8> {
+            let units = text.encode_utf16().flat_map(u16::to_le_bytes);
+            [0xFF, 0xFE].into_iter().chain(units).collect()
+        };
+        let files = [
+            (
+                "bom.csv",
+                [
+                    b"\xEF\xBB\xBF".as_slice(),
+                    "name,city\nZo\u{eb},Bern\n".as_bytes(),
+                ]
+                .concat(),
+                "UTF-8",
+                "name\tcity\nZo\u{eb}\tBern\n",
+            ),
+            (
+                "bom.html",
+                utf16le("<html><body><p>Zo\u{eb} in Bern</p></body></html>"),
+                "UTF-16LE",
+                "Zo\u{eb} in Bern",
+            ),
+            (
+                "bom.xml",
+                utf16le("<?xml version=\"1.0\"?><note>Zo\u{eb} in Bern</note>"),
+                "UTF-16LE",
+                "Zo\u{eb} in Bern",
+            ),
+        ];
+
+        let dir = tempfile::tempdir().unwrap();
+        let extractor = PureRustExtractor::new();
+        for (name, content, encoding, expected) in files {
+            let path = dir.path().join(name);
+            std::fs::write(&path, content).unwrap();
+            let (text, metadata) = extractor.extract_file(&path).unwrap();
+            assert_eq!(text.trim(), expected.trim(), "{}", name);
+            assert_eq!(metadata["Content-Encoding"], [encoding], "{}", name);
+        }
+
+        let (_text, metadata) = csv::extract_csv_from_bytes(b"name,city\nAnn,Oslo\n").unwrap();
+        assert!(!metadata.contains_key("Content-Encoding"));
+    }
+
+    #[test]
+    fn test_pdf_layout_rotated_page() {
+        let pages = pdf::extract_pdf_layout("../test_files/documents/rotated.pdf").unwrap();
+        assert_eq!(pages[0].rotation, 0);
+        assert_eq!(
+            (pages[1].rotation, pages[1].width, pages[1].height),
+            (90, 792.0, 612.0)
+        );
+
+        // Text drawn sideways on a page with /Rotate 90 reads upright once the page is displayed
+        let lines: Vec<_> = pages[1]
+            .lines
+            .iter()
+            .map(|line| line.text.as_str())
+            .collect();
+        assert_eq!(
+            lines,
+            [
+                "Rotated pages are read upright.",
+                "The quick brown fox jumps over the lazy dog."
+            ]
+        );
+        let first = &pages[1].lines[0];
+        assert_eq!((first.x.round(), first.y.round()), (72.0, 100.0));
+        assert!(pages[1].lines[1].y > first.y);
+    }
+
+    #[test]
+    fn test_upright_rotated_pages() {
+        let data = pdf::upright_rotated_pages("../test_files/documents/rotated.pdf")
+            .unwrap()
+            .unwrap();
+
+        // The rotated page is stored as it was displayed, the other page is unchanged
+        let pages = pdf::extract_pdf_layout_from_bytes(&data).unwrap();
+        assert_eq!(
+            (pages[0].rotation, pages[0].width, pages[0].height),
+            (0, 612.0, 792.0)
+        );
+        assert_eq!(
+            (pages[1].rotation, pages[1].width, pages[1].height),
+            (0, 792.0, 612.0)
+        );
+        let lines: Vec<_> = pages[1]
+            .lines
+            .iter()
+            .map(|line| line.text.as_str())
+            .collect();
+        assert_eq!(
+            lines,
+            [
+                "Rotated pages are read upright.",
+                "The quick brown fox jumps over the lazy dog."
+            ]
+        );
+        let first = &pages[1].lines[0];
+        assert_eq!((first.x.round(), first.y.round()), (72.0, 100.0));
+
+        // Split off pages are upright as well
+        let splitter = pdf::PdfPageSplitter::open("../test_files/documents/rotated.pdf").unwrap();
+        let page = pdf::extract_pdf_layout_from_bytes(&splitter.page_pdf(1).unwrap()).unwrap();
+        assert_eq!(
+            (page[0].rotation, page[0].width, page[0].height),
+            (0, 792.0, 612.0)
+        );
+
+        let path = "../test_files/documents/ten-pages.pdf";
+        assert!(pdf::upright_rotated_pages(path).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_pdf_positions() {
+        let words =
+            pdf::extract_pdf_positions("../test_files/documents/running-headers.pdf").unwrap();
+
+        // "Sales grew in every region" is set in 12 point Helvetica at (72, 680) on page 2
+        let sales = words.iter().find(|word| word.text == "Sales").unwrap();
+        assert_eq!(sales.page, 2);
+        assert_eq!((sales.x.round(), sales.y.round()), (72.0, 100.0));
+        assert_eq!((sales.width.round(), sales.height.round()), (30.0, 12.0));
+        let grew = words.iter().find(|word| word.text == "grew").unwrap();
+        assert!(grew.x > sales.x + sales.width && grew.y == sales.y);
+
+        // The second of the three pages is blank
+        let words = pdf::extract_pdf_positions("../test_files/documents/blank-page.pdf").unwrap();
+        let pages: Vec<u32> = words.iter().map(|word| word.page).collect();
+        assert_eq!(pages, [1, 1, 1, 1, 1, 3, 3, 3, 3, 3, 3]);
+    }
+
+    #[test]
+    fn test_pdf_text_by_position() {
+        // The content stream draws the lines of the two columns in turns
+        let path = "../test_files/documents/two-columns.pdf";
+        let (text, _) = pdf::extract_pdf_text(path).unwrap();
+        assert!(text.find("The right column").unwrap() < text.find("before it ends").unwrap());
+
+        let (text, metadata) = pdf::extract_pdf_text_by_position(path).unwrap();
+        assert_eq!(
+            text.trim(),
+            "The left column starts here\nand continues on this line\n\
+            before it ends at the bottom.\nThe right column comes next\n\
+            with its own second line\nand a last line of its own."
+        );
+        assert_eq!(
+            metadata.get("dc:title").unwrap(),
+            &vec!["Two columns".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_pdf_info_xmp_conflict() {
+        let (_, metadata) =
+            pdf::extract_pdf_text("../test_files/documents/xmp-conflict.pdf").unwrap();
+
+        let values = |key: &str| metadata.get(key).cloned().unwrap_or_default();
+        assert_eq!(
+            values("Metadata-Conflict-dc:title"),
+            vec!["Quarterly Report", "Quarterly Report (Draft)"]
+        );
+        // The information dictionary value is reported
+        assert_eq!(values("dc:title"), vec!["Quarterly Report"]);
+        // Equal values and equal instants are no conflict
+        assert!(!metadata.contains_key("Metadata-Conflict-dc:creator"));
+        assert!(!metadata.contains_key("Metadata-Conflict-dcterms:created"));
+        // Fields only stored as XMP are merged in
+        assert_eq!(values("pdf:producer"), vec!["Extractous Fixtures 1.0"]);
+        assert_eq!(values("pdf:hasXMP"), vec!["true"]);
+    }
+
+    #[test]
+    fn test_truncate_counts_characters() {
+        // The two byte é counts as one character
+        let (text, _) = PureRustExtractor::with_max_length(4)
+            .extract_bytes("café,crème\n".as_bytes(), crate::DocumentFormat::Csv)
+            .unwrap();
+        assert_eq!(text, "café");
+    }
+
+    #[test]
+    fn test_xlsx_structured() {
+        let sheets =
+            office::extract_xlsx_structured("../test_files/documents/typed-cells.xlsx").unwrap();
+
+        let names: Vec<&str> = sheets.iter().map(|sheet| sheet.name.as_str()).collect();
+        assert_eq!(names, ["Orders", "Notes"]);
+        let orders = &sheets[0].rows;
+        assert_eq!(
+            orders[1],
+            ["A-1", "1234.5", "3", "2024-03-15", "2024-03-16 14:30:00"]
+        );
+        assert_eq!(orders[2], ["A-2", "0.3", "12", "1999-12-31", ""]);
+        assert_eq!(sheets[0].first_cell, (0, 0));
+        // The Notes sheet starts at B2
+        assert_eq!(sheets[1].first_cell, (1, 1));
+        assert_eq!(sheets[1].rows, [["Amounts are in euros"]]);
+    }
+
+    #[cfg(feature = "office-encryption")]
+    #[test]
+    fn test_encrypted_xlsx_with_password() {
+        let (expected, _) =
+            office::extract_xlsx_text("../test_files/documents/vodafone.xlsx").unwrap();
+
+        let (text, metadata) = PureRustExtractor::new()
+            .set_password(Some("extractous".to_string()))
+            .extract_file("../test_files/documents/vodafone-encrypted.xlsx")
+            .unwrap();
+
+        assert_eq!(text, expected);
+        assert_eq!(
+            metadata.get("Parser").unwrap(),
+            &vec!["pure-rust-excel".to_string()]
+        );
+    }
+
+    #[cfg(feature = "office-encryption")]
+    #[test]
+    fn test_encrypted_xlsx_without_or_with_wrong_password() {
+        let path = "../test_files/documents/vodafone-encrypted.xlsx";
+
+        let result = PureRustExtractor::new().extract_file(path);
+        assert!(matches!(result, Err(Error::EncryptedDocument(_))));
+
+        let result = PureRustExtractor::new()
+            .set_password(Some("wrong".to_string()))
+            .extract_file(path);
+        assert!(matches!(result, Err(Error::EncryptedDocument(_))));
+    }
+}
@@ -43,6 +43,52 @@ pub fn clean_text_fast(input: &str) -> String {
     result.trim().to_string()
 }
 
+/// Clean text with the granularity of `config`. Other control characters are removed and runs
+/// of spaces collapsed, but line breaks are kept and lines are trimmed.
+pub fn clean_text_with_config(input: &str, config: &crate::TextCleaningConfig) -> String {
+    let mut lines = Vec::new();
+    let mut line = String::new();
+    for ch in input.chars() {
+        match ch {
+            '\n' => lines.push(std::mem::take(&mut line)),
+            '\u{c}' if config.preserve_page_breaks => {
+                lines.push(std::mem::take(&mut line));
+                lines.push(String::new());
+            }
+            '\t' if config.preserve_tabs => line.push('\t'),
+            ch if ch.is_whitespace() => {
+                if !line.is_empty() && !line.ends_with(' ') {
+                    line.push(' ');
+                }
+            }
+            ch if ch.is_control() => {}
+            ch => line.push(ch),
+        }
+    }
+    lines.push(line);
+
+    let mut result = String::with_capacity(input.len());
+    let mut previous_blank = false;
+    for line in &lines {
+        let line = line.trim_end_matches(' ');
+        let blank = line.trim().is_empty();
+        if !(blank && previous_blank && config.collapse_blank_lines) {
+            result.push_str(line);
+            result.push('\n');
+        }
+        previous_blank = blank;
+    }
+    result.trim_matches('\n').to_string()
+}
+
+/// Remove the C0 control characters except tabs and line breaks, e.g. NUL
+pub fn strip_control_chars(input: &str) -> String {
+    input
+        .chars()
+        .filter(|&ch| ch >= ' ' || matches!(ch, '\t' | '\n' | '\r'))
+        .collect()
+}
+
 /// Fast UTF-8 validation (placeholder for SIMD implementation)
 pub fn validate_utf8_fast(bytes: &[u8]) -> bool {
     // Use standard library validation for now
@@ -70,27 +116,154 @@ pub fn normalize_whitespace(input: &str) -> String {
     result.trim().to_string()
 }
 
-/// Remove common document artifacts and clean text
+/// Like `normalize_whitespace`, with byte-identical output, but checks 16 bytes at once for
+/// whitespace on x86_64. Only ASCII text takes the vectorized path, any other text and other
+/// targets use `normalize_whitespace`
+pub fn normalize_whitespace_simd(input: &str) -> String {
+    #[cfg(all(target_arch = "x86_64", target_feature = "sse2"))]
+    if input.is_ascii() {
+        return sse2::normalize_ascii_whitespace(input);
+    }
+    normalize_whitespace(input)
+}
+
+#[cfg(all(target_arch = "x86_64", target_feature = "sse2"))]
+mod sse2 {
+    use std::arch::x86_64::*;
+
+    /// Whether an ASCII byte is whitespace for `char::is_whitespace`, i.e. a space or one of
+    /// `\t`, `\n`, `\x0b`, `\x0c` and `\r`
+    fn is_whitespace(byte: u8) -> bool {
+        byte == b' ' || (b'\t'..=b'\r').contains(&byte)
+    }
+
+    /// Collapses runs of whitespace of an ASCII text into a space and trims the ends
+    pub(super) fn normalize_ascii_whitespace(input: &str) -> String {
+        let bytes = input.as_bytes();
+        let mut out = Vec::with_capacity(bytes.len());
+        // Leading whitespace is dropped, as if a space had been written before
+        let mut last_was_space = true;
+
+        let mut blocks = bytes.chunks_exact(16);
+        for block in &mut blocks {
+            // SAFETY: the block holds 16 bytes and sse2 is enabled for this target
+            let mask = unsafe { whitespace_mask(block) };
+            if mask == 0 {
+                out.extend_from_slice(block);
+                last_was_space = false;
+                continue;
+            }
+            for (i, &byte) in block.iter().enumerate() {
+                if mask & (1 << i) == 0 {
+                    out.push(byte);
+                    last_was_space = false;
+                } else if !last_was_space {
+                    out.push(b' ');
+                    last_was_space = true;
+                }
+            }
+        }
+        for &byte in blocks.remainder() {
+            if !is_whitespace(byte) {
+                out.push(byte);
+                last_was_space = false;
+            } else if !last_was_space {
+                out.push(b' ');
+                last_was_space = true;
+            }
+        }
+
+        if out.last() == Some(&b' ') {
+            out.pop();
+        }
+        // The output only holds bytes of the ASCII input and spaces
+        String::from_utf8(out).expect("ASCII input gives ASCII output")
+    }
+
+    /// A bit per byte of the 16 byte block, set for whitespace
+    unsafe fn whitespace_mask(block: &[u8]) -> u32 {
+        let bytes = _mm_loadu_si128(block.as_ptr() as *const __m128i);
+        let spaces = _mm_cmpeq_epi8(bytes, _mm_set1_epi8(b' ' as i8));
+        // \t to \r are the bytes 9 to 13, the byte minus 9 is at most 4 for them only
+        let offset = _mm_sub_epi8(bytes, _mm_set1_epi8(b'\t' as i8));
+        let controls = _mm_cmpeq_epi8(_mm_min_epu8(offset, _mm_set1_epi8(4)), offset);
+        _mm_movemask_epi8(_mm_or_si128(spaces, controls)) as u32
+    }
+}
+
+/// Converts the line endings of `input` to `line_ending`, treating `\r\n`, `\n` and a lone `\r`
+/// as one line break each. `LineEnding::Preserve` returns the input unchanged
+pub fn normalize_line_endings(input: &str, line_ending: crate::LineEnding) -> String {
+    let newline = match line_ending {
+        crate::LineEnding::Preserve => return input.to_string(),
+        crate::LineEnding::Lf if !input.contains('\r') => return input.to_string(),
+        crate::LineEnding::Lf => "\n",
+        crate::LineEnding::Crlf => "\r\n",
+    };
+
+    let bytes = input.as_bytes();
+    let mut result = String::with_capacity(input.len() + input.len() / 32);
+    let mut start = 0;
+    let mut i = 0;
+    while i < bytes.len() {
+        let break_len = match bytes[i] {
+            b'\r' if bytes.get(i + 1) == Some(&b'\n') => 2,
+            b'\r' | b'\n' => 1,
+            _ => {
+                i += 1;
+                continue;
+            }
+        };
+        // Line breaks are ASCII, so the slices end at char boundaries
+        result.push_str(&input[start..i]);
+        result.push_str(newline);
+        i += break_len;
+        start = i;
+    }
+    result.push_str(&input[start..]);
+    result
+}
+
+/// Remove common document artifacts and clean text, with the default
+/// [`crate::DocumentCleaningConfig`]: runs of blank lines become a single blank line
 pub fn clean_document_text(input: &str) -> String {
+    clean_document_text_with_config(input, &crate::DocumentCleaningConfig::default())
+}
+
+/// Remove common document artifacts and clean text, with the blank lines and the short lines
+/// handled as set in `config`
+pub fn clean_document_text_with_config(
+    input: &str,
+    config: &crate::DocumentCleaningConfig,
+) -> String {
     let mut result = String::with_capacity(input.len());
-    let lines: Vec<&str> = input.lines().collect();
-    
-    for line in lines {
+    let mut after_blank = false;
+
+    for line in input.lines() {
         let trimmed = line.trim();
         
-        // Skip empty lines
         if trimmed.is_empty() {
+            after_blank = true;
             continue;
         }
         
-        // Skip lines that are likely headers/footers (very short or all caps)
-        if trimmed.len() < 3 || (trimmed.len() < 20 && trimmed.chars().all(|c| c.is_uppercase() || c.is_whitespace())) {
+        // Lines that are likely headers/footers (very short or all caps)
+        let short = trimmed.len() < 3
+            || (trimmed.len() < 20
+                && trimmed
+                    .chars()
+                    .all(|c| c.is_uppercase() || c.is_whitespace()));
+        if config.drop_short_lines && short {
             continue;
         }
         
         // Clean the line
         let cleaned = clean_text_fast(trimmed);
         if !cleaned.is_empty() {
+            if after_blank && config.collapse_blank_lines && !result.is_empty() {
+                result.push('\n');
+            }
+            after_blank = false;
             result.push_str(&cleaned);
             result.push('\n');
         }
@@ -99,6 +272,28 @@ pub fn clean_document_text(input: &str) -> String {
     result.trim().to_string()
 }
 
+/// Join words hyphenated at the end of a line, e.g. `sum-` followed by a line starting with
+/// `mer, ahead` becomes `summer, ahead`. The hyphen must follow a letter and the next line
+/// start with a lowercase letter, so list items and compounds like `X-\nRay` are kept
+pub fn dehyphenate(input: &str) -> String {
+    let mut lines: Vec<String> = Vec::new();
+    for line in input.split('\n') {
+        if let Some(last) = lines.last_mut() {
+            let hyphenated = last
+                .strip_suffix('-')
+                .is_some_and(|head| head.ends_with(char::is_alphabetic));
+            let continued = line.trim_start().starts_with(char::is_lowercase);
+            if hyphenated && continued {
+                last.pop();
+                last.push_str(line.trim_start());
+                continue;
+            }
+        }
+        lines.push(line.to_string());
+    }
+    lines.join("\n")
+}
+
 /// Extract text content from mixed content (e.g., HTML with text)
 pub fn extract_text_content(input: &str) -> String {
     let mut result = String::with_capacity(input.len());
@@ -131,6 +326,12 @@ pub fn extract_text_content(input: &str) -> String {
 
 /// Optimized text truncation that respects word boundaries
 pub fn truncate_text_smart(input: &str, max_length: usize) -> String {
+    truncate_text_smart_with_suffix(input, max_length, "...")
+}
+
+/// Like `truncate_text_smart`, but appends `suffix` instead of `"..."` to a truncated text, an
+/// empty suffix appends nothing
+pub fn truncate_text_smart_with_suffix(input: &str, max_length: usize, suffix: &str) -> String {
     if input.len() <= max_length {
         return input.to_string();
     }
@@ -148,7 +349,7 @@ pub fn truncate_text_smart(input: &str, max_length: usize) -> String {
     }
     
     // If we couldn't find a word boundary in a reasonable distance, just truncate
-    if truncate_at < max_length.saturating_sub(50) {
+    if truncate_at == 0 || truncate_at < max_length.saturating_sub(50) {
         truncate_at = max_length;
     }
     
@@ -159,12 +360,138 @@ pub fn truncate_text_smart(input: &str, max_length: usize) -> String {
     
     let mut result = input[..truncate_at].to_string();
     if truncate_at < input.len() {
-        result.push_str("...");
+        result.push_str(suffix);
     }
     
     result
 }
 
+/// Splits `text` into windows of at most `size` characters, e.g. for an embedding model, where
+/// each window repeats up to `overlap` characters from the end of the one before. Windows end at
+/// whitespace and start at a word, only a word longer than `size` is cut. An overlap of `size` or
+/// more is reduced to `size - 1`, a `size` of 0 gives no windows
+pub fn chunk_text(text: &str, size: usize, overlap: usize) -> Vec<&str> {
+    let mut chunks = Vec::new();
+    if size == 0 {
+        return chunks;
+    }
+    let overlap = overlap.min(size - 1);
+    // Without trailing whitespace, every window but the last is followed by more words
+    let text = text.trim_end();
+    let chars: Vec<(usize, char)> = text.char_indices().collect();
+    let offset = |i: usize| chars.get(i).map_or(text.len(), |&(offset, _)| offset);
+    let is_space = |i: usize| chars[i].1.is_whitespace();
+
+    let mut start = 0;
+    loop {
+        while start < chars.len() && is_space(start) {
+            start += 1;
+        }
+        if chars.len() - start <= size {
+            if start < chars.len() {
+                chunks.push(&text[offset(start)..]);
+            }
+            return chunks;
+        }
+
+        let limit = start + size;
+        start = match (start + 1..=limit).rev().find(|&i| is_space(i)) {
+            Some(end) => {
+                chunks.push(text[offset(start)..offset(end)].trim_end());
+                // Step back by the overlap, then forward to the start of a word
+                let mut next = end.saturating_sub(overlap).max(start + 1);
+                while next < end && (!is_space(next - 1) || is_space(next)) {
+                    next += 1;
+                }
+                next
+            }
+            None => {
+                chunks.push(&text[offset(start)..offset(limit)]);
+                limit - overlap
+            }
+        };
+    }
+}
+
+/// Abbreviations that end in a full stop without ending the sentence, matched case insensitively
+const ABBREVIATIONS: &[&str] = &[
+    "mr", "mrs", "ms", "dr", "prof", "sr", "jr", "vs", "cf", "fig", "approx", "inc", "ltd", "corp",
+];
+
+/// Splits `text` into sentences, e.g. to build chunks that do not end mid-sentence. A sentence
+/// ends at `.`, `!` or `?`, with any closing quotes or brackets, when whitespace and a capital
+/// letter follow. Full stops of abbreviations such as `Mr.`, `e.g.`, `U.S.` or an initial before a
+/// name do not end a sentence, nor do those in numbers such as `3.14`. The sentences are trimmed
+/// slices of `text`
+pub fn split_sentences(text: &str) -> Vec<&str> {
+    let mut sentences = Vec::new();
+    let mut start = 0;
+    let mut chars = text.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        if !matches!(c, '.' | '!' | '?') {
+            continue;
+        }
+        let mut end = i + c.len_utf8();
+        while let Some(&(j, next)) = chars.peek() {
+            if !matches!(
+                next,
+                '.' | '!' | '?' | '"' | '\'' | ')' | ']' | '\u{201d}' | '\u{2019}'
+            ) {
+                break;
+            }
+            end = j + next.len_utf8();
+            chars.next();
+        }
+
+        let rest = &text[end..];
+        let next_word = rest.trim_start();
+        if next_word.len() == rest.len() || !next_word.starts_with(char::is_uppercase) {
+            continue;
+        }
+        if c == '.' && is_abbreviation(&text[start..i], next_word) {
+            continue;
+        }
+        sentences.push(text[start..end].trim());
+        start = end;
+    }
+
+    let last = text[start..].trim();
+    if !last.is_empty() {
+        sentences.push(last);
+    }
+    sentences
+}
+
+/// Whether the last word of `text`, which is followed by a full stop and then by `next`, is an
+/// abbreviation
+fn is_abbreviation(text: &str, next: &str) -> bool {
+    let quotes = ['(', '"', '\'', '\u{201c}', '\u{2018}'];
+    let mut words = text.split_whitespace().rev();
+    let word = words.next().unwrap_or_default().trim_start_matches(quotes);
+    let mut letters = word.chars();
+    match (letters.next(), letters.next()) {
+        // An initial such as the J and the R in J. R. Tolkien or the F in John F. Kennedy: a
+        // capital followed by another initial, or by a name after a name or at the start of the
+        // sentence. The B of plan B. is the end of a sentence
+        (Some(initial), None) if initial.is_uppercase() => {
+            let next = next.split_whitespace().next().unwrap_or_default();
+            let next_is_initial = next.chars().count() == 2 && next.ends_with('.');
+            let after_name = match words.next() {
+                Some(previous) => previous
+                    .trim_start_matches(quotes)
+                    .starts_with(char::is_uppercase),
+                None => true,
+            };
+            next_is_initial || after_name
+        }
+        // Dotted abbreviations such as e.g or U.S, but not numbers such as 2.50
+        _ if word.contains('.') => word.chars().all(|c| c == '.' || c.is_alphabetic()),
+        _ => ABBREVIATIONS
+            .iter()
+            .any(|abbreviation| abbreviation.eq_ignore_ascii_case(word)),
+    }
+}
+
 /// Fast character counting for different character types
 pub struct TextStats {
     pub total_chars: usize,
@@ -227,12 +554,41 @@ mod tests {
         assert_eq!(result, "Hello world with multiplespaces");
     }
     
+    #[test]
+    fn test_clean_text_with_config() {
+        let input = "Name\tPrice\r\nApple \t 1.20\x00\n\n\n\nTotal\x0cNext   page\n";
+
+        let config = crate::TextCleaningConfig::new();
+        assert_eq!(
+            clean_text_with_config(input, &config),
+            "Name Price\nApple 1.20\n\nTotal\n\nNext page"
+        );
+
+        let config = crate::TextCleaningConfig::new()
+            .set_preserve_page_breaks(false)
+            .set_preserve_tabs(true)
+            .set_collapse_blank_lines(false);
+        assert_eq!(
+            clean_text_with_config(input, &config),
+            "Name\tPrice\nApple \t 1.20\n\n\n\nTotal Next page"
+        );
+    }
+
     #[test]
     fn test_normalize_whitespace() {
         let input = "  Hello    world  \n\n  test  ";
         let result = normalize_whitespace(input);
         assert_eq!(result, "Hello world test");
     }
+
+    #[test]
+    fn test_dehyphenate() {
+        let input = "reached full capacity in the sum-\nmer, ahead of plan.\nX-\nRay\n- item";
+        assert_eq!(
+            dehyphenate(input),
+            "reached full capacity in the summer, ahead of plan.\nX-\nRay\n- item"
+        );
+    }
     
     #[test]
     fn test_extract_text_content() {
@@ -249,7 +605,172 @@ mod tests {
         assert!(result.ends_with("..."));
         assert!(!result.contains("truncat")); // Should break at word boundary
     }
+
+    #[test]
+    fn test_truncate_text_smart_with_suffix() {
+        let input = "This is a long sentence that should be truncated at word boundaries";
+        assert_eq!(
+            truncate_text_smart_with_suffix(input, 30, "\u{2026}"),
+            "This is a long sentence that\u{2026}"
+        );
+        assert_eq!(
+            truncate_text_smart_with_suffix(input, 30, ""),
+            "This is a long sentence that"
+        );
+        // A text that fits is returned without a suffix
+        assert_eq!(
+            truncate_text_smart_with_suffix("Short", 30, " [cut]"),
+            "Short"
+        );
+    }
     
+    #[test]
+    fn test_normalize_whitespace_simd() {
+        let text = "  Two\tcolumns\x0b\x0cand\r\n\r\nlines that run past sixteen bytes \n";
+        assert_eq!(
+            normalize_whitespace_simd(text),
+            "Two columns and lines that run past sixteen bytes"
+        );
+        assert_eq!(normalize_whitespace_simd(text), normalize_whitespace(text));
+        // Non-breaking and em spaces take the scalar path
+        assert_eq!(normalize_whitespace_simd("a\u{a0}\u{2003} b "), "a b");
+        assert_eq!(normalize_whitespace_simd(" \t\n "), "");
+    }
+
+    proptest::proptest! {
+        #[test]
+        fn test_normalize_whitespace_simd_parity(
+            text in "[ \t\n\r\x0b\x0ca-zA-Z0-9.]{0,200}",
+            unicode in "[ \t\na-z\u{a0}\u{2003}\u{85}é]{0,100}",
+        ) {
+            proptest::prop_assert_eq!(
+                normalize_whitespace_simd(&text),
+                normalize_whitespace(&text)
+            );
+            proptest::prop_assert_eq!(
+                normalize_whitespace_simd(&unicode),
+                normalize_whitespace(&unicode)
+            );
+        }
+    }
+
+    #[test]
+    fn test_normalize_line_endings() {
+        let mixed = "one\r\ntwo\nthree\rfour\r\n\r\n";
+        assert_eq!(
+            normalize_line_endings(mixed, crate::LineEnding::Lf),
+            "one\ntwo\nthree\nfour\n\n"
+        );
+        assert_eq!(
+            normalize_line_endings(mixed, crate::LineEnding::Crlf),
+            "one\r\ntwo\r\nthree\r\nfour\r\n\r\n"
+        );
+        assert_eq!(
+            normalize_line_endings(mixed, crate::LineEnding::Preserve),
+            mixed
+        );
+        assert_eq!(
+            normalize_line_endings("héllo\nwörld", crate::LineEnding::Crlf),
+            "héllo\r\nwörld"
+        );
+    }
+
+    #[test]
+    fn test_chunk_text() {
+        let text = "one two three four five six seven eight nine ten ";
+        let chunks = chunk_text(text, 16, 10);
+        assert_eq!(
+            chunks,
+            [
+                "one two three",
+                "two three four",
+                "three four five",
+                "four five six",
+                "five six seven",
+                "six seven eight",
+                // "seven eight" would be 11 characters of overlap
+                "eight nine ten",
+            ]
+        );
+        for pair in chunks.windows(2) {
+            assert!(pair.iter().all(|chunk| chunk.chars().count() <= 16));
+            // The next window starts with words from the end of the one before
+            let first_word = pair[1].split(' ').next().unwrap();
+            assert!(pair[0].contains(first_word), "{:?}", pair);
+        }
+
+        assert_eq!(
+            chunk_text(text, 16, 0),
+            ["one two three", "four five six", "seven eight nine", "ten"]
+        );
+        assert_eq!(chunk_text("", 16, 4), Vec::<&str>::new());
+        assert_eq!(chunk_text(text, 0, 4), Vec::<&str>::new());
+    }
+
+    #[test]
+    fn test_chunk_text_cuts_long_words_between_chars() {
+        // Each é takes two bytes, the windows count characters
+        assert_eq!(chunk_text("ééééééééé", 4, 1), ["éééé", "éééé", "ééé"]);
+        // The overlap is kept below the size, so the windows still move on
+        assert_eq!(chunk_text("abcdef", 3, 5), ["abc", "bcd", "cde", "def"]);
+        assert_eq!(chunk_text("ab ééééééé", 4, 0), ["ab", "éééé", "ééé"]);
+    }
+
+    #[test]
+    fn test_split_sentences() {
+        let text = "Mr. Smith paid 3.14 for coffee. Dr. Jones, e.g. the surgeon, works at the \
+            U.S. Army hospital! Did J. R. Tolkien write it? Yes.\n\nIt was \"fine.\" It cost 2.50. \
+            Then it ended";
+        assert_eq!(
+            split_sentences(text),
+            [
+                "Mr. Smith paid 3.14 for coffee.",
+                "Dr. Jones, e.g. the surgeon, works at the U.S. Army hospital!",
+                "Did J. R. Tolkien write it?",
+                "Yes.",
+                "It was \"fine.\"",
+                "It cost 2.50.",
+                "Then it ended",
+            ]
+        );
+        // No., Co. and St. are words as well, a lone capital is an initial only before a name
+        let text = "Is that a yes or a no. It is. Ask the co. Visit plan B. Then John F. Kennedy \
+            came.";
+        assert_eq!(
+            split_sentences(text),
+            [
+                "Is that a yes or a no.",
+                "It is.",
+                "Ask the co.",
+                "Visit plan B.",
+                "Then John F. Kennedy came.",
+            ]
+        );
+        // Without a capital letter after it, a full stop does not end the sentence
+        let text = "See page 3. or page 4. for more";
+        assert_eq!(split_sentences(text), [text]);
+        assert_eq!(split_sentences("  "), Vec::<&str>::new());
+    }
+
+    #[test]
+    fn test_clean_document_text() {
+        let input = "ANNUAL REPORT\n\n\n\nRevenue grew in 2023.\n  Costs fell.\n\nQ4\n\n\n\
+            Outlook is stable.\n3\n";
+        // Headings and single blank lines are kept
+        assert_eq!(
+            clean_document_text(input),
+            "ANNUAL REPORT\n\nRevenue grew in 2023.\nCosts fell.\n\nQ4\n\nOutlook is stable.\n3"
+        );
+
+        let config = crate::DocumentCleaningConfig::new()
+            .set_collapse_blank_lines(false)
+            .set_drop_short_lines(true);
+        assert_eq!(
+            clean_document_text_with_config(input, &config),
+            "Revenue grew in 2023.\nCosts fell.\nOutlook is stable."
+        );
+    }
+
     #[test]
     fn test_text_stats() {
         let text = "Hello world! 123";
@@ -5,7 +5,9 @@ use crate::Metadata;
 use jni::errors::jni_error_code_to_result;
 use jni::objects::{JByteBuffer, JObject, JObjectArray, JString, JValue, JValueOwned};
 use jni::{sys, JNIEnv, JavaVM};
+use std::borrow::Cow;
 use std::collections::HashMap;
+use std::str::Utf8Error;
 
 /// Calls a static method and prints any thrown exceptions to stderr
 pub fn jni_new_direct_buffer<'local>(
@@ -80,17 +82,53 @@ pub fn jni_new_string_as_jvalue<'local>(
     Ok(JValueOwned::from(jstring))
 }
 
-/// Converts a java object to a rust string
+/// creates a new java string from an optional rust str, None becomes a java null
+pub fn jni_new_optional_string_as_jvalue<'local>(
+    env: &mut JNIEnv<'local>,
+    s: Option<&str>,
+) -> ExtractResult<JValueOwned<'local>> {
+    match s {
+        Some(s) => jni_new_string_as_jvalue(env, s),
+        None => Ok(JValueOwned::from(JObject::null())),
+    }
+}
+
+/// Converts a java object to a rust string. Invalid data is replaced by U+FFFD, see
+/// `jni_jobject_to_string_strict` to detect it instead
 pub fn jni_jobject_to_string<'local>(
     env: &mut JNIEnv<'local>,
     jobject: JObject<'local>,
 ) -> ExtractResult<String> {
     let jstring_output = JString::from(jobject);
     let javastr_output = unsafe { env.get_string_unchecked(&jstring_output)? };
-    let output_str = javastr_output.to_string_lossy();
-    //let output_str = javastr_output.to_str().map_err(Error::Utf8Error)?;
+    let bytes = javastr_output.to_bytes();
+    let output_str = decode_java_string(bytes).unwrap_or_else(|_| String::from_utf8_lossy(bytes));
 
-    Ok(output_str.to_string())
+    Ok(output_str.into_owned())
+}
+
+/// Converts a java object to a rust string, returns `Error::Utf8Error` for invalid data
+pub fn jni_jobject_to_string_strict<'local>(
+    env: &mut JNIEnv<'local>,
+    jobject: JObject<'local>,
+) -> ExtractResult<String> {
+    let jstring_output = JString::from(jobject);
+    let javastr_output = unsafe { env.get_string_unchecked(&jstring_output)? };
+    let output_str = decode_java_string(javastr_output.to_bytes()).map_err(Error::Utf8Error)?;
+
+    Ok(output_str.into_owned())
+}
+
+/// Decodes the modified UTF-8 that JNI passes strings in. It differs from UTF-8 in the
+/// characters outside the basic multilingual plane, e.g. emoji, which are encoded as two
+/// surrogates of three bytes each, and in the NUL character, which is encoded as two bytes.
+/// Decoding it as UTF-8 replaces those characters
+fn decode_java_string(bytes: &[u8]) -> Result<Cow<'_, str>, Utf8Error> {
+    match cesu8::from_java_cesu8(bytes) {
+        Ok(decoded) => Ok(decoded),
+        // Plain UTF-8 is accepted too, the error is that of the UTF-8 decoding
+        Err(_) => std::str::from_utf8(bytes).map(Cow::Borrowed),
+    }
 }
 
 /// Converts a Java String[] to a Rust Vec<String>
@@ -98,6 +136,25 @@ pub fn jni_jobject_array_to_vec<'local>(
     env: &mut JNIEnv<'local>,
     array: JObject<'local>,
 ) -> ExtractResult<Vec<String>> {
+    jobject_array_to_vec(env, array, jni_jobject_to_string)
+}
+
+/// Converts a Java String[] to a Rust Vec<String>, returns `Error::Utf8Error` for invalid data
+pub fn jni_jobject_array_to_vec_strict<'local>(
+    env: &mut JNIEnv<'local>,
+    array: JObject<'local>,
+) -> ExtractResult<Vec<String>> {
+    jobject_array_to_vec(env, array, jni_jobject_to_string_strict)
+}
+
+fn jobject_array_to_vec<'local, F>(
+    env: &mut JNIEnv<'local>,
+    array: JObject<'local>,
+    to_string: F,
+) -> ExtractResult<Vec<String>>
+where
+    F: Fn(&mut JNIEnv<'local>, JObject<'local>) -> ExtractResult<String>,
+{
     let j_array_string = JObjectArray::from(array);
     let j_array_length = env.get_array_length(&j_array_string)?;
 
@@ -105,7 +162,7 @@ pub fn jni_jobject_array_to_vec<'local>(
 
     for i in 0..j_array_length {
         let elem_obj = env.get_object_array_element(&j_array_string, i)?;
-        let elem_str = jni_jobject_to_string(env, elem_obj)?;
+        let elem_str = to_string(env, elem_obj)?;
         vec.push(elem_str);
     }
 
@@ -125,7 +182,8 @@ pub fn jni_tika_metadata_to_rust_metadata<'local>(
             &[],
         )?
         .l()?;
-    let keys_names = jni_jobject_array_to_vec(env, j_keys_names)?;
+    // Keys are looked up by name, a key with replaced characters would not be found
+    let keys_names = jni_jobject_array_to_vec_strict(env, j_keys_names)?;
     let mut metadata = HashMap::new();
     for key_name in keys_names.iter() {
         let j_key_name = jni_new_string_as_jvalue(env, key_name)?;
@@ -140,9 +198,29 @@ pub fn jni_tika_metadata_to_rust_metadata<'local>(
         let key_metadata = jni_jobject_array_to_vec(env, j_obj_array_name_metadata)?;
         metadata.insert(key_name.to_string(), key_metadata);
     }
+    lift_email_headers(&mut metadata);
     Ok(metadata)
 }
 
+/// Tika's email parsers keep every header as `Message:Raw-Header:<name>`. Fills the main
+/// headers into the keys used across email formats when a parser did not set them:
+/// `Message-From`, `Message-To`, `Message-Cc` and the subject as `dc:title`
+fn lift_email_headers(metadata: &mut Metadata) {
+    for (header, key) in [
+        ("From", "Message-From"),
+        ("To", "Message-To"),
+        ("Cc", "Message-Cc"),
+        ("Subject", "dc:title"),
+    ] {
+        if metadata.contains_key(key) {
+            continue;
+        }
+        if let Some(values) = metadata.get(&format!("Message:Raw-Header:{}", header)) {
+            metadata.insert(key.to_string(), values.clone());
+        }
+    }
+}
+
 /// Checks if there is an exception in the jni environment, describes it to
 /// the stderr and finally clears it
 pub fn jni_check_exception(env: &mut JNIEnv) -> ExtractResult<bool> {
@@ -154,13 +232,55 @@ pub fn jni_check_exception(env: &mut JNIEnv) -> ExtractResult<bool> {
     Ok(false)
 }
 
+/// The number of causes of a java exception whose messages are collected
+const MAX_EXCEPTION_CAUSES: usize = 8;
+
+/// Takes the pending java exception, if any, and returns its message followed by the messages of
+/// its causes, e.g. `XML parse error: The element type "p" must be terminated`
+pub fn jni_take_exception_message<'local>(
+    env: &mut JNIEnv<'local>,
+) -> ExtractResult<Option<String>> {
+    if !env.exception_check()? {
+        return Ok(None);
+    }
+    let exception = env.exception_occurred()?;
+    env.exception_clear()?;
+
+    let mut messages: Vec<String> = Vec::new();
+    let mut throwable = JObject::from(exception);
+    // A cause chain can loop back on itself
+    for _ in 0..MAX_EXCEPTION_CAUSES {
+        if throwable.is_null() {
+            break;
+        }
+        let message = env
+            .call_method(&throwable, "getMessage", "()Ljava/lang/String;", &[])?
+            .l()?;
+        if !message.is_null() {
+            let message = jni_jobject_to_string(env, message)?;
+            // Wrapping exceptions often repeat the message of their cause
+            if !message.is_empty() && !messages.iter().any(|m| m.contains(&message)) {
+                messages.push(message);
+            }
+        }
+        throwable = env
+            .call_method(&throwable, "getCause", "()Ljava/lang/Throwable;", &[])?
+            .l()?;
+    }
+
+    if messages.is_empty() {
+        messages.push("Java exception without a message".to_string());
+    }
+    Ok(Some(messages.join(": ")))
+}
+
 /// Creates a new graalvm isolate using the invocation api. A [GraalVM isolate](https://medium.com/graalvm/isolates-and-compressed-references-more-flexible-and-efficient-memory-management-for-graalvm-a044cc50b67e) is a disjoint heap
 /// that allows multiple tasks in the same VM instance to run independently.
 ///
 /// This function uses the standard JVM invocation API and relies on the jni-sys crate.
 /// No need to specify any libraries because the graalvm native image is already
-/// linked in by the build script.
-pub fn create_vm_isolate() -> JavaVM {
+/// linked in by the build script. Returns the reason when the isolate cannot be created.
+pub fn create_vm_isolate() -> Result<JavaVM, String> {
     unsafe {
         let vm_options: Vec<sys::JavaVMOption> = vec![
             // Set java.library.path to be able to load libawt.so, which must be in the same dir as libtika_native.so
@@ -190,14 +310,12 @@ pub fn create_vm_isolate() -> JavaVM {
             &mut env as *mut *mut sys::JNIEnv as *mut *mut c_void,
             &mut args as *mut sys::JavaVMInitArgs as *mut c_void,
         );
-        jni_error_code_to_result(jni_res).unwrap_or_else(|e| {
-            panic!("Failed creating the graal native vm: {:?}", e);
-        });
+        jni_error_code_to_result(jni_res)
+            .map_err(|e| format!("creating the graal native vm returned {:?}", e))?;
 
         // This sys call already attaches the current thread to the vm
-        JavaVM::from_raw(ptr).unwrap_or_else(|e| {
-            panic!("Failed creating the graal native from pointer: {:?}", e);
-        })
+        JavaVM::from_raw(ptr)
+            .map_err(|e| format!("the graal native vm pointer is invalid: {:?}", e))
     }
 }
 
@@ -261,3 +379,23 @@ pub fn create_vm_isolate() -> JavaVM {
 //
 //     Ok(output)
 // }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_java_string() {
+        let text = "Café – 北京 😀 \0 end";
+        let modified_utf8 = cesu8::to_java_cesu8(text);
+        // The emoji takes six bytes and the NUL two, which are not valid UTF-8
+        assert!(std::str::from_utf8(&modified_utf8).is_err());
+        assert_eq!(decode_java_string(&modified_utf8).unwrap(), text);
+
+        assert_eq!(
+            decode_java_string("plain UTF-8 😀".as_bytes()).unwrap(),
+            "plain UTF-8 😀"
+        );
+        assert!(decode_java_string(b"broken \xff\xfe").is_err());
+    }
+}
@@ -1,60 +1,82 @@
 use std::sync::OnceLock;
 
-use crate::errors::ExtractResult;
+use crate::errors::{Error, ExtractResult};
 use crate::tika::jni_utils::*;
 use crate::tika::wrappers::*;
 use crate::{
-    CharSet, Metadata, OfficeParserConfig, PdfParserConfig, StreamReader, TesseractOcrConfig,
+    CharSet, Metadata, OfficeParserConfig, PdfParserConfig, StreamReader, StreamSource,
+    TesseractOcrConfig, TikaHandler,
 };
 use jni::objects::JValue;
 use jni::{AttachGuard, JavaVM};
 
 /// Returns a reference to the shared VM isolate
 /// Instead of creating a new VM for every tika call, we create a single VM that is shared
-/// throughout the application.
-pub(crate) fn vm() -> &'static JavaVM {
+/// throughout the application. If the isolate cannot be created, e.g. the native library is
+/// broken, every call returns `Error::NativeInitFailed` without trying again.
+pub(crate) fn vm() -> ExtractResult<&'static JavaVM> {
     // static items do not call `Drop` on program termination
-    static GRAAL_VM: OnceLock<JavaVM> = OnceLock::new();
-    GRAAL_VM.get_or_init(create_vm_isolate)
+    static GRAAL_VM: OnceLock<Result<JavaVM, String>> = OnceLock::new();
+    try_init(&GRAAL_VM, create_vm_isolate)
 }
 
-fn get_vm_attach_current_thread<'local>() -> ExtractResult<AttachGuard<'local>> {
-    // Attaching a thead that is already attached is a no-op. Good to have this in case this method
-    // is called from another thread
-    let env = vm().attach_current_thread()?;
+/// Initializes `cell` on the first call and returns its value, or the cached failure as
+/// `Error::NativeInitFailed`
+fn try_init<T>(
+    cell: &OnceLock<Result<T, String>>,
+    init: impl FnOnce() -> Result<T, String>,
+) -> ExtractResult<&T> {
+    cell.get_or_init(init)
+        .as_ref()
+        .map_err(|reason| Error::NativeInitFailed(reason.clone()))
+}
+
+/// Attaches the current thread to the vm for as long as the returned guard lives. Every call into
+/// Tika goes through this guard, so a thread is only attached while it parses or reads a stream
+/// and is detached again when the guard is dropped. Worker threads of a pool therefore do not
+/// keep isolate state between extractions, unless they hold a guard of their own like the workers
+/// of `ExtractorPool`. If the thread is already attached, e.g. the thread that created the vm or
+/// a nested call, the guard does nothing on drop.
+pub(crate) fn attach_current_thread<'local>() -> ExtractResult<AttachGuard<'local>> {
+    let env = vm()?.attach_current_thread()?;
     Ok(env)
 }
 
 fn parse_to_stream(
     mut env: AttachGuard,
-    data_source_val: JValue,
+    data_source_vals: &[JValue],
     char_set: &CharSet,
     pdf_conf: &PdfParserConfig,
     office_conf: &OfficeParserConfig,
     ocr_conf: &TesseractOcrConfig,
-    as_xml: bool,
+    handler: TikaHandler,
+    tika_config: Option<&str>,
     method_name: &str,
     signature: &str,
 ) -> ExtractResult<(StreamReader, Metadata)> {
     let charset_name_val = jni_new_string_as_jvalue(&mut env, &char_set.to_string())?;
+    let handler_val = jni_new_string_as_jvalue(&mut env, &handler.to_string())?;
     let j_pdf_conf = JPDFParserConfig::new(&mut env, pdf_conf)?;
     let j_office_conf = JOfficeParserConfig::new(&mut env, office_conf)?;
     let j_ocr_conf = JTesseractOcrConfig::new(&mut env, ocr_conf)?;
+    let tika_config_val = jni_new_optional_string_as_jvalue(&mut env, tika_config)?;
 
-    // Make the java parse call
+    // Make the java parse call, the data source arguments come first
+    let mut args = data_source_vals.to_vec();
+    args.extend_from_slice(&[
+        (&charset_name_val).into(),
+        (&j_pdf_conf.internal).into(),
+        (&j_office_conf.internal).into(),
+        (&j_ocr_conf.internal).into(),
+        (&handler_val).into(),
+        (&tika_config_val).into(),
+    ]);
     let call_result = jni_call_static_method(
         &mut env,
         "ai/yobix/TikaNativeMain",
         method_name,
         signature,
-        &[
-            data_source_val,
-            (&charset_name_val).into(),
-            (&j_pdf_conf.internal).into(),
-            (&j_office_conf.internal).into(),
-            (&j_ocr_conf.internal).into(),
-            JValue::Bool(if as_xml { 1 } else { 0 }),
-        ],
+        &args,
     );
     let call_result_obj = call_result?.l()?;
 
@@ -62,7 +84,13 @@ fn parse_to_stream(
     let result = JReaderResult::new(&mut env, call_result_obj)?;
     let j_reader = JReaderInputStream::new(&mut env, result.java_reader)?;
 
-    Ok((StreamReader { inner: j_reader }, result.metadata))
+    let reader = StreamReader {
+        inner: StreamSource::Tika(j_reader),
+        owned_input: None,
+        strip_control_chars: false,
+        charset: *char_set,
+    };
+    Ok((reader, result.metadata))
 }
 
 pub fn parse_file(
@@ -71,61 +99,107 @@ pub fn parse_file(
     pdf_conf: &PdfParserConfig,
     office_conf: &OfficeParserConfig,
     ocr_conf: &TesseractOcrConfig,
-    as_xml: bool
+    handler: TikaHandler,
+    tika_config: Option<&str>,
 ) -> ExtractResult<(StreamReader, Metadata)> {
-    let mut env = get_vm_attach_current_thread()?;
+    let mut env = attach_current_thread()?;
 
     let file_path_val = jni_new_string_as_jvalue(&mut env, file_path)?;
     parse_to_stream(
         env,
-        (&file_path_val).into(),
+        &[(&file_path_val).into()],
         char_set,
         pdf_conf,
         office_conf,
         ocr_conf,
-        as_xml,
+        handler,
+        tika_config,
         "parseFile",
         "(Ljava/lang/String;\
         Ljava/lang/String;\
         Lorg/apache/tika/parser/pdf/PDFParserConfig;\
         Lorg/apache/tika/parser/microsoft/OfficeParserConfig;\
         Lorg/apache/tika/parser/ocr/TesseractOCRConfig;\
-        Z\
+        Ljava/lang/String;\
+        Ljava/lang/String;\
         )Lai/yobix/ReaderResult;",
     )
 }
 
+/// Parses a byte buffer, `content_type` is the mime type of the buffer if the caller knows it
+#[allow(clippy::too_many_arguments)]
 pub fn parse_bytes(
     buffer: &[u8],
+    content_type: Option<&str>,
     char_set: &CharSet,
     pdf_conf: &PdfParserConfig,
     office_conf: &OfficeParserConfig,
     ocr_conf: &TesseractOcrConfig,
-    as_xml: bool,
+    handler: TikaHandler,
+    tika_config: Option<&str>,
 ) -> ExtractResult<(StreamReader, Metadata)> {
-    let mut env = get_vm_attach_current_thread()?;
+    let mut env = attach_current_thread()?;
 
     // Because we know the buffer is used for reading only, cast it to *mut u8 to satisfy the
     // jni_new_direct_buffer call, which requires a mutable pointer
     let mut_ptr: *mut u8 = buffer.as_ptr() as *mut u8;
 
     let byte_buffer = jni_new_direct_buffer(&mut env, mut_ptr, buffer.len())?;
+    let content_type_val = jni_new_optional_string_as_jvalue(&mut env, content_type)?;
 
     parse_to_stream(
         env,
-        (&byte_buffer).into(),
+        &[(&byte_buffer).into(), (&content_type_val).into()],
         char_set,
         pdf_conf,
         office_conf,
         ocr_conf,
-        as_xml,
+        handler,
+        tika_config,
         "parseBytes",
         "(Ljava/nio/ByteBuffer;\
         Ljava/lang/String;\
+        Ljava/lang/String;\
         Lorg/apache/tika/parser/pdf/PDFParserConfig;\
         Lorg/apache/tika/parser/microsoft/OfficeParserConfig;\
         Lorg/apache/tika/parser/ocr/TesseractOCRConfig;\
-        Z\
+        Ljava/lang/String;\
+        Ljava/lang/String;\
+        )Lai/yobix/ReaderResult;",
+    )
+}
+
+pub fn parse_reader(
+    reader: RustReader,
+    char_set: &CharSet,
+    pdf_conf: &PdfParserConfig,
+    office_conf: &OfficeParserConfig,
+    ocr_conf: &TesseractOcrConfig,
+    handler: TikaHandler,
+    tika_config: Option<&str>,
+) -> ExtractResult<(StreamReader, Metadata)> {
+    let mut env = attach_current_thread()?;
+
+    // From here on the java stream owns the reader and closes it once parsing is done
+    let input_stream = JRustInputStream::new_stream(&mut env, reader)?;
+
+    parse_to_stream(
+        env,
+        &[(&input_stream).into()],
+        char_set,
+        pdf_conf,
+        office_conf,
+        ocr_conf,
+        handler,
+        tika_config,
+        "parseInputStream",
+        "(Ljava/io/InputStream;\
+        Ljava/lang/String;\
+        Lorg/apache/tika/parser/pdf/PDFParserConfig;\
+        Lorg/apache/tika/parser/microsoft/OfficeParserConfig;\
+        Lorg/apache/tika/parser/ocr/TesseractOCRConfig;\
+        Ljava/lang/String;\
+        Ljava/lang/String;\
         )Lai/yobix/ReaderResult;",
     )
 }
@@ -136,26 +210,29 @@ pub fn parse_url(
     pdf_conf: &PdfParserConfig,
     office_conf: &OfficeParserConfig,
     ocr_conf: &TesseractOcrConfig,
-    as_xml: bool,
+    handler: TikaHandler,
+    tika_config: Option<&str>,
 ) -> ExtractResult<(StreamReader, Metadata)> {
-    let mut env = get_vm_attach_current_thread()?;
+    let mut env = attach_current_thread()?;
 
     let url_val = jni_new_string_as_jvalue(&mut env, url)?;
     parse_to_stream(
         env,
-        (&url_val).into(),
+        &[(&url_val).into()],
         char_set,
         pdf_conf,
         office_conf,
         ocr_conf,
-        as_xml,
+        handler,
+        tika_config,
         "parseUrl",
         "(Ljava/lang/String;\
         Ljava/lang/String;\
         Lorg/apache/tika/parser/pdf/PDFParserConfig;\
         Lorg/apache/tika/parser/microsoft/OfficeParserConfig;\
         Lorg/apache/tika/parser/ocr/TesseractOCRConfig;\
-        Z\
+        Ljava/lang/String;\
+        Ljava/lang/String;\
         )Lai/yobix/ReaderResult;",
     )
 }
@@ -168,13 +245,16 @@ pub fn parse_to_string(
     pdf_conf: &PdfParserConfig,
     office_conf: &OfficeParserConfig,
     ocr_conf: &TesseractOcrConfig,
-    as_xml: bool,
+    handler: TikaHandler,
+    tika_config: Option<&str>,
     method_name: &str,
     signature: &str,
 ) -> ExtractResult<(String, Metadata)> {
+    let handler_val = jni_new_string_as_jvalue(&mut env, &handler.to_string())?;
     let j_pdf_conf = JPDFParserConfig::new(&mut env, pdf_conf)?;
     let j_office_conf = JOfficeParserConfig::new(&mut env, office_conf)?;
     let j_ocr_conf = JTesseractOcrConfig::new(&mut env, ocr_conf)?;
+    let tika_config_val = jni_new_optional_string_as_jvalue(&mut env, tika_config)?;
 
     let call_result = jni_call_static_method(
         &mut env,
@@ -187,7 +267,8 @@ pub fn parse_to_string(
             (&j_pdf_conf.internal).into(),
             (&j_office_conf.internal).into(),
             (&j_ocr_conf.internal).into(),
-            JValue::Bool(if as_xml { 1 } else { 0 }),
+            (&handler_val).into(),
+            (&tika_config_val).into(),
         ],
     );
     let call_result_obj = call_result?.l()?;
@@ -204,9 +285,10 @@ pub fn parse_file_to_string(
     pdf_conf: &PdfParserConfig,
     office_conf: &OfficeParserConfig,
     ocr_conf: &TesseractOcrConfig,
-    as_xml: bool,
+    handler: TikaHandler,
+    tika_config: Option<&str>,
 ) -> ExtractResult<(String, Metadata)> {
-    let mut env = get_vm_attach_current_thread()?;
+    let mut env = attach_current_thread()?;
 
     let file_path_val = jni_new_string_as_jvalue(&mut env, file_path)?;
     parse_to_string(
@@ -216,14 +298,16 @@ pub fn parse_file_to_string(
         pdf_conf,
         office_conf,
         ocr_conf,
-        as_xml,
+        handler,
+        tika_config,
         "parseFileToString",
         "(Ljava/lang/String;\
         I\
         Lorg/apache/tika/parser/pdf/PDFParserConfig;\
         Lorg/apache/tika/parser/microsoft/OfficeParserConfig;\
         Lorg/apache/tika/parser/ocr/TesseractOCRConfig;\
-        Z\
+        Ljava/lang/String;\
+        Ljava/lang/String;\
         )Lai/yobix/StringResult;",
     )
 }
@@ -235,9 +319,10 @@ pub fn parse_bytes_to_string(
     pdf_conf: &PdfParserConfig,
     office_conf: &OfficeParserConfig,
     ocr_conf: &TesseractOcrConfig,
-    as_xml: bool,
+    handler: TikaHandler,
+    tika_config: Option<&str>,
 ) -> ExtractResult<(String, Metadata)> {
-    let mut env = get_vm_attach_current_thread()?;
+    let mut env = attach_current_thread()?;
 
     // Because we know the buffer is used for reading only, cast it to *mut u8 to satisfy the
     // jni_new_direct_buffer call, which requires a mutable pointer
@@ -252,14 +337,16 @@ pub fn parse_bytes_to_string(
         pdf_conf,
         office_conf,
         ocr_conf,
-        as_xml,
+        handler,
+        tika_config,
         "parseBytesToString",
         "(Ljava/nio/ByteBuffer;\
         I\
         Lorg/apache/tika/parser/pdf/PDFParserConfig;\
         Lorg/apache/tika/parser/microsoft/OfficeParserConfig;\
         Lorg/apache/tika/parser/ocr/TesseractOCRConfig;\
-        Z\
+        Ljava/lang/String;\
+        Ljava/lang/String;\
         )Lai/yobix/StringResult;",
     )
 }
@@ -271,9 +358,10 @@ pub fn parse_url_to_string(
     pdf_conf: &PdfParserConfig,
     office_conf: &OfficeParserConfig,
     ocr_conf: &TesseractOcrConfig,
-    as_xml: bool,
+    handler: TikaHandler,
+    tika_config: Option<&str>,
 ) -> ExtractResult<(String, Metadata)> {
-    let mut env = get_vm_attach_current_thread()?;
+    let mut env = attach_current_thread()?;
 
     let url_val = jni_new_string_as_jvalue(&mut env, url)?;
     parse_to_string(
@@ -283,14 +371,194 @@ pub fn parse_url_to_string(
         pdf_conf,
         office_conf,
         ocr_conf,
-        as_xml,
+        handler,
+        tika_config,
         "parseUrlToString",
         "(Ljava/lang/String;\
         I\
         Lorg/apache/tika/parser/pdf/PDFParserConfig;\
         Lorg/apache/tika/parser/microsoft/OfficeParserConfig;\
         Lorg/apache/tika/parser/ocr/TesseractOCRConfig;\
-        Z\
+        Ljava/lang/String;\
+        Ljava/lang/String;\
         )Lai/yobix/StringResult;",
     )
 }
+
+/// Parses a file and the files embedded in it with Tika's RecursiveParserWrapper. Returns the
+/// metadata of every document, the container first, with the text under `X-TIKA:content`.
+pub fn parse_file_with_embedded(
+    file_path: &str,
+    max_length: i32,
+    pdf_conf: &PdfParserConfig,
+    office_conf: &OfficeParserConfig,
+    ocr_conf: &TesseractOcrConfig,
+    tika_config: Option<&str>,
+) -> ExtractResult<Vec<Metadata>> {
+    let mut env = attach_current_thread()?;
+
+    let file_path_val = jni_new_string_as_jvalue(&mut env, file_path)?;
+    let j_pdf_conf = JPDFParserConfig::new(&mut env, pdf_conf)?;
+    let j_office_conf = JOfficeParserConfig::new(&mut env, office_conf)?;
+    let j_ocr_conf = JTesseractOcrConfig::new(&mut env, ocr_conf)?;
+    let tika_config_val = jni_new_optional_string_as_jvalue(&mut env, tika_config)?;
+
+    let call_result = jni_call_static_method(
+        &mut env,
+        "ai/yobix/TikaNativeMain",
+        "parseFileWithEmbedded",
+        "(Ljava/lang/String;\
+        I\
+        Lorg/apache/tika/parser/pdf/PDFParserConfig;\
+        Lorg/apache/tika/parser/microsoft/OfficeParserConfig;\
+        Lorg/apache/tika/parser/ocr/TesseractOCRConfig;\
+        Ljava/lang/String;\
+        )Lai/yobix/MetadataListResult;",
+        &[
+            (&file_path_val).into(),
+            JValue::Int(max_length),
+            (&j_pdf_conf.internal).into(),
+            (&j_office_conf.internal).into(),
+            (&j_ocr_conf.internal).into(),
+            (&tika_config_val).into(),
+        ],
+    );
+    let call_result_obj = call_result?.l()?;
+
+    let result = JMetadataListResult::new(&mut env, call_result_obj)?;
+    Ok(result.metadata_list)
+}
+
+/// Parses a file to Tika's XHTML output, as produced by the ToXMLContentHandler.
+pub fn parse_file_to_xhtml(
+    file_path: &str,
+    pdf_conf: &PdfParserConfig,
+    office_conf: &OfficeParserConfig,
+    ocr_conf: &TesseractOcrConfig,
+    tika_config: Option<&str>,
+) -> ExtractResult<(String, Metadata)> {
+    let mut env = attach_current_thread()?;
+
+    let file_path_val = jni_new_string_as_jvalue(&mut env, file_path)?;
+    let j_pdf_conf = JPDFParserConfig::new(&mut env, pdf_conf)?;
+    let j_office_conf = JOfficeParserConfig::new(&mut env, office_conf)?;
+    let j_ocr_conf = JTesseractOcrConfig::new(&mut env, ocr_conf)?;
+    let tika_config_val = jni_new_optional_string_as_jvalue(&mut env, tika_config)?;
+
+    let call_result = jni_call_static_method(
+        &mut env,
+        "ai/yobix/TikaNativeMain",
+        "parseFileToXhtml",
+        "(Ljava/lang/String;\
+        Lorg/apache/tika/parser/pdf/PDFParserConfig;\
+        Lorg/apache/tika/parser/microsoft/OfficeParserConfig;\
+        Lorg/apache/tika/parser/ocr/TesseractOCRConfig;\
+        Ljava/lang/String;\
+        )Lai/yobix/StringResult;",
+        &[
+            (&file_path_val).into(),
+            (&j_pdf_conf.internal).into(),
+            (&j_office_conf.internal).into(),
+            (&j_ocr_conf.internal).into(),
+            (&tika_config_val).into(),
+        ],
+    );
+    let call_result_obj = call_result?.l()?;
+
+    let result = JStringResult::new(&mut env, call_result_obj)?;
+    Ok((result.content, result.metadata))
+}
+
+/// Parses a file for its metadata only, the body is dropped and OCR is skipped.
+pub fn parse_file_metadata(
+    file_path: &str,
+    pdf_conf: &PdfParserConfig,
+    office_conf: &OfficeParserConfig,
+    tika_config: Option<&str>,
+) -> ExtractResult<Metadata> {
+    let mut env = attach_current_thread()?;
+
+    let file_path_val = jni_new_string_as_jvalue(&mut env, file_path)?;
+    let j_pdf_conf = JPDFParserConfig::new(&mut env, pdf_conf)?;
+    let j_office_conf = JOfficeParserConfig::new(&mut env, office_conf)?;
+    let tika_config_val = jni_new_optional_string_as_jvalue(&mut env, tika_config)?;
+
+    let call_result = jni_call_static_method(
+        &mut env,
+        "ai/yobix/TikaNativeMain",
+        "parseFileMetadata",
+        "(Ljava/lang/String;\
+        Lorg/apache/tika/parser/pdf/PDFParserConfig;\
+        Lorg/apache/tika/parser/microsoft/OfficeParserConfig;\
+        Ljava/lang/String;\
+        )Lai/yobix/StringResult;",
+        &[
+            (&file_path_val).into(),
+            (&j_pdf_conf.internal).into(),
+            (&j_office_conf.internal).into(),
+            (&tika_config_val).into(),
+        ],
+    );
+    let call_result_obj = call_result?.l()?;
+
+    let result = JStringResult::new(&mut env, call_result_obj)?;
+    Ok(result.metadata)
+}
+
+/// Returns the sorted mime types supported by the parsers registered in Tika.
+pub fn supported_mime_types() -> ExtractResult<Vec<String>> {
+    let mut env = attach_current_thread()?;
+
+    let call_result = jni_call_static_method(
+        &mut env,
+        "ai/yobix/TikaNativeMain",
+        "supportedMimeTypes",
+        "()[Ljava/lang/String;",
+        &[],
+    );
+    let types_obj = call_result?.l()?;
+    jni_jobject_array_to_vec(&mut env, types_obj)
+}
+
+/// Returns the version of the bundled Tika, e.g. `2.9.2`.
+pub fn tika_version() -> ExtractResult<String> {
+    let mut env = attach_current_thread()?;
+
+    let call_result = jni_call_static_method(
+        &mut env,
+        "ai/yobix/TikaNativeMain",
+        "tikaVersion",
+        "()Ljava/lang/String;",
+        &[],
+    );
+    let version_obj = call_result?.l()?;
+    jni_jobject_to_string(&mut env, version_obj)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    #[test]
+    fn test_failed_init_is_cached() {
+        // Stands in for a native library that fails to load
+        let cell: OnceLock<Result<JavaVM, String>> = OnceLock::new();
+        let attempts = Cell::new(0);
+        let failing_init = || {
+            attempts.set(attempts.get() + 1);
+            Err("libtika_native.so: cannot open shared object file".to_string())
+        };
+
+        for _ in 0..3 {
+            match try_init(&cell, failing_init) {
+                Err(err @ Error::NativeInitFailed(_)) => {
+                    assert_eq!(err.kind(), crate::ErrorKind::NativeInitFailed);
+                    assert!(err.to_string().contains("libtika_native.so"), "{}", err);
+                }
+                other => panic!("Expected NativeInitFailed, got {:?}", other.map(|_| ())),
+            }
+        }
+        assert_eq!(attempts.get(), 1);
+    }
+}
@@ -1,14 +1,19 @@
 use crate::errors::{Error, ExtractResult};
+use crate::tika::attach_current_thread;
 use crate::tika::jni_utils::{
-    jni_call_method, jni_jobject_to_string, jni_new_string_as_jvalue,
+    jni_call_method, jni_jobject_to_string, jni_new_string_as_jvalue, jni_take_exception_message,
     jni_tika_metadata_to_rust_metadata,
 };
-use crate::tika::vm;
-use crate::{Metadata, OfficeParserConfig, PdfParserConfig, TesseractOcrConfig, DEFAULT_BUF_SIZE};
-use bytemuck::cast_slice_mut;
-use jni::objects::{GlobalRef, JByteArray, JObject, JValue};
-use jni::sys::jsize;
-use jni::JNIEnv;
+use crate::{
+    Metadata, OfficeParserConfig, PdfParserConfig, TesseractOcrConfig, DEFAULT_BUF_SIZE,
+    LARGE_BUF_SIZE,
+};
+use bytemuck::{cast_slice, cast_slice_mut};
+use jni::objects::{GlobalRef, JByteArray, JClass, JObject, JObjectArray, JValue};
+use jni::sys::{jint, jlong, jsize};
+use jni::{JNIEnv, NativeMethod};
+use std::os::raw::c_void;
+use std::panic::{self, AssertUnwindSafe};
 
 /// Optimized wrapper for [`JObject`]s that contain `org.apache.commons.io.input.ReaderInputStream`
 /// It saves a GlobalRef to the java object, which is cleared when the last GlobalRef is dropped
@@ -26,6 +31,8 @@ pub struct JReaderInputStream {
     // Track read patterns for adaptive buffer sizing
     total_reads: usize,
     large_reads: usize,
+    /// Calls of the Java Reader's `read`, one JNI round trip each
+    pub(crate) round_trips: usize,
 }
 
 impl JReaderInputStream {
@@ -43,11 +50,12 @@ impl JReaderInputStream {
             capacity,
             total_reads: 0,
             large_reads: 0,
+            round_trips: 0,
         })
     }
 
     pub(crate) fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
-        let mut env = vm().attach_current_thread().map_err(Error::JniError)?;
+        let mut env = attach_current_thread()?;
 
         let length = buf.len() as jsize;
 
@@ -69,37 +77,84 @@ impl JReaderInputStream {
         };
 
         if optimal_capacity > self.capacity {
-            // Create the new byte array with the optimal capacity
-            let jbyte_array = env
-                .new_byte_array(optimal_capacity)
-                .map_err(|_e| Error::JniEnvCall("Failed to create byte array"))?;
+            self.grow_buffer(&mut env, optimal_capacity)?;
+        }
 
-            self.buffer = env
-                .new_global_ref(jbyte_array)
-                .map_err(|_e| Error::JniEnvCall("Failed to create global reference"))?;
+        self.read_chunk(&mut env, buf)
+    }
 
-            self.capacity = optimal_capacity;
+    /// Reads the rest of the stream into `out` in chunks of `LARGE_BUF_SIZE`, attached to the vm
+    /// once. A large document takes far fewer JNI round trips than repeated `read` calls with
+    /// the small buffers of `io::Read::read_to_end` or a `BufReader`
+    pub(crate) fn read_to_end(&mut self, out: &mut Vec<u8>) -> std::io::Result<usize> {
+        let mut env = attach_current_thread()?;
+        if self.capacity < LARGE_BUF_SIZE as jsize {
+            self.grow_buffer(&mut env, LARGE_BUF_SIZE as jsize)?;
         }
 
-        // // Create the java byte array
-        // let length = buf.len() as jsize;
-        // let jbyte_array = env
-        //     .new_byte_array(length)
-        //     .map_err(|_e| Error::JniEnvCall("Failed to create byte array"))?;
+        let start = out.len();
+        loop {
+            // Read straight into the spare room of `out`, without an intermediate buffer
+            let filled = out.len();
+            out.resize(filled + LARGE_BUF_SIZE, 0);
+            let result = self.read_chunk(&mut env, &mut out[filled..]);
+            match result {
+                Ok(0) => {
+                    out.truncate(filled);
+                    return Ok(filled - start);
+                }
+                Ok(num_read_bytes) => out.truncate(filled + num_read_bytes),
+                Err(e) => {
+                    out.truncate(filled);
+                    return Err(e);
+                }
+            }
+        }
+    }
+
+    /// Replaces the Java byte array with one of `capacity` bytes
+    fn grow_buffer(&mut self, env: &mut JNIEnv, capacity: jsize) -> ExtractResult<()> {
+        let jbyte_array = env
+            .new_byte_array(capacity)
+            .map_err(|_e| Error::JniEnvCall("Failed to create byte array"))?;
 
-        // Call the Java Reader's `read` method
-        let call_result = jni_call_method(
-            &mut env,
+        self.buffer = env
+            .new_global_ref(jbyte_array)
+            .map_err(|_e| Error::JniEnvCall("Failed to create global reference"))?;
+
+        self.capacity = capacity;
+        Ok(())
+    }
+
+    /// One call of the Java Reader's `read` for up to `buf.len()` bytes, which must fit the
+    /// Java byte array. Returns 0 at the end of the stream
+    fn read_chunk(&mut self, env: &mut JNIEnv, buf: &mut [u8]) -> std::io::Result<usize> {
+        self.round_trips += 1;
+        let call_result = env.call_method(
             &self.internal,
             "read",
             "([BII)I",
             &[
                 JValue::Object(&self.buffer),
                 JValue::Int(0),
-                JValue::Int(length),
+                JValue::Int(buf.len() as jsize),
             ],
         );
-        let num_read_bytes = call_result?.i().map_err(Error::JniError)?;
+        let num_read_bytes = match call_result {
+            Ok(value) => value.i().map_err(Error::JniError)?,
+            // Tika failed partway through the document, e.g. on a corrupt part. The error carries
+            // Tika's message, the text read so far is not the whole document
+            Err(jni::errors::Error::JavaException) => {
+                let message = jni_take_exception_message(env)?.unwrap_or_default();
+                return Err(Error::ParseError(message).into());
+            }
+            Err(e) => return Err(Error::JniError(e).into()),
+        };
+        if num_read_bytes <= 0 {
+            // End of stream reached
+            return Ok(0);
+        }
+        let num_read_bytes = num_read_bytes as usize;
 
         // Get self.buffer object as a local reference
         let obj_local = env
@@ -107,25 +162,19 @@ impl JReaderInputStream {
             .map_err(|_e| Error::JniEnvCall("Failed to create local ref"))?;
 
         // cast because java byte array is i8[]
-        let buf_of_i8: &mut [i8] = cast_slice_mut(buf);
+        let buf_of_i8: &mut [i8] = cast_slice_mut(&mut buf[..num_read_bytes]);
 
-        // Get the bytes from the Java byte array to the Rust byte array
-        // This is a copy or just memory reference. POTENTIAL performance improvement
+        // Copy the bytes read from the Java byte array to the Rust byte array
         env.get_byte_array_region(JByteArray::from(obj_local), 0, buf_of_i8)
             .map_err(|_e| Error::JniEnvCall("Failed to get byte array region"))?;
 
-        if num_read_bytes == -1 {
-            // End of stream reached
-            Ok(0)
-        } else {
-            Ok(num_read_bytes as usize)
-        }
+        Ok(num_read_bytes)
     }
 }
 
 impl Drop for JReaderInputStream {
     fn drop(&mut self) {
-        if let Ok(mut env) = vm().attach_current_thread() {
+        if let Ok(mut env) = attach_current_thread() {
             // Call the Java Reader's `close` method
             jni_call_method(&mut env, &self.internal, "close", "()V", &[]).ok();
         }
@@ -152,6 +201,7 @@ impl<'local> JStringResult {
             match status {
                 1 => Err(Error::IoError(msg)),
                 2 => Err(Error::ParseError(msg)),
+                3 => Err(Error::EncryptedDocument(msg)),
                 _ => Err(Error::Unknown(msg)),
             }
         } else {
@@ -173,6 +223,50 @@ impl<'local> JStringResult {
     }
 }
 
+/// Wrapper for the Java class `ai.yobix.MetadataListResult`
+/// Upon creation it converts the metadata of every parsed document to Rust metadata
+pub struct JMetadataListResult {
+    pub metadata_list: Vec<Metadata>,
+}
+
+impl<'local> JMetadataListResult {
+    pub(crate) fn new(env: &mut JNIEnv<'local>, obj: JObject<'local>) -> ExtractResult<Self> {
+        let is_error = jni_call_method(env, &obj, "isError", "()Z", &[])?.z()?;
+
+        if is_error {
+            let status = jni_call_method(env, &obj, "getStatus", "()B", &[])?.b()?;
+            let msg_obj = env
+                .call_method(&obj, "getErrorMessage", "()Ljava/lang/String;", &[])?
+                .l()?;
+            let msg = jni_jobject_to_string(env, msg_obj)?;
+            match status {
+                1 => Err(Error::IoError(msg)),
+                2 => Err(Error::ParseError(msg)),
+                3 => Err(Error::EncryptedDocument(msg)),
+                _ => Err(Error::Unknown(msg)),
+            }
+        } else {
+            let j_array = env
+                .call_method(
+                    &obj,
+                    "getMetadataList",
+                    "()[Lorg/apache/tika/metadata/Metadata;",
+                    &[],
+                )?
+                .l()?;
+            let j_array = JObjectArray::from(j_array);
+            let length = env.get_array_length(&j_array)?;
+
+            let mut metadata_list = Vec::with_capacity(length as usize);
+            for i in 0..length {
+                let tika_metadata_obj = env.get_object_array_element(&j_array, i)?;
+                metadata_list.push(jni_tika_metadata_to_rust_metadata(env, tika_metadata_obj)?);
+            }
+            Ok(Self { metadata_list })
+        }
+    }
+}
+
 /// Wrapper for the Java class  `ai.yobix.ReaderResult`
 /// Upon creation it parses the java ReaderResult object and saves the java
 /// `org.apache.commons.io.input.ReaderInputStream` object, which later can be used for reading
@@ -234,8 +328,14 @@ impl<'local> JPDFParserConfig<'local> {
     /// Creates a new object instance of `JPDFParserConfig` in the java world
     /// keeps reference to the object and method IDs for later use
     pub(crate) fn new(env: &mut JNIEnv<'local>, config: &PdfParserConfig) -> ExtractResult<Self> {
-        // Create the java object
-        let class = env.find_class("org/apache/tika/parser/pdf/PDFParserConfig")?;
+        config.check_features()?;
+
+        // Create the java object. A password needs our subclass, as Tika only takes it from a
+        // PasswordProvider
+        let class = match config.password {
+            Some(_) => env.find_class("ai/yobix/PasswordPDFParserConfig")?,
+            None => env.find_class("org/apache/tika/parser/pdf/PDFParserConfig")?,
+        };
         let obj = env.new_object(&class, "()V", &[])?;
 
         // Call the setters
@@ -269,6 +369,20 @@ impl<'local> JPDFParserConfig<'local> {
             "(Z)V",
             &[JValue::from(config.extract_annotation_text)],
         )?;
+        jni_call_method(
+            env,
+            &obj,
+            "setExtractAcroFormContent",
+            "(Z)V",
+            &[JValue::from(config.extract_acroform_content)],
+        )?;
+        jni_call_method(
+            env,
+            &obj,
+            "setSortByPosition",
+            "(Z)V",
+            &[JValue::from(config.sort_by_position)],
+        )?;
         // The PdfOcrStrategy enum names must match the Java org.apache.tika.parser.pdf
         // .PDFParserConfig$OCR_STRATEGY enum names
         let ocr_str_val = jni_new_string_as_jvalue(env, &config.ocr_strategy.to_string())?;
@@ -280,6 +394,17 @@ impl<'local> JPDFParserConfig<'local> {
             &[(&ocr_str_val).into()],
         )?;
 
+        if let Some(password) = &config.password {
+            let password_val = jni_new_string_as_jvalue(env, &password.0)?;
+            jni_call_method(
+                env,
+                &obj,
+                "setPassword",
+                "(Ljava/lang/String;)V",
+                &[(&password_val).into()],
+            )?;
+        }
+
         Ok(Self { internal: obj })
     }
 }
@@ -296,8 +421,16 @@ impl<'local> JOfficeParserConfig<'local> {
         env: &mut JNIEnv<'local>,
         config: &OfficeParserConfig,
     ) -> ExtractResult<Self> {
-        // Create the java object
-        let class = env.find_class("org/apache/tika/parser/microsoft/OfficeParserConfig")?;
+        // Create the java object. A password needs our subclass, as Tika only takes it from a
+        // PasswordProvider, and leaving out comments the subclass of that, as Tika has no option
+        // for it
+        let class = match (&config.password, config.include_comments) {
+            (_, false) => env.find_class("ai/yobix/CommentsOfficeParserConfig")?,
+            (Some(_), true) => env.find_class("ai/yobix/PasswordOfficeParserConfig")?,
+            (None, true) => {
+                env.find_class("org/apache/tika/parser/microsoft/OfficeParserConfig")?
+            }
+        };
         let obj = env.new_object(&class, "()V", &[])?;
 
         // Call the setters
@@ -374,6 +507,26 @@ impl<'local> JOfficeParserConfig<'local> {
             &[JValue::from(config.extract_all_alternatives_from_msg)],
         )?;
 
+        if !config.include_comments {
+            jni_call_method(
+                env,
+                &obj,
+                "setIncludeComments",
+                "(Z)V",
+                &[JValue::from(config.include_comments)],
+            )?;
+        }
+        if let Some(password) = &config.password {
+            let password_val = jni_new_string_as_jvalue(env, &password.0)?;
+            jni_call_method(
+                env,
+                &obj,
+                "setPassword",
+                "(Ljava/lang/String;)V",
+                &[(&password_val).into()],
+            )?;
+        }
+
         Ok(Self { internal: obj })
     }
 }
@@ -389,8 +542,16 @@ impl<'local> JTesseractOcrConfig<'local> {
         env: &mut JNIEnv<'local>,
         config: &TesseractOcrConfig,
     ) -> ExtractResult<Self> {
-        // Create the java object
-        let class = env.find_class("org/apache/tika/parser/ocr/TesseractOCRConfig")?;
+        config.check_tessdata()?;
+        config.check_tesseract_config()?;
+        config.check_features()?;
+
+        // Create the java object. A custom tessdata directory needs our subclass, as Tika only
+        // supports it as a parser setting
+        let class = match config.tessdata_path {
+            Some(_) => env.find_class("ai/yobix/TessdataOCRConfig")?,
+            None => env.find_class("org/apache/tika/parser/ocr/TesseractOCRConfig")?,
+        };
         let obj = env.new_object(&class, "()V", &[])?;
 
         // Call the setters
@@ -425,6 +586,9 @@ impl<'local> JTesseractOcrConfig<'local> {
             "(Z)V",
             &[JValue::from(config.apply_rotation)],
         )?;
+        if config.skip_ocr {
+            jni_call_method(env, &obj, "setSkipOcr", "(Z)V", &[JValue::from(true)])?;
+        }
 
         let lang_string_val = jni_new_string_as_jvalue(env, &config.language)?;
         jni_call_method(
@@ -435,6 +599,142 @@ impl<'local> JTesseractOcrConfig<'local> {
             &[(&lang_string_val).into()],
         )?;
 
+        // Sorted, so the same config always gives the same tesseract command line
+        let mut tesseract_config: Vec<_> = config.tesseract_config.iter().collect();
+        tesseract_config.sort();
+        for (key, value) in tesseract_config {
+            let key_val = jni_new_string_as_jvalue(env, key)?;
+            let value_val = jni_new_string_as_jvalue(env, value)?;
+            jni_call_method(
+                env,
+                &obj,
+                "addOtherTesseractConfig",
+                "(Ljava/lang/String;Ljava/lang/String;)V",
+                &[(&key_val).into(), (&value_val).into()],
+            )?;
+        }
+
+        if let Some(tessdata_path) = &config.tessdata_path {
+            let path_string_val = jni_new_string_as_jvalue(env, &tessdata_path.to_string_lossy())?;
+            jni_call_method(
+                env,
+                &obj,
+                "setTessdataPath",
+                "(Ljava/lang/String;)V",
+                &[(&path_string_val).into()],
+            )?;
+        }
+
         Ok(Self { internal: obj })
     }
 }
+
+/// A Rust reader passed to Java, see [`JRustInputStream`]
+pub(crate) type RustReader = Box<dyn std::io::Read + Send>;
+
+/// Bridge for the Java class `ai.yobix.RustInputStream`, the reverse of [`JReaderInputStream`]
+/// The Java stream reads from a Rust reader through the native methods registered here.
+///
+/// The reader is boxed and passed to Java as an opaque `long` handle. Once handed over, the Java
+/// stream owns it: the reader is dropped when the stream is closed, which Tika does when parsing
+/// finishes or fails on its background thread.
+pub struct JRustInputStream;
+
+impl JRustInputStream {
+    const CLASS: &'static str = "ai/yobix/RustInputStream";
+
+    /// Registers the native `read` and `close` callbacks of `ai.yobix.RustInputStream`.
+    /// Registering again replaces the same function pointers, so this is safe to call per parse
+    fn register_natives(env: &mut JNIEnv) -> ExtractResult<()> {
+        let methods = [
+            NativeMethod {
+                name: "readNative".into(),
+                sig: "(J[BII)I".into(),
+                fn_ptr: rust_input_stream_read as *mut c_void,
+            },
+            NativeMethod {
+                name: "closeNative".into(),
+                sig: "(J)V".into(),
+                fn_ptr: rust_input_stream_close as *mut c_void,
+            },
+        ];
+        env.register_native_methods(Self::CLASS, &methods)
+            .map_err(|_e| Error::JniEnvCall("Failed to register RustInputStream natives"))
+    }
+
+    /// Creates a `ai.yobix.RustInputStream` reading from `reader`. The returned stream owns the
+    /// reader from now on
+    pub(crate) fn new_stream<'local>(
+        env: &mut JNIEnv<'local>,
+        reader: RustReader,
+    ) -> ExtractResult<JObject<'local>> {
+        Self::register_natives(env)?;
+
+        let handle = Box::into_raw(Box::new(reader)) as jlong;
+        match env.new_object(Self::CLASS, "(J)V", &[JValue::Long(handle)]) {
+            Ok(obj) => Ok(obj),
+            Err(e) => {
+                // The handle never reached Java, take the reader back
+                unsafe { Self::drop_handle(handle) };
+                Err(Error::JniError(e))
+            }
+        }
+    }
+
+    /// Drops the reader behind a handle created by [`JRustInputStream::new_stream`]
+    ///
+    /// # Safety
+    /// The handle must not be used afterwards
+    unsafe fn drop_handle(handle: jlong) {
+        if handle != 0 {
+            drop(Box::from_raw(handle as *mut RustReader));
+        }
+    }
+}
+
+/// `RustInputStream.readNative(long handle, byte[] b, int off, int len)`
+/// Returns the number of bytes read, 0 at the end of the reader. Errors and panics of the
+/// reader are thrown as `java.io.IOException`
+extern "system" fn rust_input_stream_read<'local>(
+    mut env: JNIEnv<'local>,
+    _class: JClass<'local>,
+    handle: jlong,
+    buf: JByteArray<'local>,
+    off: jint,
+    len: jint,
+) -> jint {
+    // The Java stream synchronizes read and close, so the reader is never used concurrently
+    let reader = unsafe { &mut *(handle as *mut RustReader) };
+    let mut chunk = vec![0u8; len.max(0) as usize];
+
+    let result = panic::catch_unwind(AssertUnwindSafe(|| loop {
+        match reader.read(&mut chunk) {
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+            result => return result,
+        }
+    }));
+    let message = match result {
+        Ok(Ok(num_read_bytes)) => {
+            let buf_of_i8: &[i8] = cast_slice(&chunk[..num_read_bytes]);
+            match env.set_byte_array_region(&buf, off, buf_of_i8) {
+                Ok(()) => return num_read_bytes as jint,
+                // An exception is already pending
+                Err(_) => return 0,
+            }
+        }
+        Ok(Err(e)) => e.to_string(),
+        Err(_) => "Rust reader panicked".to_string(),
+    };
+    env.throw_new("java/io/IOException", message).ok();
+    0
+}
+
+/// `RustInputStream.closeNative(long handle)`, drops the reader
+extern "system" fn rust_input_stream_close<'local>(
+    _env: JNIEnv<'local>,
+    _class: JClass<'local>,
+    handle: jlong,
+) {
+    // The Java stream clears its handle on close, so this is called at most once per handle
+    unsafe { JRustInputStream::drop_handle(handle) };
+}
@@ -1,4 +1,4 @@
-use extractous::{Extractor, PdfOcrStrategy, PdfParserConfig, TesseractOcrConfig};
+use extractous::{CharSet, Extractor, PdfOcrStrategy, PdfParserConfig, TesseractOcrConfig};
 use std::fs;
 use std::io::Read;
 use test_case::test_case;
@@ -86,3 +86,122 @@ fn test_extract_bytes_to_stream_ara_ocr_png() {
     );
     println!("{}: {}", "ara-ocr.png", dist);
 }
+
+#[cfg(not(target_os = "macos"))]
+#[test]
+fn test_extract_file_to_stream_ocr_only_strategy_rotated_pdf() {
+    // Tika reads the copy with the rotated page stored upright after extract_file returns
+    let extractor = Extractor::new()
+        .set_use_pure_rust(false)
+        .set_pdf_config(PdfParserConfig::new().set_ocr_strategy(PdfOcrStrategy::OCR_ONLY));
+    let (mut stream, _metadata) = extractor
+        .extract_file("../test_files/documents/rotated.pdf")
+        .unwrap();
+
+    let mut buffer = Vec::new();
+    stream.read_to_end(&mut buffer).unwrap();
+    let extracted = String::from_utf8_lossy(&buffer);
+
+    assert!(
+        extracted.contains("This page is not rotated."),
+        "OCR output: {}",
+        extracted
+    );
+    assert!(
+        extracted.contains("quick brown fox"),
+        "OCR output: {}",
+        extracted
+    );
+}
+
+#[cfg(all(feature = "ocr-downscale", not(target_os = "macos")))]
+#[test]
+fn test_extract_to_stream_max_ocr_image_pixels() {
+    // Tika reads the downscaled image after the extract calls return
+    let path = "../test_files/documents/eng-ocr-large.png";
+    let extractor = Extractor::new()
+        .set_ocr_config(TesseractOcrConfig::new().set_max_ocr_image_pixels(1_000_000));
+    let buffer = fs::read(path).unwrap();
+    for (stream, metadata) in [
+        extractor.extract_file(path).unwrap(),
+        extractor.extract_bytes(&buffer).unwrap(),
+    ] {
+        assert_eq!(metadata["OCR-Downscaled"], vec!["4800x800 to 2400x400"]);
+        let extracted = stream.into_string().unwrap();
+        assert!(
+            extracted.contains("quick brown fox"),
+            "not in: {}",
+            extracted
+        );
+    }
+}
+
+#[test]
+fn test_extract_file_to_stream_error_partway() {
+    // The last entry of the file has a mismatched end tag, Tika fails after streaming the rest
+    let extractor = Extractor::new().set_use_pure_rust(false);
+    let (mut stream, _metadata) = extractor
+        .extract_file("../test_files/documents/corrupt-partway.xml")
+        .unwrap();
+
+    let mut buffer = Vec::new();
+    let error = stream.read_to_end(&mut buffer).unwrap_err();
+    let extracted = String::from_utf8_lossy(&buffer);
+
+    assert!(
+        extracted.contains("Station 300"),
+        "Extracted: {}",
+        extracted
+    );
+    assert!(
+        error.to_string().contains("XML parse error"),
+        "Error: {}",
+        error
+    );
+}
+
+#[test]
+fn test_extract_file_to_stream_read_to_end_round_trips() {
+    let file_path = "../test_files/documents/2022_Q3_AAPL.pdf";
+    let extractor = Extractor::new().set_use_pure_rust(false);
+
+    let (mut stream, _metadata) = extractor.extract_file(file_path).unwrap();
+    let mut buffer = [0u8; 8192];
+    let mut per_read = Vec::new();
+    loop {
+        match stream.read(&mut buffer).unwrap() {
+            0 => break,
+            n => per_read.extend_from_slice(&buffer[..n]),
+        }
+    }
+    let per_read_trips = stream.jni_round_trips();
+
+    let (mut stream, _metadata) = extractor.extract_file(file_path).unwrap();
+    let mut to_end = Vec::new();
+    stream.read_to_end(&mut to_end).unwrap();
+
+    assert_eq!(per_read, to_end);
+    assert!(
+        stream.jni_round_trips() < per_read_trips,
+        "read_to_end: {}, read per 8KB: {}",
+        stream.jni_round_trips(),
+        per_read_trips
+    );
+}
+
+#[test]
+fn test_extract_file_to_stream_into_string_utf16be() {
+    let file_path = "../test_files/documents/winter-sports.epub";
+    let (expected, _metadata) = Extractor::new().extract_file_to_string(file_path).unwrap();
+
+    let extractor = Extractor::new().set_encoding(CharSet::UTF_16BE);
+    let (stream, _metadata) = extractor.extract_file(file_path).unwrap();
+    let extracted = stream.into_string().unwrap();
+
+    let dist = cosine(&expected, &extracted);
+    assert!(
+        dist > 0.9,
+        "Cosine similarity is less than 0.9, dist: {}",
+        dist
+    );
+}
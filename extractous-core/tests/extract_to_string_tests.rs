@@ -1,5 +1,9 @@
-use extractous::{Extractor, PdfOcrStrategy, PdfParserConfig, TesseractOcrConfig};
+use extractous::{Error, Extractor, PdfOcrStrategy, PdfParserConfig, TesseractOcrConfig};
+use std::collections::HashMap;
 use std::fs;
+use std::io::{Read, Write};
+use std::net::TcpListener;
+use std::time::Duration;
 use test_case::test_case;
 use textdistance::nstr::cosine;
 
@@ -74,6 +78,109 @@ fn test_extract_file_to_string_ara_ocr_png() {
     );
 }
 
+#[cfg(not(target_os = "macos"))]
+#[test]
+fn test_extract_file_to_string_eng_ocr_png() {
+    let extractor = Extractor::new().set_ocr_config(TesseractOcrConfig::new().set_language("eng"));
+    let (extracted, _metadata) = extractor
+        .extract_file_to_string("../test_files/documents/eng-ocr.png")
+        .unwrap();
+
+    for words in [
+        "quick brown fox",
+        "lazy dog",
+        "optical character recognition",
+    ] {
+        assert!(
+            extracted.contains(words),
+            "{:?} not in: {}",
+            words,
+            extracted
+        );
+    }
+}
+
+#[cfg(not(target_os = "macos"))]
+#[test]
+fn test_extract_file_to_string_tessdata_path() {
+    let tessdata = test_utils::copy_installed_tessdata("eng");
+    let ocr_config = TesseractOcrConfig::new()
+        .set_tessdata_path(tessdata.path().to_path_buf())
+        .set_language("eng");
+    let extractor = Extractor::new().set_ocr_config(ocr_config);
+    let (extracted, _metadata) = extractor
+        .extract_file_to_string("../test_files/documents/eng-ocr.png")
+        .unwrap();
+
+    for words in [
+        "quick brown fox",
+        "lazy dog",
+        "optical character recognition",
+    ] {
+        assert!(
+            extracted.contains(words),
+            "{:?} not in: {}",
+            words,
+            extracted
+        );
+    }
+}
+
+#[cfg(not(target_os = "macos"))]
+#[test]
+fn test_extract_file_to_string_tesseract_config() {
+    let tessdata = test_utils::copy_installed_tessdata("eng");
+    let ocr_config = TesseractOcrConfig::new()
+        .set_tessdata_path(tessdata.path().to_path_buf())
+        .set_language("eng");
+    let path = "../test_files/documents/eng-ocr.png";
+
+    let spaces = HashMap::from([("preserve_interword_spaces".to_string(), "1".to_string())]);
+    let extractor =
+        Extractor::new().set_ocr_config(ocr_config.clone().set_tesseract_config(spaces));
+    let (extracted, _metadata) = extractor.extract_file_to_string(path).unwrap();
+    assert!(
+        extracted.contains("quick brown fox"),
+        "not in: {}",
+        extracted
+    );
+
+    // Tesseract only reads digits, so none of the words are left
+    let digits = HashMap::from([(
+        "tessedit_char_whitelist".to_string(),
+        "0123456789".to_string(),
+    )]);
+    let extractor = Extractor::new().set_ocr_config(ocr_config.set_tesseract_config(digits));
+    let (extracted, _metadata) = extractor.extract_file_to_string(path).unwrap();
+    assert!(!extracted.contains("quick brown fox"), "in: {}", extracted);
+}
+
+#[cfg(all(feature = "ocr-downscale", not(target_os = "macos")))]
+#[test]
+fn test_extract_file_to_string_max_ocr_image_pixels() {
+    // eng-ocr.png upscaled four times
+    let path = "../test_files/documents/eng-ocr-large.png";
+    let extractor = Extractor::new()
+        .set_ocr_config(TesseractOcrConfig::new().set_max_ocr_image_pixels(1_000_000));
+    let (extracted, metadata) = extractor.extract_file_to_string(path).unwrap();
+    assert_eq!(metadata["OCR-Downscaled"], vec!["4800x800 to 2400x400"]);
+    assert!(
+        extracted.contains("quick brown fox"),
+        "not in: {}",
+        extracted
+    );
+
+    let buffer = fs::read(path).unwrap();
+    let (_, metadata) = extractor.extract_bytes_to_string(&buffer).unwrap();
+    assert_eq!(metadata["OCR-Downscaled"], vec!["4800x800 to 2400x400"]);
+
+    // Images within the budget are OCRed as they are
+    let extractor = Extractor::new()
+        .set_ocr_config(TesseractOcrConfig::new().set_max_ocr_image_pixels(4_000_000));
+    let (_, metadata) = extractor.extract_file_to_string(path).unwrap();
+    assert!(!metadata.contains_key("OCR-Downscaled"));
+}
+
 #[cfg(not(target_os = "macos"))]
 #[test]
 fn test_extract_file_to_string_ocr_only_strategy_deu_ocr_pdf() {
@@ -115,3 +222,268 @@ fn test_test_extract_file_to_string_no_ocr_strategy_deu_ocr_pdf() {
 
     assert_eq!("", extracted.trim())
 }
+
+#[cfg(not(target_os = "macos"))]
+#[test]
+fn test_extract_file_to_string_ocr_fallback_on_empty_deu_ocr_pdf() {
+    // The scan has no text layer, so the text extraction without OCR finds nothing
+    let extractor = Extractor::new()
+        .set_ocr_config(TesseractOcrConfig::new().set_language("deu"))
+        .set_pdf_config(PdfParserConfig::new().set_ocr_strategy(PdfOcrStrategy::NO_OCR))
+        .set_ocr_fallback_on_empty(true);
+    let (extracted, metadata) = extractor
+        .extract_file_to_string("../test_files/documents/deu-ocr.pdf")
+        .unwrap();
+
+    let expected = fs::read_to_string("../test_files/expected_result/deu-ocr.pdf.txt").unwrap();
+    let dist = cosine(&expected, &extracted);
+    assert!(
+        dist > 0.9,
+        "Cosine similarity is less than 0.9 for file: deu-ocr.pdf, dist: {}",
+        dist
+    );
+    assert_eq!(
+        metadata.get("OCR-Fallback"),
+        Some(&vec!["true".to_string()])
+    );
+}
+
+#[cfg(not(target_os = "macos"))]
+#[test]
+fn test_extract_file_to_string_ocr_disabled_deu_ocr_pdf_and_png() {
+    // The OCR settings would read the scan, the switch overrides them
+    let extractor = Extractor::new()
+        .set_ocr_config(TesseractOcrConfig::new().set_language("deu"))
+        .set_pdf_config(PdfParserConfig::new().set_ocr_strategy(PdfOcrStrategy::OCR_ONLY))
+        .set_ocr_fallback_on_empty(true)
+        .set_ocr_enabled(false);
+    let (extracted, metadata) = extractor
+        .extract_file_to_string("../test_files/documents/deu-ocr.pdf")
+        .unwrap();
+    assert_eq!("", extracted.trim());
+    assert!(!metadata.contains_key("OCR-Fallback"));
+
+    let (extracted, _metadata) = extractor
+        .extract_file_to_string("../test_files/documents/eng-ocr.png")
+        .unwrap();
+    assert_eq!("", extracted.trim());
+}
+
+#[cfg(not(target_os = "macos"))]
+#[test]
+fn test_extract_file_to_string_ocr_only_strategy_rotated_pdf() {
+    // The second page is stored sideways and displayed upright through /Rotate 90
+    let extractor = Extractor::new()
+        .set_use_pure_rust(false)
+        .set_pdf_config(PdfParserConfig::new().set_ocr_strategy(PdfOcrStrategy::OCR_ONLY));
+    let (extracted, _metadata) = extractor
+        .extract_file_to_string("../test_files/documents/rotated.pdf")
+        .unwrap();
+
+    let expected = "This page is not rotated.\n\
+        Rotated pages are read upright.\n\
+        The quick brown fox jumps over the lazy dog.";
+    let dist = cosine(expected, &extracted);
+    assert!(
+        dist > 0.9,
+        "Cosine similarity is less than 0.9 for file: rotated.pdf, dist: {}",
+        dist
+    );
+    assert!(
+        extracted.contains("quick brown fox"),
+        "OCR output: {}",
+        extracted
+    );
+}
+
+#[test]
+fn test_extract_file_to_string_password_protected_pdf() {
+    let extractor = Extractor::new().set_document_password("extractous".to_string());
+    let (extracted, _metadata) = extractor
+        .extract_file_to_string("../test_files/documents/password-protected.pdf")
+        .unwrap();
+
+    assert!(
+        extracted.contains("Revenue grew by twelve percent in the third quarter."),
+        "Extracted: {}",
+        extracted
+    );
+}
+
+#[test]
+fn test_extract_file_to_string_password_protected_pdf_wrong_password() {
+    let extractor = Extractor::new().set_document_password("wrong".to_string());
+    let result = extractor.extract_file_to_string("../test_files/documents/password-protected.pdf");
+    assert!(
+        matches!(result, Err(Error::EncryptedDocument(_))),
+        "{:?}",
+        result
+    );
+
+    // Without a password the document cannot be opened either
+    let result =
+        Extractor::new().extract_file_to_string("../test_files/documents/password-protected.pdf");
+    assert!(
+        matches!(result, Err(Error::EncryptedDocument(_))),
+        "{:?}",
+        result
+    );
+}
+
+#[test]
+fn test_extract_file_to_string_annotate_page_boundaries_pdf() {
+    let (text, metadata) = Extractor::new()
+        .set_use_pure_rust(false)
+        .set_annotate_page_boundaries(true)
+        .extract_file_to_string("../test_files/documents/2022_Q3_AAPL.pdf")
+        .unwrap();
+
+    let page_count: usize = metadata["xmpTPg:NPages"][0].parse().unwrap();
+    assert!(page_count > 1);
+    assert_eq!(text.matches("[page ").count(), page_count);
+    assert!(text.starts_with("[page 1]\n"));
+    // Every marker starts a line and follows the marker of the page before
+    let mut previous = 0;
+    for page in 1..=page_count {
+        let marker = format!("[page {}]\n", page);
+        let position = text.find(&marker).unwrap();
+        assert!(
+            position == 0 || text[..position].ends_with('\n'),
+            "{}",
+            marker
+        );
+        assert!(page == 1 || position > previous, "{}", marker);
+        previous = position;
+    }
+}
+
+#[test]
+fn test_extract_file_metadata_only_pdf() {
+    let file_path = "../test_files/documents/2022_Q3_AAPL.pdf";
+    let (_text, metadata) = Extractor::new()
+        .set_use_pure_rust(false)
+        .extract_file_to_string(file_path)
+        .unwrap();
+    let metadata_only = Extractor::new()
+        .extract_file_metadata_only(file_path)
+        .unwrap();
+
+    for key in [
+        "Content-Type",
+        "xmpTPg:NPages",
+        "pdf:PDFVersion",
+        "dc:format",
+    ] {
+        assert!(
+            metadata_only.contains_key(key),
+            "Missing metadata key: {}",
+            key
+        );
+        assert_eq!(
+            metadata_only.get(key),
+            metadata.get(key),
+            "Metadata key: {}",
+            key
+        );
+    }
+}
+
+#[cfg(not(target_os = "macos"))]
+#[test]
+fn test_extract_file_metadata_only_skips_ocr_deu_ocr_pdf() {
+    // Extracting the text would run OCR on every page of the scan
+    let extractor = Extractor::new()
+        .set_ocr_config(TesseractOcrConfig::new().set_language("deu"))
+        .set_pdf_config(PdfParserConfig::new().set_ocr_strategy(PdfOcrStrategy::OCR_ONLY));
+    let metadata = extractor
+        .extract_file_metadata_only("../test_files/documents/deu-ocr.pdf")
+        .unwrap();
+
+    assert_eq!(
+        metadata.get("Content-Type"),
+        Some(&vec!["application/pdf".to_string()])
+    );
+    assert!(metadata.contains_key("xmpTPg:NPages"));
+    // Tika counts the pages it ran OCR on
+    let ocr_pages = metadata.get("pdf:ocrPageCount");
+    assert!(
+        ocr_pages.is_none() || ocr_pages.is_some_and(|count| count == &["0"]),
+        "{:?}",
+        ocr_pages
+    );
+}
+
+#[test]
+fn test_extract_file_to_string_tika_config_xml_without_pdf_parser() {
+    let config = r#"<?xml version="1.0" encoding="UTF-8"?>
+<properties>
+  <parsers>
+    <parser class="org.apache.tika.parser.DefaultParser">
+      <parser-exclude class="org.apache.tika.parser.pdf.PDFParser"/>
+    </parser>
+  </parsers>
+</properties>"#;
+    let extractor = Extractor::new()
+        .set_use_pure_rust(false)
+        .set_tika_config_xml(config.to_string());
+
+    // PDFs are still detected but left to the empty parser
+    let (extracted, metadata) = extractor
+        .extract_file_to_string("../test_files/documents/2022_Q3_AAPL.pdf")
+        .unwrap();
+    assert_eq!("", extracted.trim());
+    assert_eq!(metadata["Content-Type"], ["application/pdf"]);
+    assert!(!metadata.contains_key("xmpTPg:NPages"));
+
+    // The other parsers are kept
+    let (extracted, _metadata) = extractor
+        .extract_file_to_string("../test_files/documents/simple.odt")
+        .unwrap();
+    assert!(!extracted.trim().is_empty());
+
+    let result = Extractor::new()
+        .set_use_pure_rust(false)
+        .set_tika_config_xml("<properties><parsers>".to_string())
+        .extract_file_to_string("../test_files/documents/2022_Q3_AAPL.pdf");
+    assert!(matches!(result, Err(Error::ParseError(_))), "{:?}", result);
+}
+
+#[test]
+fn test_extract_url_to_string_retries_server_errors() {
+    // A server that is unavailable for the first two requests
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/notes.txt", listener.local_addr().unwrap());
+    let server = std::thread::spawn(move || {
+        for status in [
+            "503 Service Unavailable",
+            "503 Service Unavailable",
+            "200 OK",
+        ] {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = [0u8; 4096];
+            let _ = stream.read(&mut request).unwrap();
+            let body = match status {
+                "200 OK" => "The third attempt reached the server.",
+                _ => "Try again later.",
+            };
+            write!(
+                stream,
+                "HTTP/1.1 {}\r\nContent-Type: text/plain\r\nContent-Length: {}\r\n\
+                Connection: close\r\n\r\n{}",
+                status,
+                body.len(),
+                body
+            )
+            .unwrap();
+        }
+    });
+
+    let extractor = Extractor::new().set_url_retry(2, Duration::from_millis(10));
+    let (text, _metadata) = extractor.extract_url_to_string(&url).unwrap();
+    assert!(
+        text.contains("The third attempt reached the server."),
+        "{}",
+        text
+    );
+    server.join().unwrap();
+}
@@ -88,3 +88,15 @@ fn test_extract_file_to_xml(file_name: &str, target_dist: f64) {
         &extracted_metadata
     ));
 }
+
+#[test]
+fn test_extract_file_to_xhtml() {
+    let extractor = Extractor::new();
+    let (xhtml, metadata) = extractor
+        .extract_file_to_xhtml("../test_files/documents/category-level.docx")
+        .unwrap();
+
+    assert!(xhtml.contains("<body"), "XHTML has no body: {}", xhtml);
+    assert!(xhtml.contains("<p>"), "XHTML has no paragraphs: {}", xhtml);
+    assert!(metadata.contains_key("Content-Type"));
+}
@@ -58,3 +58,32 @@ pub fn is_expected_metadata_contained(
         }
     })
 }
+
+/// A tessdata directory of its own with a copy of the installed language data of `language`, the
+/// directory tesseract lists with `--list-langs`. Custom tessdata directories are tested with it,
+/// as real language data is too large to keep with the test files. The directory is removed when
+/// the returned [`tempfile::TempDir`] is dropped
+#[allow(dead_code)]
+pub fn copy_installed_tessdata(language: &str) -> tempfile::TempDir {
+    let output = std::process::Command::new("tesseract")
+        .arg("--list-langs")
+        .output()
+        .expect("tesseract is not installed");
+    // e.g. List of available languages in "/usr/share/tesseract-ocr/5/tessdata/" (3):
+    let listing = [output.stdout, output.stderr].concat();
+    let listing = String::from_utf8_lossy(&listing);
+    let installed = listing
+        .split('"')
+        .nth(1)
+        .expect("tesseract did not list its tessdata directory");
+
+    // A directory per call, as the tests run in parallel
+    let tessdata = tempfile::tempdir().unwrap();
+    let file_name = format!("{}.traineddata", language);
+    std::fs::copy(
+        std::path::Path::new(installed).join(&file_name),
+        tessdata.path().join(&file_name),
+    )
+    .unwrap();
+    tessdata
+}
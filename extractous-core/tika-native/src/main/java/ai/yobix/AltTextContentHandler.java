@@ -0,0 +1,47 @@
+package ai.yobix;
+
+import org.apache.tika.sax.ContentHandlerDecorator;
+import org.xml.sax.Attributes;
+import org.xml.sax.ContentHandler;
+import org.xml.sax.SAXException;
+
+/**
+ * Writes the alt text of images as text. The text content handlers drop attributes, so without
+ * this the description of a figure is lost while its caption, e.g. a figcaption, is kept.
+ * Images whose alt text is their file name are skipped, e.g. the
+ * {@code <img src="embedded:image0.jpg" alt="image0.jpg">} that Tika's PDF parser writes for
+ * inline images, as the name is not text of the document.
+ */
+public class AltTextContentHandler extends ContentHandlerDecorator {
+
+    /** The prefix of the src of images embedded in the document */
+    private static final String EMBEDDED_PREFIX = "embedded:";
+
+    public AltTextContentHandler(ContentHandler handler) {
+        super(handler);
+    }
+
+    @Override
+    public void startElement(String uri, String localName, String name, Attributes atts)
+            throws SAXException {
+        super.startElement(uri, localName, name, atts);
+        if ("img".equals(localName)) {
+            final String src = atts.getValue("src");
+            final String alt = atts.getValue("alt");
+            if (alt != null && !alt.isBlank() && !isFileName(src, alt.trim())) {
+                final char[] text = (" " + alt.trim() + " ").toCharArray();
+                super.characters(text, 0, text.length);
+            }
+        }
+    }
+
+    private static boolean isFileName(String src, String alt) {
+        if (src == null) {
+            return false;
+        }
+        final String path = src.startsWith(EMBEDDED_PREFIX)
+                ? src.substring(EMBEDDED_PREFIX.length())
+                : src;
+        return alt.equals(path.substring(path.lastIndexOf('/') + 1));
+    }
+}
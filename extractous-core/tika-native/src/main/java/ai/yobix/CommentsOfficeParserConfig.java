@@ -0,0 +1,135 @@
+package ai.yobix;
+
+import org.apache.commons.io.IOUtils;
+import org.apache.tika.io.TemporaryResources;
+import org.apache.tika.io.TikaInputStream;
+import org.apache.tika.metadata.Metadata;
+import org.apache.tika.parser.microsoft.OfficeParserConfig;
+
+import java.io.IOException;
+import java.io.InputStream;
+import java.nio.charset.StandardCharsets;
+import java.nio.file.Files;
+import java.nio.file.Path;
+import java.util.Enumeration;
+import java.util.zip.ZipEntry;
+import java.util.zip.ZipException;
+import java.util.zip.ZipFile;
+import java.util.zip.ZipOutputStream;
+
+/**
+ * OfficeParserConfig that can leave out the comments of Word documents. Tika has no option for
+ * this, TikaNativeMain parses a docx from a copy whose comments part is empty instead, which
+ * works the same for Tika's DOM and SAX docx parsers.
+ */
+public class CommentsOfficeParserConfig extends PasswordOfficeParserConfig {
+
+    private static final String WORD_COMMENTS = "word/comments.xml";
+
+    /** The length of the local header of a zip entry, up to the entry's name */
+    private static final int ZIP_HEADER_LENGTH = 30;
+
+    /** The longest name of a first zip entry that is looked at */
+    private static final int MAX_ENTRY_NAME_LENGTH = 256;
+
+    /** The part names that an OOXML package, such as a docx, starts with */
+    private static final String[] OOXML_PARTS = {
+            "[Content_Types].xml", "_rels/", "docProps/", "word/", "customXml/"
+    };
+
+    private static final byte[] EMPTY_COMMENTS = (
+            "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>"
+                    + "<w:comments xmlns:w=\""
+                    + "http://schemas.openxmlformats.org/wordprocessingml/2006/main\"/>"
+    ).getBytes(StandardCharsets.UTF_8);
+
+    private boolean includeComments = true;
+
+    public boolean isIncludeComments() {
+        return includeComments;
+    }
+
+    public void setIncludeComments(boolean includeComments) {
+        this.includeComments = includeComments;
+    }
+
+    /**
+     * The stream to parse with the given config: a copy of a docx without its comments when the
+     * config leaves them out, otherwise the stream itself. Only OOXML packages are copied, other
+     * documents are passed through without spooling them to a temporary file
+     */
+    static InputStream withoutComments(InputStream stream, OfficeParserConfig config)
+            throws IOException {
+        if (!(config instanceof CommentsOfficeParserConfig)
+                || ((CommentsOfficeParserConfig) config).isIncludeComments()) {
+            return stream;
+        }
+
+        final TikaInputStream input = TikaInputStream.get(stream);
+        if (!isOoxml(input)) {
+            return input;
+        }
+        final TemporaryResources tmp = new TemporaryResources();
+        try (ZipFile zip = new ZipFile(input.getPath().toFile())) {
+            if (zip.getEntry(WORD_COMMENTS) == null) {
+                tmp.close();
+                return input;
+            }
+            final Path copy = tmp.createTempFile();
+            try (ZipOutputStream out = new ZipOutputStream(Files.newOutputStream(copy))) {
+                final Enumeration<? extends ZipEntry> entries = zip.entries();
+                while (entries.hasMoreElements()) {
+                    final ZipEntry entry = entries.nextElement();
+                    out.putNextEntry(new ZipEntry(entry.getName()));
+                    if (WORD_COMMENTS.equals(entry.getName())) {
+                        out.write(EMPTY_COMMENTS);
+                    } else {
+                        try (InputStream in = zip.getInputStream(entry)) {
+                            in.transferTo(out);
+                        }
+                    }
+                    out.closeEntry();
+                }
+            }
+            input.close();
+            // The copy is deleted when the returned stream is closed
+            return TikaInputStream.get(Files.newInputStream(copy), tmp, new Metadata());
+        } catch (ZipException e) {
+            // Not a zip, so not a docx either
+            tmp.close();
+            return input;
+        }
+    }
+
+    /**
+     * Whether the stream is an OOXML package, told by the name of its first zip entry, which is
+     * one of the package's parts. The stream is reset to its start
+     */
+    private static boolean isOoxml(TikaInputStream input) throws IOException {
+        final byte[] head = new byte[ZIP_HEADER_LENGTH + MAX_ENTRY_NAME_LENGTH];
+        input.mark(head.length);
+        final int length;
+        try {
+            length = IOUtils.read(input, head);
+        } finally {
+            input.reset();
+        }
+        // The signature of a zip entry's local header, PK\3\4
+        if (length < ZIP_HEADER_LENGTH || head[0] != 'P' || head[1] != 'K' || head[2] != 3
+                || head[3] != 4) {
+            return false;
+        }
+        final int nameLength = (head[26] & 0xff) | (head[27] & 0xff) << 8;
+        if (ZIP_HEADER_LENGTH + nameLength > length) {
+            return false;
+        }
+        final String name =
+                new String(head, ZIP_HEADER_LENGTH, nameLength, StandardCharsets.UTF_8);
+        for (String part : OOXML_PARTS) {
+            if (name.startsWith(part)) {
+                return true;
+            }
+        }
+        return false;
+    }
+}
@@ -10,6 +10,7 @@ import org.apache.tika.exception.ZeroByteFileException;
 import org.apache.tika.metadata.Metadata;
 import org.apache.tika.metadata.TikaCoreProperties;
 import org.apache.tika.sax.BodyContentHandler;
+import org.apache.tika.sax.ToTextContentHandler;
 import org.apache.tika.sax.ToXMLContentHandler;
 
 public class ParsingReader extends Reader {
@@ -20,17 +21,17 @@ public class ParsingReader extends Reader {
     private final InputStream stream;
     private final Metadata metadata;
     private final ParseContext context;
-    private final boolean outputXml;
+    private final String handlerType;
     private final String encoding;
-    private transient Throwable throwable;
+    private transient volatile Throwable throwable;
 
     public ParsingReader(Parser parser, InputStream stream, Metadata metadata,
-                            ParseContext context, boolean outputXml, String encoding) throws IOException {
+                            ParseContext context, String handlerType, String encoding) throws IOException {
         this.parser = parser;
         this.stream = stream;
         this.metadata = metadata;
         this.context = context;
-        this.outputXml = outputXml;
+        this.handlerType = handlerType;
         this.encoding = encoding;
 
         PipedInputStream pipedInputStream = new PipedInputStream();
@@ -58,14 +59,16 @@ public class ParsingReader extends Reader {
 
     @Override
     public int read(char[] cbuf, int off, int len) throws IOException {
-        if (throwable instanceof ZeroByteFileException) {
-            return -1;
+        // The text produced before a failure is read first. The parsing task records the failure
+        // before it closes the pipe, so the end of the pipe is never taken for the end of the text
+        final int read = reader.read(cbuf, off, len);
+        if (read != -1 || throwable == null || throwable instanceof ZeroByteFileException) {
+            return read;
         } else if (throwable instanceof IOException) {
             throw (IOException) throwable;
-        } else if (throwable != null) {
+        } else {
             throw new IOException("", throwable);
         }
-        return reader.read(cbuf, off, len);
     }
 
     @Override
@@ -77,7 +80,18 @@ public class ParsingReader extends Reader {
 
         public void run() {
             try {
-                ContentHandler handler = outputXml ? new ToXMLContentHandler(pipedOutputStream, encoding) : new BodyContentHandler(pipedOutputStream);
+                final ContentHandler handler;
+                switch (handlerType) {
+                    case "Xhtml":
+                        handler = new ToXMLContentHandler(pipedOutputStream, encoding);
+                        break;
+                    case "Text":
+                        handler = new AltTextContentHandler(
+                                new ToTextContentHandler(pipedOutputStream, encoding));
+                        break;
+                    default:
+                        handler = new AltTextContentHandler(new BodyContentHandler(pipedOutputStream));
+                }
                 parser.parse(stream, handler, metadata, context);
             } catch (Throwable t) {
                 throwable = t;
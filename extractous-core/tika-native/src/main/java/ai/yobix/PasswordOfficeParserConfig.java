@@ -0,0 +1,21 @@
+package ai.yobix;
+
+import org.apache.tika.parser.microsoft.OfficeParserConfig;
+
+/**
+ * OfficeParserConfig with the password of encrypted documents. Tika only takes passwords from a
+ * PasswordProvider in the ParseContext, TikaNativeMain registers one for it before parsing.
+ */
+public class PasswordOfficeParserConfig extends OfficeParserConfig {
+
+    private String password;
+
+    public String getPassword() {
+        return password;
+    }
+
+    public void setPassword(String password) {
+        this.password = password;
+    }
+
+}
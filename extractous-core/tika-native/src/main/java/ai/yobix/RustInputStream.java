@@ -0,0 +1,58 @@
+package ai.yobix;
+
+import java.io.IOException;
+import java.io.InputStream;
+
+/**
+ * InputStream reading from a Rust std::io::Read. The reader lives on the Rust side and is
+ * referenced by an opaque handle, the native methods are registered by the Rust library.
+ * The stream owns the reader, closing the stream drops it.
+ */
+public class RustInputStream extends InputStream {
+
+    private long handle;
+
+    public RustInputStream(long handle) {
+        this.handle = handle;
+    }
+
+    private static native int readNative(long handle, byte[] b, int off, int len) throws IOException;
+
+    private static native void closeNative(long handle);
+
+    @Override
+    public synchronized int read() throws IOException {
+        final byte[] b = new byte[1];
+        final int n = read(b, 0, 1);
+        return n == -1 ? -1 : (b[0] & 0xFF);   // need to be in the range 0 to 255
+    }
+
+    @Override
+    public synchronized int read(byte[] b, int off, int len) throws IOException {
+
+        if (handle == 0) {
+            throw new IOException("read on a closed InputStream");
+        }
+
+        if (b == null) {
+            throw new NullPointerException();
+        } else if (off < 0 || len < 0 || len > b.length - off) {
+            throw new IndexOutOfBoundsException();
+        } else if (len == 0) {
+            return 0;
+        }
+
+        // The Rust reader returns 0 at the end of the stream
+        final int length = readNative(handle, b, off, len);
+        return length == 0 ? -1 : length;
+    }
+
+    @Override
+    public synchronized void close() throws IOException {
+        if (handle != 0) {
+            closeNative(handle);
+            handle = 0;
+        }
+    }
+
+}
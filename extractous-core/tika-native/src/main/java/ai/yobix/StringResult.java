@@ -56,6 +56,7 @@ public class StringResult {
      * 0: OK
      * 1: IOException
      * 2: TikaException
+     * 3: EncryptedDocumentException, the password is missing or wrong
      */
     public byte getStatus() {
         return status;
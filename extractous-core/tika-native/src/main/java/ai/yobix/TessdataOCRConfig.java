@@ -0,0 +1,21 @@
+package ai.yobix;
+
+import org.apache.tika.parser.ocr.TesseractOCRConfig;
+
+/**
+ * TesseractOCRConfig with a tessdata directory. Tika only supports the tessdata path as a
+ * setting of the TesseractOCRParser, TikaNativeMain applies it to the parser before parsing.
+ */
+public class TessdataOCRConfig extends TesseractOCRConfig {
+
+    private String tessdataPath;
+
+    public String getTessdataPath() {
+        return tessdataPath;
+    }
+
+    public void setTessdataPath(String tessdataPath) {
+        this.tessdataPath = tessdataPath;
+    }
+
+}
@@ -3,18 +3,28 @@ package ai.yobix;
 import org.apache.commons.io.input.ReaderInputStream;
 import org.apache.tika.Tika;
 import org.apache.tika.config.TikaConfig;
+import org.apache.tika.exception.EncryptedDocumentException;
+import org.apache.tika.exception.TikaConfigException;
 import org.apache.tika.exception.TikaException;
 import org.apache.tika.exception.WriteLimitReachedException;
 import org.apache.tika.io.TemporaryResources;
 import org.apache.tika.io.TikaInputStream;
 import org.apache.tika.metadata.Metadata;
+import org.apache.tika.mime.MediaType;
 import org.apache.tika.parser.AutoDetectParser;
+import org.apache.tika.parser.CompositeParser;
 import org.apache.tika.parser.ParseContext;
 import org.apache.tika.parser.Parser;
+import org.apache.tika.parser.ParserDecorator;
+import org.apache.tika.parser.PasswordProvider;
+import org.apache.tika.parser.RecursiveParserWrapper;
 import org.apache.tika.parser.microsoft.OfficeParserConfig;
 import org.apache.tika.parser.ocr.TesseractOCRConfig;
+import org.apache.tika.parser.ocr.TesseractOCRParser;
 import org.apache.tika.parser.pdf.PDFParserConfig;
+import org.apache.tika.sax.BasicContentHandlerFactory;
 import org.apache.tika.sax.BodyContentHandler;
+import org.apache.tika.sax.RecursiveParserWrapperHandler;
 import org.apache.tika.sax.ToXMLContentHandler;
 import org.apache.tika.sax.WriteOutContentHandler;
 import org.graalvm.nativeimage.IsolateThread;
@@ -24,7 +34,9 @@ import org.graalvm.nativeimage.c.type.CConst;
 import org.graalvm.nativeimage.c.type.CTypeConversion;
 import org.xml.sax.ContentHandler;
 import org.xml.sax.SAXException;
+import org.xml.sax.helpers.DefaultHandler;
 
+import java.io.ByteArrayInputStream;
 import java.io.IOException;
 import java.io.InputStream;
 import java.io.Reader;
@@ -37,11 +49,34 @@ import java.nio.charset.Charset;
 import java.nio.charset.StandardCharsets;
 import java.nio.file.Path;
 import java.nio.file.Paths;
+import java.util.Collections;
+import java.util.LinkedHashMap;
+import java.util.Map;
+import java.util.Set;
+import java.util.TreeSet;
 
 public class TikaNativeMain {
 
     private static final Tika tika = new Tika();
 
+    /**
+     * The most TikaConfigs built from config XML documents that are kept for reuse
+     */
+    private static final int MAX_TIKA_CONFIGS = 16;
+
+    /**
+     * The TikaConfigs built from config XML documents, keyed by the document. The least recently
+     * used config is dropped when there are more than MAX_TIKA_CONFIGS, so an application that
+     * passes many different documents does not keep all of their parsers alive
+     */
+    private static final Map<String, TikaConfig> tikaConfigs = Collections.synchronizedMap(
+            new LinkedHashMap<String, TikaConfig>(MAX_TIKA_CONFIGS, 0.75f, true) {
+                @Override
+                protected boolean removeEldestEntry(Map.Entry<String, TikaConfig> eldest) {
+                    return size() > MAX_TIKA_CONFIGS;
+                }
+            });
+
     /**
      * Parses the given file and returns its type as a mime type
      *
@@ -76,7 +111,8 @@ public class TikaNativeMain {
             PDFParserConfig pdfConfig,
             OfficeParserConfig officeConfig,
             TesseractOCRConfig tesseractConfig,
-            boolean asXML
+            String handlerType,
+            String tikaConfigXml
             // maybe replace with a single config class
     ) {
         try {
@@ -85,11 +121,14 @@ public class TikaNativeMain {
             final InputStream stream = TikaInputStream.get(path, metadata);
 
             String result = parseToStringWithConfig(
-                    stream, metadata, maxLength, pdfConfig, officeConfig, tesseractConfig, asXML);
+                    stream, metadata, maxLength, pdfConfig, officeConfig, tesseractConfig, handlerType,
+                    tikaConfigXml);
             // No need to close the stream because parseToString does so
             return new StringResult(result, metadata);
         } catch (java.io.IOException e) {
             return new StringResult((byte) 1, "Could not open file: " + e.getMessage());
+        } catch (EncryptedDocumentException e) {
+            return new StringResult((byte) 3, "Encrypted document: " + e.getMessage());
         } catch (TikaException e) {
             return new StringResult((byte) 2, "Parse error occurred : " + e.getMessage());
         }
@@ -107,7 +146,8 @@ public class TikaNativeMain {
             PDFParserConfig pdfConfig,
             OfficeParserConfig officeConfig,
             TesseractOCRConfig tesseractConfig,
-            boolean asXML
+            String handlerType,
+            String tikaConfigXml
     ) {
         try {
             final URL url = new URI(urlString).toURL();
@@ -115,7 +155,8 @@ public class TikaNativeMain {
             final TikaInputStream stream = TikaInputStream.get(url, metadata);
 
             String result = parseToStringWithConfig(
-                    stream, metadata, maxLength, pdfConfig, officeConfig, tesseractConfig, asXML);
+                    stream, metadata, maxLength, pdfConfig, officeConfig, tesseractConfig, handlerType,
+                    tikaConfigXml);
             // No need to close the stream because parseToString does so
             return new StringResult(result, metadata);
 
@@ -125,6 +166,8 @@ public class TikaNativeMain {
             return new StringResult((byte) 2, "Malformed URI error occurred: " + e.getMessage());
         } catch (java.io.IOException e) {
             return new StringResult((byte) 1, "IO error occurred: " + e.getMessage());
+        } catch (EncryptedDocumentException e) {
+            return new StringResult((byte) 3, "Encrypted document: " + e.getMessage());
         } catch (TikaException e) {
             return new StringResult((byte) 2, "Parse error occurred : " + e.getMessage());
         }
@@ -142,7 +185,8 @@ public class TikaNativeMain {
             PDFParserConfig pdfConfig,
             OfficeParserConfig officeConfig,
             TesseractOCRConfig tesseractConfig,
-            boolean asXML
+            String handlerType,
+            String tikaConfigXml
     ) {
         final Metadata metadata = new Metadata();
         final ByteBufferInputStream inStream = new ByteBufferInputStream(data);
@@ -150,46 +194,294 @@ public class TikaNativeMain {
 
         try {
             String result = parseToStringWithConfig(
-                    stream, metadata, maxLength, pdfConfig, officeConfig, tesseractConfig, asXML);
+                    stream, metadata, maxLength, pdfConfig, officeConfig, tesseractConfig, handlerType,
+                    tikaConfigXml);
             // No need to close the stream because parseToString does so
             return new StringResult(result, metadata);
         } catch (java.io.IOException e) {
             return new StringResult((byte) 1, "IO error occurred: " + e.getMessage());
+        } catch (EncryptedDocumentException e) {
+            return new StringResult((byte) 3, "Encrypted document: " + e.getMessage());
         } catch (TikaException e) {
             return new StringResult((byte) 2, "Parse error occurred : " + e.getMessage());
         }
     }
 
-    private static String parseToStringWithConfig(
-            InputStream stream,
-            Metadata metadata,
-            int maxLength,
+    /**
+     * Parses the given file and returns Tika's structured XHTML output verbatim, as produced by
+     * the ToXMLContentHandler. Unlike parseFileToString with the Xhtml handler, the output is not limited
+     * to a maximum length.
+     *
+     * @param filePath: the path of the file to be parsed
+     * @return StringResult
+     */
+    public static StringResult parseFileToXhtml(
+            String filePath,
             PDFParserConfig pdfConfig,
             OfficeParserConfig officeConfig,
             TesseractOCRConfig tesseractConfig,
-            boolean asXML
-    ) throws IOException, TikaException {
-        ContentHandler handler;
-        ContentHandler handlerForParser;
-        if (asXML) {
-            handler = new WriteOutContentHandler(new ToXMLContentHandler(), maxLength);
-            handlerForParser = handler;
-        } else {
-            handler = new WriteOutContentHandler(maxLength);
-            handlerForParser = new BodyContentHandler(handler);
+            String tikaConfigXml
+    ) {
+        try {
+            final Path path = Paths.get(filePath);
+            final Metadata metadata = new Metadata();
+            final InputStream stream = TikaInputStream.get(path, metadata);
+            final ContentHandler handler = new ToXMLContentHandler();
+
+            try {
+                parseWithConfig(
+                        stream, handler, metadata, pdfConfig, officeConfig, tesseractConfig, tikaConfigXml);
+            } catch (SAXException e) {
+                throw new TikaException("Unexpected SAX processing failure", e);
+            } finally {
+                stream.close();
+            }
+            return new StringResult(handler.toString(), metadata);
+        } catch (java.io.IOException e) {
+            return new StringResult((byte) 1, "Could not open file: " + e.getMessage());
+        } catch (EncryptedDocumentException e) {
+            return new StringResult((byte) 3, "Encrypted document: " + e.getMessage());
+        } catch (TikaException e) {
+            return new StringResult((byte) 2, "Parse error occurred : " + e.getMessage());
         }
+    }
 
+    /**
+     * Parses the given file for its metadata only. The body is passed to a handler that drops
+     * it, OCR is skipped and embedded documents are not parsed, so the parsers do only the work
+     * they need to read the metadata.
+     *
+     * @param filePath: the path of the file to be parsed
+     * @return StringResult with an empty content
+     */
+    public static StringResult parseFileMetadata(
+            String filePath,
+            PDFParserConfig pdfConfig,
+            OfficeParserConfig officeConfig,
+            String tikaConfigXml
+    ) {
         try {
-            final TikaConfig config = TikaConfig.getDefaultConfig();
+            final TikaConfig config = tikaConfig(tikaConfigXml);
+            final Path path = Paths.get(filePath);
+            final Metadata metadata = new Metadata();
+            final InputStream stream = TikaInputStream.get(path, metadata);
+
+            final TesseractOCRConfig tesseractConfig = new TesseractOCRConfig();
+            tesseractConfig.setSkipOcr(true);
             final ParseContext parsecontext = new ParseContext();
+            // Parser.class stays unset, so embedded documents are not parsed
             final Parser parser = new AutoDetectParser(config);
+            parsecontext.set(PDFParserConfig.class, pdfConfig);
+            parsecontext.set(OfficeParserConfig.class, officeConfig);
+            parsecontext.set(TesseractOCRConfig.class, tesseractConfig);
+            setPasswordProvider(parsecontext, pdfConfig, officeConfig);
 
-            parsecontext.set(Parser.class, parser);
+            try {
+                parser.parse(stream, new DefaultHandler(), metadata, parsecontext);
+            } catch (SAXException e) {
+                throw new TikaException("Unexpected SAX processing failure", e);
+            } finally {
+                stream.close();
+            }
+            return new StringResult("", metadata);
+        } catch (java.io.IOException e) {
+            return new StringResult((byte) 1, "Could not open file: " + e.getMessage());
+        } catch (EncryptedDocumentException e) {
+            return new StringResult((byte) 3, "Encrypted document: " + e.getMessage());
+        } catch (TikaException e) {
+            return new StringResult((byte) 2, "Parse error occurred : " + e.getMessage());
+        }
+    }
+
+    /**
+     * Parses the given file together with the files embedded in it, e.g. the entries of a zip or
+     * the attachments of an email or PDF, using Tika's RecursiveParserWrapper. The text of each
+     * document, limited to maxLength characters, is stored in its metadata under X-TIKA:content.
+     *
+     * @param filePath:  the path of the file to be parsed
+     * @param maxLength: maximum length of the text of each document
+     * @return MetadataListResult with the container document first
+     */
+    public static MetadataListResult parseFileWithEmbedded(
+            String filePath,
+            int maxLength,
+            PDFParserConfig pdfConfig,
+            OfficeParserConfig officeConfig,
+            TesseractOCRConfig tesseractConfig,
+            String tikaConfigXml
+    ) {
+        try {
+            final TikaConfig config = tikaConfig(tikaConfigXml);
+            final Path path = Paths.get(filePath);
+            final Metadata metadata = new Metadata();
+            final InputStream stream = TikaInputStream.get(path, metadata);
+
+            final ParseContext parsecontext = new ParseContext();
+            // The wrapper registers itself for the embedded documents, Parser.class stays unset
+            final Parser parser = new RecursiveParserWrapper(newParser(config, tesseractConfig));
             parsecontext.set(PDFParserConfig.class, pdfConfig);
             parsecontext.set(OfficeParserConfig.class, officeConfig);
             parsecontext.set(TesseractOCRConfig.class, tesseractConfig);
+            setPasswordProvider(parsecontext, pdfConfig, officeConfig);
+
+            final RecursiveParserWrapperHandler handler = new RecursiveParserWrapperHandler(
+                    new BasicContentHandlerFactory(BasicContentHandlerFactory.HANDLER_TYPE.TEXT, maxLength));
+            final InputStream input = CommentsOfficeParserConfig.withoutComments(stream, officeConfig);
+            try {
+                parser.parse(input, handler, metadata, parsecontext);
+            } catch (SAXException e) {
+                throw new TikaException("Unexpected SAX processing failure", e);
+            } finally {
+                input.close();
+                stream.close();
+            }
+            return new MetadataListResult(handler.getMetadataList());
+        } catch (java.io.IOException e) {
+            return new MetadataListResult((byte) 1, "Could not open file: " + e.getMessage());
+        } catch (EncryptedDocumentException e) {
+            return new MetadataListResult((byte) 3, "Encrypted document: " + e.getMessage());
+        } catch (TikaException e) {
+            return new MetadataListResult((byte) 2, "Parse error occurred : " + e.getMessage());
+        }
+    }
+
+    /**
+     * Creates the AutoDetectParser, pointing its TesseractOCRParser at a custom tessdata
+     * directory when the config has one
+     */
+    private static Parser newParser(TikaConfig config, TesseractOCRConfig tesseractConfig) {
+        final AutoDetectParser parser = new AutoDetectParser(config);
+        if (tesseractConfig instanceof TessdataOCRConfig) {
+            final String tessdataPath = ((TessdataOCRConfig) tesseractConfig).getTessdataPath();
+            if (tessdataPath != null) {
+                setTessdataPath(parser, tessdataPath);
+            }
+        }
+        return parser;
+    }
+
+    /**
+     * Returns the TikaConfig described by the given config XML document, or the default config
+     * when there is none. The config of a document is built on first use and reused while it is
+     * among the MAX_TIKA_CONFIGS most recently used ones
+     */
+    private static TikaConfig tikaConfig(String tikaConfigXml) throws TikaException {
+        if (tikaConfigXml == null) {
+            return TikaConfig.getDefaultConfig();
+        }
+        final TikaConfig cached = tikaConfigs.get(tikaConfigXml);
+        if (cached != null) {
+            return cached;
+        }
+
+        try {
+            final TikaConfig config = new TikaConfig(
+                    new ByteArrayInputStream(tikaConfigXml.getBytes(StandardCharsets.UTF_8)));
+            tikaConfigs.put(tikaConfigXml, config);
+            return config;
+        } catch (IOException | SAXException e) {
+            throw new TikaConfigException("Invalid Tika config XML: " + e.getMessage(), e);
+        }
+    }
+
+    private static void setTessdataPath(Parser parser, String tessdataPath) {
+        if (parser instanceof TesseractOCRParser) {
+            ((TesseractOCRParser) parser).setTessdataPath(tessdataPath);
+        } else if (parser instanceof ParserDecorator) {
+            setTessdataPath(((ParserDecorator) parser).getWrappedParser(), tessdataPath);
+        } else if (parser instanceof CompositeParser) {
+            for (Parser child : ((CompositeParser) parser).getAllComponentParsers()) {
+                setTessdataPath(child, tessdataPath);
+            }
+        }
+    }
+
+    /**
+     * Registers the passwords of the configs, if any, as the PasswordProvider Tika asks for the
+     * password of encrypted documents. PDFs get the password of the PDF config, other documents
+     * that of the Office config
+     */
+    private static void setPasswordProvider(
+            ParseContext parsecontext,
+            PDFParserConfig pdfConfig,
+            OfficeParserConfig officeConfig
+    ) {
+        final String pdfPassword = pdfConfig instanceof PasswordPDFParserConfig
+                ? ((PasswordPDFParserConfig) pdfConfig).getPassword() : null;
+        final String officePassword = officeConfig instanceof PasswordOfficeParserConfig
+                ? ((PasswordOfficeParserConfig) officeConfig).getPassword() : null;
+        if (pdfPassword == null && officePassword == null) {
+            return;
+        }
+
+        parsecontext.set(PasswordProvider.class, metadata -> {
+            final String contentType = metadata.get(Metadata.CONTENT_TYPE);
+            if (contentType != null && contentType.startsWith("application/pdf")) {
+                return pdfPassword;
+            }
+            return officePassword;
+        });
+    }
+
+    private static void parseWithConfig(
+            InputStream stream,
+            ContentHandler handler,
+            Metadata metadata,
+            PDFParserConfig pdfConfig,
+            OfficeParserConfig officeConfig,
+            TesseractOCRConfig tesseractConfig,
+            String tikaConfigXml
+    ) throws IOException, SAXException, TikaException {
+        final TikaConfig config = tikaConfig(tikaConfigXml);
+        final ParseContext parsecontext = new ParseContext();
+        final Parser parser = newParser(config, tesseractConfig);
+
+        parsecontext.set(Parser.class, parser);
+        parsecontext.set(PDFParserConfig.class, pdfConfig);
+        parsecontext.set(OfficeParserConfig.class, officeConfig);
+        parsecontext.set(TesseractOCRConfig.class, tesseractConfig);
+        setPasswordProvider(parsecontext, pdfConfig, officeConfig);
+
+        final InputStream input = CommentsOfficeParserConfig.withoutComments(stream, officeConfig);
+        try {
+            parser.parse(input, handler, metadata, parsecontext);
+        } finally {
+            // The caller closes the stream it passed in, a copy without comments is closed here
+            if (input != stream) {
+                input.close();
+            }
+        }
+    }
+
+    private static String parseToStringWithConfig(
+            InputStream stream,
+            Metadata metadata,
+            int maxLength,
+            PDFParserConfig pdfConfig,
+            OfficeParserConfig officeConfig,
+            TesseractOCRConfig tesseractConfig,
+            String handlerType,
+            String tikaConfigXml
+    ) throws IOException, TikaException {
+        ContentHandler handler;
+        ContentHandler handlerForParser;
+        switch (handlerType) {
+            case "Xhtml":
+                handler = new WriteOutContentHandler(new ToXMLContentHandler(), maxLength);
+                handlerForParser = handler;
+                break;
+            case "Text":
+                handler = new WriteOutContentHandler(maxLength);
+                handlerForParser = new AltTextContentHandler(handler);
+                break;
+            default:
+                handler = new WriteOutContentHandler(maxLength);
+                handlerForParser = new AltTextContentHandler(new BodyContentHandler(handler));
+        }
 
-            parser.parse(stream, handlerForParser, metadata, parsecontext);
+        try {
+            parseWithConfig(
+                    stream, handlerForParser, metadata, pdfConfig, officeConfig, tesseractConfig, tikaConfigXml);
         } catch (SAXException e) {
             if (!WriteLimitReachedException.isWriteLimitReached(e)) {
                 // This should never happen with BodyContentHandler...
@@ -215,7 +507,8 @@ public class TikaNativeMain {
             PDFParserConfig pdfConfig,
             OfficeParserConfig officeConfig,
             TesseractOCRConfig tesseractConfig,
-            boolean asXML
+            String handlerType,
+            String tikaConfigXml
     ) {
         try {
 //            System.out.println("pdfConfig.isExtractInlineImages = " + pdfConfig.isExtractInlineImages());
@@ -230,7 +523,9 @@ public class TikaNativeMain {
             final Metadata metadata = new Metadata();
             final TikaInputStream stream = TikaInputStream.get(path, metadata);
 
-            return parse(stream, metadata, charsetName, pdfConfig, officeConfig, tesseractConfig, asXML);
+            return parse(
+                    stream, metadata, charsetName, pdfConfig, officeConfig, tesseractConfig, handlerType,
+                    tikaConfigXml);
 
         } catch (java.io.IOException e) {
             return new ReaderResult((byte) 1, "Could not open file: " + e.getMessage());
@@ -250,14 +545,17 @@ public class TikaNativeMain {
             PDFParserConfig pdfConfig,
             OfficeParserConfig officeConfig,
             TesseractOCRConfig tesseractConfig,
-            boolean asXML
+            String handlerType,
+            String tikaConfigXml
     ) {
         try {
             final URL url = new URI(urlString).toURL();
             final Metadata metadata = new Metadata();
             final TikaInputStream stream = TikaInputStream.get(url, metadata);
 
-            return parse(stream, metadata, charsetName, pdfConfig, officeConfig, tesseractConfig, asXML);
+            return parse(
+                    stream, metadata, charsetName, pdfConfig, officeConfig, tesseractConfig, handlerType,
+                    tikaConfigXml);
 
         } catch (MalformedURLException e) {
             return new ReaderResult((byte) 2, "Malformed URL error occurred " + e.getMessage());
@@ -273,23 +571,66 @@ public class TikaNativeMain {
      * to read chunks and must be closed when reading is finished
      *
      * @param data an array of bytes
+     * @param contentType the mime type of the data if the caller knows it, null to detect it
      * @return ReaderResult
      */
     public static ReaderResult parseBytes(
             ByteBuffer data,
+            String contentType,
             String charsetName,
             PDFParserConfig pdfConfig,
             OfficeParserConfig officeConfig,
             TesseractOCRConfig tesseractConfig,
-            boolean asXML
+            String handlerType,
+            String tikaConfigXml
     ) {
 
 
         final Metadata metadata = new Metadata();
+        if (contentType != null) {
+            // The detectors take the declared type as a hint
+            metadata.set(Metadata.CONTENT_TYPE, contentType);
+        }
         final ByteBufferInputStream inStream = new ByteBufferInputStream(data);
         final TikaInputStream stream = TikaInputStream.get(inStream, new TemporaryResources(), metadata);
 
-        return parse(stream, metadata, charsetName, pdfConfig, officeConfig, tesseractConfig, asXML);
+        return parse(
+                stream, metadata, charsetName, pdfConfig, officeConfig, tesseractConfig, handlerType,
+                tikaConfigXml);
+    }
+
+    /**
+     * Parses the given InputStream and returns its content as Reader. The reader can be used
+     * to read chunks and must be closed when reading is finished. The stream is read lazily while
+     * the reader is consumed and closed when parsing finishes, or right away if parsing fails
+     *
+     * @param data the stream to be parsed, e.g. a RustInputStream
+     * @return ReaderResult
+     */
+    public static ReaderResult parseInputStream(
+            InputStream data,
+            String charsetName,
+            PDFParserConfig pdfConfig,
+            OfficeParserConfig officeConfig,
+            TesseractOCRConfig tesseractConfig,
+            String handlerType,
+            String tikaConfigXml
+    ) {
+
+        final Metadata metadata = new Metadata();
+        final TikaInputStream stream = TikaInputStream.get(data, new TemporaryResources(), metadata);
+
+        final ReaderResult result = parse(
+                stream, metadata, charsetName, pdfConfig, officeConfig, tesseractConfig, handlerType,
+                tikaConfigXml);
+        if (result.isError()) {
+            try {
+                stream.close();
+            } catch (java.io.IOException e) {
+                // the parse error is more useful to the caller
+            }
+        }
+        return result;
     }
 
     private static ReaderResult parse(
@@ -299,22 +640,25 @@ public class TikaNativeMain {
             PDFParserConfig pdfConfig,
             OfficeParserConfig officeConfig,
             TesseractOCRConfig tesseractConfig,
-            boolean asXML
+            String handlerType,
+            String tikaConfigXml
     ) {
         try {
 
-            final TikaConfig config = TikaConfig.getDefaultConfig();
+            final TikaConfig config = tikaConfig(tikaConfigXml);
             final ParseContext parsecontext = new ParseContext();
-            final Parser parser = new AutoDetectParser(config);
+            final Parser parser = newParser(config, tesseractConfig);
             final Charset charset = Charset.forName(charsetName, StandardCharsets.UTF_8);
 
             parsecontext.set(Parser.class, parser);
             parsecontext.set(PDFParserConfig.class, pdfConfig);
             parsecontext.set(OfficeParserConfig.class, officeConfig);
             parsecontext.set(TesseractOCRConfig.class, tesseractConfig);
+            setPasswordProvider(parsecontext, pdfConfig, officeConfig);
 
             //final Reader reader = new org.apache.tika.parser.ParsingReader(parser, inputStream, metadata, parsecontext);
-            final Reader reader = new ParsingReader(parser, inputStream, metadata, parsecontext, asXML, charset.name());
+            final InputStream input = CommentsOfficeParserConfig.withoutComments(inputStream, officeConfig);
+            final Reader reader = new ParsingReader(parser, input, metadata, parsecontext, handlerType, charset.name());
 
             // Convert Reader which works with chars to ReaderInputStream which works with bytes
             ReaderInputStream readerInputStream = ReaderInputStream.builder()
@@ -326,8 +670,36 @@ public class TikaNativeMain {
 
         } catch (java.io.IOException e) {
             return new ReaderResult((byte) 1, "IO error occurred: " + e.getMessage());
+        } catch (TikaException e) {
+            return new ReaderResult((byte) 2, "Parse error occurred : " + e.getMessage());
+        }
+
+    }
+
+    /**
+     * Returns the media types supported by the registered parsers, sorted and without parameters
+     *
+     * @return String[] of mime types such as "application/pdf"
+     */
+    public static String[] supportedMimeTypes() {
+        final Parser parser = new AutoDetectParser(TikaConfig.getDefaultConfig());
+        final Set<String> types = new TreeSet<>();
+        for (MediaType type : parser.getSupportedTypes(new ParseContext())) {
+            types.add(type.getBaseType().toString());
         }
+        return types.toArray(new String[0]);
+    }
 
+    /**
+     * Returns the version of the bundled Tika, as read by Tika.getString() from the pom.properties
+     * of tika-core
+     *
+     * @return String such as "2.9.2", empty if the version is unknown
+     */
+    public static String tikaVersion() {
+        final String name = Tika.getString();
+        final String prefix = "Apache Tika ";
+        return name.startsWith(prefix) ? name.substring(prefix.length()).trim() : "";
     }
 
     /**